@@ -1,19 +1,24 @@
 use pallas::network::{
     facades::NodeClient,
-    miniprotocols::{chainsync, localstate, Point, MAINNET_MAGIC},
+    miniprotocols::{chainsync, localstate::queries, Point, MAINNET_MAGIC},
 };
 use tracing::info;
 
 async fn do_localstate_query(client: &mut NodeClient) {
     client.statequery().acquire(None).await.unwrap();
 
-    let result = client
-        .statequery()
-        .query(localstate::queries::RequestV10::GetSystemStart)
+    let result = queries::get_system_start(client.statequery())
         .await
         .unwrap();
 
     info!("system start result: {:?}", result);
+
+    let era = queries::get_current_era(client.statequery()).await.unwrap();
+    let epoch = queries::get_epoch_no(client.statequery(), era)
+        .await
+        .unwrap();
+
+    info!("current era: {}, epoch: {}", era, epoch);
 }
 
 async fn do_chainsync(client: &mut NodeClient) {
@@ -0,0 +1,456 @@
+//! Utilities required for Alonzo-era transaction validation.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    min_lovelace::alonzo_min_lovelace,
+    types::{AlonzoProtParams, Failures, UTxOs, ValidationError, ValidationResult},
+    utils::{
+        check_network_id, check_redeemers, check_tx_ex_units, check_tx_size,
+        check_validity_interval, check_witnesses, find_tx_out, get_tx_size, has_redeemers,
+        is_new_pool, min_collateral, output_ref, plutus_lock, total_ex_units, utxo_value, Balance,
+        RequiredWitnesses, ScriptWitness,
+    },
+};
+
+use pallas_addresses::Address;
+use pallas_codec::minicbor::encode;
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{
+    alonzo::{
+        Certificate, Language, MintedTx, MintedWitnessSet, NativeScript, PoolKeyhash,
+        TransactionBody, TransactionInput, Value,
+    },
+    babbage::MintedDatumOption,
+    conway,
+};
+use pallas_traverse::{
+    fees::compute_script_fee, native_script::ValidityInterval, ComputeHash, Era, MultiEraOutput,
+    MultiEraTx, OriginalHash,
+};
+
+pub fn validate_alonzo_tx(
+    mtx: &MintedTx,
+    utxos: &UTxOs,
+    prot_pps: &AlonzoProtParams,
+    block_slot: u64,
+    network_id: u8,
+    registered_pools: &HashSet<PoolKeyhash>,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx_body: &TransactionBody = &mtx.transaction_body;
+    let size: u64 = get_tx_size(
+        &mtx.transaction_body,
+        &mtx.transaction_witness_set,
+        &mtx.auxiliary_data,
+    );
+    check_ins_not_empty(tx_body, failures)?;
+    check_ins_in_utxos(tx_body, utxos, failures)?;
+    check_validity_interval(
+        tx_body.validity_interval_start,
+        tx_body.ttl,
+        block_slot,
+        failures,
+    )?;
+    check_fees(mtx, size, utxos, prot_pps, failures)?;
+    check_preservation_of_value(tx_body, utxos, prot_pps, registered_pools, failures)?;
+    check_min_lovelace(tx_body, prot_pps, failures)?;
+    check_output_val_size(tx_body, prot_pps, failures)?;
+    check_network_id(
+        tx_body.outputs.iter().map(|x| &x.address),
+        tx_body.withdrawals.as_ref(),
+        tx_body.network_id.as_ref(),
+        network_id,
+        failures,
+    )?;
+    check_tx_size(size, prot_pps.max_tx_size, failures)?;
+    check_tx_ex_units(
+        mtx.transaction_witness_set.redeemer.as_ref(),
+        &prot_pps.max_tx_ex_units,
+        failures,
+    )?;
+    check_tx_witnesses(mtx, utxos, failures)?;
+    check_datums(mtx, utxos, failures)?;
+    check_redeemers(
+        &tx_body.inputs,
+        tx_body.mint.as_ref(),
+        tx_body.certificates.as_ref(),
+        tx_body.withdrawals.as_ref(),
+        mtx.transaction_witness_set.redeemer.as_ref(),
+        &plutus_script_hashes(&mtx.transaction_witness_set),
+        utxos,
+        failures,
+    )?;
+    check_script_data_hash(mtx, prot_pps, failures)
+}
+
+fn check_ins_not_empty(tx_body: &TransactionBody, failures: &mut Failures) -> ValidationResult {
+    if tx_body.inputs.is_empty() {
+        failures.report(ValidationError::TxInsEmpty)?;
+    }
+    Ok(())
+}
+
+fn check_ins_in_utxos(
+    tx_body: &TransactionBody,
+    utxos: &UTxOs,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for input in tx_body.inputs.iter() {
+        if let Err(err) = find_tx_out(input, utxos) {
+            failures.report(err)?;
+        }
+    }
+    Ok(())
+}
+
+// On top of the linear fee, txs pay for the execution units declared by their
+// redeemers, and those running Plutus scripts must provide collateral to be
+// taken in case script validation fails.
+fn check_fees(
+    mtx: &MintedTx,
+    size: u64,
+    utxos: &UTxOs,
+    prot_pps: &AlonzoProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx_body: &TransactionBody = &mtx.transaction_body;
+    let script_fee: u64 = compute_script_fee(
+        &total_ex_units(mtx.transaction_witness_set.redeemer.as_ref()),
+        &prot_pps.ex_unit_prices,
+    );
+    let min_fee: u64 = prot_pps.min_fee_b + prot_pps.min_fee_a * size + script_fee;
+    if tx_body.fee < min_fee {
+        failures.report(ValidationError::FeeTooSmall {
+            required: min_fee,
+            actual: tx_body.fee,
+        })?;
+    }
+
+    if has_redeemers(mtx.transaction_witness_set.redeemer.as_ref()) {
+        check_collaterals(tx_body, utxos, prot_pps, failures)?;
+    }
+    Ok(())
+}
+
+fn check_collaterals(
+    tx_body: &TransactionBody,
+    utxos: &UTxOs,
+    prot_pps: &AlonzoProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let collaterals: &[TransactionInput] = tx_body.collateral.as_deref().unwrap_or_default();
+    if collaterals.is_empty() {
+        return failures.report(ValidationError::CollateralMissing);
+    }
+
+    if collaterals.len() as u64 > prot_pps.max_collateral_inputs {
+        failures.report(ValidationError::TooManyCollaterals {
+            count: collaterals.len() as u64,
+            max: prot_pps.max_collateral_inputs,
+        })?;
+    }
+
+    let mut collateral_balance: Option<u64> = Some(0);
+    for input in collaterals.iter() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => {
+                failures.report(ValidationError::CollateralNotInUTxO(output_ref(input)))?;
+                collateral_balance = None;
+                continue;
+            }
+        };
+
+        match output.address() {
+            Ok(Address::Shelley(address)) if !address.payment().is_script() => (),
+            Ok(Address::Byron(_)) => (),
+            _ => failures.report(ValidationError::CollateralNotVKeyLocked(output_ref(input)))?,
+        }
+
+        let coin: u64 = match utxo_value(output).as_deref() {
+            Ok(Value::Coin(coin)) => *coin,
+            Ok(Value::Multiasset(coin, multiasset)) if multiasset.is_empty() => *coin,
+            Ok(Value::Multiasset(coin, _)) => {
+                failures.report(ValidationError::NonLovelaceCollateral)?;
+                *coin
+            }
+            Err(err) => {
+                failures.report(err.clone())?;
+                collateral_balance = None;
+                continue;
+            }
+        };
+        collateral_balance = collateral_balance.map(|x| x + coin);
+    }
+
+    let required: u64 = min_collateral(tx_body.fee, prot_pps.collateral_percentage);
+    match collateral_balance {
+        Some(provided) if provided < required => {
+            failures.report(ValidationError::CollateralMinLovelace { provided, required })
+        }
+        _ => Ok(()),
+    }
+}
+
+// Only registrations of new pools pay a deposit, re-registrations updating the
+// parameters of a pool. The value consumed can't be told when some input is
+// missing from the UTxO set.
+fn check_preservation_of_value(
+    tx_body: &TransactionBody,
+    utxos: &UTxOs,
+    prot_pps: &AlonzoProtParams,
+    registered_pools: &HashSet<PoolKeyhash>,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let mut consumed = Balance::default();
+    let mut produced = Balance::default();
+    let mut new_pools: HashSet<PoolKeyhash> = HashSet::new();
+
+    for input in tx_body.inputs.iter() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => return Ok(()),
+        };
+
+        match utxo_value(output) {
+            Ok(value) => consumed.add_value(value.as_ref(), 1),
+            Err(err) => return failures.report(err),
+        }
+    }
+
+    for (_, amount) in tx_body.withdrawals.iter().flat_map(|x| x.iter()) {
+        consumed.add_coin(*amount as i128);
+    }
+
+    for cert in tx_body.certificates.iter().flatten() {
+        match cert {
+            Certificate::StakeRegistration(_) => produced.add_coin(prot_pps.key_deposit as i128),
+            Certificate::StakeDeregistration(_) => consumed.add_coin(prot_pps.key_deposit as i128),
+            Certificate::PoolRegistration { operator, .. }
+                if is_new_pool(operator, registered_pools, &mut new_pools) =>
+            {
+                produced.add_coin(prot_pps.pool_deposit as i128)
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(mint) = &tx_body.mint {
+        consumed.add_minted(mint);
+        produced.add_burnt(mint);
+    }
+
+    for output in tx_body.outputs.iter() {
+        produced.add_value(&output.amount, 1);
+    }
+
+    produced.add_coin(tx_body.fee as i128);
+
+    if consumed != produced {
+        failures.report(ValidationError::ValueNotPreserved {
+            consumed: consumed.to_value(),
+            produced: produced.to_value(),
+        })?;
+    }
+    Ok(())
+}
+
+fn check_min_lovelace(
+    tx_body: &TransactionBody,
+    prot_pps: &AlonzoProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for (index, output) in tx_body.outputs.iter().enumerate() {
+        let coin: u64 = match &output.amount {
+            Value::Coin(x) => *x,
+            Value::Multiasset(x, _) => *x,
+        };
+
+        let min_lovelace: u64 = alonzo_min_lovelace(output, prot_pps);
+        if coin < min_lovelace {
+            failures.report(ValidationError::OutputTooSmall {
+                index,
+                min: min_lovelace,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn check_output_val_size(
+    tx_body: &TransactionBody,
+    prot_pps: &AlonzoProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for (index, output) in tx_body.outputs.iter().enumerate() {
+        let mut buff: Vec<u8> = Vec::new();
+        if encode(&output.amount, &mut buff).is_err() {
+            failures.report(ValidationError::UnknownTxSize)?;
+            continue;
+        }
+
+        if buff.len() as u64 > prot_pps.max_value_size {
+            failures.report(ValidationError::MaxValSizeExceeded {
+                index,
+                size: buff.len() as u64,
+                max: prot_pps.max_value_size,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+// All vkey and bootstrap witnesses must sign the tx body, and together with
+// the scripts in the witness set they must cover every credential required by
+// the inputs, collaterals, withdrawals, certificates and minting policies, as
+// well as the required signers.
+fn check_tx_witnesses(mtx: &MintedTx, utxos: &UTxOs, failures: &mut Failures) -> ValidationResult {
+    let tx_body: &TransactionBody = &mtx.transaction_body;
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let mut required = RequiredWitnesses::default();
+    let collaterals: &[TransactionInput] = tx_body.collateral.as_deref().unwrap_or_default();
+
+    for (input, is_collateral) in tx_body
+        .inputs
+        .iter()
+        .map(|x| (x, false))
+        .chain(collaterals.iter().map(|x| (x, true)))
+    {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) if is_collateral => {
+                failures.report(ValidationError::CollateralNotInUTxO(output_ref(input)))?;
+                continue;
+            }
+            Err(_) => continue,
+        };
+        required.add_spent_address(input, output.address(), failures)?;
+    }
+
+    required.add_tx_credentials(
+        tx_body.withdrawals.as_ref(),
+        tx_body.certificates.as_ref(),
+        tx_body.mint.as_ref(),
+        failures,
+    )?;
+    required
+        .signers
+        .extend(tx_body.required_signers.iter().flatten().copied());
+
+    let native_scripts: HashMap<Hash<28>, &NativeScript> = tx_wits
+        .native_script
+        .iter()
+        .flatten()
+        .map(|x| (x.compute_hash(), x))
+        .collect();
+    let plutus_scripts: HashSet<Hash<28>> = plutus_script_hashes(tx_wits);
+
+    check_witnesses(
+        &mtx.transaction_body.original_hash(),
+        tx_wits.vkeywitness.as_deref().unwrap_or_default(),
+        tx_wits.bootstrap_witness.as_deref().unwrap_or_default(),
+        &required,
+        |x| match native_scripts.get(x) {
+            Some(script) => Some(ScriptWitness::Native(script)),
+            None if plutus_scripts.contains(x) => Some(ScriptWitness::Plutus),
+            None => None,
+        },
+        &ValidityInterval::new(tx_body.validity_interval_start, tx_body.ttl),
+        failures,
+    )
+}
+
+fn plutus_script_hashes(tx_wits: &MintedWitnessSet) -> HashSet<Hash<28>> {
+    tx_wits
+        .plutus_script
+        .iter()
+        .flatten()
+        .map(|x| x.compute_hash())
+        .collect()
+}
+
+// Every input locked by a Plutus script must carry a datum hash whose datum is
+// in the witness set, and every datum in the witness set must be either
+// required by an input or referenced by an output.
+fn check_datums(mtx: &MintedTx, utxos: &UTxOs, failures: &mut Failures) -> ValidationResult {
+    let tx_body: &TransactionBody = &mtx.transaction_body;
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let plutus_scripts: HashSet<Hash<28>> = plutus_script_hashes(tx_wits);
+    let mut required_datums: BTreeSet<Hash<32>> = BTreeSet::new();
+
+    for input in tx_body.inputs.iter() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        if plutus_lock(output, &plutus_scripts).is_some() {
+            match output.datum() {
+                Some(MintedDatumOption::Hash(x)) => {
+                    required_datums.insert(x);
+                }
+                _ => {
+                    failures.report(ValidationError::UnspendableUTxONoDatumHash(output_ref(
+                        input,
+                    )))?;
+                }
+            };
+        }
+    }
+
+    let provided_datums: Vec<Hash<32>> = tx_wits
+        .plutus_data
+        .iter()
+        .flatten()
+        .map(|x| x.original_hash())
+        .collect();
+
+    for datum in required_datums.iter() {
+        if !provided_datums.contains(datum) {
+            failures.report(ValidationError::DatumMissing(*datum))?;
+        }
+    }
+
+    let output_datums: HashSet<Hash<32>> = tx_body
+        .outputs
+        .iter()
+        .filter_map(|x| x.datum_hash)
+        .collect();
+
+    for datum in provided_datums.iter() {
+        if !required_datums.contains(datum) && !output_datums.contains(datum) {
+            failures.report(ValidationError::UnneededDatum(*datum))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_script_data_hash(
+    mtx: &MintedTx,
+    prot_pps: &AlonzoProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let declared: Option<Hash<32>> = mtx.transaction_body.script_data_hash;
+    let computed: Option<Hash<32>> = compute_script_data_hash(mtx, prot_pps);
+    if declared != computed {
+        failures.report(ValidationError::ScriptIntegrityHashMismatch { declared, computed })?;
+    }
+    Ok(())
+}
+
+// Alonzo only supports PlutusV1, whose cost model is part of the hash if the
+// tx carries Plutus scripts.
+fn compute_script_data_hash(mtx: &MintedTx, prot_pps: &AlonzoProtParams) -> Option<Hash<32>> {
+    let uses_plutus: bool = mtx
+        .transaction_witness_set
+        .plutus_script
+        .as_ref()
+        .is_some_and(|x| !x.is_empty());
+    let views: Vec<(conway::Language, &[i64])> = prot_pps
+        .cost_models_for_script_languages
+        .iter()
+        .filter(|(language, _)| uses_plutus && *language == Language::PlutusV1)
+        .map(|(_, cost_model)| (conway::Language::PlutusV1, cost_model.as_slice()))
+        .collect();
+    MultiEraTx::from_alonzo_compatible(mtx, Era::Alonzo).compute_script_data_hash(&views)
+}
@@ -0,0 +1,619 @@
+//! Utilities required for Babbage-era transaction validation.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap, HashSet},
+};
+
+use crate::{
+    min_lovelace::babbage_min_lovelace,
+    types::{BabbageProtParams, Failures, UTxOs, ValidationError, ValidationResult},
+    utils::{
+        check_network_id, check_redeemers, check_tx_ex_units, check_tx_size,
+        check_validity_interval, check_witnesses, find_tx_out, get_tx_size, has_redeemers,
+        is_new_pool, min_collateral, output_ref, plutus_lock, total_ex_units, utxo_value, Balance,
+        RequiredWitnesses, ScriptWitness,
+    },
+};
+
+use pallas_addresses::Address;
+use pallas_codec::{minicbor::encode, utils::Bytes};
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{
+    babbage::{
+        Certificate, MintedDatumOption, MintedTransactionBody, MintedTransactionOutput, MintedTx,
+        MintedWitnessSet, NativeScript, PoolKeyhash, TransactionInput, Value,
+    },
+    conway::{Language, Script},
+};
+use pallas_traverse::{
+    fees::compute_script_fee, native_script::ValidityInterval, ComputeHash, MultiEraOutput,
+    MultiEraTx, OriginalHash,
+};
+
+pub fn validate_babbage_tx(
+    mtx: &MintedTx,
+    utxos: &UTxOs,
+    prot_pps: &BabbageProtParams,
+    block_slot: u64,
+    network_id: u8,
+    registered_pools: &HashSet<PoolKeyhash>,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx_body: &MintedTransactionBody = &mtx.transaction_body;
+    let size: u64 = get_tx_size(
+        &mtx.transaction_body,
+        &mtx.transaction_witness_set,
+        &mtx.auxiliary_data,
+    );
+    check_ins_not_empty(tx_body, failures)?;
+    check_ins_in_utxos(tx_body, utxos, failures)?;
+    check_validity_interval(
+        tx_body.validity_interval_start,
+        tx_body.ttl,
+        block_slot,
+        failures,
+    )?;
+    check_fees(mtx, size, utxos, prot_pps, failures)?;
+    check_preservation_of_value(tx_body, utxos, prot_pps, registered_pools, failures)?;
+    check_min_lovelace(tx_body, prot_pps, failures)?;
+    check_output_val_size(tx_body, prot_pps, failures)?;
+    check_network_id(
+        produced_outputs(tx_body).map(output_address),
+        tx_body.withdrawals.as_ref(),
+        tx_body.network_id.as_ref(),
+        network_id,
+        failures,
+    )?;
+    check_tx_size(size, prot_pps.max_tx_size, failures)?;
+    check_tx_ex_units(
+        mtx.transaction_witness_set.redeemer.as_ref(),
+        &prot_pps.max_tx_ex_units,
+        failures,
+    )?;
+    check_tx_witnesses(mtx, utxos, failures)?;
+    check_datums(mtx, utxos, failures)?;
+    // items validated by reference scripts need a redeemer as well
+    check_redeemers(
+        &tx_body.inputs,
+        tx_body.mint.as_ref(),
+        tx_body.certificates.as_ref(),
+        tx_body.withdrawals.as_ref(),
+        mtx.transaction_witness_set.redeemer.as_ref(),
+        &plutus_script_hashes(&available_scripts(mtx, utxos)),
+        utxos,
+        failures,
+    )?;
+    check_script_data_hash(mtx, utxos, prot_pps, failures)
+}
+
+fn check_ins_not_empty(
+    tx_body: &MintedTransactionBody,
+    failures: &mut Failures,
+) -> ValidationResult {
+    if tx_body.inputs.is_empty() {
+        failures.report(ValidationError::TxInsEmpty)?;
+    }
+    Ok(())
+}
+
+// Reference inputs are not consumed by the tx, but they must still be
+// unspent, since their datums and scripts are made available to it.
+fn check_ins_in_utxos(
+    tx_body: &MintedTransactionBody,
+    utxos: &UTxOs,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for input in tx_body.inputs.iter() {
+        if let Err(err) = find_tx_out(input, utxos) {
+            failures.report(err)?;
+        }
+    }
+
+    for input in tx_body.reference_inputs.iter().flatten() {
+        if find_tx_out(input, utxos).is_err() {
+            failures.report(ValidationError::RefInputNotInUTxO(output_ref(input)))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_fees(
+    mtx: &MintedTx,
+    size: u64,
+    utxos: &UTxOs,
+    prot_pps: &BabbageProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx_body: &MintedTransactionBody = &mtx.transaction_body;
+    let script_fee: u64 = compute_script_fee(
+        &total_ex_units(mtx.transaction_witness_set.redeemer.as_ref()),
+        &prot_pps.ex_unit_prices,
+    );
+    let min_fee: u64 = prot_pps.min_fee_b + prot_pps.min_fee_a * size + script_fee;
+    if tx_body.fee < min_fee {
+        failures.report(ValidationError::FeeTooSmall {
+            required: min_fee,
+            actual: tx_body.fee,
+        })?;
+    }
+
+    if has_redeemers(mtx.transaction_witness_set.redeemer.as_ref()) {
+        check_collaterals(tx_body, utxos, prot_pps, failures)?;
+    }
+    Ok(())
+}
+
+// Collateral inputs may now hold native assets, as long as they are sent back
+// through the collateral return output, so that the collateral actually taken
+// is made of lovelace only. When the tx declares its total collateral, it must
+// match that amount.
+fn check_collaterals(
+    tx_body: &MintedTransactionBody,
+    utxos: &UTxOs,
+    prot_pps: &BabbageProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let collaterals: &[TransactionInput] = tx_body.collateral.as_deref().unwrap_or_default();
+    if collaterals.is_empty() {
+        return failures.report(ValidationError::CollateralMissing);
+    }
+
+    if collaterals.len() as u64 > prot_pps.max_collateral_inputs {
+        failures.report(ValidationError::TooManyCollaterals {
+            count: collaterals.len() as u64,
+            max: prot_pps.max_collateral_inputs,
+        })?;
+    }
+
+    let mut balance: Option<Balance> = Some(Balance::default());
+    for input in collaterals.iter() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => {
+                failures.report(ValidationError::CollateralNotInUTxO(output_ref(input)))?;
+                balance = None;
+                continue;
+            }
+        };
+
+        match output.address() {
+            Ok(Address::Shelley(address)) if !address.payment().is_script() => (),
+            Ok(Address::Byron(_)) => (),
+            _ => failures.report(ValidationError::CollateralNotVKeyLocked(output_ref(input)))?,
+        }
+
+        match utxo_value(output) {
+            Ok(value) => {
+                if let Some(balance) = balance.as_mut() {
+                    balance.add_value(value.as_ref(), 1);
+                }
+            }
+            Err(err) => {
+                failures.report(err)?;
+                balance = None;
+            }
+        }
+    }
+
+    // The balance of the collateral can't be told when some collateral input
+    // is missing from the UTxO set or holds a value of unknown type.
+    let mut balance: Balance = match balance {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+
+    if let Some(collateral_return) = &tx_body.collateral_return {
+        balance.add_value(output_value(collateral_return), -1);
+    }
+
+    if !balance.assets.is_empty() {
+        failures.report(ValidationError::NonLovelaceCollateral)?;
+    }
+
+    let provided: u64 = balance.coin.clamp(0, u64::MAX as i128) as u64;
+    let required: u64 = min_collateral(tx_body.fee, prot_pps.collateral_percentage);
+    if balance.coin < 0 || provided < required {
+        failures.report(ValidationError::CollateralMinLovelace { provided, required })?;
+    }
+
+    match tx_body.total_collateral {
+        Some(declared) if declared as i128 != balance.coin => {
+            failures.report(ValidationError::CollateralAnnotationMismatch {
+                declared,
+                actual: balance.coin,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+// Only registrations of new pools pay a deposit, re-registrations updating the
+// parameters of a pool. Neither reference inputs nor the collateral return
+// output take part in the balance. The value consumed can't be told when some
+// input is missing from the UTxO set or holds a value of unknown type.
+fn check_preservation_of_value(
+    tx_body: &MintedTransactionBody,
+    utxos: &UTxOs,
+    prot_pps: &BabbageProtParams,
+    registered_pools: &HashSet<PoolKeyhash>,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let mut consumed = Balance::default();
+    let mut produced = Balance::default();
+    let mut new_pools: HashSet<PoolKeyhash> = HashSet::new();
+
+    for input in tx_body.inputs.iter() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => return Ok(()),
+        };
+
+        match utxo_value(output) {
+            Ok(value) => consumed.add_value(value.as_ref(), 1),
+            Err(err) => return failures.report(err),
+        }
+    }
+
+    for (_, amount) in tx_body.withdrawals.iter().flat_map(|x| x.iter()) {
+        consumed.add_coin(*amount as i128);
+    }
+
+    for cert in tx_body.certificates.iter().flatten() {
+        match cert {
+            Certificate::StakeRegistration(_) => produced.add_coin(prot_pps.key_deposit as i128),
+            Certificate::StakeDeregistration(_) => consumed.add_coin(prot_pps.key_deposit as i128),
+            Certificate::PoolRegistration { operator, .. }
+                if is_new_pool(operator, registered_pools, &mut new_pools) =>
+            {
+                produced.add_coin(prot_pps.pool_deposit as i128)
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(mint) = &tx_body.mint {
+        consumed.add_minted(mint);
+        produced.add_burnt(mint);
+    }
+
+    for output in tx_body.outputs.iter() {
+        produced.add_value(output_value(output), 1);
+    }
+
+    produced.add_coin(tx_body.fee as i128);
+
+    if consumed != produced {
+        failures.report(ValidationError::ValueNotPreserved {
+            consumed: consumed.to_value(),
+            produced: produced.to_value(),
+        })?;
+    }
+    Ok(())
+}
+
+// Outputs produced by the tx, including the collateral return one.
+fn produced_outputs<'a>(
+    tx_body: &'a MintedTransactionBody,
+) -> impl Iterator<Item = &'a MintedTransactionOutput<'a>> {
+    tx_body
+        .outputs
+        .iter()
+        .chain(tx_body.collateral_return.iter())
+}
+
+fn check_min_lovelace(
+    tx_body: &MintedTransactionBody,
+    prot_pps: &BabbageProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for (index, output) in produced_outputs(tx_body).enumerate() {
+        let coin: u64 = match output_value(output) {
+            Value::Coin(x) => *x,
+            Value::Multiasset(x, _) => *x,
+        };
+
+        let min_lovelace: u64 = babbage_min_lovelace(output, prot_pps);
+
+        if coin < min_lovelace {
+            failures.report(ValidationError::OutputTooSmall {
+                index,
+                min: min_lovelace,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn check_output_val_size(
+    tx_body: &MintedTransactionBody,
+    prot_pps: &BabbageProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for (index, output) in produced_outputs(tx_body).enumerate() {
+        let mut buff: Vec<u8> = Vec::new();
+        if encode(output_value(output), &mut buff).is_err() {
+            failures.report(ValidationError::UnknownTxSize)?;
+            continue;
+        }
+
+        if buff.len() as u64 > prot_pps.max_value_size {
+            failures.report(ValidationError::MaxValSizeExceeded {
+                index,
+                size: buff.len() as u64,
+                max: prot_pps.max_value_size,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Script made available to a tx, either through its witness set or as the
+/// reference script of one of its (reference) inputs
+enum AvailableScript<'a> {
+    Native(Cow<'a, NativeScript>),
+    PlutusV1,
+    PlutusV2,
+}
+
+// Inputs missing from the UTxO set provide no scripts.
+fn available_scripts<'a>(
+    mtx: &'a MintedTx,
+    utxos: &'a UTxOs,
+) -> HashMap<Hash<28>, AvailableScript<'a>> {
+    let tx_body: &MintedTransactionBody = &mtx.transaction_body;
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let mut res: HashMap<Hash<28>, AvailableScript> = HashMap::new();
+
+    for script in tx_wits.native_script.iter().flatten() {
+        res.insert(
+            script.compute_hash(),
+            AvailableScript::Native(Cow::Borrowed(script)),
+        );
+    }
+
+    for script in tx_wits.plutus_v1_script.iter().flatten() {
+        res.insert(script.compute_hash(), AvailableScript::PlutusV1);
+    }
+
+    for script in tx_wits.plutus_v2_script.iter().flatten() {
+        res.insert(script.compute_hash(), AvailableScript::PlutusV2);
+    }
+
+    for input in tx_body
+        .inputs
+        .iter()
+        .chain(tx_body.reference_inputs.iter().flatten())
+    {
+        let script_ref = match find_tx_out(input, utxos).map(|x| x.script_ref()) {
+            Ok(Some(x)) => x,
+            _ => continue,
+        };
+
+        match script_ref.0 {
+            Script::NativeScript(x) => {
+                res.insert(x.compute_hash(), AvailableScript::Native(Cow::Owned(x)))
+            }
+            Script::PlutusV1Script(x) => res.insert(x.compute_hash(), AvailableScript::PlutusV1),
+            Script::PlutusV2Script(x) => res.insert(x.compute_hash(), AvailableScript::PlutusV2),
+            // unknown to Babbage, so it can't satisfy any of the scripts required
+            Script::PlutusV3Script(_) => continue,
+        };
+    }
+    res
+}
+
+fn plutus_script_hashes(scripts: &HashMap<Hash<28>, AvailableScript>) -> HashSet<Hash<28>> {
+    scripts
+        .iter()
+        .filter(|(_, x)| !matches!(x, AvailableScript::Native(_)))
+        .map(|(hash, _)| *hash)
+        .collect()
+}
+
+// Gathers the credentials required by the inputs, collaterals, withdrawals,
+// certificates and minting policies, as well as the required signers.
+// Reference inputs are not spent, so they require no witnesses. Neither are
+// inputs missing from the UTxO set taken into account.
+fn required_witnesses(
+    tx_body: &MintedTransactionBody,
+    utxos: &UTxOs,
+    failures: &mut Failures,
+) -> Result<RequiredWitnesses, ValidationError> {
+    let mut required = RequiredWitnesses::default();
+    let collaterals: &[TransactionInput] = tx_body.collateral.as_deref().unwrap_or_default();
+
+    for (input, is_collateral) in tx_body
+        .inputs
+        .iter()
+        .map(|x| (x, false))
+        .chain(collaterals.iter().map(|x| (x, true)))
+    {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) if is_collateral => {
+                failures.report(ValidationError::CollateralNotInUTxO(output_ref(input)))?;
+                continue;
+            }
+            Err(_) => continue,
+        };
+        required.add_spent_address(input, output.address(), failures)?;
+    }
+
+    required.add_tx_credentials(
+        tx_body.withdrawals.as_ref(),
+        tx_body.certificates.as_ref(),
+        tx_body.mint.as_ref(),
+        failures,
+    )?;
+    required
+        .signers
+        .extend(tx_body.required_signers.iter().flatten().copied());
+    Ok(required)
+}
+
+// All vkey and bootstrap witnesses must sign the tx body, and together with
+// the available scripts they must cover every required credential and signer.
+// Scripts may be provided either in the witness set or as reference scripts.
+fn check_tx_witnesses(mtx: &MintedTx, utxos: &UTxOs, failures: &mut Failures) -> ValidationResult {
+    let tx_body: &MintedTransactionBody = &mtx.transaction_body;
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let required: RequiredWitnesses = required_witnesses(tx_body, utxos, failures)?;
+    let scripts: HashMap<Hash<28>, AvailableScript> = available_scripts(mtx, utxos);
+
+    check_witnesses(
+        &mtx.transaction_body.original_hash(),
+        tx_wits.vkeywitness.as_deref().unwrap_or_default(),
+        tx_wits.bootstrap_witness.as_deref().unwrap_or_default(),
+        &required,
+        |x| match scripts.get(x)? {
+            AvailableScript::Native(script) => Some(ScriptWitness::Native(script)),
+            _ => Some(ScriptWitness::Plutus),
+        },
+        &ValidityInterval::new(tx_body.validity_interval_start, tx_body.ttl),
+        failures,
+    )
+}
+
+// Every input locked by a Plutus script must carry a datum, either inline or
+// as a hash whose datum is in the witness set. Every datum in the witness set
+// must be either required by an input, or referenced by an output or by the
+// UTxO of a reference input.
+fn check_datums(mtx: &MintedTx, utxos: &UTxOs, failures: &mut Failures) -> ValidationResult {
+    let tx_body: &MintedTransactionBody = &mtx.transaction_body;
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let plutus_scripts: HashSet<Hash<28>> = plutus_script_hashes(&available_scripts(mtx, utxos));
+    let mut required_datums: BTreeSet<Hash<32>> = BTreeSet::new();
+
+    for input in tx_body.inputs.iter() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        if plutus_lock(output, &plutus_scripts).is_some() {
+            match output.datum() {
+                Some(MintedDatumOption::Hash(x)) => {
+                    required_datums.insert(x);
+                }
+                Some(MintedDatumOption::Data(_)) => (),
+                None => failures.report(ValidationError::UnspendableUTxONoDatumHash(
+                    output_ref(input),
+                ))?,
+            };
+        }
+    }
+
+    let provided_datums: Vec<Hash<32>> = tx_wits
+        .plutus_data
+        .iter()
+        .flatten()
+        .map(|x| x.original_hash())
+        .collect();
+
+    for datum in required_datums.iter() {
+        if !provided_datums.contains(datum) {
+            failures.report(ValidationError::DatumMissing(*datum))?;
+        }
+    }
+
+    let mut supplemental_datums: HashSet<Hash<32>> = tx_body
+        .outputs
+        .iter()
+        .filter_map(output_datum_hash)
+        .collect();
+
+    for input in tx_body.reference_inputs.iter().flatten() {
+        if let Ok(Some(MintedDatumOption::Hash(x))) = find_tx_out(input, utxos).map(|x| x.datum()) {
+            supplemental_datums.insert(x);
+        }
+    }
+
+    for datum in provided_datums.iter() {
+        if !required_datums.contains(datum) && !supplemental_datums.contains(datum) {
+            failures.report(ValidationError::UnneededDatum(*datum))?;
+        }
+    }
+    Ok(())
+}
+
+fn check_script_data_hash(
+    mtx: &MintedTx,
+    utxos: &UTxOs,
+    prot_pps: &BabbageProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let declared: Option<Hash<32>> = mtx.transaction_body.script_data_hash;
+    let computed: Option<Hash<32>> = compute_script_data_hash(mtx, utxos, prot_pps, failures)?;
+    if declared != computed {
+        failures.report(ValidationError::ScriptIntegrityHashMismatch { declared, computed })?;
+    }
+    Ok(())
+}
+
+// The language views hashed are those of the Plutus scripts needed by the tx,
+// including reference scripts. Txs with neither redeemers nor datums must not
+// declare a script data hash.
+fn compute_script_data_hash(
+    mtx: &MintedTx,
+    utxos: &UTxOs,
+    prot_pps: &BabbageProtParams,
+    failures: &mut Failures,
+) -> Result<Option<Hash<32>>, ValidationError> {
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let has_datums: bool = tx_wits.plutus_data.as_ref().is_some_and(|x| !x.is_empty());
+    if !has_redeemers(tx_wits.redeemer.as_ref()) && !has_datums {
+        return Ok(None);
+    }
+
+    let scripts: HashMap<Hash<28>, AvailableScript> = available_scripts(mtx, utxos);
+    let required: RequiredWitnesses = required_witnesses(&mtx.transaction_body, utxos, failures)?;
+    let mut uses_v1: bool = false;
+    let mut uses_v2: bool = false;
+    for script_hash in required.script_hashes.iter() {
+        match scripts.get(script_hash) {
+            Some(AvailableScript::PlutusV1) => uses_v1 = true,
+            Some(AvailableScript::PlutusV2) => uses_v2 = true,
+            _ => (),
+        }
+    }
+
+    let cost_models = &prot_pps.cost_models_for_script_languages;
+    let mut views: Vec<(Language, &[i64])> = Vec::new();
+    if uses_v1 {
+        match &cost_models.plutus_v1 {
+            Some(cost_model) => views.push((Language::PlutusV1, cost_model)),
+            None => return Ok(None),
+        }
+    }
+    if uses_v2 {
+        match &cost_models.plutus_v2 {
+            Some(cost_model) => views.push((Language::PlutusV2, cost_model)),
+            None => return Ok(None),
+        }
+    }
+    Ok(MultiEraTx::from_babbage(mtx).compute_script_data_hash(&views))
+}
+
+fn output_address<'a>(output: &'a MintedTransactionOutput) -> &'a Bytes {
+    match output {
+        MintedTransactionOutput::Legacy(x) => &x.address,
+        MintedTransactionOutput::PostAlonzo(x) => &x.address,
+    }
+}
+
+fn output_value<'a>(output: &'a MintedTransactionOutput) -> &'a Value {
+    match output {
+        MintedTransactionOutput::Legacy(x) => &x.amount,
+        MintedTransactionOutput::PostAlonzo(x) => &x.value,
+    }
+}
+
+fn output_datum_hash(output: &MintedTransactionOutput) -> Option<Hash<32>> {
+    match output {
+        MintedTransactionOutput::Legacy(x) => x.datum_hash,
+        MintedTransactionOutput::PostAlonzo(x) => match &x.datum_option {
+            Some(MintedDatumOption::Hash(x)) => Some(*x),
+            _ => None,
+        },
+    }
+}
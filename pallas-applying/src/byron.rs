@@ -1,14 +1,307 @@
 //! Utilities required for Byron-era transaction validation.
 
-use crate::types::{ByronProtParams, UTxOs, ValidationResult};
+use std::borrow::Cow;
 
-use pallas_primitives::byron::MintedTxPayload;
+use crate::types::{ByronProtParams, Failures, UTxOs, ValidationError, ValidationResult};
+
+use pallas_addresses::byron::{AddrAttrs, AddrType, AddressId, AddressPayload, SpendingData};
+use pallas_codec::{
+    minicbor::{bytes::ByteVec, encode, Encoder},
+    utils::Bytes,
+};
+use pallas_crypto::{
+    hash::Hash,
+    key::ed25519::{PublicKey, Signature},
+};
+use pallas_primitives::{
+    alonzo::Value,
+    byron::{Address, MintedTxPayload, PubKey, Signature as ByronSignature, Twit, Tx, TxIn, TxOut},
+};
+use pallas_traverse::{
+    fees::compute_byron_fee, MultiEraInput, MultiEraOutput, OriginalHash, OutputRef,
+};
 
-// TODO: implement each of the validation rules.
 pub fn validate_byron_tx(
-    _mtxp: &MintedTxPayload,
-    _utxos: &UTxOs,
-    _prot_pps: &ByronProtParams,
+    mtxp: &MintedTxPayload,
+    utxos: &UTxOs,
+    prot_pps: &ByronProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx: &Tx = &mtxp.transaction;
+    check_ins_not_empty(tx, failures)?;
+    check_outs_not_empty(tx, failures)?;
+    check_ins_in_utxos(tx, utxos, failures)?;
+    check_outs_have_lovelace(tx, failures)?;
+    check_fees(mtxp, utxos, prot_pps, failures)?;
+    check_size(mtxp, prot_pps, failures)?;
+    check_witnesses(mtxp, utxos, prot_pps, failures)
+}
+
+fn check_ins_not_empty(tx: &Tx, failures: &mut Failures) -> ValidationResult {
+    if tx.inputs.is_empty() {
+        failures.report(ValidationError::TxInsEmpty)?;
+    }
+    Ok(())
+}
+
+fn check_outs_not_empty(tx: &Tx, failures: &mut Failures) -> ValidationResult {
+    if tx.outputs.is_empty() {
+        failures.report(ValidationError::TxOutsEmpty)?;
+    }
+    Ok(())
+}
+
+fn check_ins_in_utxos(tx: &Tx, utxos: &UTxOs, failures: &mut Failures) -> ValidationResult {
+    for input in tx.inputs.iter() {
+        if let Err(err) = find_tx_out(input, utxos) {
+            failures.report(err)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_outs_have_lovelace(tx: &Tx, failures: &mut Failures) -> ValidationResult {
+    for (index, output) in tx.outputs.iter().enumerate() {
+        if output.amount == 0 {
+            failures.report(ValidationError::OutputWithoutLovelace(index))?;
+        }
+    }
+    Ok(())
+}
+
+// The fee of a Byron tx is implicit: it is whatever is left after subtracting
+// the outputs from the inputs, and it must cover the linear fee policy. It
+// can't be told when some input is missing from the UTxO set.
+fn check_fees(
+    mtxp: &MintedTxPayload,
+    utxos: &UTxOs,
+    prot_pps: &ByronProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx: &Tx = &mtxp.transaction;
+    let mut inputs_balance: u64 = 0;
+    for input in tx.inputs.iter() {
+        match find_tx_out(input, utxos) {
+            Ok(tx_out) => inputs_balance = inputs_balance.saturating_add(tx_out.amount),
+            Err(_) => return Ok(()),
+        }
+    }
+    let outputs_balance: u64 = tx
+        .outputs
+        .iter()
+        .fold(0, |acc, x| acc.saturating_add(x.amount));
+    let paid_fee: u64 = match inputs_balance.checked_sub(outputs_balance) {
+        Some(x) => x,
+        None => {
+            return failures.report(ValidationError::ValueNotPreserved {
+                consumed: Value::Coin(inputs_balance),
+                produced: Value::Coin(outputs_balance),
+            })
+        }
+    };
+    let min_fee: u64 = compute_byron_fee(mtxp, Some(&prot_pps.fee_policy));
+    if paid_fee < min_fee {
+        failures.report(ValidationError::FeeTooSmall {
+            required: min_fee,
+            actual: paid_fee,
+        })?;
+    }
+    Ok(())
+}
+
+fn check_size(
+    mtxp: &MintedTxPayload,
+    prot_pps: &ByronProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let size: u64 = match get_tx_size(mtxp) {
+        Ok(x) => x,
+        Err(err) => return failures.report(err),
+    };
+    if size > prot_pps.max_tx_size {
+        failures.report(ValidationError::MaxTxSizeExceeded {
+            size,
+            max: prot_pps.max_tx_size,
+        })?;
+    }
+    Ok(())
+}
+
+fn get_tx_size(mtxp: &MintedTxPayload) -> Result<u64, ValidationError> {
+    let mut buff: Vec<u8> = Vec::new();
+    match encode(mtxp, &mut buff) {
+        Ok(()) => Ok(buff.len() as u64),
+        Err(_) => Err(ValidationError::UnknownTxSize),
+    }
+}
+
+// Every input must be redeemed by a witness whose key hashes into the root of
+// the address holding the spent output, and whose signature is valid over the
+// tx id under the network's protocol magic.
+fn check_witnesses(
+    mtxp: &MintedTxPayload,
+    utxos: &UTxOs,
+    prot_pps: &ByronProtParams,
+    failures: &mut Failures,
 ) -> ValidationResult {
+    let tx: &Tx = &mtxp.transaction;
+    let tx_hash: Hash<32> = mtxp.transaction.original_hash();
+    let witnesses: Vec<TaggedWitness> = match tag_witnesses(&mtxp.witness) {
+        Ok(x) => x,
+        Err(err) => return failures.report(err),
+    };
+    for input in tx.inputs.iter() {
+        let tx_out: &TxOut = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        if let Err(err) = check_witness(input, tx_out, &witnesses, prot_pps, &tx_hash) {
+            failures.report(err)?;
+        }
+    }
     Ok(())
 }
+
+fn check_witness(
+    input: &TxIn,
+    tx_out: &TxOut,
+    witnesses: &[TaggedWitness],
+    prot_pps: &ByronProtParams,
+    tx_hash: &Hash<32>,
+) -> ValidationResult {
+    let witness: &TaggedWitness = match find_witness(&tx_out.address, witnesses)? {
+        Some(x) => x,
+        None => return Err(ValidationError::MissingWitness(input_ref(input)?)),
+    };
+    let data_to_verify: Vec<u8> = get_data_to_verify(witness, prot_pps.protocol_magic, tx_hash)?;
+    if !witness
+        .public_key()?
+        .verify(data_to_verify, &witness.signature()?)
+    {
+        return Err(ValidationError::WrongSignature(Bytes::from(
+            witness.pub_key.to_vec(),
+        )));
+    }
+    Ok(())
+}
+
+enum WitnessKind {
+    PubKey,
+    Redeem,
+}
+
+struct TaggedWitness<'a> {
+    kind: WitnessKind,
+    pub_key: &'a PubKey,
+    signature: &'a ByronSignature,
+}
+
+impl TaggedWitness<'_> {
+    fn addr_type(&self) -> AddrType {
+        match self.kind {
+            WitnessKind::PubKey => AddrType::PubKey,
+            WitnessKind::Redeem => AddrType::Redeem,
+        }
+    }
+
+    fn spending_data(&self) -> SpendingData {
+        let key: ByteVec = self.pub_key.clone();
+        match self.kind {
+            WitnessKind::PubKey => SpendingData::PubKey(key),
+            WitnessKind::Redeem => SpendingData::Redeem(key),
+        }
+    }
+
+    // Redeem keys are plain Ed25519 keys, while regular keys are extended with
+    // a chain code that plays no role in signature verification.
+    fn public_key(&self) -> Result<PublicKey, ValidationError> {
+        self.pub_key
+            .get(..PublicKey::SIZE)
+            .and_then(|bytes| PublicKey::try_from(bytes).ok())
+            .ok_or(ValidationError::UnableToProcessWitnesses)
+    }
+
+    fn signature(&self) -> Result<Signature, ValidationError> {
+        Signature::try_from(&self.signature[..])
+            .map_err(|_| ValidationError::UnableToProcessWitnesses)
+    }
+
+    // The sign tag distinguishes signatures over txs from signatures over
+    // other Byron objects.
+    fn sign_tag(&self) -> u8 {
+        match self.kind {
+            WitnessKind::PubKey => 1,
+            WitnessKind::Redeem => 2,
+        }
+    }
+}
+
+fn tag_witnesses(wits: &[Twit]) -> Result<Vec<TaggedWitness>, ValidationError> {
+    let mut res: Vec<TaggedWitness> = Vec::new();
+    for wit in wits.iter() {
+        match wit {
+            Twit::PkWitness(x) => res.push(TaggedWitness {
+                kind: WitnessKind::PubKey,
+                pub_key: &x.0 .0,
+                signature: &x.0 .1,
+            }),
+            Twit::RedeemWitness(x) => res.push(TaggedWitness {
+                kind: WitnessKind::Redeem,
+                pub_key: &x.0 .0,
+                signature: &x.0 .1,
+            }),
+            _ => return Err(ValidationError::UnableToProcessWitnesses),
+        }
+    }
+    Ok(res)
+}
+
+fn input_ref(input: &TxIn) -> Result<OutputRef, ValidationError> {
+    match input {
+        TxIn::Variant0(_) => {
+            Ok(MultiEraInput::Byron(Box::new(Cow::Owned(input.clone()))).output_ref())
+        }
+        TxIn::Other(..) => Err(ValidationError::UnknownInputType),
+    }
+}
+
+fn find_tx_out<'a>(input: &TxIn, utxos: &'a UTxOs) -> Result<&'a TxOut, ValidationError> {
+    let key: MultiEraInput = MultiEraInput::Byron(Box::new(Cow::Owned(input.clone())));
+    utxos
+        .get(&key)
+        .and_then(MultiEraOutput::as_byron)
+        .ok_or(ValidationError::InputNotInUTxO(input_ref(input)?))
+}
+
+fn find_witness<'a, 'b>(
+    address: &Address,
+    witnesses: &'a [TaggedWitness<'b>],
+) -> Result<Option<&'a TaggedWitness<'b>>, ValidationError> {
+    let addr_payload: AddressPayload =
+        pallas_addresses::ByronAddress::new(&address.payload.0, address.crc)
+            .decode()
+            .map_err(|_| ValidationError::UnableToProcessWitnesses)?;
+    Ok(witnesses
+        .iter()
+        .find(|witness| redeems(witness, &addr_payload.root, &addr_payload.attributes)))
+}
+
+fn redeems(witness: &TaggedWitness, root: &AddressId, attrs: &AddrAttrs) -> bool {
+    let hash: AddressId =
+        AddressPayload::hash_address_id(&witness.addr_type(), &witness.spending_data(), attrs);
+    hash == *root
+}
+
+fn get_data_to_verify(
+    witness: &TaggedWitness,
+    protocol_magic: u32,
+    tx_hash: &Hash<32>,
+) -> Result<Vec<u8>, ValidationError> {
+    let mut buff: Vec<u8> = vec![witness.sign_tag()];
+    let mut encoder: Encoder<&mut Vec<u8>> = Encoder::new(&mut buff);
+    encoder
+        .u32(protocol_magic)
+        .and_then(|e| e.bytes(tx_hash.as_ref()))
+        .map_err(|_| ValidationError::UnableToProcessWitnesses)?;
+    Ok(buff)
+}
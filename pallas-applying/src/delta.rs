@@ -0,0 +1,88 @@
+//! Changes made by blocks to the UTxO set.
+
+use std::collections::HashMap;
+
+use pallas_traverse::{MultiEraTx, OutputRef};
+use thiserror::Error;
+
+use crate::utxo::{output_ref, EraCbor, UtxoProvider};
+
+#[derive(Debug, Error)]
+pub enum ApplyError<E> {
+    #[error("input {0} is not in the UTxO set")]
+    InputNotInUTxO(OutputRef),
+
+    #[error("unable to resolve input {0}")]
+    Provider(OutputRef, #[source] E),
+}
+
+/// The outputs removed from and added to the UTxO set
+///
+/// Consumed inputs are kept together with the outputs they resolved to, so
+/// that the [inverse](UtxoDelta::inverse) of a delta restores them. Outputs
+/// both produced and consumed within the same block are left out of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UtxoDelta {
+    pub consumed: HashMap<OutputRef, EraCbor>,
+    pub produced: HashMap<OutputRef, EraCbor>,
+}
+
+impl UtxoDelta {
+    /// Adds the effects of a tx applied after the ones already in the delta
+    ///
+    /// Valid txs consume their inputs and produce their outputs, while txs
+    /// failing phase-2 validation consume their collateral and produce their
+    /// collateral return output, if any.
+    pub fn add_tx<P: UtxoProvider>(
+        &mut self,
+        tx: &MultiEraTx,
+        provider: &P,
+    ) -> Result<(), ApplyError<P::Error>> {
+        for input in tx.consumes() {
+            let Some(output_ref) = output_ref(&input) else {
+                continue;
+            };
+
+            if self.produced.remove(&output_ref).is_some() {
+                continue;
+            }
+
+            if self.consumed.contains_key(&output_ref) {
+                return Err(ApplyError::InputNotInUTxO(output_ref));
+            }
+
+            match provider.resolve(&output_ref) {
+                Ok(Some(output)) => self.consumed.insert(output_ref, output),
+                Ok(None) => return Err(ApplyError::InputNotInUTxO(output_ref)),
+                Err(err) => return Err(ApplyError::Provider(output_ref, err)),
+            };
+        }
+
+        let tx_hash = tx.hash();
+
+        for (index, output) in tx.produces() {
+            let output_ref = OutputRef::new(tx_hash, index as u64);
+            self.produced
+                .insert(output_ref, EraCbor(tx.era(), output.encode()));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the delta undoing this one
+    pub fn inverse(&self) -> Self {
+        Self {
+            consumed: self.produced.clone(),
+            produced: self.consumed.clone(),
+        }
+    }
+
+    /// Applies the delta to an in-memory UTxO set
+    pub fn apply_to(&self, utxos: &mut HashMap<OutputRef, EraCbor>) {
+        for output_ref in self.consumed.keys() {
+            utxos.remove(output_ref);
+        }
+
+        utxos.extend(self.produced.clone());
+    }
+}
@@ -1,24 +1,133 @@
 //! Logic for validating and applying new blocks and txs to the chain state
 
+pub mod alonzo;
+pub mod babbage;
 pub mod byron;
+pub mod delta;
+pub mod min_lovelace;
+pub mod params;
+pub mod shelley_ma;
 pub mod types;
+mod utils;
+pub mod utxo;
 
+use alonzo::validate_alonzo_tx;
+use babbage::validate_babbage_tx;
 use byron::validate_byron_tx;
+use pallas_traverse::{Era, MultiEraBlock, MultiEraTx};
+use shelley_ma::validate_shelley_ma_tx;
 
-use pallas_traverse::{MultiEraTx, MultiEraTx::Byron as ByronTxPayload};
+pub use delta::{ApplyError, UtxoDelta};
+pub use min_lovelace::min_lovelace;
+pub use utxo::{EraCbor, ResolvedUtxos, UtxoProvider};
 
 pub use types::{
-    MultiEraProtParams, MultiEraProtParams::Byron as ByronProtParams, UTxOs, ValidationResult,
+    Environment, Failures, MultiEraProtParams, UTxOs, ValidationError, ValidationMode,
+    ValidationResult,
 };
 
-pub fn validate(
+/// Validates a tx, stopping at the first rule it breaks
+pub fn validate(metx: &MultiEraTx, utxos: &UTxOs, env: &Environment) -> ValidationResult {
+    validate_with(
+        metx,
+        utxos,
+        env,
+        &mut Failures::new(ValidationMode::FailFast),
+    )
+}
+
+/// Validates a tx against every rule, gathering all the failures found
+///
+/// Rules that depend on the outcome of a broken one are skipped rather than
+/// reported as broken too. For instance, the preservation of value is not
+/// checked when some input is missing from the UTxO set.
+pub fn validate_all(
+    metx: &MultiEraTx,
+    utxos: &UTxOs,
+    env: &Environment,
+) -> Result<(), Vec<ValidationError>> {
+    let mut failures = Failures::new(ValidationMode::AccumulateAll);
+    // Failures that prevent validation from going on are returned right away,
+    // and come after the ones gathered so far.
+    let res: ValidationResult = validate_with(metx, utxos, env, &mut failures);
+    let mut errors: Vec<ValidationError> = failures.into_errors();
+    errors.extend(res.err());
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+/// Computes the changes made by a block to the UTxO set
+///
+/// The outputs consumed by the block are resolved through `provider`, which is
+/// expected to hold the UTxO set right before the block. Txs are not
+/// validated.
+pub fn apply_block<P: UtxoProvider>(
+    block: &MultiEraBlock,
+    provider: &P,
+) -> Result<UtxoDelta, ApplyError<P::Error>> {
+    let mut delta = UtxoDelta::default();
+
+    for tx in block.txs() {
+        delta.add_tx(&tx, provider)?;
+    }
+
+    Ok(delta)
+}
+
+fn validate_with(
     metx: &MultiEraTx,
     utxos: &UTxOs,
-    prot_pps: &MultiEraProtParams,
+    env: &Environment,
+    failures: &mut Failures,
 ) -> ValidationResult {
-    match (metx, prot_pps) {
-        (ByronTxPayload(mtxp), ByronProtParams(bpp)) => validate_byron_tx(mtxp, utxos, bpp),
-        // TODO: implement the rest of the eras.
-        _ => Ok(()),
+    match (metx, &env.prot_params) {
+        (MultiEraTx::Byron(mtxp), MultiEraProtParams::Byron(bpp)) => {
+            validate_byron_tx(mtxp, utxos, bpp, failures)
+        }
+        (MultiEraTx::AlonzoCompatible(mtx, era), MultiEraProtParams::Shelley(spp))
+            if matches!(era, Era::Shelley | Era::Allegra | Era::Mary) =>
+        {
+            validate_shelley_ma_tx(
+                mtx,
+                utxos,
+                spp,
+                env.block_slot,
+                env.network_id,
+                &env.registered_pools,
+                era,
+                failures,
+            )
+        }
+        (MultiEraTx::AlonzoCompatible(mtx, Era::Alonzo), MultiEraProtParams::Alonzo(app)) => {
+            validate_alonzo_tx(
+                mtx,
+                utxos,
+                app,
+                env.block_slot,
+                env.network_id,
+                &env.registered_pools,
+                failures,
+            )
+        }
+        (MultiEraTx::Babbage(mtx), MultiEraProtParams::Babbage(bpp)) => validate_babbage_tx(
+            mtx,
+            utxos,
+            bpp,
+            env.block_slot,
+            env.network_id,
+            &env.registered_pools,
+            failures,
+        ),
+        (MultiEraTx::Byron(_), _) => failures.report(ValidationError::TxAndProtParamsDiffer),
+        (MultiEraTx::AlonzoCompatible(..), _) => {
+            failures.report(ValidationError::TxAndProtParamsDiffer)
+        }
+        (MultiEraTx::Babbage(_), _) => failures.report(ValidationError::TxAndProtParamsDiffer),
+        // TODO: implement the rules of Conway.
+        (MultiEraTx::Conway(_), _) => failures.report(ValidationError::UnsupportedEra(Era::Conway)),
+        _ => failures.report(ValidationError::UnsupportedEra(metx.era())),
     }
 }
@@ -0,0 +1,133 @@
+//! Minimum amount of lovelace that outputs must hold in each era.
+//!
+//! The functions taking era-specific outputs are meant for outputs still
+//! being built, while [min_lovelace] works on outputs decoded from txs.
+
+use std::collections::HashSet;
+
+use pallas_codec::minicbor::{self, Encode};
+use pallas_primitives::alonzo::{Coin, Multiasset, TransactionOutput, Value};
+use pallas_traverse::MultiEraOutput;
+
+use crate::types::{
+    AlonzoProtParams, BabbageProtParams, ConwayProtParams, MultiEraProtParams, ShelleyProtParams,
+};
+
+/// Size of a UTxO entry without its value, in 8-byte words
+const UTXO_ENTRY_SIZE_WITHOUT_VAL: u64 = 27;
+
+/// Returns the minimum lovelace of an output under the given protocol
+/// parameters, or `None` if these are from an era the output can't belong to
+///
+/// Byron outputs have no minimum.
+pub fn min_lovelace(output: &MultiEraOutput, prot_params: &MultiEraProtParams) -> Option<u64> {
+    match (prot_params, output) {
+        (MultiEraProtParams::Byron(_), MultiEraOutput::Byron(_)) => Some(0),
+        (MultiEraProtParams::Shelley(pps), MultiEraOutput::AlonzoCompatible(x)) => {
+            Some(shelley_ma_min_lovelace(&x.amount, pps))
+        }
+        (MultiEraProtParams::Alonzo(pps), MultiEraOutput::AlonzoCompatible(x)) => {
+            Some(alonzo_min_lovelace(x, pps))
+        }
+        (MultiEraProtParams::Babbage(pps), MultiEraOutput::AlonzoCompatible(x)) => {
+            Some(babbage_min_lovelace(x, pps))
+        }
+        (MultiEraProtParams::Babbage(pps), MultiEraOutput::Babbage(x)) => {
+            Some(babbage_min_lovelace(x, pps))
+        }
+        (MultiEraProtParams::Conway(pps), MultiEraOutput::AlonzoCompatible(x)) => {
+            Some(conway_min_lovelace(x, pps))
+        }
+        (MultiEraProtParams::Conway(pps), MultiEraOutput::Babbage(x)) => {
+            Some(conway_min_lovelace(x, pps))
+        }
+        (MultiEraProtParams::Conway(pps), MultiEraOutput::Conway(x)) => {
+            Some(conway_min_lovelace(x, pps))
+        }
+        _ => None,
+    }
+}
+
+/// Minimum lovelace of an output holding the given value in the Shelley,
+/// Allegra and Mary eras
+///
+/// This is the `minUTxOValue` protocol parameter, except for Mary outputs
+/// with native assets, for which it scales with the size of the asset bundle.
+pub fn shelley_ma_min_lovelace(value: &Value, prot_pps: &ShelleyProtParams) -> u64 {
+    let multiasset = match value {
+        Value::Multiasset(_, x) if !x.is_empty() => x,
+        _ => return prot_pps.min_utxo_value,
+    };
+
+    // Lovelace take no room in Mary, so the price of a word is derived from
+    // the size of an entry without value.
+    let ada_per_utxo_word: u64 = prot_pps.min_utxo_value / UTXO_ENTRY_SIZE_WITHOUT_VAL;
+
+    prot_pps
+        .min_utxo_value
+        .max(ada_per_utxo_word * (UTXO_ENTRY_SIZE_WITHOUT_VAL + bundle_size(multiasset)))
+}
+
+/// Minimum lovelace of an output in the Alonzo era, proportional to its size
+/// in 8-byte words, which accounts for the multi-asset bundle and the datum
+/// hash
+pub fn alonzo_min_lovelace(output: &TransactionOutput, prot_pps: &AlonzoProtParams) -> u64 {
+    const COIN_SIZE: u64 = 2;
+    const DATA_HASH_SIZE: u64 = 10;
+
+    let value_size: u64 = match &output.amount {
+        Value::Multiasset(_, multiasset) if !multiasset.is_empty() => bundle_size(multiasset),
+        _ => COIN_SIZE,
+    };
+
+    let data_hash_size: u64 = match output.datum_hash {
+        Some(_) => DATA_HASH_SIZE,
+        None => 0,
+    };
+
+    prot_pps.coins_per_utxo_word * (UTXO_ENTRY_SIZE_WITHOUT_VAL + value_size + data_hash_size)
+}
+
+/// Minimum lovelace of an output in the Babbage era, proportional to its
+/// serialized size plus a constant overhead accounting for the UTxO entry it
+/// will become
+///
+/// Any encodable output is accepted, be it decoded from a tx or being built.
+/// Note that the size depends on the lovelace held by the output, since
+/// larger amounts take more bytes to encode.
+pub fn babbage_min_lovelace<T: Encode<()>>(output: &T, prot_pps: &BabbageProtParams) -> u64 {
+    size_based_min_lovelace(output, prot_pps.ada_per_utxo_byte)
+}
+
+/// Minimum lovelace of an output in the Conway era, computed as in Babbage
+/// from the `coinsPerUTxOByte` protocol parameter
+pub fn conway_min_lovelace<T: Encode<()>>(output: &T, prot_pps: &ConwayProtParams) -> u64 {
+    size_based_min_lovelace(output, prot_pps.ada_per_utxo_byte)
+}
+
+fn size_based_min_lovelace<T: Encode<()>>(output: &T, ada_per_utxo_byte: u64) -> u64 {
+    const UTXO_ENTRY_OVERHEAD: u64 = 160;
+
+    // to_vec is infallible
+    let output_size: u64 = minicbor::to_vec(output).unwrap().len() as u64;
+
+    ada_per_utxo_byte * (UTXO_ENTRY_OVERHEAD + output_size)
+}
+
+// Size of a multi-asset bundle in 8-byte words, where each distinct asset name
+// counts once no matter how many policies use it.
+fn bundle_size(multiasset: &Multiasset<Coin>) -> u64 {
+    const POLICY_ID_SIZE: u64 = 28;
+
+    let num_policies: u64 = multiasset.len() as u64;
+    let num_assets: u64 = multiasset.iter().map(|(_, x)| x.len() as u64).sum();
+    let asset_names: HashSet<&[u8]> = multiasset
+        .iter()
+        .flat_map(|(_, assets)| assets.iter().map(|(name, _)| name.as_slice()))
+        .collect();
+    let sum_asset_name_lengths: u64 = asset_names.iter().map(|x| x.len() as u64).sum();
+
+    let bytes: u64 = num_assets * 12 + sum_asset_name_lengths + num_policies * POLICY_ID_SIZE;
+
+    6 + bytes.div_ceil(8)
+}
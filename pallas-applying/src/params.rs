@@ -0,0 +1,705 @@
+//! Protocol parameters in effect across epochs
+//!
+//! Parameters change through update proposals submitted by the genesis
+//! delegates. [ParamsTracker] starts from the parameters in the genesis files,
+//! collects the proposals found in the blocks it is fed with, and applies them
+//! at the epoch boundary once enough genesis delegates agree on them, keeping
+//! the parameters in effect for every epoch seen so far.
+//!
+//! Byron proposals follow a process of their own, driven by the thresholds of
+//! the Byron parameters. A proposal is confirmed once its votes reach the
+//! vote threshold, or expires if it is not confirmed within the implicit
+//! agreement period. A confirmed proposal is adopted at an epoch boundary once
+//! the block issuers endorsing its protocol version reach the threshold of the
+//! softfork rule. As in the ledger, that threshold starts at its initial value
+//! and drops by its decrement with every epoch since the last adoption, down
+//! to its minimum. The ledger weighs votes and endorsements by stake, whereas
+//! this module weighs the distinct genesis delegates casting them evenly,
+//! which is what the stake of the Byron chain amounted to. Parameter changes
+//! made by Conway governance actions are not tracked.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    num::ParseIntError,
+};
+
+use pallas_configs::{byron, shelley};
+use pallas_crypto::hash::{Hash, Hasher};
+use pallas_primitives::{
+    alonzo::{
+        self, ExUnitPrices, ExUnits, Genesishash, Language, Nonce, NonceVariant, ProtocolVersion,
+        RationalNumber, UnitInterval,
+    },
+    babbage,
+    byron::{BVer, BVerMod, TxFeePol, UpdId},
+};
+use pallas_traverse::{Era, MultiEraBlock};
+use thiserror::Error;
+
+use crate::types::{
+    AlonzoProtParams, BabbageProtParams, ByronProtParams, FeePolicy, MultiEraProtParams,
+    ShelleyProtParams, SoftforkRule,
+};
+
+#[derive(Debug, Error)]
+pub enum ParamsError {
+    #[error("block from epoch {block} comes after blocks from epoch {current}")]
+    PastEpoch { block: u64, current: u64 },
+    #[error("no initial protocol parameters for the {0} era")]
+    MissingEraParams(Era),
+}
+
+/// Protocol parameters of the eras from Shelley onwards
+///
+/// The parameters introduced by Alonzo are unset up to then. They come from
+/// the Alonzo genesis file rather than from an update, so they are to be set
+/// on the hard fork through [ParamsTracker::override_current].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolParams {
+    pub minfee_a: u32,
+    pub minfee_b: u32,
+    pub max_block_body_size: u32,
+    pub max_transaction_size: u32,
+    pub max_block_header_size: u32,
+    pub key_deposit: u64,
+    pub pool_deposit: u64,
+    pub maximum_epoch: u64,
+    pub desired_number_of_stake_pools: u32,
+    pub pool_pledge_influence: RationalNumber,
+    pub expansion_rate: UnitInterval,
+    pub treasury_growth_rate: UnitInterval,
+    pub decentralization_constant: UnitInterval,
+    pub extra_entropy: Nonce,
+    pub protocol_version: ProtocolVersion,
+    pub min_utxo_value: u64,
+    pub min_pool_cost: u64,
+    /// Lovelace per UTxO word up to Alonzo, and per UTxO byte from Babbage
+    /// onwards
+    pub ada_per_utxo_byte: u64,
+    pub cost_models_for_script_languages: babbage::CostMdls,
+    pub execution_costs: Option<ExUnitPrices>,
+    pub max_tx_ex_units: Option<ExUnits>,
+    pub max_block_ex_units: Option<ExUnits>,
+    pub max_value_size: Option<u32>,
+    pub collateral_percentage: Option<u32>,
+    pub max_collateral_inputs: Option<u32>,
+}
+
+fn set<T: Clone>(param: &mut T, update: &Option<T>) {
+    if let Some(x) = update {
+        *param = x.clone();
+    }
+}
+
+fn set_some<T: Clone>(param: &mut Option<T>, update: &Option<T>) {
+    if update.is_some() {
+        param.clone_from(update);
+    }
+}
+
+// Whether the given number of genesis delegates out of all of them holds the
+// given portion of the stake, in units of 10^-15.
+fn reaches_threshold(count: usize, delegates: usize, threshold: u64) -> bool {
+    count as u128 * 1_000_000_000_000_000 >= delegates as u128 * threshold as u128
+}
+
+// Genesis files hold rationals as decimal numbers, which are read back as the
+// fraction they stand for.
+fn decimal_to_rational(x: f64) -> RationalNumber {
+    let mut denominator: u64 = 1;
+
+    while (x * denominator as f64 - (x * denominator as f64).round()).abs() > 1e-9
+        && denominator < 1_000_000_000_000
+    {
+        denominator *= 10;
+    }
+
+    let numerator = (x * denominator as f64).round() as u64;
+
+    let (mut a, mut b) = (numerator, denominator);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    RationalNumber {
+        numerator: numerator / a.max(1),
+        denominator: denominator / a.max(1),
+    }
+}
+
+impl ProtocolParams {
+    /// Builds the protocol parameters in effect at the start of the Shelley
+    /// era
+    pub fn from_shelley_genesis(genesis: &shelley::GenesisFile) -> Self {
+        let params = &genesis.protocol_params;
+
+        let extra_entropy = match params.extra_entropy.tag.as_str() {
+            "NeutralNonce" => None,
+            _ => params.extra_entropy.contents.as_deref(),
+        }
+        .and_then(|x| x.parse::<Hash<32>>().ok());
+
+        Self {
+            minfee_a: params.min_fee_a,
+            minfee_b: params.min_fee_b,
+            max_block_body_size: params.max_block_body_size,
+            max_transaction_size: params.max_tx_size,
+            max_block_header_size: params.max_block_header_size,
+            key_deposit: params.key_deposit,
+            pool_deposit: params.pool_deposit,
+            maximum_epoch: params.e_max,
+            desired_number_of_stake_pools: params.n_opt,
+            pool_pledge_influence: decimal_to_rational(params.a0),
+            expansion_rate: decimal_to_rational(params.rho),
+            treasury_growth_rate: decimal_to_rational(params.tau),
+            decentralization_constant: decimal_to_rational(params.decentralisation_param),
+            extra_entropy: Nonce {
+                variant: match extra_entropy {
+                    Some(_) => NonceVariant::Nonce,
+                    None => NonceVariant::NeutralNonce,
+                },
+                hash: extra_entropy,
+            },
+            protocol_version: (params.protocol_version.major, params.protocol_version.minor),
+            min_utxo_value: params.min_utxo_value,
+            min_pool_cost: params.min_pool_cost,
+            ada_per_utxo_byte: 0,
+            cost_models_for_script_languages: babbage::CostMdls {
+                plutus_v1: None,
+                plutus_v2: None,
+            },
+            execution_costs: None,
+            max_tx_ex_units: None,
+            max_block_ex_units: None,
+            max_value_size: None,
+            collateral_percentage: None,
+            max_collateral_inputs: None,
+        }
+    }
+
+    /// Applies an update proposed in any of the eras from Shelley to Alonzo
+    ///
+    /// The cost models of the languages in the update replace the current
+    /// ones, while the other languages keep theirs.
+    pub fn apply_alonzo_update(&mut self, update: &alonzo::ProtocolParamUpdate) {
+        set(&mut self.minfee_a, &update.minfee_a);
+        set(&mut self.minfee_b, &update.minfee_b);
+        set(&mut self.max_block_body_size, &update.max_block_body_size);
+        set(&mut self.max_transaction_size, &update.max_transaction_size);
+        set(
+            &mut self.max_block_header_size,
+            &update.max_block_header_size,
+        );
+        set(&mut self.key_deposit, &update.key_deposit);
+        set(&mut self.pool_deposit, &update.pool_deposit);
+        set(&mut self.maximum_epoch, &update.maximum_epoch);
+        set(
+            &mut self.desired_number_of_stake_pools,
+            &update.desired_number_of_stake_pools,
+        );
+        set(
+            &mut self.pool_pledge_influence,
+            &update.pool_pledge_influence,
+        );
+        set(&mut self.expansion_rate, &update.expansion_rate);
+        set(&mut self.treasury_growth_rate, &update.treasury_growth_rate);
+        set(
+            &mut self.decentralization_constant,
+            &update.decentralization_constant,
+        );
+        set(&mut self.extra_entropy, &update.extra_entropy);
+        set(&mut self.min_utxo_value, &update.min_utxo_value);
+        set(&mut self.min_pool_cost, &update.min_pool_cost);
+        set(&mut self.ada_per_utxo_byte, &update.ada_per_utxo_byte);
+        set_some(&mut self.execution_costs, &update.execution_costs);
+        set_some(&mut self.max_tx_ex_units, &update.max_tx_ex_units);
+        set_some(&mut self.max_block_ex_units, &update.max_block_ex_units);
+        set_some(&mut self.max_value_size, &update.max_value_size);
+        set_some(
+            &mut self.collateral_percentage,
+            &update.collateral_percentage,
+        );
+        set_some(
+            &mut self.max_collateral_inputs,
+            &update.max_collateral_inputs,
+        );
+
+        if let Some(cost_models) = &update.cost_models_for_script_languages {
+            for (language, cost_model) in cost_models.iter() {
+                match language {
+                    Language::PlutusV1 => {
+                        self.cost_models_for_script_languages.plutus_v1 = Some(cost_model.clone())
+                    }
+                }
+            }
+        }
+
+        self.set_protocol_version(update.protocol_version, update.ada_per_utxo_byte);
+    }
+
+    /// Applies an update proposed in the Babbage era
+    ///
+    /// The cost models of the languages in the update replace the current
+    /// ones, while the other languages keep theirs.
+    pub fn apply_babbage_update(&mut self, update: &babbage::ProtocolParamUpdate) {
+        set(&mut self.minfee_a, &update.minfee_a);
+        set(&mut self.minfee_b, &update.minfee_b);
+        set(&mut self.max_block_body_size, &update.max_block_body_size);
+        set(&mut self.max_transaction_size, &update.max_transaction_size);
+        set(
+            &mut self.max_block_header_size,
+            &update.max_block_header_size,
+        );
+        set(&mut self.key_deposit, &update.key_deposit);
+        set(&mut self.pool_deposit, &update.pool_deposit);
+        set(&mut self.maximum_epoch, &update.maximum_epoch);
+        set(
+            &mut self.desired_number_of_stake_pools,
+            &update.desired_number_of_stake_pools,
+        );
+        set(
+            &mut self.pool_pledge_influence,
+            &update.pool_pledge_influence,
+        );
+        set(&mut self.expansion_rate, &update.expansion_rate);
+        set(&mut self.treasury_growth_rate, &update.treasury_growth_rate);
+        set(&mut self.min_pool_cost, &update.min_pool_cost);
+        set(&mut self.ada_per_utxo_byte, &update.ada_per_utxo_byte);
+        set_some(&mut self.execution_costs, &update.execution_costs);
+        set_some(&mut self.max_tx_ex_units, &update.max_tx_ex_units);
+        set_some(&mut self.max_block_ex_units, &update.max_block_ex_units);
+        set_some(&mut self.max_value_size, &update.max_value_size);
+        set_some(
+            &mut self.collateral_percentage,
+            &update.collateral_percentage,
+        );
+        set_some(
+            &mut self.max_collateral_inputs,
+            &update.max_collateral_inputs,
+        );
+
+        if let Some(cost_models) = &update.cost_models_for_script_languages {
+            let current = &mut self.cost_models_for_script_languages;
+            set_some(&mut current.plutus_v1, &cost_models.plutus_v1);
+            set_some(&mut current.plutus_v2, &cost_models.plutus_v2);
+        }
+
+        self.set_protocol_version(update.protocol_version, update.ada_per_utxo_byte);
+    }
+
+    // The hard fork into Babbage turns the lovelace per UTxO word into
+    // lovelace per UTxO byte, unless the update sets the latter itself.
+    fn set_protocol_version(&mut self, version: Option<ProtocolVersion>, per_utxo: Option<u64>) {
+        if let Some(version) = version {
+            if self.protocol_version.0 < 7 && version.0 >= 7 && per_utxo.is_none() {
+                self.ada_per_utxo_byte /= 8;
+            }
+
+            self.protocol_version = version;
+        }
+    }
+
+    pub fn to_shelley_params(&self) -> ShelleyProtParams {
+        ShelleyProtParams {
+            min_fee_a: self.minfee_a as u64,
+            min_fee_b: self.minfee_b as u64,
+            max_tx_size: self.max_transaction_size as u64,
+            min_utxo_value: self.min_utxo_value,
+            key_deposit: self.key_deposit,
+            pool_deposit: self.pool_deposit,
+        }
+    }
+
+    /// Returns `None` if any of the parameters introduced by Alonzo is unset
+    pub fn to_alonzo_params(&self) -> Option<AlonzoProtParams> {
+        let cost_models = match &self.cost_models_for_script_languages.plutus_v1 {
+            Some(x) => vec![(Language::PlutusV1, x.clone())],
+            None => vec![],
+        };
+
+        Some(AlonzoProtParams {
+            min_fee_a: self.minfee_a as u64,
+            min_fee_b: self.minfee_b as u64,
+            max_tx_size: self.max_transaction_size as u64,
+            max_value_size: self.max_value_size? as u64,
+            coins_per_utxo_word: self.ada_per_utxo_byte,
+            key_deposit: self.key_deposit,
+            pool_deposit: self.pool_deposit,
+            collateral_percentage: self.collateral_percentage? as u64,
+            max_collateral_inputs: self.max_collateral_inputs? as u64,
+            max_tx_ex_units: self.max_tx_ex_units.clone()?,
+            ex_unit_prices: self.execution_costs.clone()?,
+            cost_models_for_script_languages: cost_models.into(),
+        })
+    }
+
+    /// Returns `None` if any of the parameters introduced by Alonzo is unset
+    pub fn to_babbage_params(&self) -> Option<BabbageProtParams> {
+        Some(BabbageProtParams {
+            min_fee_a: self.minfee_a as u64,
+            min_fee_b: self.minfee_b as u64,
+            max_tx_size: self.max_transaction_size as u64,
+            max_value_size: self.max_value_size? as u64,
+            ada_per_utxo_byte: self.ada_per_utxo_byte,
+            key_deposit: self.key_deposit,
+            pool_deposit: self.pool_deposit,
+            collateral_percentage: self.collateral_percentage? as u64,
+            max_collateral_inputs: self.max_collateral_inputs? as u64,
+            max_tx_ex_units: self.max_tx_ex_units.clone()?,
+            ex_unit_prices: self.execution_costs.clone()?,
+            cost_models_for_script_languages: self.cost_models_for_script_languages.clone(),
+        })
+    }
+}
+
+impl ByronProtParams {
+    /// Applies the parameter changes of an adopted Byron proposal
+    pub fn apply_update(&mut self, update: &BVerMod) {
+        if let Some(x) = *update.script_version {
+            self.script_version = x as u32;
+        }
+
+        set(&mut self.slot_duration, &*update.slot_duration);
+        set(&mut self.max_block_size, &*update.max_block_size);
+        set(&mut self.max_tx_size, &*update.max_tx_size);
+        set(&mut self.update_vote_thd, &*update.update_vote_thd);
+        set(&mut self.update_implicit, &*update.update_implicit);
+
+        if let Some((init_thd, min_thd, thd_decrement)) = *update.soft_fork_rule {
+            self.softfork_rule = SoftforkRule {
+                init_thd,
+                min_thd,
+                thd_decrement,
+            };
+        }
+
+        if let Some(TxFeePol::Variant0(policy)) = &*update.tx_fee_policy {
+            let (summand, multiplier) = policy.0;
+
+            self.fee_policy = FeePolicy {
+                constant: summand as u64,
+                size_coeficient: multiplier as u64,
+            };
+        }
+    }
+}
+
+/// Protocol parameters in effect during an epoch
+#[derive(Debug, Clone, PartialEq)]
+pub enum EraParams {
+    Byron(ByronProtParams),
+    Shelley(Box<ProtocolParams>),
+}
+
+impl EraParams {
+    /// The parameters as expected by the validation of txs, chosen by the
+    /// major protocol version
+    ///
+    /// Returns `None` for protocol versions that validation doesn't support
+    /// yet, and for Alonzo onwards if any of the parameters introduced by
+    /// Alonzo is unset.
+    pub fn to_prot_params(&self) -> Option<MultiEraProtParams> {
+        match self {
+            EraParams::Byron(x) => Some(MultiEraProtParams::Byron(Box::new(Cow::Borrowed(x)))),
+            EraParams::Shelley(x) => match x.protocol_version.0 {
+                2..=4 => Some(MultiEraProtParams::Shelley(Box::new(Cow::Owned(
+                    x.to_shelley_params(),
+                )))),
+                5..=6 => Some(MultiEraProtParams::Alonzo(Box::new(Cow::Owned(
+                    x.to_alonzo_params()?,
+                )))),
+                7..=8 => Some(MultiEraProtParams::Babbage(Box::new(Cow::Owned(
+                    x.to_babbage_params()?,
+                )))),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ParamsUpdate {
+    Alonzo(alonzo::ProtocolParamUpdate),
+    Babbage(babbage::ProtocolParamUpdate),
+}
+
+#[derive(Debug, Clone)]
+struct ByronProposal {
+    block_version: Option<BVer>,
+    block_version_mod: Option<BVerMod>,
+    /// Slot of the block registering the proposal
+    slot: u64,
+    confirmed: bool,
+    votes: BTreeSet<Vec<u8>>,
+    endorsements: BTreeSet<Vec<u8>>,
+}
+
+/// Folds the update proposals found in blocks into the protocol parameters in
+/// effect for each epoch
+///
+/// Blocks are to be fed in chain order, along with the epoch they belong to,
+/// which [pallas_traverse::history::EraHistory] can tell from their slot.
+#[derive(Debug, Clone)]
+pub struct ParamsTracker {
+    genesis_delegates: usize,
+    quorum: usize,
+    epoch: u64,
+    /// Parameters by the first epoch they are in effect, the last ones being
+    /// the current ones
+    history: Vec<(u64, EraParams)>,
+    shelley_genesis: Option<ProtocolParams>,
+    /// Proposals by the epoch they target, and then by genesis key hash
+    proposals: BTreeMap<u64, BTreeMap<Genesishash, ParamsUpdate>>,
+    byron_proposals: HashMap<UpdId, ByronProposal>,
+    /// Epoch from which the last adopted Byron proposal is in effect
+    byron_adoption: u64,
+}
+
+impl ParamsTracker {
+    /// Starts tracking from the given parameters, in effect from epoch 0
+    ///
+    /// Byron proposals are weighed against the stake of `genesis_delegates`
+    /// delegates, while later proposals are applied once `quorum` genesis
+    /// delegates agree on them.
+    pub fn new(genesis: EraParams, genesis_delegates: usize, quorum: usize) -> Self {
+        Self {
+            genesis_delegates,
+            quorum,
+            epoch: 0,
+            history: vec![(0, genesis)],
+            shelley_genesis: None,
+            proposals: BTreeMap::new(),
+            byron_proposals: HashMap::new(),
+            byron_adoption: 0,
+        }
+    }
+
+    /// Starts tracking from the Byron genesis parameters, switching to the
+    /// Shelley ones on the first Shelley block
+    pub fn from_genesis(
+        byron: &byron::GenesisFile,
+        shelley: &shelley::GenesisFile,
+    ) -> Result<Self, ParseIntError> {
+        let genesis = EraParams::Byron(ByronProtParams::from_genesis(byron)?);
+
+        Ok(Self {
+            shelley_genesis: Some(ProtocolParams::from_shelley_genesis(shelley)),
+            ..Self::new(
+                genesis,
+                byron.heavy_delegation.len(),
+                shelley.update_quorum as usize,
+            )
+        })
+    }
+
+    /// The epoch of the last block applied
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The parameters in effect during the current epoch
+    pub fn current(&self) -> &EraParams {
+        // history is never empty
+        &self.history.last().unwrap().1
+    }
+
+    /// The parameters in effect during the given epoch, or `None` if it is
+    /// past the current one
+    pub fn params_for(&self, epoch: u64) -> Option<&EraParams> {
+        if epoch > self.epoch {
+            return None;
+        }
+
+        self.history
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= epoch)
+            .map(|(_, x)| x)
+    }
+
+    /// Replaces the parameters of the current epoch
+    ///
+    /// This is meant for hard forks that take new parameters from a genesis
+    /// file, such as the Alonzo one.
+    pub fn override_current(&mut self, params: EraParams) {
+        match self.history.last_mut() {
+            Some((start, x)) if *start == self.epoch => *x = params,
+            _ => self.history.push((self.epoch, params)),
+        }
+    }
+
+    /// Collects the proposals, votes and endorsements found in a block of the
+    /// given epoch, applying the ones that are due at any epoch boundary in
+    /// between
+    pub fn apply_block(&mut self, block: &MultiEraBlock, epoch: u64) -> Result<(), ParamsError> {
+        if epoch < self.epoch {
+            return Err(ParamsError::PastEpoch {
+                block: epoch,
+                current: self.epoch,
+            });
+        }
+
+        while self.epoch < epoch {
+            self.end_epoch();
+        }
+
+        if block.era() != Era::Byron && matches!(self.current(), EraParams::Byron(_)) {
+            let params = self
+                .shelley_genesis
+                .take()
+                .ok_or(ParamsError::MissingEraParams(block.era()))?;
+
+            self.override_current(EraParams::Shelley(Box::new(params)));
+        }
+
+        if let Some(byron) = block.as_byron() {
+            self.collect_byron(byron, block.slot());
+        }
+
+        for tx in block.txs().iter().filter(|x| x.is_valid()) {
+            let updates = match (tx.as_alonzo(), tx.as_babbage()) {
+                (Some(x), _) => x.transaction_body.update.as_ref().map(|x| {
+                    let updates = x.proposed_protocol_parameter_updates.iter();
+                    let updates =
+                        updates.map(|(k, v)| (k.clone(), ParamsUpdate::Alonzo(v.clone())));
+                    (x.epoch, updates.collect::<Vec<_>>())
+                }),
+                (_, Some(x)) => x.transaction_body.update.as_ref().map(|x| {
+                    let updates = x.proposed_protocol_parameter_updates.iter();
+                    let updates =
+                        updates.map(|(k, v)| (k.clone(), ParamsUpdate::Babbage(v.clone())));
+                    (x.epoch, updates.collect::<Vec<_>>())
+                }),
+                _ => None,
+            };
+
+            // a later proposal by the same genesis key replaces the earlier one
+            if let Some((target, updates)) = updates {
+                self.proposals.entry(target).or_default().extend(updates);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn collect_byron(&mut self, block: &pallas_primitives::byron::MintedBlock, slot: u64) {
+        let (vote_thd, implicit) = match self.current() {
+            EraParams::Byron(x) => (x.update_vote_thd, x.update_implicit),
+            EraParams::Shelley(_) => return,
+        };
+
+        let delegates = self.genesis_delegates;
+        let payload = &block.body.upd_payload;
+
+        // unconfirmed proposals expire once the implicit agreement period is
+        // over
+        self.byron_proposals
+            .retain(|_, x| x.confirmed || slot <= x.slot + implicit);
+
+        if let Some(proposal) = &*payload.proposal {
+            // the id hashes the proposal as found in the block
+            let id = Hasher::<256>::hash(proposal.raw_cbor());
+
+            // the proposer implicitly votes for its own proposal
+            let votes = proposal.from.iter().map(|x| x.to_vec()).collect();
+
+            self.byron_proposals.entry(id).or_insert(ByronProposal {
+                block_version: proposal.block_version,
+                block_version_mod: proposal.block_version_mod.clone(),
+                slot,
+                confirmed: false,
+                votes,
+                endorsements: BTreeSet::new(),
+            });
+        }
+
+        for vote in payload.votes.iter() {
+            if let Some(proposal) = self.byron_proposals.get_mut(&vote.proposal_id) {
+                match vote.vote {
+                    true => proposal.votes.insert(vote.voter.to_vec()),
+                    false => proposal.votes.remove(&vote.voter.to_vec()),
+                };
+            }
+        }
+
+        // votes cast once a proposal is confirmed no longer matter
+        for proposal in self.byron_proposals.values_mut() {
+            proposal.confirmed |= reaches_threshold(proposal.votes.len(), delegates, vote_thd);
+        }
+
+        // issuers endorse the protocol version of confirmed proposals by
+        // announcing it in the header of their blocks
+        let issuer = &block.header.consensus_data.1;
+        let version = block.header.extra_data.block_version;
+
+        for proposal in self.byron_proposals.values_mut() {
+            if proposal.confirmed && proposal.block_version == Some(version) {
+                proposal.endorsements.insert(issuer.to_vec());
+            }
+        }
+    }
+
+    fn end_epoch(&mut self) {
+        let mut params = self.current().clone();
+        let quorum = self.quorum;
+        let delegates = self.genesis_delegates;
+
+        match &mut params {
+            EraParams::Byron(params) => {
+                let rule = &params.softfork_rule;
+                let passed = self.epoch + 1 - self.byron_adoption;
+                let decrement = rule.thd_decrement.saturating_mul(passed);
+                let adoption_thd = rule.init_thd.saturating_sub(decrement).max(rule.min_thd);
+
+                let mut adopted: Vec<_> = self
+                    .byron_proposals
+                    .iter()
+                    .filter(|(_, x)| {
+                        x.confirmed
+                            && reaches_threshold(x.endorsements.len(), delegates, adoption_thd)
+                    })
+                    .map(|(id, _)| *id)
+                    .collect();
+
+                adopted.sort();
+
+                if !adopted.is_empty() {
+                    self.byron_adoption = self.epoch + 1;
+                }
+
+                for id in adopted {
+                    // ids were just taken from the map
+                    let proposal = self.byron_proposals.remove(&id).unwrap();
+
+                    if let Some(update) = &proposal.block_version_mod {
+                        params.apply_update(update);
+                    }
+                }
+            }
+            EraParams::Shelley(params) => {
+                let proposals = self.proposals.remove(&self.epoch).unwrap_or_default();
+
+                let agreed = proposals
+                    .values()
+                    .find(|update| proposals.values().filter(|x| x == update).count() >= quorum);
+
+                match agreed {
+                    Some(ParamsUpdate::Alonzo(x)) => params.apply_alonzo_update(x),
+                    Some(ParamsUpdate::Babbage(x)) => params.apply_babbage_update(x),
+                    None => (),
+                }
+            }
+        }
+
+        self.epoch += 1;
+
+        // proposals targeting past epochs can no longer be applied
+        let epoch = self.epoch;
+        self.proposals.retain(|target, _| *target >= epoch);
+
+        if &params != self.current() {
+            self.history.push((self.epoch, params));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Utilities required for Shelley-era transaction validation, also covering
+//! the Allegra and Mary eras.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    min_lovelace::shelley_ma_min_lovelace,
+    types::{Failures, ShelleyProtParams, UTxOs, ValidationError, ValidationResult},
+    utils::{
+        check_network_id, check_tx_size, check_witnesses, find_tx_out, get_tx_size, is_new_pool,
+        utxo_value, Balance, RequiredWitnesses, ScriptWitness,
+    },
+};
+
+use pallas_crypto::hash::Hash;
+use pallas_primitives::alonzo::{
+    Certificate, MintedTx, MintedWitnessSet, NativeScript, PoolKeyhash, TransactionBody, Value,
+};
+use pallas_traverse::{native_script::ValidityInterval, ComputeHash, Era, OriginalHash};
+
+#[allow(clippy::too_many_arguments)]
+pub fn validate_shelley_ma_tx(
+    mtx: &MintedTx,
+    utxos: &UTxOs,
+    prot_pps: &ShelleyProtParams,
+    block_slot: u64,
+    network_id: u8,
+    registered_pools: &HashSet<PoolKeyhash>,
+    era: &Era,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx_body: &TransactionBody = &mtx.transaction_body;
+    let tx_wits: &MintedWitnessSet = &mtx.transaction_witness_set;
+    let size: u64 = get_tx_size(
+        &mtx.transaction_body,
+        &mtx.transaction_witness_set,
+        &mtx.auxiliary_data,
+    );
+    check_ins_not_empty(tx_body, failures)?;
+    check_ins_in_utxos(tx_body, utxos, failures)?;
+    check_validity_interval(tx_body, block_slot, era, failures)?;
+    check_tx_size(size, prot_pps.max_tx_size, failures)?;
+    check_min_lovelace(tx_body, prot_pps, era, failures)?;
+    check_preservation_of_value(tx_body, utxos, prot_pps, registered_pools, failures)?;
+    check_fees(tx_body, size, prot_pps, failures)?;
+    check_network_id(
+        tx_body.outputs.iter().map(|x| &x.address),
+        tx_body.withdrawals.as_ref(),
+        None,
+        network_id,
+        failures,
+    )?;
+    check_tx_witnesses(mtx, tx_wits, utxos, failures)
+}
+
+fn check_ins_not_empty(tx_body: &TransactionBody, failures: &mut Failures) -> ValidationResult {
+    if tx_body.inputs.is_empty() {
+        failures.report(ValidationError::TxInsEmpty)?;
+    }
+    Ok(())
+}
+
+fn check_ins_in_utxos(
+    tx_body: &TransactionBody,
+    utxos: &UTxOs,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for input in tx_body.inputs.iter() {
+        if let Err(err) = find_tx_out(input, utxos) {
+            failures.report(err)?;
+        }
+    }
+    Ok(())
+}
+
+// Shelley txs must carry a TTL that has not been exceeded. From Allegra
+// onwards, both bounds of the validity interval are optional and the upper
+// bound is exclusive.
+fn check_validity_interval(
+    tx_body: &TransactionBody,
+    block_slot: u64,
+    era: &Era,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let ttl_exceeded = |ttl: u64| ValidationError::TTLExceeded {
+        ttl,
+        slot: block_slot,
+    };
+
+    match (era, tx_body.ttl) {
+        (Era::Shelley, None) => failures.report(ValidationError::MissingTTL)?,
+        (Era::Shelley, Some(ttl)) if block_slot > ttl => failures.report(ttl_exceeded(ttl))?,
+        (Era::Shelley, Some(_)) => (),
+        (_, Some(ttl)) if block_slot >= ttl => failures.report(ttl_exceeded(ttl))?,
+        _ => (),
+    }
+
+    match tx_body.validity_interval_start {
+        Some(start) if block_slot < start => failures.report(ValidationError::TxNotYetValid {
+            start,
+            slot: block_slot,
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn check_min_lovelace(
+    tx_body: &TransactionBody,
+    prot_pps: &ShelleyProtParams,
+    era: &Era,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for (index, output) in tx_body.outputs.iter().enumerate() {
+        let (coin, min_lovelace) = match &output.amount {
+            Value::Coin(coin) => (*coin, prot_pps.min_utxo_value),
+            Value::Multiasset(coin, _) if *era == Era::Mary => {
+                (*coin, shelley_ma_min_lovelace(&output.amount, prot_pps))
+            }
+            Value::Multiasset(coin, _) => (*coin, prot_pps.min_utxo_value),
+        };
+
+        if coin < min_lovelace {
+            failures.report(ValidationError::OutputTooSmall {
+                index,
+                min: min_lovelace,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+// The value consumed by the tx (inputs, withdrawals, deposit refunds and, from
+// Mary onwards, minted assets) must equal the value it produces (outputs, fee
+// and new deposits).
+//
+// Only registrations of new pools pay a deposit, re-registrations updating the
+// parameters of a pool. The value consumed can't be told when some input is
+// missing from the UTxO set.
+fn check_preservation_of_value(
+    tx_body: &TransactionBody,
+    utxos: &UTxOs,
+    prot_pps: &ShelleyProtParams,
+    registered_pools: &HashSet<PoolKeyhash>,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let mut consumed = Balance::default();
+    let mut produced = Balance::default();
+    let mut new_pools: HashSet<PoolKeyhash> = HashSet::new();
+
+    for input in tx_body.inputs.iter() {
+        let output = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => return Ok(()),
+        };
+
+        match utxo_value(output) {
+            Ok(value) => consumed.add_value(value.as_ref(), 1),
+            Err(err) => return failures.report(err),
+        }
+    }
+
+    for (_, amount) in tx_body.withdrawals.iter().flat_map(|x| x.iter()) {
+        consumed.add_coin(*amount as i128);
+    }
+
+    for cert in tx_body.certificates.iter().flatten() {
+        match cert {
+            Certificate::StakeRegistration(_) => produced.add_coin(prot_pps.key_deposit as i128),
+            Certificate::StakeDeregistration(_) => consumed.add_coin(prot_pps.key_deposit as i128),
+            Certificate::PoolRegistration { operator, .. }
+                if is_new_pool(operator, registered_pools, &mut new_pools) =>
+            {
+                produced.add_coin(prot_pps.pool_deposit as i128)
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(mint) = &tx_body.mint {
+        consumed.add_minted(mint);
+        produced.add_burnt(mint);
+    }
+
+    for output in tx_body.outputs.iter() {
+        produced.add_value(&output.amount, 1);
+    }
+
+    produced.add_coin(tx_body.fee as i128);
+
+    if consumed != produced {
+        failures.report(ValidationError::ValueNotPreserved {
+            consumed: consumed.to_value(),
+            produced: produced.to_value(),
+        })?;
+    }
+    Ok(())
+}
+
+fn check_fees(
+    tx_body: &TransactionBody,
+    size: u64,
+    prot_pps: &ShelleyProtParams,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let min_fee: u64 = prot_pps.min_fee_b + prot_pps.min_fee_a * size;
+    if tx_body.fee < min_fee {
+        failures.report(ValidationError::FeeTooSmall {
+            required: min_fee,
+            actual: tx_body.fee,
+        })?;
+    }
+    Ok(())
+}
+
+// All vkey and bootstrap witnesses must sign the tx body, and together with
+// the native scripts in the witness set they must cover every credential
+// required by the inputs, withdrawals, certificates and minting policies.
+fn check_tx_witnesses(
+    mtx: &MintedTx,
+    tx_wits: &MintedWitnessSet,
+    utxos: &UTxOs,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let tx_body: &TransactionBody = &mtx.transaction_body;
+    let mut required = RequiredWitnesses::default();
+
+    for input in tx_body.inputs.iter() {
+        if let Ok(output) = find_tx_out(input, utxos) {
+            required.add_spent_address(input, output.address(), failures)?;
+        }
+    }
+
+    required.add_tx_credentials(
+        tx_body.withdrawals.as_ref(),
+        tx_body.certificates.as_ref(),
+        tx_body.mint.as_ref(),
+        failures,
+    )?;
+
+    let native_scripts: HashMap<Hash<28>, &NativeScript> = tx_wits
+        .native_script
+        .iter()
+        .flatten()
+        .map(|x| (x.compute_hash(), x))
+        .collect();
+
+    check_witnesses(
+        &mtx.transaction_body.original_hash(),
+        tx_wits.vkeywitness.as_deref().unwrap_or_default(),
+        tx_wits.bootstrap_witness.as_deref().unwrap_or_default(),
+        &required,
+        |x| native_scripts.get(x).map(|x| ScriptWitness::Native(x)),
+        &ValidityInterval::new(tx_body.validity_interval_start, tx_body.ttl),
+        failures,
+    )
+}
@@ -1,27 +1,309 @@
 //! Base types used for validating transactions in each era.
 
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    num::ParseIntError,
+};
 
-pub use pallas_traverse::{MultiEraInput, MultiEraOutput};
+use pallas_codec::utils::Bytes;
+use pallas_configs::byron::GenesisFile;
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{
+    alonzo::{AddrKeyhash, CostMdls, ExUnitPrices, ExUnits, PoolKeyhash, RedeemerTag, Value},
+    babbage, conway,
+};
+pub use pallas_traverse::{
+    fees::PolicyParams as FeePolicy, Era, MultiEraInput, MultiEraOutput, OutputRef,
+};
+use thiserror::Error;
 
 pub type UTxOs<'b> = HashMap<MultiEraInput<'b>, MultiEraOutput<'b>>;
 
-// TODO: add a field for each protocol parameter in the Byron era.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ByronProtParams {
+    pub fee_policy: FeePolicy,
+    pub max_tx_size: u64,
+    pub max_block_size: u64,
+    pub script_version: u32,
+    pub protocol_magic: u32,
+    /// Slot duration, in milliseconds
+    pub slot_duration: u64,
+    /// Portion of the stake whose votes confirm an update proposal, in units
+    /// of 10^-15
+    pub update_vote_thd: u64,
+    /// Number of slots after which an unconfirmed update proposal expires
+    pub update_implicit: u64,
+    pub softfork_rule: SoftforkRule,
+}
+
+/// Portions of the stake whose endorsements adopt a confirmed update
+/// proposal, in units of 10^-15
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftforkRule {
+    pub init_thd: u64,
+    pub min_thd: u64,
+    pub thd_decrement: u64,
+}
+
+impl ByronProtParams {
+    /// Builds the protocol parameters in effect at the start of the Byron era
+    ///
+    /// Genesis files encode most numeric values as strings, so this fails if
+    /// any of them is not a valid unsigned integer.
+    pub fn from_genesis(genesis: &GenesisFile) -> Result<Self, ParseIntError> {
+        let block_version_data = &genesis.block_version_data;
+
+        Ok(Self {
+            fee_policy: FeePolicy {
+                constant: block_version_data.tx_fee_policy.summand.parse()?,
+                size_coeficient: block_version_data.tx_fee_policy.multiplier.parse()?,
+            },
+            max_tx_size: block_version_data.max_tx_size.parse()?,
+            max_block_size: block_version_data.max_block_size.parse()?,
+            script_version: block_version_data.script_version,
+            protocol_magic: genesis.protocol_consts.protocol_magic,
+            slot_duration: block_version_data.slot_duration.parse()?,
+            update_vote_thd: block_version_data.update_vote_thd.parse()?,
+            update_implicit: block_version_data.update_implicit.parse()?,
+            softfork_rule: SoftforkRule {
+                init_thd: block_version_data.softfork_rule.init_thd.parse()?,
+                min_thd: block_version_data.softfork_rule.min_thd.parse()?,
+                thd_decrement: block_version_data.softfork_rule.thd_decrement.parse()?,
+            },
+        })
+    }
+}
+
+/// Protocol parameters relevant to the validation of Shelley, Allegra and
+/// Mary txs
 #[derive(Debug, Clone)]
-pub struct ByronProtParams;
+pub struct ShelleyProtParams {
+    pub min_fee_a: u64,
+    pub min_fee_b: u64,
+    pub max_tx_size: u64,
+    pub min_utxo_value: u64,
+    pub key_deposit: u64,
+    pub pool_deposit: u64,
+}
+
+/// Protocol parameters relevant to the validation of Alonzo txs
+#[derive(Debug, Clone)]
+pub struct AlonzoProtParams {
+    pub min_fee_a: u64,
+    pub min_fee_b: u64,
+    pub max_tx_size: u64,
+    pub max_value_size: u64,
+    pub coins_per_utxo_word: u64,
+    pub key_deposit: u64,
+    pub pool_deposit: u64,
+    /// Collateral to be provided, as a percentage of the tx fee
+    pub collateral_percentage: u64,
+    pub max_collateral_inputs: u64,
+    pub max_tx_ex_units: ExUnits,
+    pub ex_unit_prices: ExUnitPrices,
+    pub cost_models_for_script_languages: CostMdls,
+}
+
+/// Protocol parameters relevant to the validation of Babbage txs
+#[derive(Debug, Clone)]
+pub struct BabbageProtParams {
+    pub min_fee_a: u64,
+    pub min_fee_b: u64,
+    pub max_tx_size: u64,
+    pub max_value_size: u64,
+    pub ada_per_utxo_byte: u64,
+    pub key_deposit: u64,
+    pub pool_deposit: u64,
+    /// Collateral to be provided, as a percentage of the tx fee
+    pub collateral_percentage: u64,
+    pub max_collateral_inputs: u64,
+    pub max_tx_ex_units: ExUnits,
+    pub ex_unit_prices: ExUnitPrices,
+    pub cost_models_for_script_languages: babbage::CostMdls,
+}
+
+/// Protocol parameters relevant to the validation of Conway txs
+#[derive(Debug, Clone)]
+pub struct ConwayProtParams {
+    pub min_fee_a: u64,
+    pub min_fee_b: u64,
+    pub max_tx_size: u64,
+    pub max_value_size: u64,
+    pub ada_per_utxo_byte: u64,
+    pub key_deposit: u64,
+    pub pool_deposit: u64,
+    /// Collateral to be provided, as a percentage of the tx fee
+    pub collateral_percentage: u64,
+    pub max_collateral_inputs: u64,
+    pub max_tx_ex_units: ExUnits,
+    pub ex_unit_prices: ExUnitPrices,
+    pub cost_models_for_script_languages: conway::CostMdls,
+}
 
-// TODO: add variants for the other eras.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum MultiEraProtParams<'b> {
     Byron(Box<Cow<'b, ByronProtParams>>),
+    Shelley(Box<Cow<'b, ShelleyProtParams>>),
+    Alonzo(Box<Cow<'b, AlonzoProtParams>>),
+    Babbage(Box<Cow<'b, BabbageProtParams>>),
+    Conway(Box<Cow<'b, ConwayProtParams>>),
 }
 
-// TODO: replace this generic variant with validation-rule-specific ones.
+/// Chain state against which a tx is validated
 #[derive(Debug)]
+pub struct Environment<'b> {
+    pub prot_params: MultiEraProtParams<'b>,
+    /// Slot of the block that would include the tx
+    pub block_slot: u64,
+    /// Network id as encoded in Shelley address headers (1 for mainnet)
+    pub network_id: u8,
+    /// Pools registered before the tx, whose re-registrations pay no deposit
+    pub registered_pools: Cow<'b, HashSet<PoolKeyhash>>,
+}
+
+/// Failure of one of the ledger rules checked when validating a tx
+///
+/// Each variant carries the data needed to tell which part of the tx broke
+/// the rule. Outputs are referred to by their position in the tx body, the
+/// collateral return output, if any, coming right after the regular ones.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[non_exhaustive]
 pub enum ValidationError {
-    ValidationError,
+    #[error("input {0} is not in the UTxO set")]
+    InputNotInUTxO(OutputRef),
+    #[error("reference input {0} is not in the UTxO set")]
+    RefInputNotInUTxO(OutputRef),
+    #[error("the tx spends an input of unknown type")]
+    UnknownInputType,
+    #[error("the tx has no inputs")]
+    TxInsEmpty,
+    #[error("the tx has no outputs")]
+    TxOutsEmpty,
+    #[error("output {0} holds no lovelace")]
+    OutputWithoutLovelace(usize),
+    #[error("the value consumed by the tx differs from the value it produces")]
+    ValueNotPreserved { consumed: Value, produced: Value },
+    #[error("fee of {actual} lovelace is below the minimum of {required}")]
+    FeeTooSmall { required: u64, actual: u64 },
+    #[error("unable to compute the size of the tx")]
+    UnknownTxSize,
+    #[error("tx size of {size} bytes exceeds the maximum of {max}")]
+    MaxTxSizeExceeded { size: u64, max: u64 },
+    #[error("unable to process the witnesses of the tx")]
+    UnableToProcessWitnesses,
+    #[error("missing witness for input {0}")]
+    MissingWitness(OutputRef),
+    #[error("invalid signature by key {0}")]
+    WrongSignature(Bytes),
+    #[error("the tx and the protocol parameters belong to different eras")]
+    TxAndProtParamsDiffer,
+    #[error("validation of {0} txs is not supported")]
+    UnsupportedEra(Era),
+    #[error("the tx has no TTL")]
+    MissingTTL,
+    #[error("the tx expired at slot {ttl}, before slot {slot}")]
+    TTLExceeded { ttl: u64, slot: u64 },
+    #[error("the tx is not valid until slot {start}, after slot {slot}")]
+    TxNotYetValid { start: u64, slot: u64 },
+    #[error("output {index} holds less than the minimum of {min} lovelace")]
+    OutputTooSmall { index: usize, min: u64 },
+    #[error("address {0} belongs to another network")]
+    WrongNetworkID(Bytes),
+    #[error("the tx network id {0} does not match the network")]
+    TxWrongNetworkID(u8),
+    #[error("missing vkey witness for key hash {0}")]
+    MissingVKeyWitness(AddrKeyhash),
+    #[error("missing witness for script {0}")]
+    MissingScriptWitness(Hash<28>),
+    #[error("native script {0} is not satisfied")]
+    NativeScriptNotSatisfied(Hash<28>),
+    #[error("value of output {index} takes {size} bytes, exceeding the maximum of {max}")]
+    MaxValSizeExceeded { index: usize, size: u64, max: u64 },
+    #[error("execution units {provided:?} exceed the maximum of {max:?}")]
+    TxExUnitsExceeded { provided: ExUnits, max: ExUnits },
+    #[error("the tx runs Plutus scripts but provides no collateral")]
+    CollateralMissing,
+    #[error("{count} collateral inputs exceed the maximum of {max}")]
+    TooManyCollaterals { count: u64, max: u64 },
+    #[error("collateral input {0} is not in the UTxO set")]
+    CollateralNotInUTxO(OutputRef),
+    #[error("collateral input {0} is locked by a script")]
+    CollateralNotVKeyLocked(OutputRef),
+    #[error("the collateral holds assets other than lovelace")]
+    NonLovelaceCollateral,
+    #[error("collateral of {provided} lovelace is below the minimum of {required}")]
+    CollateralMinLovelace { provided: u64, required: u64 },
+    #[error("declared total collateral of {declared} lovelace differs from the actual {actual}")]
+    CollateralAnnotationMismatch { declared: u64, actual: i128 },
+    #[error("missing signature of required signer {0}")]
+    MissingReqSigner(AddrKeyhash),
+    #[error("input {0} is locked by a Plutus script but has no datum")]
+    UnspendableUTxONoDatumHash(OutputRef),
+    #[error("missing datum {0}")]
+    DatumMissing(Hash<32>),
+    #[error("datum {0} is not needed by the tx")]
+    UnneededDatum(Hash<32>),
+    #[error("missing redeemer for {tag:?} item {index}")]
+    RedeemerMissing { tag: RedeemerTag, index: u32 },
+    #[error("redeemer for {tag:?} item {index} is not needed")]
+    UnneededRedeemer { tag: RedeemerTag, index: u32 },
+    #[error("the script data hash does not match the redeemers, datums and cost models in use")]
+    ScriptIntegrityHashMismatch {
+        declared: Option<Hash<32>>,
+        computed: Option<Hash<32>>,
+    },
 }
 
 pub type ValidationResult = Result<(), ValidationError>;
+
+/// How validation proceeds once a rule is found to be broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Stop at the first failure
+    FailFast,
+    /// Check every rule, gathering all failures
+    AccumulateAll,
+}
+
+/// Failures found while validating a tx
+///
+/// Rules report their failures here. When failing fast, the first failure is
+/// handed back as an error, so that validation stops there. Otherwise it is
+/// recorded, and validation goes on.
+#[derive(Debug)]
+pub struct Failures {
+    mode: ValidationMode,
+    errors: Vec<ValidationError>,
+}
+
+impl Failures {
+    pub fn new(mode: ValidationMode) -> Self {
+        Self {
+            mode,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Reports a failure, which is only recorded once
+    pub fn report(&mut self, error: ValidationError) -> ValidationResult {
+        match self.mode {
+            ValidationMode::FailFast => Err(error),
+            ValidationMode::AccumulateAll => {
+                if !self.errors.contains(&error) {
+                    self.errors.push(error);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+}
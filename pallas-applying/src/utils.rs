@@ -0,0 +1,613 @@
+//! Helpers shared by the validation rules of several eras.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashSet},
+};
+
+use pallas_addresses::{
+    byron::{AddrType, AddressPayload, SpendingData},
+    Address, Error as AddressError, ShelleyPaymentPart, StakePayload,
+};
+use pallas_codec::{
+    minicbor::bytes::ByteVec,
+    utils::{Bytes, KeepRaw, Nullable},
+};
+use pallas_crypto::{
+    hash::{Hash, Hasher},
+    key::ed25519::{PublicKey, Signature},
+};
+use pallas_primitives::{
+    alonzo::{
+        AddrKeyhash, AssetName, BootstrapWitness, Certificate, ExUnits, Mint, NativeScript,
+        NetworkId, PolicyId, PoolKeyhash, Redeemer, RedeemerTag, StakeCredential, TransactionInput,
+        VKeyWitness, Value, Withdrawals,
+    },
+    babbage::MintedTransactionOutput,
+};
+use pallas_traverse::{
+    native_script::{eval_native_script, ValidityInterval},
+    MultiEraInput, MultiEraOutput, OutputRef,
+};
+
+use crate::types::{Failures, UTxOs, ValidationError, ValidationResult};
+
+/// Signed balance of lovelace and native assets
+///
+/// Used to check the preservation of value, where the amounts consumed and
+/// produced by a tx are accumulated separately, and to compute the collateral
+/// actually taken from a tx.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Balance {
+    pub coin: i128,
+    pub assets: BTreeMap<(PolicyId, AssetName), i128>,
+}
+
+impl Balance {
+    pub fn add_coin(&mut self, amount: i128) {
+        self.coin += amount;
+    }
+
+    pub fn add_value(&mut self, value: &Value, sign: i128) {
+        match value {
+            Value::Coin(coin) => self.add_coin(sign * *coin as i128),
+            Value::Multiasset(coin, multiasset) => {
+                self.add_coin(sign * *coin as i128);
+
+                for (policy, assets) in multiasset.iter() {
+                    for (name, amount) in assets.iter() {
+                        self.add_asset(policy, name, sign * *amount as i128);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn add_minted(&mut self, mint: &Mint) {
+        for (policy, assets) in mint.iter() {
+            for (name, amount) in assets.iter().filter(|(_, x)| *x > 0) {
+                self.add_asset(policy, name, *amount as i128);
+            }
+        }
+    }
+
+    pub fn add_burnt(&mut self, mint: &Mint) {
+        for (policy, assets) in mint.iter() {
+            for (name, amount) in assets.iter().filter(|(_, x)| *x < 0) {
+                self.add_asset(policy, name, -(*amount as i128));
+            }
+        }
+    }
+
+    fn add_asset(&mut self, policy: &PolicyId, name: &AssetName, amount: i128) {
+        let key = (*policy, name.clone());
+        let entry = self.assets.entry(key).or_default();
+        *entry += amount;
+
+        if *entry == 0 {
+            self.assets.remove(&(*policy, name.clone()));
+        }
+    }
+
+    /// Converts the balance into a value, with negative amounts clamped to
+    /// zero
+    pub fn to_value(&self) -> Value {
+        let coin: u64 = self.coin.clamp(0, u64::MAX as i128) as u64;
+        if self.assets.is_empty() {
+            return Value::Coin(coin);
+        }
+
+        let mut multiasset: BTreeMap<PolicyId, Vec<(AssetName, u64)>> = BTreeMap::new();
+        for ((policy, name), amount) in self.assets.iter() {
+            multiasset
+                .entry(*policy)
+                .or_default()
+                .push((name.clone(), (*amount).clamp(0, u64::MAX as i128) as u64));
+        }
+
+        Value::Multiasset(
+            coin,
+            multiasset
+                .into_iter()
+                .map(|(policy, assets)| (policy, assets.into()))
+                .collect::<Vec<_>>()
+                .into(),
+        )
+    }
+}
+
+/// Tells whether a pool registration is that of a new pool, which pays a
+/// deposit, rather than an update of a pool registered before the tx or by an
+/// earlier certificate of the same tx. New pools are added to `new_pools`.
+pub(crate) fn is_new_pool(
+    operator: &PoolKeyhash,
+    registered_pools: &HashSet<PoolKeyhash>,
+    new_pools: &mut HashSet<PoolKeyhash>,
+) -> bool {
+    !registered_pools.contains(operator) && new_pools.insert(*operator)
+}
+
+/// Checks that a vkey witness holds a valid signature of the given tx hash
+pub(crate) fn verify_vkey_witness(witness: &VKeyWitness, tx_hash: &Hash<32>) -> bool {
+    verify_signature(&witness.vkey, &witness.signature, tx_hash)
+}
+
+/// Checks that a bootstrap witness holds a valid signature of the given tx hash
+pub(crate) fn verify_bootstrap_witness(witness: &BootstrapWitness, tx_hash: &Hash<32>) -> bool {
+    verify_signature(&witness.public_key, &witness.signature, tx_hash)
+}
+
+fn verify_signature(vkey: &[u8], signature: &[u8], tx_hash: &Hash<32>) -> bool {
+    let public_key = match PublicKey::try_from(vkey) {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+
+    let signature = match Signature::try_from(signature) {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+
+    public_key.verify(tx_hash, &signature)
+}
+
+/// Checks whether a bootstrap witness corresponds to the root of a Byron
+/// address payload
+pub(crate) fn bootstrap_redeems(witness: &BootstrapWitness, payload: &AddressPayload) -> bool {
+    let extended_key = [witness.public_key.as_slice(), witness.chain_code.as_slice()].concat();
+    let spending_data = SpendingData::PubKey(ByteVec::from(extended_key));
+    let root =
+        AddressPayload::hash_address_id(&AddrType::PubKey, &spending_data, &payload.attributes);
+
+    root == payload.root
+}
+
+/// Size of a tx as accounted by the ledger, that of a 3-element array holding
+/// the body, the witness set and the (nullable) auxiliary data
+///
+/// For backwards compatibility with Shelley-era wallets, this leaves out the
+/// validity flag of the txs of Alonzo onwards.
+pub(crate) fn get_tx_size<B, W, A: Clone>(
+    tx_body: &KeepRaw<B>,
+    tx_wits: &KeepRaw<W>,
+    aux_data: &Nullable<KeepRaw<A>>,
+) -> u64 {
+    let aux_data_size: usize = match aux_data {
+        Nullable::Some(x) => x.raw_cbor().len(),
+        _ => 1,
+    };
+
+    (1 + tx_body.raw_cbor().len() + tx_wits.raw_cbor().len() + aux_data_size) as u64
+}
+
+/// Checks that the outputs, reward accounts and network id field of a tx
+/// belong to the given network
+///
+/// Byron addresses carry no network id, so only outputs locked by Shelley
+/// addresses are checked.
+pub(crate) fn check_network_id<'a>(
+    output_addresses: impl IntoIterator<Item = &'a Bytes>,
+    withdrawals: Option<&Withdrawals>,
+    tx_network_id: Option<&NetworkId>,
+    network_id: u8,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for address in output_addresses {
+        if let Ok(Address::Shelley(x)) = Address::from_bytes(address) {
+            if x.network().value() != network_id {
+                failures.report(ValidationError::WrongNetworkID(address.clone()))?;
+            }
+        }
+    }
+
+    for (reward_account, _) in withdrawals.iter().flat_map(|x| x.iter()) {
+        match Address::from_bytes(reward_account) {
+            Ok(Address::Stake(address)) if address.network().value() == network_id => (),
+            _ => failures.report(ValidationError::WrongNetworkID(reward_account.clone()))?,
+        }
+    }
+
+    let tx_network_id: Option<u8> = tx_network_id.map(|x| match x {
+        NetworkId::One => 0,
+        NetworkId::Two => 1,
+    });
+
+    match tx_network_id {
+        Some(x) if x != network_id => failures.report(ValidationError::TxWrongNetworkID(x)),
+        _ => Ok(()),
+    }
+}
+
+/// Credentials whose witnesses a tx must provide
+///
+/// Genesis key delegations and MIR certificates require a quorum of genesis
+/// delegates, which can't be determined without the genesis config, so they
+/// are not taken into account.
+#[derive(Default)]
+pub(crate) struct RequiredWitnesses {
+    pub vkey_hashes: BTreeSet<AddrKeyhash>,
+    pub script_hashes: BTreeSet<Hash<28>>,
+    /// Byron addresses locking spent outputs, to be redeemed by bootstrap
+    /// witnesses
+    pub bootstrap_payloads: Vec<(OutputRef, AddressPayload)>,
+    pub signers: BTreeSet<AddrKeyhash>,
+}
+
+impl RequiredWitnesses {
+    fn add_credential(&mut self, credential: &StakeCredential) {
+        match credential {
+            StakeCredential::AddrKeyhash(x) => self.vkey_hashes.insert(*x),
+            StakeCredential::Scripthash(x) => self.script_hashes.insert(*x),
+        };
+    }
+
+    /// Adds the credential of the address locking the output spent by the
+    /// given input
+    pub fn add_spent_address(
+        &mut self,
+        input: &TransactionInput,
+        address: Result<Address, AddressError>,
+        failures: &mut Failures,
+    ) -> ValidationResult {
+        match address {
+            Ok(Address::Shelley(address)) => match address.payment() {
+                ShelleyPaymentPart::Key(x) => self.vkey_hashes.insert(*x),
+                ShelleyPaymentPart::Script(x) => self.script_hashes.insert(*x),
+            },
+            Ok(Address::Byron(address)) => match address.decode() {
+                Ok(payload) => {
+                    self.bootstrap_payloads.push((output_ref(input), payload));
+                    return Ok(());
+                }
+                Err(_) => return failures.report(ValidationError::UnableToProcessWitnesses),
+            },
+            _ => return failures.report(ValidationError::UnableToProcessWitnesses),
+        };
+        Ok(())
+    }
+
+    /// Adds the credentials required by the withdrawals, certificates and
+    /// minting policies of a tx
+    pub fn add_tx_credentials(
+        &mut self,
+        withdrawals: Option<&Withdrawals>,
+        certificates: Option<&Vec<Certificate>>,
+        mint: Option<&Mint>,
+        failures: &mut Failures,
+    ) -> ValidationResult {
+        for (reward_account, _) in withdrawals.iter().flat_map(|x| x.iter()) {
+            match Address::from_bytes(reward_account) {
+                Ok(Address::Stake(address)) => match address.payload() {
+                    StakePayload::Stake(x) => self.vkey_hashes.insert(*x),
+                    StakePayload::Script(x) => self.script_hashes.insert(*x),
+                },
+                _ => {
+                    failures.report(ValidationError::UnableToProcessWitnesses)?;
+                    continue;
+                }
+            };
+        }
+
+        for cert in certificates.iter().flat_map(|x| x.iter()) {
+            match cert {
+                Certificate::StakeDeregistration(x) => self.add_credential(x),
+                Certificate::StakeDelegation(x, _) => self.add_credential(x),
+                Certificate::PoolRegistration {
+                    operator,
+                    pool_owners,
+                    ..
+                } => {
+                    self.vkey_hashes.insert(*operator);
+                    self.vkey_hashes.extend(pool_owners.iter().copied());
+                }
+                Certificate::PoolRetirement(x, _) => {
+                    self.vkey_hashes.insert(*x);
+                }
+                _ => (),
+            }
+        }
+
+        for (policy, _) in mint.iter().flat_map(|x| x.iter()) {
+            self.script_hashes.insert(*policy);
+        }
+        Ok(())
+    }
+}
+
+/// Script available to a tx for witnessing a credential
+pub(crate) enum ScriptWitness<'a> {
+    Native(&'a NativeScript),
+    /// Plutus scripts are left to phase-2 validation
+    Plutus,
+}
+
+/// Checks that all vkey and bootstrap witnesses sign the tx body, and that
+/// together with the scripts available to the tx they cover every required
+/// credential and signer
+///
+/// Native scripts are evaluated against the keys of the vkey witnesses and the
+/// validity interval of the tx.
+pub(crate) fn check_witnesses<'a>(
+    tx_hash: &Hash<32>,
+    vkey_wits: &[VKeyWitness],
+    bootstrap_wits: &[BootstrapWitness],
+    required: &RequiredWitnesses,
+    scripts: impl Fn(&Hash<28>) -> Option<ScriptWitness<'a>>,
+    validity_interval: &ValidityInterval,
+    failures: &mut Failures,
+) -> ValidationResult {
+    for wit in vkey_wits.iter() {
+        if !verify_vkey_witness(wit, tx_hash) {
+            failures.report(ValidationError::WrongSignature(wit.vkey.clone()))?;
+        }
+    }
+
+    for wit in bootstrap_wits.iter() {
+        if !verify_bootstrap_witness(wit, tx_hash) {
+            failures.report(ValidationError::WrongSignature(wit.public_key.clone()))?;
+        }
+    }
+
+    for (input, payload) in required.bootstrap_payloads.iter() {
+        if !bootstrap_wits.iter().any(|x| bootstrap_redeems(x, payload)) {
+            failures.report(ValidationError::MissingWitness(input.clone()))?;
+        }
+    }
+
+    let provided_vkey_hashes: HashSet<AddrKeyhash> = vkey_wits
+        .iter()
+        .map(|x| Hasher::<224>::hash(&x.vkey))
+        .collect();
+
+    for vkey_hash in required.vkey_hashes.iter() {
+        if !provided_vkey_hashes.contains(vkey_hash) {
+            failures.report(ValidationError::MissingVKeyWitness(*vkey_hash))?;
+        }
+    }
+
+    for signer in required.signers.iter() {
+        if !provided_vkey_hashes.contains(signer) {
+            failures.report(ValidationError::MissingReqSigner(*signer))?;
+        }
+    }
+
+    for script_hash in required.script_hashes.iter() {
+        match scripts(script_hash) {
+            Some(ScriptWitness::Native(script)) => {
+                if !eval_native_script(script, &provided_vkey_hashes, validity_interval) {
+                    failures.report(ValidationError::NativeScriptNotSatisfied(*script_hash))?;
+                }
+            }
+            Some(ScriptWitness::Plutus) => (),
+            None => failures.report(ValidationError::MissingScriptWitness(*script_hash))?,
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the block slot falls within the validity interval of a tx
+///
+/// Both bounds are optional, and the upper one is exclusive.
+pub(crate) fn check_validity_interval(
+    validity_interval_start: Option<u64>,
+    ttl: Option<u64>,
+    block_slot: u64,
+    failures: &mut Failures,
+) -> ValidationResult {
+    if let Some(start) = validity_interval_start {
+        if block_slot < start {
+            failures.report(ValidationError::TxNotYetValid {
+                start,
+                slot: block_slot,
+            })?;
+        }
+    }
+
+    if let Some(ttl) = ttl {
+        if block_slot >= ttl {
+            failures.report(ValidationError::TTLExceeded {
+                ttl,
+                slot: block_slot,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn check_tx_size(
+    size: u64,
+    max_tx_size: u64,
+    failures: &mut Failures,
+) -> ValidationResult {
+    if size > max_tx_size {
+        failures.report(ValidationError::MaxTxSizeExceeded {
+            size,
+            max: max_tx_size,
+        })?;
+    }
+    Ok(())
+}
+
+pub(crate) fn has_redeemers(redeemers: Option<&Vec<Redeemer>>) -> bool {
+    redeemers.is_some_and(|x| !x.is_empty())
+}
+
+/// Execution units declared by all the redeemers of a tx
+pub(crate) fn total_ex_units(redeemers: Option<&Vec<Redeemer>>) -> ExUnits {
+    let mut res: ExUnits = ExUnits { mem: 0, steps: 0 };
+    for redeemer in redeemers.into_iter().flatten() {
+        res.mem = res.mem.saturating_add(redeemer.ex_units.mem);
+        res.steps = res.steps.saturating_add(redeemer.ex_units.steps);
+    }
+    res
+}
+
+pub(crate) fn check_tx_ex_units(
+    redeemers: Option<&Vec<Redeemer>>,
+    max_tx_ex_units: &ExUnits,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let ex_units: ExUnits = total_ex_units(redeemers);
+    if ex_units.mem > max_tx_ex_units.mem || ex_units.steps > max_tx_ex_units.steps {
+        failures.report(ValidationError::TxExUnitsExceeded {
+            provided: ex_units,
+            max: max_tx_ex_units.clone(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Collateral required by a tx running Plutus scripts, the collateral
+/// percentage of the fee rounded up to the next lovelace
+pub(crate) fn min_collateral(fee: u64, collateral_percentage: u64) -> u64 {
+    ((fee as u128) * (collateral_percentage as u128)).div_ceil(100) as u64
+}
+
+/// Hash of the Plutus script locking the output, if any
+pub(crate) fn plutus_lock(
+    output: &MultiEraOutput,
+    plutus_scripts: &HashSet<Hash<28>>,
+) -> Option<Hash<28>> {
+    match output.address() {
+        Ok(Address::Shelley(address)) => match address.payment() {
+            ShelleyPaymentPart::Script(x) if plutus_scripts.contains(x) => Some(*x),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Checks that there is exactly one redeemer for each item of a tx validated
+/// by one of the given Plutus scripts
+///
+/// Redeemers point to the item through its index in the canonical order of the
+/// corresponding tx body field: inputs are sorted by tx id and index, policies
+/// by hash, and reward accounts by network and credential, with script
+/// credentials first. Certificates keep the order in which they appear in the
+/// tx. Outputs spent by the inputs are looked up in the UTxO set, and redeemer
+/// pointers to inputs can't be told when some input is missing from it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn check_redeemers(
+    inputs: &[TransactionInput],
+    mint: Option<&Mint>,
+    certificates: Option<&Vec<Certificate>>,
+    withdrawals: Option<&Withdrawals>,
+    redeemers: Option<&Vec<Redeemer>>,
+    plutus_scripts: &HashSet<Hash<28>>,
+    utxos: &UTxOs,
+    failures: &mut Failures,
+) -> ValidationResult {
+    let mut required: Vec<(RedeemerTag, u32)> = Vec::new();
+
+    let mut inputs: Vec<&TransactionInput> = inputs.iter().collect();
+    inputs.sort();
+    for (index, input) in inputs.iter().enumerate() {
+        let output: &MultiEraOutput = match find_tx_out(input, utxos) {
+            Ok(x) => x,
+            Err(_) => return Ok(()),
+        };
+        if plutus_lock(output, plutus_scripts).is_some() {
+            required.push((RedeemerTag::Spend, index as u32));
+        }
+    }
+
+    let mut policies: Vec<&PolicyId> = mint
+        .iter()
+        .flat_map(|x| x.iter())
+        .map(|(policy, _)| policy)
+        .collect();
+    policies.sort();
+    for (index, policy) in policies.iter().enumerate() {
+        if plutus_scripts.contains(*policy) {
+            required.push((RedeemerTag::Mint, index as u32));
+        }
+    }
+
+    for (index, cert) in certificates.into_iter().flatten().enumerate() {
+        match cert {
+            Certificate::StakeDeregistration(StakeCredential::Scripthash(x))
+            | Certificate::StakeDelegation(StakeCredential::Scripthash(x), _)
+                if plutus_scripts.contains(x) =>
+            {
+                required.push((RedeemerTag::Cert, index as u32))
+            }
+            _ => (),
+        }
+    }
+
+    let mut reward_accounts: Vec<(u8, bool, Hash<28>)> = Vec::new();
+    for (reward_account, _) in withdrawals.iter().flat_map(|x| x.iter()) {
+        match Address::from_bytes(reward_account) {
+            Ok(Address::Stake(address)) => {
+                let (is_key, hash) = match address.payload() {
+                    StakePayload::Stake(x) => (true, *x),
+                    StakePayload::Script(x) => (false, *x),
+                };
+                reward_accounts.push((address.network().value(), is_key, hash));
+            }
+            _ => return failures.report(ValidationError::UnableToProcessWitnesses),
+        }
+    }
+    reward_accounts.sort();
+    for (index, (_, is_key, hash)) in reward_accounts.iter().enumerate() {
+        if !is_key && plutus_scripts.contains(hash) {
+            required.push((RedeemerTag::Reward, index as u32));
+        }
+    }
+
+    let provided: Vec<(RedeemerTag, u32)> = redeemers
+        .into_iter()
+        .flatten()
+        .map(|x| (x.tag.clone(), x.index))
+        .collect();
+
+    for (tag, index) in required.iter() {
+        if !provided.contains(&(tag.clone(), *index)) {
+            failures.report(ValidationError::RedeemerMissing {
+                tag: tag.clone(),
+                index: *index,
+            })?;
+        }
+    }
+
+    for (tag, index) in provided.iter() {
+        if !required.contains(&(tag.clone(), *index)) {
+            failures.report(ValidationError::UnneededRedeemer {
+                tag: tag.clone(),
+                index: *index,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// The output spent by the given input, produced in any era
+pub(crate) fn find_tx_out<'a, 'b>(
+    input: &TransactionInput,
+    utxos: &'a UTxOs<'b>,
+) -> Result<&'a MultiEraOutput<'b>, ValidationError> {
+    let key: MultiEraInput = MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(input.clone())));
+    utxos
+        .get(&key)
+        .ok_or_else(|| ValidationError::InputNotInUTxO(output_ref(input)))
+}
+
+/// Value of an output spent by a tx, which may have been produced in any
+/// previous era
+pub(crate) fn utxo_value<'a>(
+    output: &'a MultiEraOutput,
+) -> Result<Cow<'a, Value>, ValidationError> {
+    match output {
+        MultiEraOutput::Byron(x) => Ok(Cow::Owned(Value::Coin(x.amount))),
+        MultiEraOutput::AlonzoCompatible(x) => Ok(Cow::Borrowed(&x.amount)),
+        MultiEraOutput::Babbage(x) => match x.as_ref().as_ref() {
+            MintedTransactionOutput::Legacy(x) => Ok(Cow::Borrowed(&x.amount)),
+            MintedTransactionOutput::PostAlonzo(x) => Ok(Cow::Borrowed(&x.value)),
+        },
+        _ => Err(ValidationError::UnknownInputType),
+    }
+}
+
+pub(crate) fn output_ref(input: &TransactionInput) -> OutputRef {
+    OutputRef::new(input.transaction_id, input.index)
+}
@@ -0,0 +1,140 @@
+//! Sources of the unspent outputs against which txs are validated.
+//!
+//! Validation works on a [UTxOs] map whose outputs borrow from CBOR held by
+//! the caller. A [UtxoProvider] instead resolves inputs to owned CBOR, so the
+//! UTxO set can live anywhere (memory, disk, a remote node). Only the outputs
+//! spent or referenced by the tx at hand are fetched, through [ResolvedUtxos].
+
+use std::{collections::HashMap, convert::Infallible, ops::Deref};
+
+use pallas_codec::{minicbor, utils::CborWrap};
+use pallas_primitives::byron::TxIn;
+use pallas_traverse::{Era, MultiEraInput, MultiEraOutput, MultiEraTx, OutputRef};
+
+use crate::types::UTxOs;
+
+/// The CBOR of an output, tagged with the era in which it was produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraCbor(pub Era, pub Vec<u8>);
+
+impl EraCbor {
+    pub fn era(&self) -> Era {
+        self.0
+    }
+
+    pub fn cbor(&self) -> &[u8] {
+        &self.1
+    }
+
+    pub fn decode(&self) -> Result<MultiEraOutput, minicbor::decode::Error> {
+        MultiEraOutput::decode(self.0, &self.1)
+    }
+}
+
+impl From<&MultiEraOutput<'_>> for EraCbor {
+    fn from(output: &MultiEraOutput<'_>) -> Self {
+        let era = match output {
+            MultiEraOutput::Byron(_) => Era::Byron,
+            MultiEraOutput::Babbage(_) => Era::Babbage,
+            MultiEraOutput::Conway(_) => Era::Conway,
+            // Shelley to Alonzo outputs share the same encoding, so any of
+            // those eras decodes them alike.
+            _ => Era::Alonzo,
+        };
+
+        EraCbor(era, output.encode())
+    }
+}
+
+/// A source of unspent outputs
+pub trait UtxoProvider {
+    type Error;
+
+    /// Returns the output referred to by `output_ref`, or `None` if it is not
+    /// (or no longer) unspent
+    fn resolve(&self, output_ref: &OutputRef) -> Result<Option<EraCbor>, Self::Error>;
+}
+
+impl UtxoProvider for HashMap<OutputRef, EraCbor> {
+    type Error = Infallible;
+
+    fn resolve(&self, output_ref: &OutputRef) -> Result<Option<EraCbor>, Self::Error> {
+        Ok(self.get(output_ref).cloned())
+    }
+}
+
+impl<T: UtxoProvider> UtxoProvider for &T {
+    type Error = T::Error;
+
+    fn resolve(&self, output_ref: &OutputRef) -> Result<Option<EraCbor>, Self::Error> {
+        (*self).resolve(output_ref)
+    }
+}
+
+/// The outputs spent or referenced by a tx, fetched from a [UtxoProvider]
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedUtxos(HashMap<OutputRef, EraCbor>);
+
+impl ResolvedUtxos {
+    /// Fetches the outputs of the inputs, collateral inputs and reference
+    /// inputs of `tx`
+    ///
+    /// Inputs unknown to the provider are left out, so that validation can
+    /// report them.
+    pub fn fetch<P: UtxoProvider>(tx: &MultiEraTx, provider: &P) -> Result<Self, P::Error> {
+        let mut resolved = HashMap::new();
+
+        for output_ref in tx_output_refs(tx).into_values() {
+            if resolved.contains_key(&output_ref) {
+                continue;
+            }
+
+            if let Some(output) = provider.resolve(&output_ref)? {
+                resolved.insert(output_ref, output);
+            }
+        }
+
+        Ok(Self(resolved))
+    }
+
+    /// Decodes the fetched outputs into the map expected by validation
+    pub fn utxos<'a>(&'a self, tx: &'a MultiEraTx) -> Result<UTxOs<'a>, minicbor::decode::Error> {
+        let mut utxos = UTxOs::new();
+
+        for (input, output_ref) in tx_output_refs(tx) {
+            if let Some(output) = self.0.get(&output_ref) {
+                utxos.insert(input, output.decode()?);
+            }
+        }
+
+        Ok(utxos)
+    }
+}
+
+impl Deref for ResolvedUtxos {
+    type Target = HashMap<OutputRef, EraCbor>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+fn tx_output_refs<'a>(tx: &'a MultiEraTx) -> HashMap<MultiEraInput<'a>, OutputRef> {
+    tx.inputs()
+        .into_iter()
+        .chain(tx.collateral())
+        .chain(tx.reference_inputs())
+        .filter_map(|input| output_ref(&input).map(|output_ref| (input, output_ref)))
+        .collect()
+}
+
+// Byron inputs other than regular ones refer to no output.
+pub(crate) fn output_ref(input: &MultiEraInput) -> Option<OutputRef> {
+    match input.as_byron() {
+        Some(TxIn::Variant0(CborWrap((tx_hash, index)))) => {
+            Some(OutputRef::new(*tx_hash, *index as u64))
+        }
+        Some(TxIn::Other(..)) => None,
+        None => Some(input.output_ref()),
+    }
+}
@@ -0,0 +1,449 @@
+mod common;
+
+use std::{borrow::Cow, collections::HashSet};
+
+use common::*;
+use pallas_addresses::ShelleyPaymentPart;
+use pallas_applying::{
+    types::{AlonzoProtParams, Environment, ValidationError},
+    validate, UTxOs, ValidationResult,
+};
+use pallas_codec::utils::KeyValuePairs;
+use pallas_crypto::hash::Hash;
+use pallas_primitives::alonzo::{
+    AssetName, Certificate, ExUnitPrices, ExUnits, Language, PoolKeyhash, RationalNumber,
+    TransactionInput, TransactionOutput, Value,
+};
+use pallas_traverse::{MultiEraBlock, MultiEraInput, MultiEraOutput, MultiEraTx};
+
+// The PlutusV1 cost model set in the Alonzo genesis file, shared by mainnet
+// and the public testnets.
+fn plutus_v1_cost_model() -> Vec<i64> {
+    vec![
+        197209, 0, 1, 1, 396231, 621, 0, 1, 150000, 1000, 0, 1, 150000, 32, 2477736, 29175, 4,
+        29773, 100, 29773, 100, 29773, 100, 29773, 100, 29773, 100, 29773, 100, 100, 100, 29773,
+        100, 150000, 32, 150000, 32, 150000, 32, 150000, 1000, 0, 1, 150000, 32, 150000, 1000, 0,
+        8, 148000, 425507, 118, 0, 1, 1, 150000, 1000, 0, 8, 150000, 112536, 247, 1, 150000, 10000,
+        1, 136542, 1326, 1, 1000, 150000, 1000, 1, 150000, 32, 150000, 32, 150000, 32, 1, 1,
+        150000, 1, 150000, 4, 103599, 248, 1, 103599, 248, 1, 145276, 1366, 1, 179690, 497, 1,
+        150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 148000, 425507,
+        118, 0, 1, 1, 61516, 11218, 0, 1, 150000, 32, 148000, 425507, 118, 0, 1, 1, 148000, 425507,
+        118, 0, 1, 1, 2477736, 29175, 4, 0, 82363, 4, 150000, 5000, 0, 1, 150000, 32, 197209, 0, 1,
+        1, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32,
+        3345831, 1, 1,
+    ]
+}
+
+fn mainnet_prot_params() -> AlonzoProtParams {
+    AlonzoProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        max_value_size: 5000,
+        coins_per_utxo_word: 34482,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+        collateral_percentage: 150,
+        max_collateral_inputs: 3,
+        max_tx_ex_units: ExUnits {
+            mem: 14000000,
+            steps: 10000000000,
+        },
+        ex_unit_prices: ExUnitPrices {
+            mem_price: RationalNumber {
+                numerator: 577,
+                denominator: 10000,
+            },
+            step_price: RationalNumber {
+                numerator: 721,
+                denominator: 10000000,
+            },
+        },
+        cost_models_for_script_languages: KeyValuePairs::from(vec![(
+            Language::PlutusV1,
+            plutus_v1_cost_model(),
+        )]),
+    }
+}
+
+fn tx_out(utxos: &UTxOs, input: &TransactionInput) -> TransactionOutput {
+    let key: MultiEraInput = MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(input.clone())));
+    utxos[&key].as_alonzo().unwrap().clone()
+}
+
+// Validates every tx in each of the given blocks.
+fn validate_blocks(names: &[&str], network_id: u8) {
+    let prot_params: AlonzoProtParams = mainnet_prot_params();
+    for name in names {
+        let block_bytes: Vec<u8> = block_bytes(name);
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let env: Environment = mk_env(&prot_params, block.slot(), network_id);
+        for metx in block.txs().iter() {
+            let utxos: UTxOs = mk_utxos_for_tx(metx, &prot_params, network_id);
+            match validate(metx, &utxos, &env) {
+                Ok(()) => (),
+                Err(err) => panic!("Unexpected error on tx {} ({:?}).", metx.hash(), err),
+            }
+        }
+    }
+}
+
+// Validates the first tx in alonzo9.block, which spends an input locked by a
+// Plutus script (the first one in tx order), against the given protocol
+// parameters and a UTxO set modified by the given function.
+fn validate_plutus_tx(
+    prot_pps: &AlonzoProtParams,
+    network_id: u8,
+    modify_utxos: impl Fn(&MultiEraTx, &mut UTxOs),
+) -> ValidationResult {
+    let block_bytes: Vec<u8> = block_bytes("alonzo9.block");
+    let block: MultiEraBlock = decode_block(&block_bytes);
+    let metx: MultiEraTx = nth_tx(&block, 0);
+    let mut utxos: UTxOs = mk_utxos_for_tx(&metx, &mainnet_prot_params(), 1);
+    modify_utxos(&metx, &mut utxos);
+    validate(&metx, &utxos, &mk_env(prot_pps, block.slot(), network_id))
+}
+
+fn script_input(metx: &MultiEraTx) -> TransactionInput {
+    metx.as_alonzo().unwrap().transaction_body.inputs[0].clone()
+}
+
+fn regular_input(metx: &MultiEraTx) -> TransactionInput {
+    metx.as_alonzo().unwrap().transaction_body.inputs[1].clone()
+}
+
+fn collateral_input(metx: &MultiEraTx) -> TransactionInput {
+    metx.as_alonzo()
+        .unwrap()
+        .transaction_body
+        .collateral
+        .as_ref()
+        .unwrap()[0]
+        .clone()
+}
+
+#[cfg(test)]
+mod alonzo_tests {
+    use super::*;
+
+    #[test]
+    // Every tx in alonzo9.block and alonzo21.block, which include txs running
+    // Plutus scripts and txs redeemed by bootstrap witnesses.
+    fn successful_mainnet_blocks() {
+        validate_blocks(&["alonzo9.block", "alonzo21.block"], 1);
+    }
+
+    #[test]
+    // Every tx in alonzo9.block and alonzo21.block only redeemed by bootstrap
+    // witnesses, spending outputs produced by Byron txs.
+    fn byron_utxos_spent() {
+        let prot_params: AlonzoProtParams = mainnet_prot_params();
+        let mut spent: usize = 0;
+        for name in ["alonzo9.block", "alonzo21.block"] {
+            let block_bytes: Vec<u8> = block_bytes(name);
+            let block: MultiEraBlock = decode_block(&block_bytes);
+            let env: Environment = mk_env(&prot_params, block.slot(), 1);
+            for metx in block.txs().iter() {
+                if !metx.vkey_witnesses().is_empty() {
+                    continue;
+                }
+                let utxos: UTxOs = mk_utxos_for_tx(metx, &prot_params, 1);
+                if !utxos
+                    .values()
+                    .any(|x| matches!(x, MultiEraOutput::Byron(_)))
+                {
+                    continue;
+                }
+                assert_eq!(validate(metx, &utxos, &env), Ok(()), "tx {}", metx.hash());
+                spent += 1;
+            }
+        }
+        assert!(spent > 0);
+    }
+
+    #[test]
+    // Every tx in alonzo12.block, alonzo14.block and alonzo27.block, which
+    // include txs running several Plutus scripts and txs adding datums to the
+    // witness set without running any script.
+    fn successful_testnet_blocks() {
+        validate_blocks(&["alonzo12.block", "alonzo14.block", "alonzo27.block"], 0);
+    }
+
+    #[test]
+    // The seventh tx in alonzo2.block, a testnet tx which registers a pool.
+    // Registering a new pool pays the deposit, while re-registering a known
+    // one doesn't.
+    fn pool_reregistration_pays_no_deposit() {
+        let block_bytes: Vec<u8> = block_bytes("alonzo2.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 6);
+        let operator: PoolKeyhash = match metx.certs()[0].as_alonzo() {
+            Some(Certificate::PoolRegistration { operator, .. }) => *operator,
+            _ => panic!("Unexpected certificate."),
+        };
+        let prot_params: AlonzoProtParams = mainnet_prot_params();
+        let reregistration_params: AlonzoProtParams = AlonzoProtParams {
+            pool_deposit: 0,
+            ..mainnet_prot_params()
+        };
+        let new_pool_utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 0);
+        let reregistration_utxos: UTxOs = mk_utxos_for_tx(&metx, &reregistration_params, 0);
+        let env: Environment = mk_env(&prot_params, block.slot(), 0);
+        let registered_env: Environment = Environment {
+            registered_pools: Cow::Owned(HashSet::from([operator])),
+            ..mk_env(&prot_params, block.slot(), 0)
+        };
+
+        assert_eq!(validate(&metx, &new_pool_utxos, &env), Ok(()));
+        assert_eq!(
+            validate(&metx, &reregistration_utxos, &registered_env),
+            Ok(())
+        );
+        assert!(matches!(
+            validate(&metx, &reregistration_utxos, &env),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+        assert!(matches!(
+            validate(&metx, &new_pool_utxos, &registered_env),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+    }
+
+    #[test]
+    fn successful_plutus_tx() {
+        match validate_plutus_tx(&mainnet_prot_params(), 1, |_, _| ()) {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+    }
+
+    #[test]
+    // The fee paid covers the linear fee, but not the cost of the execution
+    // units at higher prices.
+    fn fees_below_min() {
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            ex_unit_prices: ExUnitPrices {
+                mem_price: RationalNumber {
+                    numerator: 1,
+                    denominator: 1,
+                },
+                step_price: RationalNumber {
+                    numerator: 1,
+                    denominator: 1,
+                },
+            },
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::FeeTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn too_many_collaterals() {
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            max_collateral_inputs: 0,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::TooManyCollaterals { .. })
+        ));
+    }
+
+    #[test]
+    fn collateral_not_in_utxo() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let input = collateral_input(metx);
+            utxos.remove(&MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(
+                input,
+            ))));
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::CollateralNotInUTxO(_))
+        ));
+    }
+
+    #[test]
+    fn collateral_not_vkey_locked() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &collateral_input(metx));
+            output.address = script_address(metx, 1).into();
+            add_to_utxo(utxos, collateral_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::CollateralNotVKeyLocked(_))
+        ));
+    }
+
+    #[test]
+    fn non_lovelace_collateral() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &collateral_input(metx));
+            let asset: KeyValuePairs<AssetName, u64> =
+                KeyValuePairs::from(vec![(Vec::from(*b"token").into(), 1)]);
+            output.amount = Value::Multiasset(
+                metx.fee().unwrap() * 2,
+                KeyValuePairs::from(vec![(Hash::new([0; 28]), asset)]),
+            );
+            add_to_utxo(utxos, collateral_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::NonLovelaceCollateral)
+        ));
+    }
+
+    #[test]
+    fn collateral_min_lovelace() {
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            collateral_percentage: 1000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::CollateralMinLovelace { .. })
+        ));
+    }
+
+    #[test]
+    fn min_lovelace_unreached() {
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            coins_per_utxo_word: 10000000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::OutputTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn max_val_size_exceeded() {
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            max_value_size: 10,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::MaxValSizeExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn wrong_network_id() {
+        assert!(matches!(
+            validate_plutus_tx(&mainnet_prot_params(), 0, |_, _| ()),
+            Err(ValidationError::WrongNetworkID(_))
+        ));
+    }
+
+    #[test]
+    fn tx_ex_units_exceeded() {
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            max_tx_ex_units: ExUnits {
+                mem: 1000,
+                steps: 10000000000,
+            },
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::TxExUnitsExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn unspendable_utxo_no_datum_hash() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &script_input(metx));
+            output.datum_hash = None;
+            add_to_utxo(utxos, script_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::UnspendableUTxONoDatumHash(_))
+        ));
+    }
+
+    #[test]
+    fn datum_missing() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &script_input(metx));
+            output.datum_hash = Some(Hash::new([0; 32]));
+            add_to_utxo(utxos, script_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::DatumMissing(_))
+        ));
+    }
+
+    #[test]
+    // The input locked by the Plutus script is assigned a vkey address, so
+    // its datum is no longer needed.
+    fn unneeded_datum() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &script_input(metx));
+            output.address = witness_address(metx, 1).into();
+            add_to_utxo(utxos, script_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::UnneededDatum(_))
+        ));
+    }
+
+    #[test]
+    // Both inputs are locked by the Plutus script, but there is a redeemer for
+    // only one of them.
+    fn redeemer_missing() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &regular_input(metx));
+            let script_output: TransactionOutput = tx_out(utxos, &script_input(metx));
+            output.address = script_output.address;
+            output.datum_hash = script_output.datum_hash;
+            add_to_utxo(utxos, regular_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::RedeemerMissing { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_script_witness() {
+        let validation_result = validate_plutus_tx(&mainnet_prot_params(), 1, |metx, utxos| {
+            let mut output: TransactionOutput = tx_out(utxos, &script_input(metx));
+            let payment = ShelleyPaymentPart::script_hash(Hash::new([0; 28]));
+            output.address = enterprise_address(1, payment).into();
+            add_to_utxo(utxos, script_input(metx), output);
+        });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::MissingScriptWitness(_))
+        ));
+    }
+
+    #[test]
+    // The cost model in the protocol parameters differs from the one the
+    // script data hash was computed with.
+    fn script_integrity_hash_mismatch() {
+        let mut cost_model: Vec<i64> = plutus_v1_cost_model();
+        cost_model[0] += 1;
+        let prot_params: AlonzoProtParams = AlonzoProtParams {
+            cost_models_for_script_languages: KeyValuePairs::from(vec![(
+                Language::PlutusV1,
+                cost_model,
+            )]),
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_plutus_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::ScriptIntegrityHashMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,625 @@
+mod common;
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use common::*;
+use pallas_applying::{
+    types::{BabbageProtParams, Environment, ValidationError},
+    validate, validate_all, EraCbor, ResolvedUtxos, UTxOs, ValidationResult,
+};
+use pallas_codec::{minicbor, utils::KeyValuePairs};
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{
+    babbage::{
+        AssetName, Certificate, CostMdls, ExUnitPrices, ExUnits, MintedPostAlonzoTransactionOutput,
+        MintedTransactionOutput, MintedTx, PoolKeyhash, RationalNumber, TransactionInput, Value,
+    },
+    conway,
+};
+use pallas_traverse::{Era, MultiEraBlock, MultiEraInput, MultiEraOutput, MultiEraTx, OutputRef};
+
+// The PlutusV1 cost model in effect on mainnet after the Vasil hard fork, also
+// proposed in the updates of babbage4.block and babbage5.block.
+fn plutus_v1_cost_model() -> Vec<i64> {
+    vec![
+        205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475, 4, 23000,
+        100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100, 100, 23000, 100,
+        19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32, 497525, 14068, 4, 2, 196500,
+        453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000, 216773, 62, 1, 1060367, 12586, 1, 208512,
+        421, 1, 187000, 1000, 52998, 1, 80436, 32, 43249, 32, 1000, 32, 80556, 1, 57667, 4, 1000,
+        10, 197145, 156, 1, 197145, 156, 1, 204924, 473, 1, 208896, 511, 1, 52467, 32, 64832, 32,
+        65493, 32, 22558, 32, 16563, 32, 76511, 32, 196500, 453240, 220, 0, 1, 1, 69522, 11687, 0,
+        1, 60091, 32, 196500, 453240, 220, 0, 1, 1, 196500, 453240, 220, 0, 1, 1, 806990, 30482, 4,
+        1927926, 82523, 4, 265318, 0, 4, 0, 85931, 32, 205665, 812, 1, 1, 41182, 32, 212342, 32,
+        31220, 32, 32696, 32, 43357, 32, 32247, 32, 38314, 32, 9462713, 1021, 10,
+    ]
+}
+
+// The PlutusV2 cost model in effect on mainnet after the Vasil hard fork, also
+// proposed in the update of babbage5.block.
+fn plutus_v2_cost_model() -> Vec<i64> {
+    // Cost of the SECP256k1 builtins, which were disabled at the time.
+    const DISABLED: i64 = 20000000000;
+
+    vec![
+        205665, 812, 1, 1, 1000, 571, 0, 1, 1000, 24177, 4, 1, 1000, 32, 117366, 10475, 4, 23000,
+        100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 23000, 100, 100, 100, 23000, 100,
+        19537, 32, 175354, 32, 46417, 4, 221973, 511, 0, 1, 89141, 32, 497525, 14068, 4, 2, 196500,
+        453240, 220, 0, 1, 1, 1000, 28662, 4, 2, 245000, 216773, 62, 1, 1060367, 12586, 1, 208512,
+        421, 1, 187000, 1000, 52998, 1, 80436, 32, 43249, 32, 1000, 32, 80556, 1, 57667, 4, 1000,
+        10, 197145, 156, 1, 197145, 156, 1, 204924, 473, 1, 208896, 511, 1, 52467, 32, 64832, 32,
+        65493, 32, 22558, 32, 16563, 32, 76511, 32, 196500, 453240, 220, 0, 1, 1, 69522, 11687, 0,
+        1, 60091, 32, 196500, 453240, 220, 0, 1, 1, 196500, 453240, 220, 0, 1, 1, 1159724, 392670,
+        0, 2, 806990, 30482, 4, 1927926, 82523, 4, 265318, 0, 4, 0, 85931, 32, 205665, 812, 1, 1,
+        41182, 32, 212342, 32, 31220, 32, 32696, 32, 43357, 32, 32247, 32, 38314, 32, DISABLED,
+        DISABLED, 9462713, 1021, 10, DISABLED, 0, DISABLED,
+    ]
+}
+
+fn mainnet_prot_params() -> BabbageProtParams {
+    BabbageProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        max_value_size: 5000,
+        ada_per_utxo_byte: 4310,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+        collateral_percentage: 150,
+        max_collateral_inputs: 3,
+        max_tx_ex_units: ExUnits {
+            mem: 14000000,
+            steps: 10000000000,
+        },
+        ex_unit_prices: ExUnitPrices {
+            mem_price: RationalNumber {
+                numerator: 577,
+                denominator: 10000,
+            },
+            step_price: RationalNumber {
+                numerator: 721,
+                denominator: 10000000,
+            },
+        },
+        cost_models_for_script_languages: CostMdls {
+            plutus_v1: Some(plutus_v1_cost_model()),
+            plutus_v2: Some(plutus_v2_cost_model()),
+        },
+    }
+}
+
+fn output_value<'a>(output: &'a MintedTransactionOutput) -> &'a Value {
+    match output {
+        MintedTransactionOutput::Legacy(x) => &x.amount,
+        MintedTransactionOutput::PostAlonzo(x) => &x.value,
+    }
+}
+
+fn tx_out<'a>(
+    utxos: &UTxOs<'a>,
+    input: &TransactionInput,
+) -> MintedPostAlonzoTransactionOutput<'a> {
+    let key: MultiEraInput = MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(input.clone())));
+    match &utxos[&key] {
+        MultiEraOutput::Babbage(x) => match x.as_ref().as_ref() {
+            MintedTransactionOutput::PostAlonzo(x) => x.clone(),
+            _ => panic!("unexpected UTxO"),
+        },
+        _ => panic!("unexpected UTxO"),
+    }
+}
+
+fn remove_from_utxo(utxos: &mut UTxOs, input: &TransactionInput) {
+    utxos.remove(&MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(
+        input.clone(),
+    ))));
+}
+
+// An in-memory UTxO provider holding the same outputs as the given UTxO set.
+fn mk_provider(utxos: &UTxOs) -> HashMap<OutputRef, EraCbor> {
+    utxos
+        .iter()
+        .map(|(input, output)| (input.output_ref(), EraCbor::from(output)))
+        .collect()
+}
+
+// Validates the given txs of a block.
+fn validate_txs(name: &str, indexes: &[usize], network_id: u8) {
+    let prot_params: BabbageProtParams = mainnet_prot_params();
+    let block_bytes: Vec<u8> = block_bytes(name);
+    let block: MultiEraBlock = decode_block(&block_bytes);
+    let env: Environment = mk_env(&prot_params, block.slot(), network_id);
+    for index in indexes {
+        let metx: MultiEraTx = nth_tx(&block, *index);
+        let utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, network_id);
+        match validate(&metx, &utxos, &env) {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error on tx {} ({:?}).", metx.hash(), err),
+        }
+    }
+}
+
+// Validates the given tx of babbage9.block against the given protocol
+// parameters and a UTxO set modified by the given function.
+fn validate_mainnet_tx(
+    index: usize,
+    prot_pps: &BabbageProtParams,
+    network_id: u8,
+    modify_utxos: impl Fn(&MintedTx, &mut UTxOs),
+) -> ValidationResult {
+    let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+    let block: MultiEraBlock = decode_block(&block_bytes);
+    let metx: MultiEraTx = nth_tx(&block, index);
+    let mtx: &MintedTx = metx.as_babbage().unwrap();
+    let mut utxos: UTxOs = mk_utxos_for_tx(&metx, &mainnet_prot_params(), 1);
+    modify_utxos(mtx, &mut utxos);
+    validate(&metx, &utxos, &mk_env(prot_pps, block.slot(), network_id))
+}
+
+// The second tx in babbage9.block spends an input locked by a PlutusV2 script
+// provided through a reference input, and has a collateral return output
+// together with its total collateral.
+fn validate_reference_script_tx(
+    prot_pps: &BabbageProtParams,
+    network_id: u8,
+    modify_utxos: impl Fn(&MintedTx, &mut UTxOs),
+) -> ValidationResult {
+    validate_mainnet_tx(1, prot_pps, network_id, modify_utxos)
+}
+
+fn script_input<'a>(mtx: &'a MintedTx) -> &'a TransactionInput {
+    let mut inputs: Vec<&TransactionInput> = mtx.transaction_body.inputs.iter().collect();
+    inputs.sort();
+    let redeemer = &mtx.transaction_witness_set.redeemer.as_ref().unwrap()[0];
+    inputs[redeemer.index as usize]
+}
+
+fn collateral_input<'a>(mtx: &'a MintedTx) -> &'a TransactionInput {
+    &mtx.transaction_body.collateral.as_ref().unwrap()[0]
+}
+
+fn reference_input<'a>(mtx: &'a MintedTx) -> &'a TransactionInput {
+    &mtx.transaction_body.reference_inputs.as_ref().unwrap()[0]
+}
+
+#[cfg(test)]
+mod babbage_tests {
+    use super::*;
+
+    #[test]
+    // Every tx in babbage9.block, which include txs running PlutusV1 scripts
+    // from the witness set and PlutusV2 scripts from reference inputs, txs with
+    // collateral return outputs, and txs adding datums to the witness set
+    // without running any script.
+    fn successful_mainnet_block() {
+        validate_txs("babbage9.block", &(0..47).collect::<Vec<usize>>(), 1);
+    }
+
+    #[test]
+    // Every tx in babbage4.block, one of which runs a PlutusV1 script, and the
+    // tx in babbage6.block, which mints under a PlutusV2 policy and has a
+    // collateral return output.
+    fn successful_testnet_txs() {
+        validate_txs("babbage4.block", &[0, 1, 2, 3, 4], 0);
+        validate_txs("babbage6.block", &[0], 0);
+    }
+
+    #[test]
+    // The first tx in babbage10.block, a testnet tx which registers a pool.
+    // Registering a new pool pays the deposit, while re-registering a known
+    // one doesn't.
+    fn pool_reregistration_pays_no_deposit() {
+        let block_bytes: Vec<u8> = block_bytes("babbage10.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 0);
+        let mtx: &MintedTx = metx.as_babbage().unwrap();
+        let operator: PoolKeyhash = match &mtx.transaction_body.certificates.as_ref().unwrap()[0] {
+            Certificate::PoolRegistration { operator, .. } => *operator,
+            _ => panic!("Unexpected certificate."),
+        };
+        let prot_params: BabbageProtParams = mainnet_prot_params();
+        let reregistration_params: BabbageProtParams = BabbageProtParams {
+            pool_deposit: 0,
+            ..mainnet_prot_params()
+        };
+        let new_pool_utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 0);
+        let reregistration_utxos: UTxOs = mk_utxos_for_tx(&metx, &reregistration_params, 0);
+        let env: Environment = mk_env(&prot_params, block.slot(), 0);
+        let registered_env: Environment = Environment {
+            registered_pools: Cow::Owned(HashSet::from([operator])),
+            ..mk_env(&prot_params, block.slot(), 0)
+        };
+
+        assert_eq!(validate(&metx, &new_pool_utxos, &env), Ok(()));
+        assert_eq!(
+            validate(&metx, &reregistration_utxos, &registered_env),
+            Ok(())
+        );
+        assert!(matches!(
+            validate(&metx, &reregistration_utxos, &env),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+        assert!(matches!(
+            validate(&metx, &new_pool_utxos, &registered_env),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+    }
+
+    #[test]
+    fn successful_reference_script_tx() {
+        match validate_reference_script_tx(&mainnet_prot_params(), 1, |_, _| ()) {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+    }
+
+    #[test]
+    fn ref_input_missing_in_utxo() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                remove_from_utxo(utxos, reference_input(mtx));
+            });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::RefInputNotInUTxO(_))
+        ));
+    }
+
+    #[test]
+    // The spent, collateral and reference inputs are all fetched from a UTxO
+    // provider, the reference input being unknown to a second one.
+    fn utxos_from_provider() {
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 1);
+        let mtx: &MintedTx = metx.as_babbage().unwrap();
+        let prot_params: BabbageProtParams = mainnet_prot_params();
+        let env: Environment = mk_env(&prot_params, block.slot(), 1);
+        let mut provider: HashMap<OutputRef, EraCbor> =
+            mk_provider(&mk_utxos_for_tx(&metx, &prot_params, 1));
+        let resolved: ResolvedUtxos = ResolvedUtxos::fetch(&metx, &provider).unwrap();
+        assert_eq!(resolved.len(), provider.len());
+        match validate(&metx, &resolved.utxos(&metx).unwrap(), &env) {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+        let reference_input: OutputRef = OutputRef::new(
+            reference_input(mtx).transaction_id,
+            reference_input(mtx).index,
+        );
+        provider.remove(&reference_input);
+        let resolved: ResolvedUtxos = ResolvedUtxos::fetch(&metx, &provider).unwrap();
+        assert_eq!(
+            validate(&metx, &resolved.utxos(&metx).unwrap(), &env),
+            Err(ValidationError::RefInputNotInUTxO(reference_input))
+        );
+    }
+
+    #[test]
+    // The reference input is missing from the UTxO set, and so is the script
+    // it provides, while the tx is validated against another network. All
+    // failures are gathered.
+    fn all_failures_gathered() {
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 1);
+        let mtx: &MintedTx = metx.as_babbage().unwrap();
+        let mut utxos: UTxOs = mk_utxos_for_tx(&metx, &mainnet_prot_params(), 1);
+        remove_from_utxo(&mut utxos, reference_input(mtx));
+        let prot_params: BabbageProtParams = mainnet_prot_params();
+        let env: Environment = mk_env(&prot_params, block.slot(), 0);
+        let errors: Vec<ValidationError> = validate_all(&metx, &utxos, &env).unwrap_err();
+        let reference_input: OutputRef = OutputRef::new(
+            reference_input(mtx).transaction_id,
+            reference_input(mtx).index,
+        );
+        assert!(errors.contains(&ValidationError::RefInputNotInUTxO(reference_input)));
+        assert!(errors.contains(&ValidationError::MissingScriptWitness(
+            spending_script_hash(&metx)
+        )));
+        assert!(errors
+            .iter()
+            .any(|x| matches!(x, ValidationError::WrongNetworkID(_))));
+    }
+
+    #[test]
+    // The reference input no longer holds the script locking the spent input.
+    fn missing_reference_script() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                let mut output = tx_out(utxos, reference_input(mtx));
+                output.script_ref = None;
+                add_to_utxo(
+                    utxos,
+                    reference_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+            });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::MissingScriptWitness(_))
+        ));
+    }
+
+    #[test]
+    fn fees_below_min() {
+        let prot_params: BabbageProtParams = BabbageProtParams {
+            min_fee_a: 1000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_reference_script_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::FeeTooSmall { required, actual }) if actual < required
+        ));
+    }
+
+    #[test]
+    fn collateral_min_lovelace() {
+        let prot_params: BabbageProtParams = BabbageProtParams {
+            collateral_percentage: 1000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_reference_script_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::CollateralMinLovelace { .. })
+        ));
+    }
+
+    #[test]
+    // The collateral input holds more lovelace than the collateral return
+    // output plus the total collateral declared by the tx.
+    fn collateral_annotation_mismatch() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                let mut output = tx_out(utxos, collateral_input(mtx));
+                output.value = add_coin(&output.value, 1);
+                add_to_utxo(
+                    utxos,
+                    collateral_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+            });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::CollateralAnnotationMismatch { declared, actual })
+                if actual == declared as i128 + 1
+        ));
+    }
+
+    #[test]
+    // The collateral input is a Conway output, whose value is unknown to the
+    // Babbage rules. The failure is gathered together with the later ones.
+    fn unknown_collateral_type() {
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 1);
+        let mtx: &MintedTx = metx.as_babbage().unwrap();
+        let prot_params: BabbageProtParams = BabbageProtParams {
+            max_tx_size: 1,
+            ..mainnet_prot_params()
+        };
+        let output_bytes: Vec<u8> = minicbor::to_vec(tx_out(
+            &mk_utxos_for_tx(&metx, &prot_params, 1),
+            collateral_input(mtx),
+        ))
+        .unwrap();
+        let conway_output: conway::MintedTransactionOutput =
+            minicbor::decode(&output_bytes).unwrap();
+        let mut utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        utxos.insert(
+            MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(collateral_input(mtx).clone()))),
+            MultiEraOutput::Conway(Box::new(Cow::Owned(conway_output))),
+        );
+        let errors: Vec<ValidationError> =
+            validate_all(&metx, &utxos, &mk_env(&prot_params, block.slot(), 1)).unwrap_err();
+        assert!(errors.contains(&ValidationError::UnknownInputType));
+        assert!(errors
+            .iter()
+            .any(|x| matches!(x, ValidationError::MaxTxSizeExceeded { .. })));
+    }
+
+    #[test]
+    // The collateral input holds less lovelace than the collateral return
+    // output, so the actual collateral is negative rather than zero.
+    fn negative_collateral_annotation_mismatch() {
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 1);
+        let mtx: &MintedTx = metx.as_babbage().unwrap();
+        let prot_params: BabbageProtParams = mainnet_prot_params();
+        let mut utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        let mut output = tx_out(&utxos, collateral_input(mtx));
+        output.value = Value::Coin(1);
+        add_to_utxo(
+            &mut utxos,
+            collateral_input(mtx).clone(),
+            MintedTransactionOutput::PostAlonzo(output),
+        );
+        let returned: i128 =
+            match output_value(mtx.transaction_body.collateral_return.as_ref().unwrap()) {
+                Value::Coin(x) => *x as i128,
+                Value::Multiasset(x, _) => *x as i128,
+            };
+        let errors: Vec<ValidationError> =
+            validate_all(&metx, &utxos, &mk_env(&prot_params, block.slot(), 1)).unwrap_err();
+        assert!(
+            errors.contains(&ValidationError::CollateralAnnotationMismatch {
+                declared: mtx.transaction_body.total_collateral.unwrap(),
+                actual: 1 - returned,
+            })
+        );
+    }
+
+    #[test]
+    // The collateral input holds a native asset not sent back through the
+    // collateral return output.
+    fn non_lovelace_collateral() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                let mut output = tx_out(utxos, collateral_input(mtx));
+                let coin: u64 = match output.value {
+                    Value::Coin(x) => x,
+                    Value::Multiasset(x, _) => x,
+                };
+                let asset: KeyValuePairs<AssetName, u64> =
+                    KeyValuePairs::from(vec![(Vec::from(*b"token").into(), 1)]);
+                output.value =
+                    Value::Multiasset(coin, KeyValuePairs::from(vec![(Hash::new([0; 28]), asset)]));
+                add_to_utxo(
+                    utxos,
+                    collateral_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+            });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::NonLovelaceCollateral)
+        ));
+    }
+
+    #[test]
+    fn min_lovelace_unreached() {
+        let prot_params: BabbageProtParams = BabbageProtParams {
+            ada_per_utxo_byte: 1000000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_reference_script_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::OutputTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn max_val_size_exceeded() {
+        let prot_params: BabbageProtParams = BabbageProtParams {
+            max_value_size: 1,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_reference_script_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::MaxValSizeExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn wrong_network_id() {
+        assert!(matches!(
+            validate_reference_script_tx(&mainnet_prot_params(), 0, |_, _| ()),
+            Err(ValidationError::WrongNetworkID(_))
+        ));
+    }
+
+    #[test]
+    fn unspendable_utxo_no_datum_hash() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                let mut output = tx_out(utxos, script_input(mtx));
+                output.datum_option = None;
+                add_to_utxo(
+                    utxos,
+                    script_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+            });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::UnspendableUTxONoDatumHash(_))
+        ));
+    }
+
+    #[test]
+    // The input locked by the Plutus script holds an inline datum, so the one
+    // in the witness set is no longer needed.
+    fn unneeded_datum() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                let mut output = tx_out(utxos, script_input(mtx));
+                output.datum_option = Some(inline_datum());
+                add_to_utxo(
+                    utxos,
+                    script_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+                let mut output = tx_out(utxos, reference_input(mtx));
+                output.datum_option = None;
+                add_to_utxo(
+                    utxos,
+                    reference_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+            });
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::UnneededDatum(_))
+        ));
+    }
+
+    #[test]
+    // The datum in the witness set is only referenced by the UTxO of the
+    // reference input, which makes it a valid supplemental datum.
+    fn supplemental_datum() {
+        let validation_result =
+            validate_reference_script_tx(&mainnet_prot_params(), 1, |mtx, utxos| {
+                let mut output = tx_out(utxos, script_input(mtx));
+                output.datum_option = Some(inline_datum());
+                add_to_utxo(
+                    utxos,
+                    script_input(mtx).clone(),
+                    MintedTransactionOutput::PostAlonzo(output),
+                );
+            });
+        match validation_result {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+    }
+
+    #[test]
+    // The reference script is a PlutusV1 one, so the language views differ
+    // from those the script data hash was computed with.
+    fn script_integrity_hash_mismatch() {
+        let prot_params: BabbageProtParams = BabbageProtParams {
+            cost_models_for_script_languages: CostMdls {
+                plutus_v1: Some(plutus_v1_cost_model()),
+                plutus_v2: Some(plutus_v1_cost_model()),
+            },
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_reference_script_tx(&prot_params, 1, |_, _| ()),
+            Err(ValidationError::ScriptIntegrityHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    // The 45th tx in babbage9.block runs PlutusV1 scripts from its witness
+    // set, whose view is encoded with the quirks of the Alonzo era.
+    fn successful_plutus_v1_tx() {
+        match validate_mainnet_tx(44, &mainnet_prot_params(), 1, |_, _| ()) {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+    }
+
+    #[test]
+    // The rules of Conway are not implemented yet, so its txs are rejected
+    // rather than let through unchecked, whatever the protocol parameters.
+    fn conway_tx_is_rejected() {
+        let tx_bytes: Vec<u8> = block_bytes("conway1.tx");
+        let metx: MultiEraTx = MultiEraTx::decode(Era::Conway, &tx_bytes).unwrap();
+        let prot_params: BabbageProtParams = mainnet_prot_params();
+        let env: Environment = mk_env(&prot_params, 75_000_000, 1);
+        let utxos: UTxOs = UTxOs::new();
+        assert_eq!(
+            validate(&metx, &utxos, &env),
+            Err(ValidationError::UnsupportedEra(Era::Conway))
+        );
+        assert_eq!(
+            validate_all(&metx, &utxos, &env),
+            Err(vec![ValidationError::UnsupportedEra(Era::Conway)])
+        );
+    }
+}
@@ -1,135 +1,130 @@
-use rand::Rng;
-use std::{borrow::Cow, vec::Vec};
+use std::{borrow::Cow, collections::HashSet, path::PathBuf, vec::Vec};
 
+use pallas_addresses::{
+    byron::{AddrAttrs, AddrType, AddressPayload, SpendingData},
+    ByronAddress,
+};
 use pallas_applying::{
-    types::{ByronProtParams, MultiEraProtParams},
+    types::{ByronProtParams, Environment, FeePolicy, MultiEraProtParams, ValidationError},
     validate, UTxOs, ValidationResult,
 };
 use pallas_codec::{
     minicbor::{
-        bytes::ByteVec,
         decode::{Decode, Decoder},
         encode,
     },
-    utils::{CborWrap, EmptyMap, KeepRaw, MaybeIndefArray, TagWrap},
+    utils::{KeepRaw, MaybeIndefArray, TagWrap},
 };
-use pallas_crypto::hash::Hash;
+use pallas_configs::byron::GenesisFile;
 use pallas_primitives::byron::{
-    Address, Attributes, MintedTxPayload as ByronTxPayload, Tx as ByronTx, TxId as ByronTxId,
-    TxIn as ByronTxIn, TxOut as ByronTxOut, Witnesses as ByronWitnesses,
+    Address, MintedTxPayload as ByronTxPayload, Twit, Tx as ByronTx, TxIn as ByronTxIn,
+    TxOut as ByronTxOut, Witnesses as ByronWitnesses,
 };
-use pallas_traverse::{MultiEraInput, MultiEraOutput, MultiEraTx};
-
-#[cfg(test)]
-mod byron_tests {
-    use super::*;
-
-    #[test]
-    // Note that:
-    //      i)   the transaction input contains 100000 lovelace,
-    //      ii)  the minimum_fee_constant protocol parameter is 7,
-    //      iii) the minimum_fee_factor protocol parameter is 11, and
-    //      iv)  the size of the transaction is 82 bytes—it is easy to verify that
-    //              82 == pallas_applying::get_byron_tx_size(tx)
-    // The expected fees are therefore 7 + 11 * 82 = 909 lovelace, which is why the output contains
-    // 100000 - 909 = 99091 lovelace.
-    fn successful_case() {
-        let protocol_params: ByronProtParams = ByronProtParams;
-        let mut tx_ins: ByronTxIns = empty_byron_tx_ins();
-        let tx_in: ByronTxIn = new_tx_in(random_tx_id(), 3);
-        add_byron_tx_in(&mut tx_ins, &tx_in);
-        let mut tx_outs: ByronTxOuts = new_byron_tx_outs();
-        let tx_out: ByronTxOut = new_byron_tx_out(new_address(random_address_payload(), 0), 99091);
-        add_byron_tx_out(&mut tx_outs, &tx_out);
-        let mut utxos: UTxOs = new_utxos();
-        // input_tx_out is the ByronTxOut associated with tx_in.
-        let input_tx_out: ByronTxOut =
-            new_byron_tx_out(new_address(random_address_payload(), 0), 100000);
-        add_to_utxo(&mut utxos, tx_in, input_tx_out);
-        let validation_result = mk_byron_tx_and_validate(
-            &new_byron_tx(tx_ins, tx_outs, empty_byron_attributes()),
-            &empty_byron_witnesses(),
-            &utxos,
-            &protocol_params,
-        );
-        match validation_result {
-            Ok(()) => (),
-            Err(err) => assert!(false, "Unexpected error (sucessful_case - {:?}).", err),
-        }
-    }
-}
-
-// Types aliases.
-type ByronTxIns = MaybeIndefArray<ByronTxIn>;
-type ByronTxOuts = MaybeIndefArray<ByronTxOut>;
+use pallas_traverse::{MultiEraBlock, MultiEraInput, MultiEraOutput, MultiEraTx};
 
 // Helper functions.
-fn empty_byron_tx_ins() -> ByronTxIns {
-    MaybeIndefArray::Def(Vec::new())
+fn block_bytes(name: &str) -> Vec<u8> {
+    let path: String = format!("{}/../test_data/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let block_str: String = std::fs::read_to_string(path).expect("missing block file");
+    hex::decode(block_str.trim()).expect("bad block file")
 }
 
-fn random_tx_id() -> ByronTxId {
-    let mut rng = rand::thread_rng();
-    let mut bytes = [0u8; 32];
-    for elem in bytes.iter_mut() {
-        *elem = rng.gen();
+fn nth_tx(block_bytes: &[u8], index: usize) -> ByronTxPayload {
+    match MultiEraBlock::decode(block_bytes) {
+        Ok(MultiEraBlock::Byron(block)) => block.body.tx_payload[index].clone(),
+        _ => panic!("Unable to decode Byron block."),
     }
-    Hash::new(bytes)
 }
 
-fn new_tx_in(tx_id: ByronTxId, index: u32) -> ByronTxIn {
-    ByronTxIn::Variant0(CborWrap((tx_id, index)))
+fn load_genesis(network: &str) -> GenesisFile {
+    let path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("test_data")
+        .join(format!("{network}-byron-genesis.json"));
+    pallas_configs::byron::from_file(&path).expect("bad genesis file")
 }
 
-fn add_byron_tx_in(ins: &mut ByronTxIns, new_in: &ByronTxIn) {
-    match ins {
-        MaybeIndefArray::Def(vec) | MaybeIndefArray::Indef(vec) => vec.push(new_in.clone()),
+fn mainnet_prot_params() -> ByronProtParams {
+    ByronProtParams::from_genesis(&load_genesis("mainnet")).expect("bad genesis values")
+}
+
+fn testnet_prot_params() -> ByronProtParams {
+    ByronProtParams {
+        max_tx_size: 65536,
+        protocol_magic: 1097911063,
+        ..mainnet_prot_params()
     }
 }
 
-fn new_byron_tx_outs() -> ByronTxOuts {
-    MaybeIndefArray::Def(Vec::new())
+fn min_fee(mtxp: &ByronTxPayload, prot_pps: &ByronProtParams) -> u64 {
+    pallas_traverse::fees::compute_byron_fee(mtxp, Some(&prot_pps.fee_policy))
 }
 
-fn random_address_payload() -> TagWrap<ByteVec, 24> {
-    let mut rng = rand::thread_rng();
-    let mut bytes = [0u8; 24];
-    for elem in bytes.iter_mut() {
-        *elem = rng.gen();
+// Byron validation depends on neither the slot nor the network id.
+fn mk_env(prot_pps: &ByronProtParams) -> Environment {
+    Environment {
+        prot_params: MultiEraProtParams::Byron(Box::new(Cow::Borrowed(prot_pps))),
+        block_slot: 0,
+        network_id: 1,
+        registered_pools: Cow::Owned(HashSet::new()),
     }
-    TagWrap::<ByteVec, 24>::new(ByteVec::from(bytes.to_vec()))
 }
 
-fn new_address(payload: TagWrap<ByteVec, 24>, crc: u32) -> Address {
+// The actual addresses of the spent outputs are not part of the fixtures, so
+// each input is assigned a bootstrap address derived from the verification key
+// of one of the witnesses, which the ledger accepts as the owner of the input.
+fn witness_address(mtxp: &ByronTxPayload, index: usize) -> Address {
+    let witness: &Twit = &mtxp.witness[index.min(mtxp.witness.len() - 1)];
+    let pub_key = match witness {
+        Twit::PkWitness(x) => x.0 .0.clone(),
+        _ => panic!("Unexpected witness type."),
+    };
+    let payload: AddressPayload = AddressPayload::new(
+        AddrType::PubKey,
+        SpendingData::PubKey(pub_key),
+        empty_attrs(),
+    );
+    let address: ByronAddress = ByronAddress::from_decoded(payload);
     Address {
-        payload: payload,
-        crc: crc,
+        payload: TagWrap(address.payload.0),
+        crc: address.crc,
     }
 }
 
-fn new_byron_tx_out(address: Address, amount: u64) -> ByronTxOut {
-    ByronTxOut {
-        address: address,
-        amount: amount,
-    }
+fn empty_attrs() -> AddrAttrs {
+    Vec::new().into()
+}
+
+fn mk_tx_out(address: Address, amount: u64) -> ByronTxOut {
+    ByronTxOut { address, amount }
 }
 
-fn add_byron_tx_out(outs: &mut ByronTxOuts, new_out: &ByronTxOut) {
-    match outs {
-        MaybeIndefArray::Def(vec) | MaybeIndefArray::Indef(vec) => vec.push(new_out.clone()),
+// Builds a UTxO set holding every input of the tx, in such a way that the tx
+// pays exactly the given fee.
+fn mk_utxos_for_tx<'a>(mtxp: &ByronTxPayload, fee: u64) -> UTxOs<'a> {
+    let mut utxos: UTxOs = UTxOs::new();
+    let outputs_balance: u64 = mtxp.transaction.outputs.iter().map(|o| o.amount).sum();
+    let inputs: &Vec<ByronTxIn> = &mtxp.transaction.inputs;
+    for (index, input) in inputs.iter().enumerate() {
+        let amount: u64 = match index {
+            0 => outputs_balance + fee - (inputs.len() as u64 - 1),
+            _ => 1,
+        };
+        add_to_utxo(
+            &mut utxos,
+            input.clone(),
+            mk_tx_out(witness_address(mtxp, index), amount),
+        );
     }
+    utxos
 }
 
-fn add_to_utxo<'a>(utxos: &mut UTxOs<'a>, tx_in: ByronTxIn, tx_out: ByronTxOut) {
+fn add_to_utxo(utxos: &mut UTxOs, tx_in: ByronTxIn, tx_out: ByronTxOut) {
     let multi_era_in: MultiEraInput = MultiEraInput::Byron(Box::new(Cow::Owned(tx_in)));
     let multi_era_out: MultiEraOutput = MultiEraOutput::Byron(Box::new(Cow::Owned(tx_out)));
     utxos.insert(multi_era_in, multi_era_out);
 }
 
-fn empty_byron_attributes() -> Attributes {
-    EmptyMap
-}
-
 /// pallas_applying::validate takes a MultiEraTx, not a ByronTx and a ByronWitnesses. To be able to
 /// build a MultiEraTx from a ByronTx and a ByronWitnesses, we need to encode each of them and then
 /// decode them into KeepRaw<ByronTx> and KeepRaw<ByronWitnesses> values, respectively.
@@ -141,52 +136,270 @@ fn mk_byron_tx_and_validate(
 ) -> ValidationResult {
     // Encode btx and decode into a KeepRaw<ByronTx> value.
     let mut btx_buf: Vec<u8> = Vec::new();
-    match encode(btx, &mut btx_buf) {
-        Ok(_) => (),
-        Err(err) => assert!(false, "Unable to encode ByronTx ({:?}).", err),
+    if let Err(err) = encode(btx, &mut btx_buf) {
+        panic!("Unable to encode ByronTx ({:?}).", err);
+    }
+    let kpbtx: KeepRaw<ByronTx> = match Decode::decode(&mut Decoder::new(&btx_buf), &mut ()) {
+        Ok(kp) => kp,
+        Err(err) => panic!("Unable to decode ByronTx ({:?}).", err),
     };
-    let kpbtx: KeepRaw<ByronTx> =
-        match Decode::decode(&mut Decoder::new(&btx_buf.as_slice()), &mut ()) {
-            Ok(kp) => kp,
-            Err(err) => panic!("Unable to decode ByronTx ({:?}).", err),
-        };
 
     // Encode bwit and decode into a KeepRaw<ByronWitnesses> value.
     let mut wit_buf: Vec<u8> = Vec::new();
-    match encode(bwit, &mut wit_buf) {
-        Ok(_) => (),
-        Err(err) => assert!(false, "Unable to encode ByronWitnesses ({:?}).", err),
+    if let Err(err) = encode(bwit, &mut wit_buf) {
+        panic!("Unable to encode ByronWitnesses ({:?}).", err);
+    }
+    let kpbwit: KeepRaw<ByronWitnesses> = match Decode::decode(&mut Decoder::new(&wit_buf), &mut ())
+    {
+        Ok(kp) => kp,
+        Err(err) => panic!("Unable to decode ByronWitnesses ({:?}).", err),
     };
-    let kpbwit: KeepRaw<ByronWitnesses> =
-        match Decode::decode(&mut Decoder::new(&wit_buf.as_slice()), &mut ()) {
-            Ok(kp) => kp,
-            Err(err) => panic!("Unable to decode ByronWitnesses ({:?}).", err),
-        };
 
     let mtxp: ByronTxPayload = ByronTxPayload {
         transaction: kpbtx,
         witness: kpbwit,
     };
     let metx: MultiEraTx = MultiEraTx::from_byron(&mtxp);
-    validate(
-        &metx,
-        utxos,
-        &MultiEraProtParams::Byron(Box::new(Cow::Borrowed(&prot_pps))),
-    )
+    validate(&metx, utxos, &mk_env(prot_pps))
 }
 
-fn new_byron_tx(ins: ByronTxIns, outs: ByronTxOuts, attrs: Attributes) -> ByronTx {
-    ByronTx {
-        inputs: ins,
-        outputs: outs,
-        attributes: attrs,
+#[cfg(test)]
+mod byron_tests {
+    use super::*;
+
+    #[test]
+    fn prot_params_from_genesis() {
+        let mainnet: ByronProtParams = mainnet_prot_params();
+        assert_eq!(
+            mainnet.fee_policy,
+            FeePolicy {
+                constant: 155_381_000_000_000,
+                size_coeficient: 43_946_000_000,
+            }
+        );
+        assert_eq!(mainnet.max_tx_size, 4096);
+        assert_eq!(mainnet.max_block_size, 2_000_000);
+        assert_eq!(mainnet.script_version, 0);
+        assert_eq!(mainnet.protocol_magic, 764824073);
+        assert_eq!(mainnet.slot_duration, 20000);
+
+        let preview: ByronProtParams =
+            ByronProtParams::from_genesis(&load_genesis("preview")).expect("bad genesis values");
+        assert_eq!(preview.protocol_magic, 2);
+        assert_eq!(preview.fee_policy, mainnet.fee_policy);
     }
-}
 
-fn empty_byron_witnesses() -> ByronWitnesses {
-    MaybeIndefArray::Def(Vec::new())
-}
+    #[test]
+    // The first tx in byron4.block spends a single input and pays a fee of
+    // 171070 lovelace, the value reported for it by every chain explorer.
+    fn successful_mainnet_tx() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let validation_result: ValidationResult = mk_byron_tx_and_validate(
+            &mtxp.transaction,
+            &mtxp.witness,
+            &utxos,
+            &mainnet_prot_params(),
+        );
+        match validation_result {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+    }
 
-fn new_utxos<'a>() -> UTxOs<'a> {
-    UTxOs::new()
+    #[test]
+    // The second tx in byron2.block spends six inputs, each one redeemed by a
+    // different witness.
+    fn successful_mainnet_multi_input_tx() {
+        let block_bytes: Vec<u8> = block_bytes("byron2.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 1);
+        let prot_params: ByronProtParams = mainnet_prot_params();
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, min_fee(&mtxp, &prot_params));
+        let validation_result: ValidationResult =
+            mk_byron_tx_and_validate(&mtxp.transaction, &mtxp.witness, &utxos, &prot_params);
+        match validation_result {
+            Ok(()) => (),
+            Err(err) => panic!("Unexpected error ({:?}).", err),
+        }
+    }
+
+    #[test]
+    // Every tx in byron7.block, which belongs to the legacy testnet and
+    // therefore is signed under a different protocol magic.
+    fn successful_testnet_block() {
+        let block_bytes: Vec<u8> = block_bytes("byron7.block");
+        let block: MultiEraBlock = MultiEraBlock::decode(&block_bytes).unwrap();
+        let txs: Vec<MultiEraTx> = block.txs();
+        assert!(!txs.is_empty());
+        let prot_params: ByronProtParams = testnet_prot_params();
+        for metx in txs.iter() {
+            let mtxp: &ByronTxPayload = metx.as_byron().unwrap();
+            let utxos: UTxOs = mk_utxos_for_tx(mtxp, min_fee(mtxp, &prot_params));
+            match validate(metx, &utxos, &mk_env(&prot_params)) {
+                Ok(()) => (),
+                Err(err) => panic!("Unexpected error on tx {} ({:?}).", metx.hash(), err),
+            }
+        }
+    }
+
+    #[test]
+    // Identical to successful_mainnet_tx, except that all inputs are removed.
+    fn empty_ins() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let mut tx: ByronTx = (*mtxp.transaction).clone();
+        tx.inputs = MaybeIndefArray::Def(Vec::new());
+        let validation_result: ValidationResult =
+            mk_byron_tx_and_validate(&tx, &mtxp.witness, &utxos, &mainnet_prot_params());
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::TxInsEmpty)
+        ));
+    }
+
+    #[test]
+    // Identical to successful_mainnet_tx, except that all outputs are removed.
+    fn empty_outs() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let mut tx: ByronTx = (*mtxp.transaction).clone();
+        tx.outputs = MaybeIndefArray::Def(Vec::new());
+        let validation_result: ValidationResult =
+            mk_byron_tx_and_validate(&tx, &mtxp.witness, &utxos, &mainnet_prot_params());
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::TxOutsEmpty)
+        ));
+    }
+
+    #[test]
+    // The UTxO set does not contain an entry for the input of the tx.
+    fn unfound_utxo_input() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let validation_result: ValidationResult = mk_byron_tx_and_validate(
+            &mtxp.transaction,
+            &mtxp.witness,
+            &UTxOs::new(),
+            &mainnet_prot_params(),
+        );
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::InputNotInUTxO(_))
+        ));
+    }
+
+    #[test]
+    // One of the outputs of the tx carries no lovelace.
+    fn output_without_lovelace() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let mut tx: ByronTx = (*mtxp.transaction).clone();
+        let mut outputs: Vec<ByronTxOut> = tx.outputs.to_vec();
+        outputs[0].amount = 0;
+        tx.outputs = MaybeIndefArray::Def(outputs);
+        let validation_result: ValidationResult =
+            mk_byron_tx_and_validate(&tx, &mtxp.witness, &utxos, &mainnet_prot_params());
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::OutputWithoutLovelace(_))
+        ));
+    }
+
+    #[test]
+    // The outputs of the tx hold more lovelace than the spent UTxO.
+    fn value_not_preserved() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let mut utxos: UTxOs = UTxOs::new();
+        let input: &ByronTxIn = &mtxp.transaction.inputs[0];
+        add_to_utxo(
+            &mut utxos,
+            input.clone(),
+            mk_tx_out(witness_address(&mtxp, 0), 1),
+        );
+        let validation_result: ValidationResult = mk_byron_tx_and_validate(
+            &mtxp.transaction,
+            &mtxp.witness,
+            &utxos,
+            &mainnet_prot_params(),
+        );
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+    }
+
+    #[test]
+    // The spent UTxO leaves one lovelace less than the minimum fee.
+    fn fees_below_min() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171069);
+        let validation_result: ValidationResult = mk_byron_tx_and_validate(
+            &mtxp.transaction,
+            &mtxp.witness,
+            &utxos,
+            &mainnet_prot_params(),
+        );
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::FeeTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    // The maximum tx size protocol parameter is set below the size of the tx.
+    fn max_tx_size_exceeded() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let prot_params: ByronProtParams = ByronProtParams {
+            max_tx_size: 100,
+            ..mainnet_prot_params()
+        };
+        let validation_result: ValidationResult =
+            mk_byron_tx_and_validate(&mtxp.transaction, &mtxp.witness, &utxos, &prot_params);
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::MaxTxSizeExceeded { .. })
+        ));
+    }
+
+    #[test]
+    // The witness set of the tx is empty.
+    fn missing_witness() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let validation_result: ValidationResult = mk_byron_tx_and_validate(
+            &mtxp.transaction,
+            &MaybeIndefArray::Def(Vec::new()),
+            &utxos,
+            &mainnet_prot_params(),
+        );
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::MissingWitness(_))
+        ));
+    }
+
+    #[test]
+    // The signature is checked against the protocol magic of a testnet.
+    fn wrong_signature() {
+        let block_bytes: Vec<u8> = block_bytes("byron4.block");
+        let mtxp: ByronTxPayload = nth_tx(&block_bytes, 0);
+        let utxos: UTxOs = mk_utxos_for_tx(&mtxp, 171070);
+        let prot_params: ByronProtParams = testnet_prot_params();
+        let validation_result: ValidationResult =
+            mk_byron_tx_and_validate(&mtxp.transaction, &mtxp.witness, &utxos, &prot_params);
+        assert!(matches!(
+            validation_result,
+            Err(ValidationError::WrongSignature(_))
+        ));
+    }
 }
@@ -0,0 +1,456 @@
+//! Helpers shared by the tests of the eras from Shelley onwards.
+
+// Each test crate only uses some of the helpers.
+#![allow(dead_code)]
+
+use std::{borrow::Cow, collections::HashSet};
+
+use pallas_addresses::{
+    byron::{AddrType, AddressPayload, SpendingData},
+    Address, ByronAddress, Network, ShelleyAddress, ShelleyDelegationPart, ShelleyPaymentPart,
+};
+use pallas_applying::{
+    types::{
+        AlonzoProtParams, BabbageProtParams, Environment, MultiEraProtParams, ShelleyProtParams,
+    },
+    UTxOs,
+};
+use pallas_codec::{
+    minicbor::{self, bytes::ByteVec},
+    utils::{Bytes, CborWrap, KeepRaw},
+};
+use pallas_crypto::hash::{Hash, Hasher};
+use pallas_primitives::{
+    alonzo::{self, Certificate, TransactionInput, Value},
+    babbage::{
+        MintedDatumOption, MintedPostAlonzoTransactionOutput, MintedTransactionOutput, PlutusData,
+        PlutusV2Script, Script,
+    },
+    byron,
+    conway::RedeemerTag,
+};
+use pallas_traverse::{
+    ComputeHash, Era, MultiEraBlock, MultiEraInput, MultiEraOutput, MultiEraTx, MultiEraValue,
+    OriginalHash,
+};
+
+/// Protocol parameters of the eras from Shelley onwards
+pub trait EraParams {
+    fn key_deposit(&self) -> u64;
+
+    fn pool_deposit(&self) -> u64;
+
+    fn prot_params(&self) -> MultiEraProtParams<'_>;
+}
+
+impl EraParams for ShelleyProtParams {
+    fn key_deposit(&self) -> u64 {
+        self.key_deposit
+    }
+
+    fn pool_deposit(&self) -> u64 {
+        self.pool_deposit
+    }
+
+    fn prot_params(&self) -> MultiEraProtParams<'_> {
+        MultiEraProtParams::Shelley(Box::new(Cow::Borrowed(self)))
+    }
+}
+
+impl EraParams for AlonzoProtParams {
+    fn key_deposit(&self) -> u64 {
+        self.key_deposit
+    }
+
+    fn pool_deposit(&self) -> u64 {
+        self.pool_deposit
+    }
+
+    fn prot_params(&self) -> MultiEraProtParams<'_> {
+        MultiEraProtParams::Alonzo(Box::new(Cow::Borrowed(self)))
+    }
+}
+
+impl EraParams for BabbageProtParams {
+    fn key_deposit(&self) -> u64 {
+        self.key_deposit
+    }
+
+    fn pool_deposit(&self) -> u64 {
+        self.pool_deposit
+    }
+
+    fn prot_params(&self) -> MultiEraProtParams<'_> {
+        MultiEraProtParams::Babbage(Box::new(Cow::Borrowed(self)))
+    }
+}
+
+/// Outputs that can be added to a UTxO set
+pub trait UtxoOutput<'a> {
+    fn into_utxo(self) -> MultiEraOutput<'a>;
+}
+
+impl<'a> UtxoOutput<'a> for MultiEraOutput<'a> {
+    fn into_utxo(self) -> MultiEraOutput<'a> {
+        self
+    }
+}
+
+impl<'a> UtxoOutput<'a> for alonzo::TransactionOutput {
+    fn into_utxo(self) -> MultiEraOutput<'a> {
+        MultiEraOutput::AlonzoCompatible(Box::new(Cow::Owned(self)))
+    }
+}
+
+impl<'a> UtxoOutput<'a> for MintedTransactionOutput<'a> {
+    fn into_utxo(self) -> MultiEraOutput<'a> {
+        MultiEraOutput::Babbage(Box::new(Cow::Owned(self)))
+    }
+}
+
+pub fn block_bytes(name: &str) -> Vec<u8> {
+    let path: String = format!("{}/../test_data/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let block_str: String = std::fs::read_to_string(path).expect("missing block file");
+    hex::decode(block_str.trim()).expect("bad block file")
+}
+
+pub fn decode_block(block_bytes: &[u8]) -> MultiEraBlock {
+    MultiEraBlock::decode(block_bytes).expect("Unable to decode block.")
+}
+
+pub fn nth_tx<'b>(block: &'b MultiEraBlock, index: usize) -> MultiEraTx<'b> {
+    block.txs().swap_remove(index)
+}
+
+pub fn mk_env(prot_pps: &impl EraParams, block_slot: u64, network_id: u8) -> Environment {
+    Environment {
+        prot_params: prot_pps.prot_params(),
+        block_slot,
+        network_id,
+        registered_pools: Cow::Owned(HashSet::new()),
+    }
+}
+
+pub fn enterprise_address(network_id: u8, payment: ShelleyPaymentPart) -> Vec<u8> {
+    ShelleyAddress::new(
+        Network::from(network_id),
+        payment,
+        ShelleyDelegationPart::Null,
+    )
+    .to_vec()
+}
+
+// Address owned by the first witness of the tx: a bootstrap address if the tx
+// only carries bootstrap witnesses, and a Shelley enterprise address otherwise.
+pub fn witness_address(metx: &MultiEraTx, network_id: u8) -> Vec<u8> {
+    if let Some(wit) = metx.vkey_witnesses().first() {
+        let payment = ShelleyPaymentPart::key_hash(Hasher::<224>::hash(&wit.vkey));
+        return enterprise_address(network_id, payment);
+    }
+
+    let wit = metx
+        .bootstrap_witnesses()
+        .first()
+        .expect("missing witnesses");
+    let extended_key: Vec<u8> = [wit.public_key.to_vec(), wit.chain_code.to_vec()].concat();
+    let payload: AddressPayload = AddressPayload::new(
+        AddrType::PubKey,
+        SpendingData::PubKey(ByteVec::from(extended_key)),
+        Vec::new().into(),
+    );
+    ByronAddress::from_decoded(payload).to_vec()
+}
+
+// Computes the value that the inputs of the tx must hold for it to be
+// balanced, given the deposits required by the protocol parameters.
+pub fn consumed_value(metx: &MultiEraTx, prot_pps: &impl EraParams) -> Value {
+    let mut coin: i128 = metx.fee().unwrap_or_default() as i128;
+
+    for (_, amount) in metx.withdrawals().collect::<Vec<_>>() {
+        coin -= amount as i128;
+    }
+
+    for cert in metx.certs() {
+        match cert.as_alonzo() {
+            Some(Certificate::StakeRegistration(_)) => coin += prot_pps.key_deposit() as i128,
+            Some(Certificate::StakeDeregistration(_)) => coin -= prot_pps.key_deposit() as i128,
+            Some(Certificate::PoolRegistration { .. }) => coin += prot_pps.pool_deposit() as i128,
+            _ => (),
+        }
+    }
+
+    let consumed: MultiEraValue = metx
+        .outputs()
+        .iter()
+        .try_fold(MultiEraValue::default(), |acc, x| {
+            acc.checked_add(&x.value())
+        })
+        .and_then(|x| x.checked_sub(&metx.mint_value()))
+        .and_then(|x| match coin < 0 {
+            true => x.checked_sub(&MultiEraValue::from_coin(coin.unsigned_abs() as u64)),
+            false => x.checked_add(&MultiEraValue::from_coin(coin as u64)),
+        })
+        .expect("overflowing consumed value");
+
+    consumed
+        .to_alonzo_compatible()
+        .expect("negative consumed value")
+}
+
+// Adds lovelace to the given value.
+pub fn add_coin(value: &Value, amount: u64) -> Value {
+    match value {
+        Value::Coin(x) => Value::Coin(x + amount),
+        Value::Multiasset(x, multiasset) => Value::Multiasset(x + amount, multiasset.clone()),
+    }
+}
+
+fn coin_of(value: &Value) -> u64 {
+    match value {
+        Value::Coin(x) => *x,
+        Value::Multiasset(x, _) => *x,
+    }
+}
+
+// Builds an output as found in the UTxO set when the tx is validated in the
+// given era. Outputs holding only lovelace at a bootstrap address are taken to
+// be produced by Byron txs, so they are decoded as Byron outputs.
+pub fn mk_tx_out<'a>(
+    era: Era,
+    address: Vec<u8>,
+    value: Value,
+    datum_option: Option<MintedDatumOption<'a>>,
+) -> MultiEraOutput<'a> {
+    if let (Ok(Address::Byron(_)), Value::Coin(amount)) = (Address::from_bytes(&address), &value) {
+        return mk_byron_tx_out(address, *amount);
+    }
+
+    match era {
+        Era::Babbage => MintedTransactionOutput::PostAlonzo(MintedPostAlonzoTransactionOutput {
+            address: address.into(),
+            value,
+            datum_option,
+            script_ref: None,
+        })
+        .into_utxo(),
+        _ => alonzo::TransactionOutput {
+            address: address.into(),
+            amount: value,
+            datum_hash: match datum_option {
+                Some(MintedDatumOption::Hash(x)) => Some(x),
+                _ => None,
+            },
+        }
+        .into_utxo(),
+    }
+}
+
+// Decodes a Byron-era output, as a UTxO provider does for the outputs of
+// Byron txs.
+fn mk_byron_tx_out<'a>(address: Vec<u8>, amount: u64) -> MultiEraOutput<'a> {
+    let address: byron::Address = minicbor::decode(&address).expect("bad Byron address");
+    let cbor: Vec<u8> = minicbor::to_vec(byron::TxOut { address, amount }).unwrap();
+    match MultiEraOutput::decode(Era::Byron, &cbor).expect("bad Byron output") {
+        MultiEraOutput::Byron(x) => MultiEraOutput::Byron(Box::new(Cow::Owned(x.into_owned()))),
+        _ => unreachable!(),
+    }
+}
+
+pub fn add_to_utxo<'a>(
+    utxos: &mut UTxOs<'a>,
+    tx_in: TransactionInput,
+    tx_out: impl UtxoOutput<'a>,
+) {
+    let multi_era_in: MultiEraInput = MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(tx_in)));
+    utxos.insert(multi_era_in, tx_out.into_utxo());
+}
+
+fn tx_inputs(inputs: Vec<MultiEraInput>) -> Vec<TransactionInput> {
+    inputs
+        .iter()
+        .map(|x| x.as_alonzo().expect("unexpected input").clone())
+        .collect()
+}
+
+// PlutusV2 script made available as a reference script to the Babbage txs that
+// don't carry any Plutus script in their witness set. Its actual contents are
+// irrelevant, since Plutus scripts are not run in phase-1 validation.
+pub fn reference_script() -> PlutusV2Script {
+    PlutusV2Script(Bytes::from(b"reference script".to_vec()))
+}
+
+// Hash of a Plutus script of the tx used for spending, that is, one that is
+// not a minting policy, or else that of the reference script.
+pub fn spending_script_hash(metx: &MultiEraTx) -> Hash<28> {
+    let policies: Vec<Hash<28>> = metx.mints().iter().map(|x| *x.policy()).collect();
+    let hashes: Vec<Hash<28>> = metx
+        .plutus_v1_scripts()
+        .iter()
+        .map(|x| x.compute_hash())
+        .chain(metx.plutus_v2_scripts().iter().map(|x| x.compute_hash()))
+        .collect();
+
+    hashes
+        .iter()
+        .find(|x| !policies.contains(x))
+        .or(hashes.first())
+        .copied()
+        .unwrap_or_else(|| reference_script().compute_hash())
+}
+
+pub fn script_address(metx: &MultiEraTx, network_id: u8) -> Vec<u8> {
+    enterprise_address(
+        network_id,
+        ShelleyPaymentPart::script_hash(spending_script_hash(metx)),
+    )
+}
+
+// Datum inlined in the Babbage outputs locked by Plutus scripts when the tx
+// provides no datum for them.
+pub fn inline_datum() -> MintedDatumOption<'static> {
+    // Constructor 0 with no fields.
+    const DATUM: &[u8] = &[0xd8, 0x79, 0x80];
+    let datum: KeepRaw<PlutusData> = minicbor::decode(DATUM).unwrap();
+    MintedDatumOption::Data(CborWrap(datum))
+}
+
+// Builds a UTxO set holding every input, collateral input and reference input
+// of the tx, in such a way that the tx is balanced and its collateral covers
+// the fee.
+//
+// Inputs pointed to by spending redeemers are locked by a Plutus script of the
+// tx, or by the reference script held by the first reference input if the
+// witness set has no Plutus scripts. Their datum hashes are taken from the
+// datums in the witness set that are not referenced by any output, and in
+// Babbage they hold an inline datum if there are none. Reference inputs carry
+// those datum hashes as well. The rest of the outputs are owned by the first
+// witness of the tx, which makes them Byron outputs for txs only redeemed by
+// bootstrap witnesses.
+//
+// The first collateral input holds the collateral return plus the declared
+// total collateral, or twice the fee if there is none. The whole consumed
+// value minus the fixed amounts is assigned to the first regular input (or to
+// the first non-collateral one if all of them are locked by scripts), and
+// every other input holds a small amount of lovelace.
+pub fn mk_utxos_for_tx<'a>(
+    metx: &MultiEraTx,
+    prot_pps: &impl EraParams,
+    network_id: u8,
+) -> UTxOs<'a> {
+    const OTHER_INPUTS_AMOUNT: u64 = 2000000;
+
+    let era: Era = metx.era();
+    let mut utxos: UTxOs = UTxOs::new();
+    let inputs: Vec<TransactionInput> = tx_inputs(metx.inputs());
+
+    let mut sorted_inputs: Vec<&TransactionInput> = inputs.iter().collect();
+    sorted_inputs.sort();
+    let script_inputs: Vec<&TransactionInput> = metx
+        .redeemers()
+        .iter()
+        .filter(|x| x.tag() == RedeemerTag::Spend)
+        .map(|x| sorted_inputs[x.index() as usize])
+        .collect();
+
+    let output_datums: Vec<Hash<32>> = metx
+        .outputs()
+        .iter()
+        .filter_map(|x| match x.datum() {
+            Some(MintedDatumOption::Hash(x)) => Some(x),
+            _ => None,
+        })
+        .collect();
+    let spent_datums: Vec<Hash<32>> = metx
+        .plutus_data()
+        .iter()
+        .map(|x| x.original_hash())
+        .filter(|x| !output_datums.contains(x))
+        .collect();
+    let datum_option = |index: usize| match spent_datums.len() {
+        0 => None,
+        n => Some(MintedDatumOption::Hash(spent_datums[index % n])),
+    };
+
+    let consumed: Value = consumed_value(metx, prot_pps);
+    let other_amount: u64 = OTHER_INPUTS_AMOUNT.min(coin_of(&consumed) / (2 * inputs.len() as u64));
+    let collaterals: Vec<TransactionInput> = tx_inputs(metx.collateral());
+    let collateral_taken: u64 = metx
+        .total_collateral()
+        .unwrap_or(metx.fee().unwrap_or_default() * 2);
+    let collateral_value = |input: &TransactionInput| -> Value {
+        if collaterals.first() != Some(input) {
+            return Value::Coin(0);
+        }
+        match metx.collateral_return() {
+            Some(x) => add_coin(&x.value().to_alonzo_compatible().unwrap(), collateral_taken),
+            None => Value::Coin(collateral_taken),
+        }
+    };
+    let fixed_value = |input: &TransactionInput| match collaterals.contains(input) {
+        true => collateral_value(input),
+        false => Value::Coin(other_amount),
+    };
+    let balance_index: usize = inputs
+        .iter()
+        .position(|x| !collaterals.contains(x) && !script_inputs.contains(&x))
+        .or_else(|| inputs.iter().position(|x| !collaterals.contains(x)))
+        .unwrap_or(0);
+    let others: u64 = inputs
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != balance_index)
+        .map(|(_, input)| coin_of(&fixed_value(input)))
+        .sum();
+    let balance_value: Value = match consumed {
+        Value::Coin(x) => Value::Coin(x - others),
+        Value::Multiasset(x, y) => Value::Multiasset(x - others, y),
+    };
+
+    let address: Vec<u8> = witness_address(metx, network_id);
+    let mut script_index: usize = 0;
+    for (index, input) in inputs.iter().enumerate() {
+        let value: Value = match index == balance_index {
+            true => balance_value.clone(),
+            false => fixed_value(input),
+        };
+        let tx_out: MultiEraOutput = match script_inputs.contains(&input) {
+            true => {
+                let datum_option = match era {
+                    Era::Babbage => Some(datum_option(script_index).unwrap_or_else(inline_datum)),
+                    _ => datum_option(script_index),
+                };
+                script_index += 1;
+                mk_tx_out(era, script_address(metx, network_id), value, datum_option)
+            }
+            false => mk_tx_out(era, address.clone(), value, None),
+        };
+        add_to_utxo(&mut utxos, input.clone(), tx_out);
+    }
+
+    for input in collaterals.iter() {
+        if !inputs.contains(input) {
+            let tx_out = mk_tx_out(era, address.clone(), collateral_value(input), None);
+            add_to_utxo(&mut utxos, input.clone(), tx_out);
+        }
+    }
+
+    let has_plutus_scripts: bool =
+        !metx.plutus_v1_scripts().is_empty() || !metx.plutus_v2_scripts().is_empty();
+    for (index, input) in tx_inputs(metx.reference_inputs()).into_iter().enumerate() {
+        let mut tx_out = mk_tx_out(
+            era,
+            address.clone(),
+            Value::Coin(OTHER_INPUTS_AMOUNT),
+            datum_option(index),
+        );
+        if let MultiEraOutput::Babbage(x) = &mut tx_out {
+            if let MintedTransactionOutput::PostAlonzo(x) = x.to_mut() {
+                if index == 0 && !has_plutus_scripts {
+                    x.script_ref = Some(CborWrap(Script::PlutusV2Script(reference_script())));
+                }
+            }
+        }
+        add_to_utxo(&mut utxos, input, tx_out);
+    }
+    utxos
+}
@@ -0,0 +1,122 @@
+use std::{borrow::Cow, collections::HashMap, vec::Vec};
+
+use pallas_applying::{apply_block, ApplyError, EraCbor, UtxoDelta};
+use pallas_primitives::babbage::MintedTx;
+use pallas_traverse::{MultiEraBlock, MultiEraTx, OutputRef};
+
+// Helper functions.
+fn block_bytes(name: &str) -> Vec<u8> {
+    let path: String = format!("{}/../test_data/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let block_str: String = std::fs::read_to_string(path).expect("missing block file");
+    hex::decode(block_str.trim()).expect("bad block file")
+}
+
+fn decode_block(block_bytes: &[u8]) -> MultiEraBlock {
+    MultiEraBlock::decode(block_bytes).expect("Unable to decode block.")
+}
+
+fn output_ref(tx: &MultiEraTx, index: usize) -> OutputRef {
+    OutputRef::new(tx.hash(), index as u64)
+}
+
+fn dummy_output(block: &MultiEraBlock) -> EraCbor {
+    EraCbor::from(&block.txs()[0].outputs()[0])
+}
+
+// A UTxO set holding every output consumed by the given txs, except for the
+// ones produced by those same txs.
+fn mk_utxos(txs: &[MultiEraTx], output: &EraCbor) -> HashMap<OutputRef, EraCbor> {
+    let produced: Vec<OutputRef> = txs
+        .iter()
+        .flat_map(|tx| (0..tx.outputs().len()).map(|index| output_ref(tx, index)))
+        .collect();
+    txs.iter()
+        .flat_map(|tx| tx.consumes())
+        .map(|input| input.output_ref())
+        .filter(|output_ref| !produced.contains(output_ref))
+        .map(|output_ref| (output_ref, output.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod delta_tests {
+    use super::*;
+
+    #[test]
+    // Several txs in alonzo14.block spend outputs produced earlier in the same
+    // block, so these outputs are neither consumed nor produced by the block.
+    fn intra_block_outputs_left_out() {
+        let block_bytes: Vec<u8> = block_bytes("alonzo14.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let txs: Vec<MultiEraTx> = block.txs();
+        let utxos: HashMap<OutputRef, EraCbor> = mk_utxos(&txs, &dummy_output(&block));
+        let delta: UtxoDelta = apply_block(&block, &utxos).unwrap();
+        assert_eq!(delta.consumed, utxos);
+        let spent_in_block: OutputRef = OutputRef::new(
+            "e9c15ed44b4fcf28eae52fe588c82f0c38f7fca18d3f522af1f68e87ab68aae5"
+                .parse()
+                .unwrap(),
+            0,
+        );
+        assert!(!delta.produced.contains_key(&spent_in_block));
+        let produced: usize = txs.iter().map(|tx| tx.outputs().len()).sum();
+        let consumed: usize = txs.iter().map(|tx| tx.consumes().len()).sum();
+        assert_eq!(
+            delta.produced.len(),
+            produced - (consumed - delta.consumed.len())
+        );
+    }
+
+    #[test]
+    fn inverse_delta_undoes_block() {
+        let block_bytes: Vec<u8> = block_bytes("alonzo14.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let utxos: HashMap<OutputRef, EraCbor> = mk_utxos(&block.txs(), &dummy_output(&block));
+        let delta: UtxoDelta = apply_block(&block, &utxos).unwrap();
+        let mut state: HashMap<OutputRef, EraCbor> = utxos.clone();
+        delta.apply_to(&mut state);
+        assert!(delta.produced.keys().all(|x| state.contains_key(x)));
+        assert!(delta.consumed.keys().all(|x| !state.contains_key(x)));
+        delta.inverse().apply_to(&mut state);
+        assert_eq!(state, utxos);
+    }
+
+    #[test]
+    // The second tx in babbage9.block has a collateral return output, and is
+    // marked as failing phase-2 validation.
+    fn invalid_tx_consumes_collateral() {
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let valid_tx: MultiEraTx = block.txs().swap_remove(1);
+        let mut mtx: MintedTx = valid_tx.as_babbage().unwrap().clone();
+        mtx.success = false;
+        let metx: MultiEraTx = MultiEraTx::Babbage(Box::new(Cow::Owned(mtx)));
+        let utxos: HashMap<OutputRef, EraCbor> =
+            mk_utxos(std::slice::from_ref(&metx), &dummy_output(&block));
+        let mut delta: UtxoDelta = UtxoDelta::default();
+        delta.add_tx(&metx, &utxos).unwrap();
+        let collateral: Vec<OutputRef> = metx
+            .collateral()
+            .iter()
+            .map(|input| input.output_ref())
+            .collect();
+        assert_eq!(delta.consumed.len(), collateral.len());
+        assert!(collateral.iter().all(|x| delta.consumed.contains_key(x)));
+        let collateral_return: OutputRef = output_ref(&metx, metx.outputs().len());
+        assert_eq!(
+            delta.produced.keys().collect::<Vec<_>>(),
+            vec![&collateral_return]
+        );
+    }
+
+    #[test]
+    fn input_not_in_utxo() {
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let utxos: HashMap<OutputRef, EraCbor> = HashMap::new();
+        assert!(matches!(
+            apply_block(&block, &utxos),
+            Err(ApplyError::InputNotInUTxO(_))
+        ));
+    }
+}
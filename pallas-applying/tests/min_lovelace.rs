@@ -0,0 +1,220 @@
+use std::{borrow::Cow, vec::Vec};
+
+use pallas_applying::{
+    min_lovelace,
+    min_lovelace::{
+        alonzo_min_lovelace, babbage_min_lovelace, conway_min_lovelace, shelley_ma_min_lovelace,
+    },
+    types::{
+        AlonzoProtParams, BabbageProtParams, ConwayProtParams, MultiEraProtParams,
+        ShelleyProtParams,
+    },
+};
+use pallas_codec::utils::{Bytes, KeyValuePairs};
+use pallas_crypto::hash::Hash;
+use pallas_primitives::alonzo::{
+    CostMdls, ExUnitPrices, ExUnits, RationalNumber, TransactionOutput, Value,
+};
+use pallas_traverse::{MultiEraBlock, MultiEraOutput};
+
+// Helper functions.
+fn block_bytes(name: &str) -> Vec<u8> {
+    let path: String = format!("{}/../test_data/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let block_str: String = std::fs::read_to_string(path).expect("missing block file");
+    hex::decode(block_str.trim()).expect("bad block file")
+}
+
+fn shelley_prot_params() -> ShelleyProtParams {
+    ShelleyProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        min_utxo_value: 1000000,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+    }
+}
+
+fn ex_unit_prices() -> ExUnitPrices {
+    ExUnitPrices {
+        mem_price: RationalNumber {
+            numerator: 577,
+            denominator: 10000,
+        },
+        step_price: RationalNumber {
+            numerator: 721,
+            denominator: 10000000,
+        },
+    }
+}
+
+fn max_tx_ex_units() -> ExUnits {
+    ExUnits {
+        mem: 14000000,
+        steps: 10000000000,
+    }
+}
+
+fn alonzo_prot_params() -> AlonzoProtParams {
+    AlonzoProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        max_value_size: 5000,
+        coins_per_utxo_word: 34482,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+        collateral_percentage: 150,
+        max_collateral_inputs: 3,
+        max_tx_ex_units: max_tx_ex_units(),
+        ex_unit_prices: ex_unit_prices(),
+        cost_models_for_script_languages: CostMdls::from(vec![]),
+    }
+}
+
+fn babbage_prot_params() -> BabbageProtParams {
+    BabbageProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        max_value_size: 5000,
+        ada_per_utxo_byte: 4310,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+        collateral_percentage: 150,
+        max_collateral_inputs: 3,
+        max_tx_ex_units: max_tx_ex_units(),
+        ex_unit_prices: ex_unit_prices(),
+        cost_models_for_script_languages: pallas_primitives::babbage::CostMdls {
+            plutus_v1: None,
+            plutus_v2: None,
+        },
+    }
+}
+
+fn conway_prot_params() -> ConwayProtParams {
+    ConwayProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        max_value_size: 5000,
+        ada_per_utxo_byte: 4310,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+        collateral_percentage: 150,
+        max_collateral_inputs: 3,
+        max_tx_ex_units: max_tx_ex_units(),
+        ex_unit_prices: ex_unit_prices(),
+        cost_models_for_script_languages: pallas_primitives::conway::CostMdls {
+            plutus_v1: None,
+            plutus_v2: None,
+            plutus_v3: None,
+        },
+    }
+}
+
+// A value holding a single asset, whose name is 32 characters long, under each
+// of the given policies.
+fn multiasset(policies: &[u8]) -> Value {
+    let asset_name: Bytes = Bytes::from(vec![b'a'; 32]);
+    let bundle = policies
+        .iter()
+        .map(|x| {
+            let assets = KeyValuePairs::from(vec![(asset_name.clone(), 1)]);
+            (Hash::from([*x; 28]), assets)
+        })
+        .collect::<Vec<_>>();
+    Value::Multiasset(0, KeyValuePairs::from(bundle))
+}
+
+fn alonzo_output(amount: Value, datum_hash: Option<Hash<32>>) -> TransactionOutput {
+    TransactionOutput {
+        address: Bytes::from(vec![0x61; 29]),
+        amount,
+        datum_hash,
+    }
+}
+
+#[cfg(test)]
+mod min_lovelace_tests {
+    use super::*;
+
+    #[test]
+    // The single-policy bundle is one of the examples in the Mary ledger
+    // documentation.
+    fn shelley_ma_values() {
+        let prot_pps: ShelleyProtParams = shelley_prot_params();
+        assert_eq!(shelley_ma_min_lovelace(&Value::Coin(0), &prot_pps), 1000000);
+        assert_eq!(
+            shelley_ma_min_lovelace(&multiasset(&[1]), &prot_pps),
+            1555554
+        );
+        // The asset name is shared by both policies, so it is counted once.
+        assert_eq!(
+            shelley_ma_min_lovelace(&multiasset(&[1, 2]), &prot_pps),
+            1740739
+        );
+    }
+
+    #[test]
+    fn alonzo_values() {
+        let prot_pps: AlonzoProtParams = alonzo_prot_params();
+        let ada_only = alonzo_output(Value::Coin(0), None);
+        assert_eq!(alonzo_min_lovelace(&ada_only, &prot_pps), 999978);
+        let with_datum_hash = alonzo_output(Value::Coin(0), Some(Hash::from([0; 32])));
+        assert_eq!(alonzo_min_lovelace(&with_datum_hash, &prot_pps), 1344798);
+        let with_assets = alonzo_output(multiasset(&[1]), None);
+        assert_eq!(alonzo_min_lovelace(&with_assets, &prot_pps), 1448244);
+    }
+
+    #[test]
+    // Every output of babbage9.block is charged for its serialized size.
+    fn babbage_values() {
+        let prot_pps: BabbageProtParams = babbage_prot_params();
+        let multi_era_pps = MultiEraProtParams::Babbage(Box::new(Cow::Borrowed(&prot_pps)));
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = MultiEraBlock::decode(&block_bytes).unwrap();
+        for tx in block.txs() {
+            for output in tx.outputs() {
+                let expected: u64 = 4310 * (160 + output.encode().len() as u64);
+                assert_eq!(min_lovelace(&output, &multi_era_pps), Some(expected));
+                assert!(expected <= output.lovelace_amount());
+            }
+        }
+        let ada_only = alonzo_output(Value::Coin(1000000), None);
+        assert_eq!(
+            babbage_min_lovelace(&ada_only, &prot_pps),
+            4310 * (160 + 37)
+        );
+    }
+
+    #[test]
+    // Every output of conway1.block, in both the legacy and the map formats,
+    // is charged for its serialized size as in Babbage.
+    fn conway_values() {
+        let prot_pps: ConwayProtParams = conway_prot_params();
+        let multi_era_pps = MultiEraProtParams::Conway(Box::new(Cow::Borrowed(&prot_pps)));
+        let block_bytes: Vec<u8> = block_bytes("conway1.block");
+        let block: MultiEraBlock = MultiEraBlock::decode(&block_bytes).unwrap();
+        for tx in block.txs() {
+            for output in tx.outputs() {
+                assert!(matches!(output, MultiEraOutput::Conway(_)));
+                let expected: u64 = 4310 * (160 + output.encode().len() as u64);
+                assert_eq!(min_lovelace(&output, &multi_era_pps), Some(expected));
+            }
+        }
+        let ada_only = alonzo_output(Value::Coin(1000000), None);
+        assert_eq!(conway_min_lovelace(&ada_only, &prot_pps), 4310 * (160 + 37));
+    }
+
+    #[test]
+    fn params_from_another_era() {
+        let prot_pps: ShelleyProtParams = shelley_prot_params();
+        let multi_era_pps = MultiEraProtParams::Shelley(Box::new(Cow::Borrowed(&prot_pps)));
+        let block_bytes: Vec<u8> = block_bytes("babbage9.block");
+        let block: MultiEraBlock = MultiEraBlock::decode(&block_bytes).unwrap();
+        let txs = block.txs();
+        let output: MultiEraOutput = txs[0].outputs().swap_remove(0);
+        assert_eq!(min_lovelace(&output, &multi_era_pps), None);
+    }
+}
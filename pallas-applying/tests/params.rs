@@ -0,0 +1,462 @@
+use pallas_applying::{
+    params::{EraParams, ParamsError, ParamsTracker, ProtocolParams},
+    types::ByronProtParams,
+};
+use pallas_codec::{
+    minicbor::{self, bytes::ByteVec, Encoder},
+    utils::{Bytes, CborWrap},
+};
+use pallas_crypto::hash::Hasher;
+use pallas_primitives::{
+    alonzo,
+    byron::{self, BVer, BVerMod, SlotId, Up, UpProp, UpVote},
+};
+use pallas_traverse::MultiEraBlock;
+
+// Helper functions.
+fn test_data(name: &str) -> std::path::PathBuf {
+    std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("test_data")
+        .join(name)
+}
+
+fn block_bytes(name: &str) -> Vec<u8> {
+    let block_str: String = std::fs::read_to_string(test_data(name)).expect("missing block file");
+    hex::decode(block_str.trim()).expect("bad block file")
+}
+
+fn shelley_genesis_params() -> ProtocolParams {
+    let genesis = pallas_configs::shelley::from_file(&test_data("preview-shelley-genesis.json"));
+    ProtocolParams::from_shelley_genesis(&genesis.unwrap())
+}
+
+fn byron_genesis_params() -> ByronProtParams {
+    let genesis = pallas_configs::byron::from_file(&test_data("preview-byron-genesis.json"));
+    ByronProtParams::from_genesis(&genesis.unwrap()).unwrap()
+}
+
+fn shelley_params(tracker: &ParamsTracker, epoch: u64) -> ProtocolParams {
+    match tracker.params_for(epoch) {
+        Some(EraParams::Shelley(x)) => *x.clone(),
+        x => panic!("unexpected params {x:?}"),
+    }
+}
+
+fn byron_params(tracker: &ParamsTracker, epoch: u64) -> ByronProtParams {
+    match tracker.params_for(epoch) {
+        Some(EraParams::Byron(x)) => x.clone(),
+        x => panic!("unexpected params {x:?}"),
+    }
+}
+
+// A Shelley block whose first tx proposes the given updates, as pairs of a
+// genesis key byte and the hex-encoded update, for the target epoch.
+fn shelley_block(updates: &[(u8, &str)], target: u64) -> Vec<u8> {
+    let cbor = block_bytes("shelley1.block");
+    let mut block: alonzo::Block = minicbor::decode(&cbor[2..]).unwrap();
+
+    let updates: Vec<(Bytes, alonzo::ProtocolParamUpdate)> = updates
+        .iter()
+        .map(|(key, update)| {
+            let update = minicbor::decode(&hex::decode(update).unwrap()).unwrap();
+            (Bytes::from(vec![*key; 28]), update)
+        })
+        .collect();
+
+    block.transaction_bodies[0].update = Some(alonzo::Update {
+        proposed_protocol_parameter_updates: updates.into(),
+        epoch: target,
+    });
+
+    minicbor::to_vec((2, block)).unwrap()
+}
+
+fn byron_params_update(max_tx_size: u64, fee_policy: (i64, i64)) -> BVerMod {
+    let mut cbor = vec![];
+    let mut encoder = Encoder::new(&mut cbor);
+    encoder.array(14).unwrap();
+
+    for field in 0..14 {
+        match field {
+            4 => encoder.array(1).unwrap().u64(max_tx_size).unwrap(),
+            12 => encoder
+                .array(1)
+                .unwrap()
+                .array(2)
+                .unwrap()
+                .u8(0)
+                .unwrap()
+                .encode(CborWrap(fee_policy))
+                .unwrap(),
+            _ => encoder.array(0).unwrap(),
+        };
+    }
+
+    minicbor::decode(&cbor).unwrap()
+}
+
+fn byron_proposal(version: BVer, update: BVerMod, proposer: u8) -> UpProp {
+    UpProp {
+        block_version: Some(version),
+        block_version_mod: Some(update),
+        software_version: Some(("cardano-sl".to_string(), 1)),
+        data: vec![].into(),
+        attributes: None,
+        from: Some(ByteVec::from(vec![proposer; 64])),
+        signature: Some(ByteVec::from(vec![0; 64])),
+    }
+}
+
+fn byron_vote(proposal: &UpProp, voter: u8) -> UpVote {
+    UpVote {
+        voter: ByteVec::from(vec![voter; 64]),
+        proposal_id: Hasher::<256>::hash_cbor(proposal),
+        vote: true,
+        signature: ByteVec::from(vec![0; 64]),
+    }
+}
+
+// A Byron block of the given slot issued by the given key, announcing the given
+// block version and carrying the given proposal and votes.
+fn byron_block(
+    slot: u64,
+    issuer: u8,
+    version: BVer,
+    proposal: Option<&UpProp>,
+    votes: &[UpVote],
+) -> Vec<u8> {
+    let mut cbor = vec![];
+    let mut encoder = Encoder::new(&mut cbor);
+    encoder.array(2).unwrap();
+
+    match proposal {
+        Some(x) => encoder.array(1).unwrap().encode(x).unwrap(),
+        None => encoder.array(0).unwrap(),
+    };
+
+    encoder.array(votes.len() as u64).unwrap();
+    for vote in votes {
+        encoder.encode(vote).unwrap();
+    }
+
+    let payload: Up = minicbor::decode(&cbor).unwrap();
+
+    let cbor = block_bytes("byron1.block");
+    let mut block: byron::Block = minicbor::decode(&cbor[2..]).unwrap();
+    block.body.upd_payload = payload;
+    block.header.consensus_data.0 = SlotId {
+        epoch: slot / 21600,
+        slot: slot % 21600,
+    };
+    block.header.consensus_data.1 = ByteVec::from(vec![issuer; 64]);
+    block.header.extra_data.block_version = version;
+
+    minicbor::to_vec((1, block)).unwrap()
+}
+
+fn apply(tracker: &mut ParamsTracker, cbor: &[u8], epoch: u64) {
+    let block = MultiEraBlock::decode(cbor).unwrap();
+    tracker.apply_block(&block, epoch).unwrap();
+}
+
+// Proposes a Byron update, confirms it and has it endorsed by the given number
+// of issuers within the given epoch.
+fn endorse_byron_update(
+    tracker: &mut ParamsTracker,
+    epoch: u64,
+    version: BVer,
+    max_tx_size: u64,
+    endorsers: u8,
+) {
+    let slot = epoch * 21600;
+    let proposal = byron_proposal(version, byron_params_update(max_tx_size, (1, 2)), 1);
+
+    let block = byron_block(slot + 1, 1, (0, 0, 0), Some(&proposal), &[]);
+    apply(tracker, &block, epoch);
+
+    let block = byron_block(slot + 2, 2, (0, 0, 0), None, &[byron_vote(&proposal, 2)]);
+    apply(tracker, &block, epoch);
+
+    for issuer in 1..=endorsers {
+        let block = byron_block(slot + 2 + issuer as u64, issuer, version, None, &[]);
+        apply(tracker, &block, epoch);
+    }
+}
+
+#[test]
+fn genesis_params_are_read() {
+    let params = shelley_genesis_params();
+
+    assert_eq!(params.minfee_a, 44);
+    assert_eq!(params.protocol_version, (6, 0));
+    assert_eq!(params.expansion_rate.numerator, 3);
+    assert_eq!(params.expansion_rate.denominator, 1000);
+    assert_eq!(params.treasury_growth_rate.numerator, 1);
+    assert_eq!(params.treasury_growth_rate.denominator, 5);
+
+    // the parameters introduced by Alonzo come from another genesis file
+    assert!(params.to_alonzo_params().is_none());
+    assert_eq!(params.to_shelley_params().min_utxo_value, 1_000_000);
+}
+
+#[test]
+fn shelley_proposals_apply_on_quorum() {
+    let genesis = shelley_genesis_params();
+    let mut tracker = ParamsTracker::new(EraParams::Shelley(Box::new(genesis.clone())), 7, 2);
+
+    // {0: 45}, {0: 50}
+    let block = shelley_block(&[(1, "a100182d"), (2, "a100182d"), (3, "a1001832")], 5);
+    apply(&mut tracker, &block, 5);
+
+    // proposals for epoch 5 take effect from epoch 6
+    assert_eq!(shelley_params(&tracker, 5), genesis);
+
+    let block = shelley_block(&[], 7);
+    apply(&mut tracker, &block, 7);
+
+    assert_eq!(shelley_params(&tracker, 4), genesis);
+    assert_eq!(shelley_params(&tracker, 5), genesis);
+    assert_eq!(shelley_params(&tracker, 6).minfee_a, 45);
+    assert_eq!(shelley_params(&tracker, 7).minfee_a, 45);
+    assert!(tracker.params_for(8).is_none());
+}
+
+#[test]
+fn later_shelley_proposals_replace_earlier_ones() {
+    let genesis = shelley_genesis_params();
+    let mut tracker = ParamsTracker::new(EraParams::Shelley(Box::new(genesis.clone())), 7, 2);
+
+    let block = shelley_block(&[(1, "a100182d"), (2, "a100182d"), (3, "a1001832")], 0);
+    apply(&mut tracker, &block, 0);
+
+    // genesis key 2 changes its mind, siding with key 3
+    let block = shelley_block(&[(2, "a1001832")], 0);
+    apply(&mut tracker, &block, 0);
+
+    let block = shelley_block(&[], 1);
+    apply(&mut tracker, &block, 1);
+
+    assert_eq!(shelley_params(&tracker, 1).minfee_a, 50);
+
+    // a single proposal is not enough
+    let block = shelley_block(&[(1, "a100182d")], 1);
+    apply(&mut tracker, &block, 1);
+
+    let block = shelley_block(&[], 2);
+    apply(&mut tracker, &block, 2);
+
+    assert_eq!(shelley_params(&tracker, 2).minfee_a, 50);
+
+    let block = shelley_block(&[], 1);
+    let block = MultiEraBlock::decode(&block).unwrap();
+
+    assert!(matches!(
+        tracker.apply_block(&block, 1),
+        Err(ParamsError::PastEpoch {
+            block: 1,
+            current: 2
+        })
+    ));
+}
+
+#[test]
+fn byron_genesis_params_are_read() {
+    let params = byron_genesis_params();
+
+    assert_eq!(params.update_vote_thd, 1_000_000_000_000);
+    assert_eq!(params.update_implicit, 10_000);
+    assert_eq!(params.softfork_rule.init_thd, 900_000_000_000_000);
+    assert_eq!(params.softfork_rule.min_thd, 600_000_000_000_000);
+    assert_eq!(params.softfork_rule.thd_decrement, 50_000_000_000_000);
+}
+
+#[test]
+fn byron_proposals_apply_once_endorsed() {
+    // out of 4 genesis delegates, 2 confirm a proposal and 3 adopt it
+    let genesis = ByronProtParams {
+        update_vote_thd: 500_000_000_000_000,
+        ..byron_genesis_params()
+    };
+    let mut tracker = ParamsTracker::new(EraParams::Byron(genesis.clone()), 4, 2);
+
+    let version = (0, 1, 0);
+    let proposal = byron_proposal(version, byron_params_update(8192, (1, 2)), 1);
+
+    let block = byron_block(1, 1, (0, 0, 0), Some(&proposal), &[]);
+    apply(&mut tracker, &block, 0);
+
+    // the proposal is not confirmed yet, hence not endorsed
+    let block = byron_block(2, 3, version, None, &[]);
+    apply(&mut tracker, &block, 0);
+
+    let block = byron_block(3, 2, (0, 0, 0), None, &[byron_vote(&proposal, 2)]);
+    apply(&mut tracker, &block, 0);
+
+    let block = byron_block(21601, 3, version, None, &[]);
+    apply(&mut tracker, &block, 1);
+
+    let block = byron_block(21602, 4, version, None, &[]);
+    apply(&mut tracker, &block, 1);
+
+    let block = byron_block(43201, 4, version, None, &[]);
+    apply(&mut tracker, &block, 2);
+
+    assert_eq!(byron_params(&tracker, 2), genesis);
+
+    let block = byron_block(43202, 1, version, None, &[]);
+    apply(&mut tracker, &block, 2);
+
+    let block = byron_block(64801, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 3);
+
+    assert_eq!(byron_params(&tracker, 2), genesis);
+
+    let adopted = byron_params(&tracker, 3);
+    assert_eq!(adopted.max_tx_size, 8192);
+    assert_eq!(adopted.fee_policy.constant, 1);
+    assert_eq!(adopted.fee_policy.size_coeficient, 2);
+    assert_eq!(adopted.max_block_size, genesis.max_block_size);
+}
+
+#[test]
+fn byron_adoption_threshold_decreases() {
+    // out of 4 genesis delegates, 3 endorsing a proposal reach the softfork
+    // threshold once it drops from 0.9 to 0.75, while 2 never reach its
+    // minimum of 0.6
+    let genesis = ByronProtParams {
+        update_vote_thd: 500_000_000_000_000,
+        ..byron_genesis_params()
+    };
+    let mut tracker = ParamsTracker::new(EraParams::Byron(genesis.clone()), 4, 2);
+
+    endorse_byron_update(&mut tracker, 0, (0, 1, 0), 8192, 3);
+
+    let block = byron_block(3 * 21600, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 3);
+
+    // the threshold is 0.85 and then 0.8 at the first two boundaries
+    assert_eq!(byron_params(&tracker, 1), genesis);
+    assert_eq!(byron_params(&tracker, 2), genesis);
+    assert_eq!(byron_params(&tracker, 3).max_tx_size, 8192);
+
+    // the threshold starts over after an adoption
+    endorse_byron_update(&mut tracker, 3, (0, 2, 0), 4096, 3);
+
+    let block = byron_block(6 * 21600, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 6);
+
+    assert_eq!(byron_params(&tracker, 4).max_tx_size, 8192);
+    assert_eq!(byron_params(&tracker, 5).max_tx_size, 8192);
+    assert_eq!(byron_params(&tracker, 6).max_tx_size, 4096);
+
+    endorse_byron_update(&mut tracker, 6, (0, 3, 0), 2048, 2);
+
+    let block = byron_block(20 * 21600, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 20);
+
+    assert_eq!(byron_params(&tracker, 20).max_tx_size, 4096);
+}
+
+#[test]
+fn unconfirmed_byron_proposals_expire() {
+    let genesis = ByronProtParams {
+        update_vote_thd: 500_000_000_000_000,
+        update_implicit: 10,
+        ..byron_genesis_params()
+    };
+    let mut tracker = ParamsTracker::new(EraParams::Byron(genesis.clone()), 4, 2);
+
+    let version = (0, 1, 0);
+    let proposal = byron_proposal(version, byron_params_update(8192, (1, 2)), 1);
+
+    let block = byron_block(1, 1, (0, 0, 0), Some(&proposal), &[]);
+    apply(&mut tracker, &block, 0);
+
+    // the vote comes after the implicit agreement period
+    let block = byron_block(12, 2, (0, 0, 0), None, &[byron_vote(&proposal, 2)]);
+    apply(&mut tracker, &block, 0);
+
+    for issuer in 1..=4 {
+        let block = byron_block(12 + issuer as u64, issuer, version, None, &[]);
+        apply(&mut tracker, &block, 0);
+    }
+
+    let block = byron_block(21601, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 1);
+
+    assert_eq!(byron_params(&tracker, 1), genesis);
+}
+
+#[test]
+fn byron_proposal_ids_hash_the_original_bytes() {
+    let genesis = ByronProtParams {
+        update_vote_thd: 1_000_000_000_000_000,
+        ..byron_genesis_params()
+    };
+    let mut tracker = ParamsTracker::new(EraParams::Byron(genesis.clone()), 2, 2);
+
+    let version = (0, 1, 0);
+    let proposal = byron_proposal(version, byron_params_update(8192, (1, 2)), 1);
+
+    // the proposal is encoded as an indefinite-length array in the block,
+    // which differs from its re-encoding
+    let canonical = minicbor::to_vec(&proposal).unwrap();
+    let mut original = vec![0x9f];
+    original.extend(&canonical[1..]);
+    original.push(0xff);
+
+    let block = byron_block(1, 1, (0, 0, 0), Some(&proposal), &[]);
+    let start = block
+        .windows(canonical.len())
+        .position(|x| x == canonical)
+        .unwrap();
+    let block = [
+        &block[..start],
+        &original,
+        &block[start + canonical.len()..],
+    ]
+    .concat();
+    apply(&mut tracker, &block, 0);
+
+    let vote = UpVote {
+        proposal_id: Hasher::<256>::hash(&original),
+        ..byron_vote(&proposal, 2)
+    };
+    let block = byron_block(2, 2, (0, 0, 0), None, &[vote]);
+    apply(&mut tracker, &block, 0);
+
+    for issuer in 1..=2 {
+        let block = byron_block(2 + issuer as u64, issuer, version, None, &[]);
+        apply(&mut tracker, &block, 0);
+    }
+
+    let block = byron_block(21601, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 1);
+
+    assert_eq!(byron_params(&tracker, 1).max_tx_size, 8192);
+}
+
+#[test]
+fn shelley_genesis_params_follow_byron() {
+    let byron = pallas_configs::byron::from_file(&test_data("preview-byron-genesis.json"));
+    let shelley = pallas_configs::shelley::from_file(&test_data("preview-shelley-genesis.json"));
+    let mut tracker = ParamsTracker::from_genesis(&byron.unwrap(), &shelley.unwrap()).unwrap();
+
+    let block = byron_block(1, 1, (0, 0, 0), None, &[]);
+    apply(&mut tracker, &block, 0);
+
+    let block = shelley_block(&[], 1);
+    apply(&mut tracker, &block, 1);
+
+    assert_eq!(byron_params(&tracker, 0), byron_genesis_params());
+    assert_eq!(shelley_params(&tracker, 1), shelley_genesis_params());
+    assert!(tracker.current().to_prot_params().is_none());
+
+    let mut params = shelley_genesis_params();
+    params.protocol_version = (4, 0);
+    tracker.override_current(EraParams::Shelley(Box::new(params)));
+
+    assert!(tracker.current().to_prot_params().is_some());
+    assert_eq!(shelley_params(&tracker, 1).protocol_version, (4, 0));
+}
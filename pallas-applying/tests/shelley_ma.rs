@@ -0,0 +1,414 @@
+mod common;
+
+use std::{borrow::Cow, collections::HashSet};
+
+use common::*;
+use pallas_addresses::ShelleyPaymentPart;
+use pallas_applying::{
+    types::{
+        ByronProtParams, Environment, MultiEraProtParams, ShelleyProtParams, SoftforkRule,
+        ValidationError,
+    },
+    validate, validate_all, UTxOs, ValidationResult,
+};
+use pallas_crypto::hash::Hasher;
+use pallas_primitives::alonzo::{Certificate, MintedTx, PoolKeyhash, Value};
+use pallas_traverse::{fees::PolicyParams, MultiEraBlock, MultiEraOutput, MultiEraTx};
+
+fn mainnet_prot_params() -> ShelleyProtParams {
+    ShelleyProtParams {
+        min_fee_a: 44,
+        min_fee_b: 155381,
+        max_tx_size: 16384,
+        min_utxo_value: 1000000,
+        key_deposit: 2000000,
+        pool_deposit: 500000000,
+    }
+}
+
+fn validate_nth_tx(
+    block_name: &str,
+    index: usize,
+    prot_pps: &ShelleyProtParams,
+    utxos_pps: &ShelleyProtParams,
+) -> ValidationResult {
+    let block_bytes: Vec<u8> = block_bytes(block_name);
+    let block: MultiEraBlock = decode_block(&block_bytes);
+    let metx: MultiEraTx = nth_tx(&block, index);
+    let utxos: UTxOs = mk_utxos_for_tx(&metx, utxos_pps, 1);
+    validate(&metx, &utxos, &mk_env(prot_pps, block.slot(), 1))
+}
+
+#[cfg(test)]
+mod shelley_ma_tests {
+    use super::*;
+
+    #[test]
+    // Every tx in shelley1.block, allegra1.block and mary1.block, which
+    // together cover certificates, withdrawals, bootstrap witnesses, Byron
+    // outputs, and minting under native scripts.
+    fn successful_mainnet_blocks() {
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        for name in ["shelley1.block", "allegra1.block", "mary1.block"] {
+            let block_bytes: Vec<u8> = block_bytes(name);
+            let block: MultiEraBlock = MultiEraBlock::decode(&block_bytes).unwrap();
+            let env: Environment = mk_env(&prot_params, block.slot(), 1);
+            for metx in block.txs().iter() {
+                let utxos: UTxOs = mk_utxos_for_tx(metx, &prot_params, 1);
+                match validate(metx, &utxos, &env) {
+                    Ok(()) => (),
+                    Err(err) => panic!("Unexpected error on tx {} ({:?}).", metx.hash(), err),
+                }
+            }
+        }
+    }
+
+    #[test]
+    // Every tx in shelley1.block, allegra1.block and mary1.block only redeemed
+    // by bootstrap witnesses, spending outputs produced by Byron txs.
+    fn byron_utxos_spent() {
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let mut spent: usize = 0;
+        for name in ["shelley1.block", "allegra1.block", "mary1.block"] {
+            let block_bytes: Vec<u8> = block_bytes(name);
+            let block: MultiEraBlock = decode_block(&block_bytes);
+            let env: Environment = mk_env(&prot_params, block.slot(), 1);
+            for metx in block.txs().iter() {
+                if !metx.vkey_witnesses().is_empty() {
+                    continue;
+                }
+                let utxos: UTxOs = mk_utxos_for_tx(metx, &prot_params, 1);
+                assert!(utxos
+                    .values()
+                    .all(|x| matches!(x, MultiEraOutput::Byron(_))));
+                assert_eq!(validate(metx, &utxos, &env), Ok(()), "tx {}", metx.hash());
+                spent += 1;
+            }
+        }
+        assert!(spent > 0);
+    }
+
+    #[test]
+    // The third tx in shelley1.block, validated against an empty UTxO set.
+    fn unfound_utxo_input() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let env: Environment = mk_env(&prot_params, block.slot(), 1);
+        assert!(matches!(
+            validate(&metx, &UTxOs::new(), &env),
+            Err(ValidationError::InputNotInUTxO(x)) if x == metx.inputs()[0].output_ref()
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, validated at a slot after its TTL.
+    fn ttl_exceeded() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let mtx: &MintedTx = metx.as_alonzo().unwrap();
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        let ttl: u64 = mtx.transaction_body.ttl.unwrap();
+        assert!(matches!(
+            validate(&metx, &utxos, &mk_env(&prot_params, ttl + 1, 1)),
+            Err(ValidationError::TTLExceeded { ttl: x, slot }) if x == ttl && slot == ttl + 1
+        ));
+    }
+
+    #[test]
+    // The first tx in mary1.block, validated at a slot equal to its TTL, which
+    // is no longer valid from Allegra onwards.
+    fn ttl_is_exclusive_after_shelley() {
+        let block_bytes: Vec<u8> = block_bytes("mary1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 0);
+        let mtx: &MintedTx = metx.as_alonzo().unwrap();
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        let ttl: u64 = mtx.transaction_body.ttl.unwrap();
+        assert!(matches!(
+            validate(&metx, &utxos, &mk_env(&prot_params, ttl, 1)),
+            Err(ValidationError::TTLExceeded { .. })
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, with a maximum tx size lower than its
+    // actual size.
+    fn max_tx_size_exceeded() {
+        let prot_params: ShelleyProtParams = ShelleyProtParams {
+            max_tx_size: 100,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_nth_tx("shelley1.block", 2, &prot_params, &mainnet_prot_params()),
+            Err(ValidationError::MaxTxSizeExceeded { .. })
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, with a minimum UTxO value greater than
+    // the amount of any of its outputs.
+    fn min_lovelace_unreached() {
+        let prot_params: ShelleyProtParams = ShelleyProtParams {
+            min_utxo_value: u64::MAX,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_nth_tx("shelley1.block", 2, &prot_params, &mainnet_prot_params()),
+            Err(ValidationError::OutputTooSmall {
+                index: 0,
+                min: u64::MAX
+            })
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, spending an input that holds one extra
+    // lovelace.
+    fn value_not_preserved() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let mtx: &MintedTx = metx.as_alonzo().unwrap();
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let mut utxos: UTxOs = UTxOs::new();
+        let amount: Value = match consumed_value(&metx, &prot_params) {
+            Value::Coin(x) => Value::Coin(x + 1),
+            _ => panic!("Unexpected multi-asset value."),
+        };
+        add_to_utxo(
+            &mut utxos,
+            mtx.transaction_body.inputs[0].clone(),
+            mk_tx_out(metx.era(), witness_address(&metx, 1), amount, None),
+        );
+        assert!(matches!(
+            validate(&metx, &utxos, &mk_env(&prot_params, block.slot(), 1)),
+            Err(ValidationError::ValueNotPreserved {
+                consumed: Value::Coin(consumed),
+                produced: Value::Coin(produced),
+            }) if consumed == produced + 1
+        ));
+    }
+
+    #[test]
+    // The seventh tx in alonzo2.block, a testnet tx which registers a pool and
+    // makes use of no Alonzo feature, validated as a Mary tx. Registering a new pool pays
+    // the deposit, while re-registering a known one doesn't.
+    fn pool_reregistration_pays_no_deposit() {
+        let mut block_bytes: Vec<u8> = block_bytes("alonzo2.block");
+        block_bytes[1] = 4;
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 6);
+        let mtx: &MintedTx = metx.as_alonzo().unwrap();
+        let operator: PoolKeyhash = match &mtx.transaction_body.certificates.as_ref().unwrap()[0] {
+            Certificate::PoolRegistration { operator, .. } => *operator,
+            _ => panic!("Unexpected certificate."),
+        };
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let reregistration_params: ShelleyProtParams = ShelleyProtParams {
+            pool_deposit: 0,
+            ..mainnet_prot_params()
+        };
+        let new_pool_utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        let reregistration_utxos: UTxOs = mk_utxos_for_tx(&metx, &reregistration_params, 1);
+        let env: Environment = Environment {
+            network_id: 0,
+            ..mk_env(&prot_params, block.slot(), 1)
+        };
+        let registered_env: Environment = Environment {
+            network_id: 0,
+            registered_pools: Cow::Owned(HashSet::from([operator])),
+            ..mk_env(&prot_params, block.slot(), 1)
+        };
+
+        assert_eq!(validate(&metx, &new_pool_utxos, &env), Ok(()));
+        assert_eq!(
+            validate(&metx, &reregistration_utxos, &registered_env),
+            Ok(())
+        );
+        assert!(matches!(
+            validate(&metx, &reregistration_utxos, &env),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+        assert!(matches!(
+            validate(&metx, &new_pool_utxos, &registered_env),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+    }
+
+    #[test]
+    // The first tx in mary1.block, with a deposit for stake registration
+    // greater than the one the tx pays.
+    fn deposits_not_preserved() {
+        let prot_params: ShelleyProtParams = ShelleyProtParams {
+            key_deposit: 3000000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_nth_tx("mary1.block", 0, &prot_params, &mainnet_prot_params()),
+            Err(ValidationError::ValueNotPreserved { .. })
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, with a constant fee greater than the one
+    // paid.
+    fn fees_below_min() {
+        let prot_params: ShelleyProtParams = ShelleyProtParams {
+            min_fee_b: 1000000,
+            ..mainnet_prot_params()
+        };
+        assert!(matches!(
+            validate_nth_tx("shelley1.block", 2, &prot_params, &mainnet_prot_params()),
+            Err(ValidationError::FeeTooSmall { required, actual }) if actual < required
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, validated against an empty UTxO set and
+    // with a minimum UTxO value greater than the amount of any of its outputs.
+    // All failures are gathered, while the rules depending on the missing
+    // inputs are skipped.
+    fn all_failures_gathered() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let prot_params: ShelleyProtParams = ShelleyProtParams {
+            min_utxo_value: u64::MAX,
+            ..mainnet_prot_params()
+        };
+        let env: Environment = mk_env(&prot_params, block.slot(), 1);
+        let mut expected: Vec<ValidationError> = metx
+            .inputs()
+            .iter()
+            .map(|x| ValidationError::InputNotInUTxO(x.output_ref()))
+            .collect();
+        for index in 0..metx.outputs().len() {
+            expected.push(ValidationError::OutputTooSmall {
+                index,
+                min: u64::MAX,
+            });
+        }
+        assert_eq!(validate_all(&metx, &UTxOs::new(), &env), Err(expected));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, validated as if it belonged to a testnet.
+    fn wrong_network_id() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        let env: Environment = Environment {
+            network_id: 0,
+            ..mk_env(&prot_params, block.slot(), 1)
+        };
+        assert!(matches!(
+            validate(&metx, &utxos, &env),
+            Err(ValidationError::WrongNetworkID(_))
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, spending an input locked by a key that
+    // did not sign it.
+    fn missing_vk_witness() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let mtx: &MintedTx = metx.as_alonzo().unwrap();
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let mut utxos: UTxOs = UTxOs::new();
+        let address: Vec<u8> = enterprise_address(
+            1,
+            ShelleyPaymentPart::key_hash(Hasher::<224>::hash(&[0; 32])),
+        );
+        add_to_utxo(
+            &mut utxos,
+            mtx.transaction_body.inputs[0].clone(),
+            mk_tx_out(
+                metx.era(),
+                address,
+                consumed_value(&metx, &prot_params),
+                None,
+            ),
+        );
+        assert!(matches!(
+            validate(&metx, &utxos, &mk_env(&prot_params, block.slot(), 1)),
+            Err(ValidationError::MissingVKeyWitness(_))
+        ));
+    }
+
+    #[test]
+    // The third tx in shelley1.block, spending an input locked by a script
+    // that is not in the witness set.
+    fn missing_script_witness() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let mtx: &MintedTx = metx.as_alonzo().unwrap();
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let mut utxos: UTxOs = UTxOs::new();
+        let address: Vec<u8> = enterprise_address(
+            1,
+            ShelleyPaymentPart::script_hash(Hasher::<224>::hash(&[0; 32])),
+        );
+        add_to_utxo(
+            &mut utxos,
+            mtx.transaction_body.inputs[0].clone(),
+            mk_tx_out(
+                metx.era(),
+                address,
+                consumed_value(&metx, &prot_params),
+                None,
+            ),
+        );
+        assert!(matches!(
+            validate(&metx, &utxos, &mk_env(&prot_params, block.slot(), 1)),
+            Err(ValidationError::MissingScriptWitness(_))
+        ));
+    }
+
+    #[test]
+    // A Shelley tx validated against Byron protocol parameters.
+    fn tx_and_prot_params_differ() {
+        let block_bytes: Vec<u8> = block_bytes("shelley1.block");
+        let block: MultiEraBlock = decode_block(&block_bytes);
+        let metx: MultiEraTx = nth_tx(&block, 2);
+        let prot_params: ShelleyProtParams = mainnet_prot_params();
+        let utxos: UTxOs = mk_utxos_for_tx(&metx, &prot_params, 1);
+        let byron_params: ByronProtParams = ByronProtParams {
+            fee_policy: PolicyParams {
+                constant: 0,
+                size_coeficient: 0,
+            },
+            max_tx_size: 4096,
+            max_block_size: 2000000,
+            script_version: 0,
+            protocol_magic: 764824073,
+            slot_duration: 20000,
+            update_vote_thd: 1000000000000,
+            update_implicit: 10000,
+            softfork_rule: SoftforkRule {
+                init_thd: 900000000000000,
+                min_thd: 600000000000000,
+                thd_decrement: 50000000000000,
+            },
+        };
+        let env: Environment = Environment {
+            prot_params: MultiEraProtParams::Byron(Box::new(Cow::Borrowed(&byron_params))),
+            block_slot: block.slot(),
+            network_id: 1,
+            registered_pools: Cow::Owned(HashSet::new()),
+        };
+        assert!(matches!(
+            validate(&metx, &utxos, &env),
+            Err(ValidationError::TxAndProtParamsDiffer)
+        ));
+    }
+}
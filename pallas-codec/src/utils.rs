@@ -387,6 +387,212 @@ impl<I, const T: u64> Deref for TagWrap<I, T> {
     }
 }
 
+/// Tag used to mark arrays holding sets
+pub const TAG_SET: u64 = 258;
+
+/// An array holding a set of values, optionally wrapped in tag 258
+///
+/// The tag became part of the ledger CDDL in the Conway era, but untagged sets
+/// are still valid. To keep the decoding / encoding isomorphic, we remember
+/// whether the original array was tagged. Sets built from a vec are tagged.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[serde(from = "Vec::<T>", into = "Vec::<T>")]
+pub struct Set<T>
+where
+    T: Clone,
+{
+    tagged: bool,
+    items: Vec<T>,
+}
+
+impl<T> Set<T>
+where
+    T: Clone,
+{
+    pub fn is_tagged(&self) -> bool {
+        self.tagged
+    }
+
+    pub fn to_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Deref for Set<T>
+where
+    T: Clone,
+{
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<T> From<Vec<T>> for Set<T>
+where
+    T: Clone,
+{
+    fn from(items: Vec<T>) -> Self {
+        Set {
+            tagged: true,
+            items,
+        }
+    }
+}
+
+impl<T> From<Set<T>> for Vec<T>
+where
+    T: Clone,
+{
+    fn from(other: Set<T>) -> Self {
+        other.items
+    }
+}
+
+impl<'b, C, T> minicbor::decode::Decode<'b, C> for Set<T>
+where
+    T: Decode<'b, C> + Clone,
+{
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        let tagged = decode_set_tag(d)?;
+        let items = d.decode_with(ctx)?;
+
+        Ok(Set { tagged, items })
+    }
+}
+
+impl<C, T> minicbor::encode::Encode<C> for Set<T>
+where
+    T: Encode<C> + Clone,
+{
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        if self.tagged {
+            e.tag(Tag::Unassigned(TAG_SET))?;
+        }
+
+        e.encode_with(&self.items, ctx)?;
+
+        Ok(())
+    }
+}
+
+/// A [Set] that should hold at least one value
+///
+/// Building one from an empty vec fails. Decoding is lenient, though: txs
+/// built by pre-Conway tooling may hold empty arrays where the CDDL now asks
+/// for non-empty sets, and these still have to be readable.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[serde(try_from = "Vec::<T>", into = "Vec::<T>")]
+pub struct NonEmptySet<T>
+where
+    T: Clone,
+{
+    tagged: bool,
+    items: Vec<T>,
+}
+
+impl<T> NonEmptySet<T>
+where
+    T: Clone,
+{
+    pub fn from_vec(items: Vec<T>) -> Option<Self> {
+        match items.is_empty() {
+            true => None,
+            false => Some(NonEmptySet {
+                tagged: true,
+                items,
+            }),
+        }
+    }
+
+    pub fn is_tagged(&self) -> bool {
+        self.tagged
+    }
+
+    pub fn to_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Deref for NonEmptySet<T>
+where
+    T: Clone,
+{
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<T> TryFrom<Vec<T>> for NonEmptySet<T>
+where
+    T: Clone,
+{
+    type Error = &'static str;
+
+    fn try_from(items: Vec<T>) -> Result<Self, Self::Error> {
+        NonEmptySet::from_vec(items).ok_or("empty set where at least one value is required")
+    }
+}
+
+impl<T> From<NonEmptySet<T>> for Vec<T>
+where
+    T: Clone,
+{
+    fn from(other: NonEmptySet<T>) -> Self {
+        other.items
+    }
+}
+
+impl<'b, C, T> minicbor::decode::Decode<'b, C> for NonEmptySet<T>
+where
+    T: Decode<'b, C> + Clone,
+{
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        let tagged = decode_set_tag(d)?;
+        let items = d.decode_with(ctx)?;
+
+        Ok(NonEmptySet { tagged, items })
+    }
+}
+
+impl<C, T> minicbor::encode::Encode<C> for NonEmptySet<T>
+where
+    T: Encode<C> + Clone,
+{
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        if self.tagged {
+            e.tag(Tag::Unassigned(TAG_SET))?;
+        }
+
+        e.encode_with(&self.items, ctx)?;
+
+        Ok(())
+    }
+}
+
+// Consumes the set tag, if present, and tells whether it was.
+fn decode_set_tag(d: &mut minicbor::Decoder) -> Result<bool, minicbor::decode::Error> {
+    if d.datatype()? != minicbor::data::Type::Tag {
+        return Ok(false);
+    }
+
+    match d.tag()? {
+        Tag::Unassigned(TAG_SET) => Ok(true),
+        _ => Err(minicbor::decode::Error::message("invalid tag for set")),
+    }
+}
+
 /// An empty map
 ///
 /// don't ask me why, that's what the CDDL asks for.
@@ -649,7 +855,7 @@ impl<C, T> minicbor::Encode<C> for KeepRaw<'_, T> {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 #[serde(from = "Option::<T>", into = "Option::<T>")]
 pub enum Nullable<T>
 where
@@ -835,3 +1041,40 @@ impl TryFrom<i128> for Int {
         Ok(Self(inner))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_tag_is_preserved() {
+        let tagged = hex::decode("d90102820102").unwrap();
+        let set: Set<u8> = minicbor::decode(&tagged).unwrap();
+        assert!(set.is_tagged());
+        assert_eq!(set.as_slice(), &[1, 2]);
+        assert_eq!(minicbor::to_vec(&set).unwrap(), tagged);
+
+        let untagged = hex::decode("820102").unwrap();
+        let set: Set<u8> = minicbor::decode(&untagged).unwrap();
+        assert!(!set.is_tagged());
+        assert_eq!(minicbor::to_vec(&set).unwrap(), untagged);
+    }
+
+    #[test]
+    fn non_empty_set_built_only_from_values() {
+        assert!(NonEmptySet::<u8>::from_vec(vec![]).is_none());
+        assert!(NonEmptySet::<u8>::try_from(vec![1]).is_ok());
+
+        // lenient decoding of empty sets left by pre-Conway tooling
+        let empty = hex::decode("80").unwrap();
+        let set: NonEmptySet<u8> = minicbor::decode(&empty).unwrap();
+        assert!(set.is_empty());
+        assert_eq!(minicbor::to_vec(&set).unwrap(), empty);
+    }
+
+    #[test]
+    fn set_rejects_other_tags() {
+        let other = hex::decode("d90103820102").unwrap();
+        assert!(minicbor::decode::<Set<u8>>(&other).is_err());
+    }
+}
@@ -1,3 +1,4 @@
 //! Genesis data structs and utilities
 
 pub mod byron;
+pub mod shelley;
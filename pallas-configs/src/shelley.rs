@@ -0,0 +1,100 @@
+//! Parsing of Shelley configuration data
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisFile {
+    pub active_slots_coeff: f64,
+    pub epoch_length: u64,
+    /// Genesis delegates, keyed by the hash of their genesis key
+    pub gen_delegs: HashMap<String, GenDelegs>,
+    #[serde(rename = "maxKESEvolutions")]
+    pub max_kes_evolutions: u64,
+    pub max_lovelace_supply: u64,
+    pub network_id: String,
+    pub network_magic: u32,
+    pub protocol_params: ProtocolParams,
+    pub security_param: u64,
+    /// Slot length in seconds, which may be fractional
+    pub slot_length: f64,
+    #[serde(rename = "slotsPerKESPeriod")]
+    pub slots_per_kes_period: u64,
+    pub system_start: String,
+    pub update_quorum: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenDelegs {
+    pub delegate: String,
+    pub vrf: String,
+}
+
+/// Protocol parameters in effect at the start of the Shelley era
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolParams {
+    pub protocol_version: ProtocolVersion,
+    pub decentralisation_param: f64,
+    pub e_max: u64,
+    pub extra_entropy: ExtraEntropy,
+    pub max_tx_size: u32,
+    pub max_block_body_size: u32,
+    pub max_block_header_size: u32,
+    pub min_fee_a: u32,
+    pub min_fee_b: u32,
+    #[serde(rename = "minUTxOValue")]
+    pub min_utxo_value: u64,
+    pub pool_deposit: u64,
+    pub min_pool_cost: u64,
+    pub key_deposit: u64,
+    pub n_opt: u32,
+    pub rho: f64,
+    pub tau: f64,
+    pub a0: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u64,
+    pub minor: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtraEntropy {
+    pub tag: String,
+    /// Hex-encoded nonce, absent for the neutral one
+    pub contents: Option<String>,
+}
+
+pub fn from_file(path: &std::path::Path) -> Result<GenesisFile, std::io::Error> {
+    let file = std::fs::File::open(path)?;
+    let reader = std::io::BufReader::new(file);
+    let parsed: GenesisFile = serde_json::from_reader(reader)?;
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_json_loads() {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("test_data")
+            .join("preview-shelley-genesis.json");
+
+        let f = from_file(&path).unwrap();
+
+        assert_eq!(f.network_magic, 2);
+        assert_eq!(f.epoch_length, 86400);
+        assert_eq!(f.security_param, 432);
+        assert_eq!(f.slot_length, 1.0);
+        assert_eq!(f.protocol_params.protocol_version.major, 6);
+        assert_eq!(f.protocol_params.min_fee_a, 44);
+        assert_eq!(f.protocol_params.rho, 0.003);
+    }
+}
@@ -1,7 +1,9 @@
 use std::path::Path;
+use std::time::Duration;
 
 use thiserror::Error;
 use tokio::net::TcpListener;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tracing::{debug, error};
 
@@ -12,9 +14,10 @@ use crate::miniprotocols::handshake::{n2c, n2n, Confirmation, VersionNumber};
 use crate::miniprotocols::PROTOCOL_N2N_HANDSHAKE;
 use crate::{
     miniprotocols::{
-        blockfetch, chainsync, handshake, localstate, PROTOCOL_N2C_CHAIN_SYNC,
-        PROTOCOL_N2C_HANDSHAKE, PROTOCOL_N2C_STATE_QUERY, PROTOCOL_N2N_BLOCK_FETCH,
-        PROTOCOL_N2N_CHAIN_SYNC,
+        blockfetch, chainsync, handshake, keepalive, localstate, peersharing,
+        PROTOCOL_N2C_CHAIN_SYNC, PROTOCOL_N2C_HANDSHAKE, PROTOCOL_N2C_STATE_QUERY,
+        PROTOCOL_N2N_BLOCK_FETCH, PROTOCOL_N2N_CHAIN_SYNC, PROTOCOL_N2N_KEEP_ALIVE,
+        PROTOCOL_N2N_PEER_SHARING,
     },
     multiplexer::{self, Bearer},
 };
@@ -31,16 +34,55 @@ pub enum Error {
     IncompatibleVersion,
 }
 
+/// Time between the keep-alive messages sent by a [PeerClient]
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+async fn run_keepalive(
+    mut client: keepalive::Client,
+    rtt: watch::Sender<Option<Duration>>,
+) -> Result<(), keepalive::ClientError> {
+    let mut cookie: keepalive::KeepAliveCookie = 0;
+
+    loop {
+        tokio::time::sleep(KEEP_ALIVE_INTERVAL).await;
+
+        let elapsed = client.keepalive_roundtrip(cookie).await?;
+        debug!(?elapsed, "keep-alive round-trip");
+
+        rtt.send_replace(Some(elapsed));
+        cookie = cookie.wrapping_add(1);
+    }
+}
+
 /// Client of N2N Ouroboros
+///
+/// A background task keeps the connection alive, sending a keep-alive message
+/// every [KEEP_ALIVE_INTERVAL] and reporting the round-trip time of the last
+/// one through `keepalive_rtt`.
 pub struct PeerClient {
     pub plexer_handle: JoinHandle<Result<(), crate::multiplexer::Error>>,
+    pub keepalive_handle: JoinHandle<Result<(), keepalive::ClientError>>,
+    pub keepalive_rtt: watch::Receiver<Option<Duration>>,
     pub handshake: handshake::Confirmation<handshake::n2n::VersionData>,
     pub chainsync: chainsync::N2NClient,
     pub blockfetch: blockfetch::Client,
+    pub peersharing: peersharing::Client,
 }
 
 impl PeerClient {
     pub async fn connect(address: &str, magic: u64) -> Result<Self, Error> {
+        let versions = handshake::n2n::VersionTable::v7_and_above(magic);
+        Self::connect_with_versions(address, versions).await
+    }
+
+    /// Connect to a peer proposing the given versions during the handshake
+    ///
+    /// Peer sharing is only available if negotiated, which requires proposing
+    /// [handshake::n2n::VersionTable::v11_and_above] with the flag enabled.
+    pub async fn connect_with_versions(
+        address: &str,
+        versions: handshake::n2n::VersionTable,
+    ) -> Result<Self, Error> {
         debug!("connecting");
         let bearer = Bearer::connect_tcp(address)
             .await
@@ -51,10 +93,11 @@ impl PeerClient {
         let channel0 = plexer.subscribe_client(0);
         let channel2 = plexer.subscribe_client(2);
         let channel3 = plexer.subscribe_client(3);
+        let channel8 = plexer.subscribe_client(PROTOCOL_N2N_KEEP_ALIVE);
+        let channel10 = plexer.subscribe_client(PROTOCOL_N2N_PEER_SHARING);
 
         let plexer_handle = tokio::spawn(async move { plexer.run().await });
 
-        let versions = handshake::n2n::VersionTable::v7_and_above(magic);
         let mut client = handshake::Client::new(channel0);
 
         let handshake = client
@@ -67,11 +110,18 @@ impl PeerClient {
             return Err(Error::IncompatibleVersion);
         }
 
+        let (rtt_sender, keepalive_rtt) = watch::channel(None);
+        let keepalive = keepalive::Client::new(channel8);
+        let keepalive_handle = tokio::spawn(run_keepalive(keepalive, rtt_sender));
+
         Ok(Self {
             plexer_handle,
+            keepalive_handle,
+            keepalive_rtt,
             handshake,
             chainsync: chainsync::Client::new(channel2),
             blockfetch: blockfetch::Client::new(channel3),
+            peersharing: peersharing::Client::new(channel10),
         })
     }
 
@@ -83,7 +133,17 @@ impl PeerClient {
         &mut self.blockfetch
     }
 
+    pub fn peersharing(&mut self) -> &mut peersharing::Client {
+        &mut self.peersharing
+    }
+
+    /// The round-trip time of the last keep-alive message, if any
+    pub fn keepalive_rtt(&self) -> Option<Duration> {
+        *self.keepalive_rtt.borrow()
+    }
+
     pub fn abort(&mut self) {
+        self.keepalive_handle.abort();
         self.plexer_handle.abort();
     }
 }
@@ -94,10 +154,22 @@ pub struct PeerServer {
     pub version: (VersionNumber, n2n::VersionData),
     pub chainsync: chainsync::N2NServer,
     pub blockfetch: blockfetch::Server,
+    pub keepalive: keepalive::Server,
+    pub peersharing: peersharing::Server,
 }
 
 impl PeerServer {
     pub async fn accept(listener: &TcpListener, magic: u64) -> Result<Self, Error> {
+        let versions = n2n::VersionTable::v7_and_above(magic);
+        Self::accept_with_versions(listener, versions).await
+    }
+
+    /// Accept a peer connection, agreeing on one of the given versions during
+    /// the handshake
+    pub async fn accept_with_versions(
+        listener: &TcpListener,
+        versions: n2n::VersionTable,
+    ) -> Result<Self, Error> {
         let (bearer, _) = Bearer::accept_tcp(listener)
             .await
             .map_err(Error::ConnectFailure)?;
@@ -107,15 +179,19 @@ impl PeerServer {
         let hs_channel = server_plexer.subscribe_server(PROTOCOL_N2N_HANDSHAKE);
         let cs_channel = server_plexer.subscribe_server(PROTOCOL_N2N_CHAIN_SYNC);
         let bf_channel = server_plexer.subscribe_server(PROTOCOL_N2N_BLOCK_FETCH);
+        let ka_channel = server_plexer.subscribe_server(PROTOCOL_N2N_KEEP_ALIVE);
+        let ps_channel = server_plexer.subscribe_server(PROTOCOL_N2N_PEER_SHARING);
 
         let mut server_hs: handshake::Server<n2n::VersionData> = handshake::Server::new(hs_channel);
         let server_cs = chainsync::N2NServer::new(cs_channel);
         let server_bf = blockfetch::Server::new(bf_channel);
+        let server_ka = keepalive::Server::new(ka_channel);
+        let server_ps = peersharing::Server::new(ps_channel);
 
         let plexer_handle = tokio::spawn(async move { server_plexer.run().await });
 
         let accepted_version = server_hs
-            .handshake(n2n::VersionTable::v7_and_above(magic))
+            .handshake(versions)
             .await
             .map_err(Error::HandshakeProtocol)?;
 
@@ -125,6 +201,8 @@ impl PeerServer {
                 version: ver,
                 chainsync: server_cs,
                 blockfetch: server_bf,
+                keepalive: server_ka,
+                peersharing: server_ps,
             })
         } else {
             plexer_handle.abort();
@@ -140,6 +218,14 @@ impl PeerServer {
         &mut self.blockfetch
     }
 
+    pub fn keepalive(&mut self) -> &mut keepalive::Server {
+        &mut self.keepalive
+    }
+
+    pub fn peersharing(&mut self) -> &mut peersharing::Server {
+        &mut self.peersharing
+    }
+
     pub fn abort(&mut self) {
         self.plexer_handle.abort();
     }
@@ -11,6 +11,15 @@ const PROTOCOL_V7: u64 = 7;
 const PROTOCOL_V8: u64 = 8;
 const PROTOCOL_V9: u64 = 9;
 const PROTOCOL_V10: u64 = 10;
+const PROTOCOL_V11: u64 = 11;
+const PROTOCOL_V12: u64 = 12;
+const PROTOCOL_V13: u64 = 13;
+
+/// Value of the version data flag for nodes not taking part in peer sharing
+pub const PEER_SHARING_DISABLED: u8 = 0;
+
+/// Value of the version data flag for nodes taking part in peer sharing
+pub const PEER_SHARING_ENABLED: u8 = 1;
 
 impl VersionTable {
     pub fn v4_and_above(network_magic: u64) -> VersionTable {
@@ -55,12 +64,42 @@ impl VersionTable {
 
         VersionTable { values }
     }
+
+    /// Versions from v11 onwards, which negotiate the use of the
+    /// PeerSharing mini-protocol during the handshake.
+    pub fn v11_and_above(network_magic: u64, peer_sharing: bool) -> VersionTable {
+        let peer_sharing = match peer_sharing {
+            true => PEER_SHARING_ENABLED,
+            false => PEER_SHARING_DISABLED,
+        };
+
+        let values = vec![
+            (
+                PROTOCOL_V11,
+                VersionData::with_peer_sharing(network_magic, false, peer_sharing, false),
+            ),
+            (
+                PROTOCOL_V12,
+                VersionData::with_peer_sharing(network_magic, false, peer_sharing, false),
+            ),
+            (
+                PROTOCOL_V13,
+                VersionData::with_peer_sharing(network_magic, false, peer_sharing, false),
+            ),
+        ]
+        .into_iter()
+        .collect::<HashMap<u64, VersionData>>();
+
+        VersionTable { values }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct VersionData {
     network_magic: u64,
     initiator_and_responder_diffusion_mode: bool,
+    peer_sharing: Option<u8>,
+    query: Option<bool>,
 }
 
 impl VersionData {
@@ -68,8 +107,31 @@ impl VersionData {
         VersionData {
             network_magic,
             initiator_and_responder_diffusion_mode,
+            peer_sharing: None,
+            query: None,
+        }
+    }
+
+    /// Create the version data of a v11 or later handshake proposal, which
+    /// carries the `peer_sharing` and `query` flags
+    pub fn with_peer_sharing(
+        network_magic: u64,
+        initiator_and_responder_diffusion_mode: bool,
+        peer_sharing: u8,
+        query: bool,
+    ) -> Self {
+        VersionData {
+            network_magic,
+            initiator_and_responder_diffusion_mode,
+            peer_sharing: Some(peer_sharing),
+            query: Some(query),
         }
     }
+
+    /// Check if the node is willing to take part in peer sharing
+    pub fn peer_sharing_enabled(&self) -> bool {
+        matches!(self.peer_sharing, Some(x) if x != PEER_SHARING_DISABLED)
+    }
 }
 
 impl Encode<()> for VersionData {
@@ -78,9 +140,20 @@ impl Encode<()> for VersionData {
         e: &mut Encoder<W>,
         _ctx: &mut (),
     ) -> Result<(), encode::Error<W::Error>> {
-        e.array(2)?
-            .u64(self.network_magic)?
-            .bool(self.initiator_and_responder_diffusion_mode)?;
+        match (self.peer_sharing, self.query) {
+            (Some(peer_sharing), Some(query)) => {
+                e.array(4)?
+                    .u64(self.network_magic)?
+                    .bool(self.initiator_and_responder_diffusion_mode)?
+                    .u8(peer_sharing)?
+                    .bool(query)?;
+            }
+            _ => {
+                e.array(2)?
+                    .u64(self.network_magic)?
+                    .bool(self.initiator_and_responder_diffusion_mode)?;
+            }
+        };
 
         Ok(())
     }
@@ -88,13 +161,25 @@ impl Encode<()> for VersionData {
 
 impl<'b> Decode<'b, ()> for VersionData {
     fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
-        d.array()?;
+        let len = d.array()?;
         let network_magic = d.u64()?;
         let initiator_and_responder_diffusion_mode = d.bool()?;
 
+        let (peer_sharing, query) = match len {
+            Some(4) => (Some(d.u8()?), Some(d.bool()?)),
+            Some(2) => (None, None),
+            _ => {
+                return Err(decode::Error::message(
+                    "unexpected array length for n2n version data",
+                ))
+            }
+        };
+
         Ok(Self {
             network_magic,
             initiator_and_responder_diffusion_mode,
+            peer_sharing,
+            query,
         })
     }
 }
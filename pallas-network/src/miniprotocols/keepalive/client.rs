@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::multiplexer;
+
+use super::{KeepAliveCookie, Message, State};
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("attempted to receive message while agency is ours")]
+    AgencyIsOurs,
+
+    #[error("attempted to send message while agency is theirs")]
+    AgencyIsTheirs,
+
+    #[error("inbound message is not valid for current state")]
+    InvalidInbound,
+
+    #[error("outbound message is not valid for current state")]
+    InvalidOutbound,
+
+    #[error("keep-alive response cookie {1} doesn't match the requested {0}")]
+    CookieMismatch(KeepAliveCookie, KeepAliveCookie),
+
+    #[error("error while sending or receiving data through the multiplexer")]
+    Plexer(multiplexer::Error),
+}
+
+/// Represents the client for the KeepAlive mini-protocol.
+///
+/// Peers drop connections that stay silent for too long, which the client
+/// prevents by sending keep-alive messages from time to time. The time it
+/// takes the server to respond is a measure of the round-trip time to the
+/// peer.
+pub struct Client(State, multiplexer::ChannelBuffer);
+
+impl Client {
+    /// Create a new KeepAlive client from a multiplexer agent channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - A multiplexer agent channel used for communication with
+    ///   the remote node.
+    pub fn new(channel: multiplexer::AgentChannel) -> Self {
+        Self(State::Client, multiplexer::ChannelBuffer::new(channel))
+    }
+
+    /// Get the current state of the client.
+    ///
+    /// Returns the current state of the client.
+    pub fn state(&self) -> &State {
+        &self.0
+    }
+
+    /// Check if the client is done.
+    ///
+    /// Returns true if the client is in the `Done` state, false otherwise.
+    pub fn is_done(&self) -> bool {
+        self.0 == State::Done
+    }
+
+    fn has_agency(&self) -> bool {
+        match self.state() {
+            State::Client => true,
+            State::Server(_) => false,
+            State::Done => false,
+        }
+    }
+
+    fn assert_agency_is_ours(&self) -> Result<(), ClientError> {
+        if !self.has_agency() {
+            Err(ClientError::AgencyIsTheirs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_agency_is_theirs(&self) -> Result<(), ClientError> {
+        if self.has_agency() {
+            Err(ClientError::AgencyIsOurs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_outbound_state(&self, msg: &Message) -> Result<(), ClientError> {
+        match (&self.0, msg) {
+            (State::Client, Message::KeepAlive(..)) => Ok(()),
+            (State::Client, Message::Done) => Ok(()),
+            _ => Err(ClientError::InvalidOutbound),
+        }
+    }
+
+    fn assert_inbound_state(&self, msg: &Message) -> Result<(), ClientError> {
+        match (&self.0, msg) {
+            (State::Server(..), Message::ResponseKeepAlive(..)) => Ok(()),
+            _ => Err(ClientError::InvalidInbound),
+        }
+    }
+
+    pub async fn send_message(&mut self, msg: &Message) -> Result<(), ClientError> {
+        self.assert_agency_is_ours()?;
+        self.assert_outbound_state(msg)?;
+        self.1
+            .send_msg_chunks(msg)
+            .await
+            .map_err(ClientError::Plexer)?;
+
+        Ok(())
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Message, ClientError> {
+        self.assert_agency_is_theirs()?;
+        let msg = self.1.recv_full_msg().await.map_err(ClientError::Plexer)?;
+        self.assert_inbound_state(&msg)?;
+
+        Ok(msg)
+    }
+
+    pub async fn send_keepalive(&mut self, cookie: KeepAliveCookie) -> Result<(), ClientError> {
+        let msg = Message::KeepAlive(cookie);
+        self.send_message(&msg).await?;
+        self.0 = State::Server(cookie);
+
+        Ok(())
+    }
+
+    /// Receive the response to the last keep-alive message sent.
+    ///
+    /// Returns an `Error` if the response doesn't echo the cookie of the
+    /// request.
+    pub async fn recv_keepalive_response(&mut self) -> Result<(), ClientError> {
+        match (self.recv_message().await?, &self.0) {
+            (Message::ResponseKeepAlive(received), State::Server(sent)) => {
+                if received != *sent {
+                    return Err(ClientError::CookieMismatch(*sent, received));
+                }
+
+                self.0 = State::Client;
+                Ok(())
+            }
+            _ => Err(ClientError::InvalidInbound),
+        }
+    }
+
+    /// Send a keep-alive message and wait for its response.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookie` - The value to be echoed back by the server.
+    ///
+    /// Returns the time elapsed between the request and its response.
+    pub async fn keepalive_roundtrip(
+        &mut self,
+        cookie: KeepAliveCookie,
+    ) -> Result<Duration, ClientError> {
+        let start = Instant::now();
+
+        self.send_keepalive(cookie).await?;
+        debug!(cookie, "keep-alive sent");
+        self.recv_keepalive_response().await?;
+
+        Ok(start.elapsed())
+    }
+
+    /// Send a `Done` message to the remote node and set the client's state to
+    /// `Done`.
+    pub async fn send_done(&mut self) -> Result<(), ClientError> {
+        let msg = Message::Done;
+        self.send_message(&msg).await?;
+        self.0 = State::Done;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use pallas_codec::minicbor::{decode, encode, Decode, Decoder, Encode, Encoder};
+
+use super::Message;
+
+impl Encode<()> for Message {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Message::KeepAlive(cookie) => {
+                e.array(2)?.u16(0)?;
+                e.u16(*cookie)?;
+                Ok(())
+            }
+            Message::ResponseKeepAlive(cookie) => {
+                e.array(2)?.u16(1)?;
+                e.u16(*cookie)?;
+                Ok(())
+            }
+            Message::Done => {
+                e.array(1)?.u16(2)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'b> Decode<'b, ()> for Message {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => Ok(Message::KeepAlive(d.u16()?)),
+            1 => Ok(Message::ResponseKeepAlive(d.u16()?)),
+            2 => Ok(Message::Done),
+            _ => Err(decode::Error::message(
+                "unknown variant for keepalive message",
+            )),
+        }
+    }
+}
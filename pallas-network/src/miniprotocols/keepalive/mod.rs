@@ -0,0 +1,11 @@
+//! KeepAlive mini-protocol implementation
+
+mod client;
+mod codec;
+mod protocol;
+mod server;
+
+pub use client::*;
+pub use codec::*;
+pub use protocol::*;
+pub use server::*;
@@ -0,0 +1,16 @@
+/// Opaque value echoed back by the server, matching responses to requests
+pub type KeepAliveCookie = u16;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum State {
+    Client,
+    Server(KeepAliveCookie),
+    Done,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    KeepAlive(KeepAliveCookie),
+    ResponseKeepAlive(KeepAliveCookie),
+    Done,
+}
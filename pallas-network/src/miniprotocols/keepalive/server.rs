@@ -0,0 +1,159 @@
+use thiserror::Error;
+
+use crate::multiplexer;
+
+use super::{KeepAliveCookie, Message, State};
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("attempted to receive message while agency is ours")]
+    AgencyIsOurs,
+
+    #[error("attempted to send message while agency is theirs")]
+    AgencyIsTheirs,
+
+    #[error("inbound message is not valid for current state")]
+    InvalidInbound,
+
+    #[error("outbound message is not valid for current state")]
+    InvalidOutbound,
+
+    #[error("error while sending or receiving data through the multiplexer")]
+    Plexer(multiplexer::Error),
+}
+
+/// Represents the server for the KeepAlive mini-protocol.
+pub struct Server(State, multiplexer::ChannelBuffer);
+
+impl Server {
+    /// Create a new KeepAlive server from a multiplexer agent channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - A multiplexer agent channel used for communication with
+    ///   the client.
+    pub fn new(channel: multiplexer::AgentChannel) -> Self {
+        Self(State::Client, multiplexer::ChannelBuffer::new(channel))
+    }
+
+    /// Get the current state of the server.
+    ///
+    /// Returns the current state of the server.
+    pub fn state(&self) -> &State {
+        &self.0
+    }
+
+    /// Check if the server is done.
+    ///
+    /// Returns true if server is in the `Done` state, false otherwise.
+    pub fn is_done(&self) -> bool {
+        self.0 == State::Done
+    }
+
+    fn has_agency(&self) -> bool {
+        match self.state() {
+            State::Client => false,
+            State::Server(_) => true,
+            State::Done => false,
+        }
+    }
+
+    fn assert_agency_is_ours(&self) -> Result<(), ServerError> {
+        if !self.has_agency() {
+            Err(ServerError::AgencyIsTheirs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_agency_is_theirs(&self) -> Result<(), ServerError> {
+        if self.has_agency() {
+            Err(ServerError::AgencyIsOurs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_outbound_state(&self, msg: &Message) -> Result<(), ServerError> {
+        match (&self.0, msg) {
+            (State::Server(..), Message::ResponseKeepAlive(..)) => Ok(()),
+            _ => Err(ServerError::InvalidOutbound),
+        }
+    }
+
+    fn assert_inbound_state(&self, msg: &Message) -> Result<(), ServerError> {
+        match (&self.0, msg) {
+            (State::Client, Message::KeepAlive(..)) => Ok(()),
+            (State::Client, Message::Done) => Ok(()),
+            _ => Err(ServerError::InvalidInbound),
+        }
+    }
+
+    pub async fn send_message(&mut self, msg: &Message) -> Result<(), ServerError> {
+        self.assert_agency_is_ours()?;
+        self.assert_outbound_state(msg)?;
+        self.1
+            .send_msg_chunks(msg)
+            .await
+            .map_err(ServerError::Plexer)?;
+
+        Ok(())
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Message, ServerError> {
+        self.assert_agency_is_theirs()?;
+        let msg = self.1.recv_full_msg().await.map_err(ServerError::Plexer)?;
+        self.assert_inbound_state(&msg)?;
+
+        Ok(msg)
+    }
+
+    /// Receive a message from the client while the miniprotocol is in the
+    /// `Client` state.
+    ///
+    /// If the message is a `KeepAlive`, return its cookie and progress the
+    /// server state to `Server`. If the message is a `Done`, return None and
+    /// progress the server state to `Done`.
+    pub async fn recv_while_client(&mut self) -> Result<Option<KeepAliveCookie>, ServerError> {
+        match self.recv_message().await? {
+            Message::KeepAlive(cookie) => {
+                self.0 = State::Server(cookie);
+
+                Ok(Some(cookie))
+            }
+            Message::Done => {
+                self.0 = State::Done;
+
+                Ok(None)
+            }
+            _ => Err(ServerError::InvalidInbound),
+        }
+    }
+
+    /// Respond to the last keep-alive message received, echoing its cookie.
+    pub async fn send_keepalive_response(&mut self) -> Result<(), ServerError> {
+        let cookie = match self.0 {
+            State::Server(cookie) => cookie,
+            _ => return Err(ServerError::AgencyIsTheirs),
+        };
+
+        let msg = Message::ResponseKeepAlive(cookie);
+        self.send_message(&msg).await?;
+        self.0 = State::Client;
+
+        Ok(())
+    }
+
+    /// Wait for the next keep-alive message and respond to it.
+    ///
+    /// Returns the cookie of the message, or None if the client is done.
+    pub async fn respond_keepalive(&mut self) -> Result<Option<KeepAliveCookie>, ServerError> {
+        let cookie = self.recv_while_client().await?;
+
+        if cookie.is_some() {
+            self.send_keepalive_response().await?;
+        }
+
+        Ok(cookie)
+    }
+}
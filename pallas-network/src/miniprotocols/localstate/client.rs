@@ -23,6 +23,10 @@ pub enum Error {
     AcquirePointNotFound,
     #[error("failure acquiring point, too old")]
     AcquirePointTooOld,
+    #[error("query for era {0} while the ledger is in era {1}")]
+    EraMismatch(String, String),
+    #[error("failure decoding query response: {0}")]
+    InvalidQueryResponse(String),
     #[error("error while sending or receiving data through the channel")]
     Plexer(multiplexer::Error),
 }
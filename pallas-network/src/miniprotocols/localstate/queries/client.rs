@@ -0,0 +1,123 @@
+use pallas_codec::minicbor::{self, Decode};
+use pallas_codec::utils::{Bytes, Set};
+
+use crate::miniprotocols::localstate::{ClientV10, Error};
+use crate::miniprotocols::Point;
+
+use super::*;
+
+fn decode_response<T>(response: &GenericResponse) -> Result<T, Error>
+where
+    T: for<'b> Decode<'b, ()>,
+{
+    minicbor::decode(&response.0).map_err(|err| Error::InvalidQueryResponse(err.to_string()))
+}
+
+async fn query_ledger<T>(client: &mut ClientV10, era: Era, query: LedgerQuery) -> Result<T, Error>
+where
+    T: for<'b> Decode<'b, ()>,
+{
+    let request = RequestV10::BlockQuery(BlockQuery::IfCurrent(era, query));
+    let response = client.query(request).await?;
+
+    match decode_response(&response)? {
+        EraResult::Ok(x) => Ok(x),
+        EraResult::Mismatch(query, ledger) => Err(Error::EraMismatch(query, ledger)),
+    }
+}
+
+async fn query_hard_fork<T>(client: &mut ClientV10, query: HardForkQuery) -> Result<T, Error>
+where
+    T: for<'b> Decode<'b, ()>,
+{
+    let request = RequestV10::BlockQuery(BlockQuery::HardFork(query));
+    let response = client.query(request).await?;
+
+    decode_response(&response)
+}
+
+pub async fn get_system_start(client: &mut ClientV10) -> Result<SystemStart, Error> {
+    let response = client.query(RequestV10::GetSystemStart).await?;
+    decode_response(&response)
+}
+
+pub async fn get_chain_point(client: &mut ClientV10) -> Result<Point, Error> {
+    let response = client.query(RequestV10::GetChainPoint).await?;
+    decode_response(&response)
+}
+
+/// Get the index of the era the ledger of the node is in
+pub async fn get_current_era(client: &mut ClientV10) -> Result<Era, Error> {
+    query_hard_fork(client, HardForkQuery::GetCurrentEra).await
+}
+
+/// Get the summary of the eras, which allows converting slots to time
+///
+/// The summary is left as CBOR, to be read by
+/// `pallas_traverse::history::EraHistory::from_interpreter`.
+pub async fn get_era_history(client: &mut ClientV10) -> Result<Vec<u8>, Error> {
+    let request = RequestV10::BlockQuery(BlockQuery::HardFork(HardForkQuery::GetInterpreter));
+    let response = client.query(request).await?;
+
+    Ok(response.0)
+}
+
+pub async fn get_epoch_no(client: &mut ClientV10, era: Era) -> Result<Epoch, Error> {
+    query_ledger(client, era, LedgerQuery::GetEpochNo).await
+}
+
+pub async fn get_current_pparams(
+    client: &mut ClientV10,
+    era: Era,
+) -> Result<ProtocolParams, Error> {
+    query_ledger(client, era, LedgerQuery::GetCurrentPParams).await
+}
+
+pub async fn get_stake_distribution(
+    client: &mut ClientV10,
+    era: Era,
+) -> Result<StakeDistribution, Error> {
+    query_ledger(client, era, LedgerQuery::GetStakeDistribution).await
+}
+
+/// Get the unspent outputs locked by any of the given addresses
+///
+/// # Arguments
+///
+/// * `addresses` - The raw bytes of each address.
+pub async fn get_utxo_by_address(
+    client: &mut ClientV10,
+    era: Era,
+    addresses: Vec<Bytes>,
+) -> Result<UTxO, Error> {
+    query_ledger(client, era, LedgerQuery::GetUTxOByAddress(addresses)).await
+}
+
+/// Get those of the given outputs that are still unspent
+pub async fn get_utxo_by_txin(
+    client: &mut ClientV10,
+    era: Era,
+    inputs: Vec<TransactionInput>,
+) -> Result<UTxO, Error> {
+    query_ledger(client, era, LedgerQuery::GetUTxOByTxIn(inputs)).await
+}
+
+/// Get the ids of the registered stake pools
+pub async fn get_stake_pools(client: &mut ClientV10, era: Era) -> Result<Vec<PoolKeyhash>, Error> {
+    let pools: Set<PoolKeyhash> = query_ledger(client, era, LedgerQuery::GetStakePools).await?;
+    Ok(pools.to_vec())
+}
+
+/// Get the pools the given credentials delegate to, and their reward balance
+pub async fn get_filtered_delegations_and_rewards(
+    client: &mut ClientV10,
+    era: Era,
+    credentials: Vec<StakeCredential>,
+) -> Result<FilteredDelegationsAndRewards, Error> {
+    let query = LedgerQuery::GetFilteredDelegationsAndRewardAccounts(credentials);
+    query_ledger(client, era, query).await
+}
+
+pub async fn get_genesis_config(client: &mut ClientV10, era: Era) -> Result<GenesisConfig, Error> {
+    query_ledger(client, era, LedgerQuery::GetGenesisConfig).await
+}
@@ -0,0 +1,1252 @@
+use pallas_codec::minicbor::{
+    data::{Tag, Type},
+    decode, encode, Decode, Decoder, Encode, Encoder,
+};
+use pallas_codec::utils::Set;
+
+use super::*;
+
+impl Encode<()> for RequestV10 {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::BlockQuery(query) => {
+                e.array(2)?.u16(0)?;
+                e.encode(query)?;
+                Ok(())
+            }
+            Self::GetSystemStart => {
+                e.array(1)?.u16(1)?;
+                Ok(())
+            }
+            Self::GetChainBlockNo => {
+                e.array(1)?.u16(2)?;
+                Ok(())
+            }
+            Self::GetChainPoint => {
+                e.array(1)?.u16(3)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'b> Decode<'b, ()> for RequestV10 {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => Ok(Self::BlockQuery(d.decode()?)),
+            1 => Ok(Self::GetSystemStart),
+            2 => Ok(Self::GetChainBlockNo),
+            3 => Ok(Self::GetChainPoint),
+            _ => Err(decode::Error::message("unknown variant for query request")),
+        }
+    }
+}
+
+impl Encode<()> for BlockQuery {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::IfCurrent(era, query) => {
+                e.array(2)?.u16(0)?;
+                e.array(2)?.u16(*era)?;
+                e.encode(query)?;
+                Ok(())
+            }
+            Self::HardFork(query) => {
+                e.array(2)?.u16(2)?;
+                e.encode(query)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'b> Decode<'b, ()> for BlockQuery {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => {
+                d.array()?;
+                let era = d.u16()?;
+                Ok(Self::IfCurrent(era, d.decode()?))
+            }
+            2 => Ok(Self::HardFork(d.decode()?)),
+            _ => Err(decode::Error::message("unknown variant for block query")),
+        }
+    }
+}
+
+impl Encode<()> for LedgerQuery {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::GetEpochNo => {
+                e.array(1)?.u16(1)?;
+            }
+            Self::GetCurrentPParams => {
+                e.array(1)?.u16(3)?;
+            }
+            Self::GetStakeDistribution => {
+                e.array(1)?.u16(5)?;
+            }
+            Self::GetUTxOByAddress(addresses) => {
+                e.array(2)?.u16(6)?;
+                e.encode(addresses)?;
+            }
+            Self::GetFilteredDelegationsAndRewardAccounts(credentials) => {
+                e.array(2)?.u16(10)?;
+                e.encode(credentials)?;
+            }
+            Self::GetGenesisConfig => {
+                e.array(1)?.u16(11)?;
+            }
+            Self::GetUTxOByTxIn(inputs) => {
+                e.array(2)?.u16(15)?;
+                e.encode(inputs)?;
+            }
+            Self::GetStakePools => {
+                e.array(1)?.u16(16)?;
+            }
+        };
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for LedgerQuery {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            1 => Ok(Self::GetEpochNo),
+            3 => Ok(Self::GetCurrentPParams),
+            5 => Ok(Self::GetStakeDistribution),
+            6 => Ok(Self::GetUTxOByAddress(d.decode::<Set<_>>()?.to_vec())),
+            10 => Ok(Self::GetFilteredDelegationsAndRewardAccounts(
+                d.decode::<Set<_>>()?.to_vec(),
+            )),
+            11 => Ok(Self::GetGenesisConfig),
+            15 => Ok(Self::GetUTxOByTxIn(d.decode::<Set<_>>()?.to_vec())),
+            16 => Ok(Self::GetStakePools),
+            _ => Err(decode::Error::message("unknown variant for ledger query")),
+        }
+    }
+}
+
+impl Encode<()> for HardForkQuery {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::GetInterpreter => e.array(1)?.u16(0)?,
+            Self::GetCurrentEra => e.array(1)?.u16(1)?,
+        };
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for HardForkQuery {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => Ok(Self::GetInterpreter),
+            1 => Ok(Self::GetCurrentEra),
+            _ => Err(decode::Error::message(
+                "unknown variant for hard-fork query",
+            )),
+        }
+    }
+}
+
+impl<T> Encode<()> for EraResult<T>
+where
+    T: Encode<()>,
+{
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::Ok(x) => {
+                e.array(1)?;
+                e.encode(x)?;
+            }
+            // the era indexes are not kept, the names are enough to report
+            // the mismatch
+            Self::Mismatch(query, ledger) => {
+                e.array(2)?;
+                e.array(2)?.u16(0)?.str(query)?;
+                e.array(2)?.u16(0)?.str(ledger)?;
+            }
+        };
+
+        Ok(())
+    }
+}
+
+impl<'b, T> Decode<'b, ()> for EraResult<T>
+where
+    T: Decode<'b, ()>,
+{
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        match d.array()? {
+            Some(1) => Ok(Self::Ok(d.decode()?)),
+            Some(2) => {
+                d.array()?;
+                d.u16()?;
+                let query = d.str()?.to_owned();
+
+                d.array()?;
+                d.u16()?;
+                let ledger = d.str()?.to_owned();
+
+                Ok(Self::Mismatch(query, ledger))
+            }
+            _ => Err(decode::Error::message("unexpected length for era result")),
+        }
+    }
+}
+
+impl Encode<()> for RationalNumber {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.tag(Tag::Unassigned(30))?;
+        e.array(2)?.u64(self.numerator)?.u64(self.denominator)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for RationalNumber {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.tag()?;
+        d.array()?;
+
+        Ok(Self {
+            numerator: d.u64()?,
+            denominator: d.u64()?,
+        })
+    }
+}
+
+impl Encode<()> for StakeCredential {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::AddrKeyhash(hash) => e.array(2)?.u16(0)?.encode(hash)?,
+            Self::Scripthash(hash) => e.array(2)?.u16(1)?.encode(hash)?,
+        };
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for StakeCredential {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => Ok(Self::AddrKeyhash(d.decode()?)),
+            1 => Ok(Self::Scripthash(d.decode()?)),
+            _ => Err(decode::Error::message(
+                "unknown variant for stake credential",
+            )),
+        }
+    }
+}
+
+impl Encode<()> for TransactionInput {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(2)?.encode(self.transaction_id)?.u64(self.index)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for TransactionInput {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            transaction_id: d.decode()?,
+            index: d.u64()?,
+        })
+    }
+}
+
+fn decode_raw(d: &mut Decoder) -> Result<Vec<u8>, decode::Error> {
+    let start = d.position();
+    d.skip()?;
+    let end = d.position();
+
+    Ok(d.input()[start..end].to_vec())
+}
+
+fn encode_raw<W: encode::Write>(
+    e: &mut Encoder<W>,
+    raw: &[u8],
+) -> Result<(), encode::Error<W::Error>> {
+    e.writer_mut().write_all(raw).map_err(encode::Error::write)
+}
+
+impl Encode<()> for TransactionOutput {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        encode_raw(e, &self.0)
+    }
+}
+
+impl<'b> Decode<'b, ()> for TransactionOutput {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        Ok(Self(decode_raw(d)?))
+    }
+}
+
+impl Encode<()> for GenericResponse {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        encode_raw(e, &self.0)
+    }
+}
+
+impl<'b> Decode<'b, ()> for GenericResponse {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        Ok(Self(decode_raw(d)?))
+    }
+}
+
+impl Encode<()> for SystemStart {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(3)?
+            .u64(self.year)?
+            .u64(self.day_of_year)?
+            .u64(self.picoseconds_of_day)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for SystemStart {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            year: d.u64()?,
+            day_of_year: d.u64()?,
+            picoseconds_of_day: d.u64()?,
+        })
+    }
+}
+
+impl Encode<()> for Nonce {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Self::Neutral => e.array(1)?.u16(0)?,
+            Self::Hash(hash) => e.array(2)?.u16(1)?.encode(hash)?,
+        };
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for Nonce {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => Ok(Self::Neutral),
+            1 => Ok(Self::Hash(d.decode()?)),
+            _ => Err(decode::Error::message("unknown variant for nonce")),
+        }
+    }
+}
+
+impl Encode<()> for ExUnits {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(2)?.u64(self.mem)?.u64(self.steps)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for ExUnits {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            mem: d.u64()?,
+            steps: d.u64()?,
+        })
+    }
+}
+
+impl Encode<()> for ExUnitPrices {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(2)?
+            .encode(&self.mem_price)?
+            .encode(&self.step_price)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for ExUnitPrices {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            mem_price: d.decode()?,
+            step_price: d.decode()?,
+        })
+    }
+}
+
+impl Encode<()> for PoolVotingThresholds {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(5)?
+            .encode(&self.motion_no_confidence)?
+            .encode(&self.committee_normal)?
+            .encode(&self.committee_no_confidence)?
+            .encode(&self.hard_fork_initiation)?
+            .encode(&self.security_voting_threshold)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for PoolVotingThresholds {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            motion_no_confidence: d.decode()?,
+            committee_normal: d.decode()?,
+            committee_no_confidence: d.decode()?,
+            hard_fork_initiation: d.decode()?,
+            security_voting_threshold: d.decode()?,
+        })
+    }
+}
+
+impl Encode<()> for DRepVotingThresholds {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(10)?
+            .encode(&self.motion_no_confidence)?
+            .encode(&self.committee_normal)?
+            .encode(&self.committee_no_confidence)?
+            .encode(&self.update_constitution)?
+            .encode(&self.hard_fork_initiation)?
+            .encode(&self.pp_network_group)?
+            .encode(&self.pp_economic_group)?
+            .encode(&self.pp_technical_group)?
+            .encode(&self.pp_governance_group)?
+            .encode(&self.treasury_withdrawal)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for DRepVotingThresholds {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            motion_no_confidence: d.decode()?,
+            committee_normal: d.decode()?,
+            committee_no_confidence: d.decode()?,
+            update_constitution: d.decode()?,
+            hard_fork_initiation: d.decode()?,
+            pp_network_group: d.decode()?,
+            pp_economic_group: d.decode()?,
+            pp_technical_group: d.decode()?,
+            pp_governance_group: d.decode()?,
+            treasury_withdrawal: d.decode()?,
+        })
+    }
+}
+
+// Protocol params are encoded as an array whose layout depends on the era. Up
+// to Babbage, the node spreads the protocol version over two items, which
+// makes 18 items up to Mary, 25 for Alonzo and 23 for Babbage. Conway nests
+// the protocol version in an array of its own and appends its governance
+// params to the Babbage ones, which makes 31 items. Either form of the
+// protocol version is accepted in every era.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamsLayout {
+    Shelley,
+    Alonzo,
+    Babbage,
+    Conway,
+}
+
+impl ParamsLayout {
+    fn of(params: &ProtocolParams) -> Self {
+        match params {
+            x if x.min_utxo_value.is_some() => Self::Shelley,
+            x if x.coins_per_utxo_word.is_some() => Self::Alonzo,
+            x if x.pool_voting_thresholds.is_some() => Self::Conway,
+            _ => Self::Babbage,
+        }
+    }
+
+    // The length of the array, for either form of the protocol version
+    fn from_len(len: u64) -> Option<Self> {
+        match len {
+            17 | 18 => Some(Self::Shelley),
+            24 | 25 => Some(Self::Alonzo),
+            22 | 23 => Some(Self::Babbage),
+            31 | 32 => Some(Self::Conway),
+            _ => None,
+        }
+    }
+}
+
+fn decode_protocol_version(d: &mut Decoder) -> Result<(u64, u64), decode::Error> {
+    if d.datatype()? == Type::Array {
+        d.array()?;
+    }
+
+    Ok((d.u64()?, d.u64()?))
+}
+
+impl Encode<()> for ProtocolParams {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        let layout = ParamsLayout::of(self);
+
+        let len = match layout {
+            ParamsLayout::Shelley => 18,
+            ParamsLayout::Alonzo => 25,
+            ParamsLayout::Babbage => 23,
+            ParamsLayout::Conway => 31,
+        };
+
+        e.array(len)?
+            .u32(self.minfee_a)?
+            .u32(self.minfee_b)?
+            .u32(self.max_block_body_size)?
+            .u32(self.max_transaction_size)?
+            .u32(self.max_block_header_size)?
+            .u64(self.key_deposit)?
+            .u64(self.pool_deposit)?
+            .u64(self.maximum_epoch)?
+            .u32(self.desired_number_of_stake_pools)?
+            .encode(&self.pool_pledge_influence)?
+            .encode(&self.expansion_rate)?
+            .encode(&self.treasury_growth_rate)?;
+
+        if let ParamsLayout::Shelley | ParamsLayout::Alonzo = layout {
+            e.encode(&self.decentralization_constant)?
+                .encode(&self.extra_entropy)?;
+        }
+
+        if layout == ParamsLayout::Conway {
+            e.array(2)?;
+        }
+
+        e.u64(self.protocol_version.0)?
+            .u64(self.protocol_version.1)?;
+
+        if layout == ParamsLayout::Shelley {
+            e.encode(self.min_utxo_value)?.u64(self.min_pool_cost)?;
+            return Ok(());
+        }
+
+        e.u64(self.min_pool_cost)?;
+
+        match layout {
+            ParamsLayout::Alonzo => e.encode(self.coins_per_utxo_word)?,
+            _ => e.encode(self.coins_per_utxo_byte)?,
+        };
+
+        e.encode(&self.cost_models)?
+            .encode(&self.execution_costs)?
+            .encode(&self.max_tx_ex_units)?
+            .encode(&self.max_block_ex_units)?
+            .encode(self.max_value_size)?
+            .encode(self.collateral_percentage)?
+            .encode(self.max_collateral_inputs)?;
+
+        if layout == ParamsLayout::Conway {
+            e.encode(&self.pool_voting_thresholds)?
+                .encode(&self.drep_voting_thresholds)?
+                .encode(self.min_committee_size)?
+                .encode(self.committee_term_limit)?
+                .encode(self.governance_action_validity_period)?
+                .encode(self.governance_action_deposit)?
+                .encode(self.drep_deposit)?
+                .encode(self.drep_inactivity_period)?
+                .encode(&self.minfee_refscript_cost_per_byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for ProtocolParams {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        let layout = d
+            .array()?
+            .and_then(ParamsLayout::from_len)
+            .ok_or_else(|| decode::Error::message("unexpected length for protocol params"))?;
+
+        let minfee_a = d.u32()?;
+        let minfee_b = d.u32()?;
+        let max_block_body_size = d.u32()?;
+        let max_transaction_size = d.u32()?;
+        let max_block_header_size = d.u32()?;
+        let key_deposit = d.u64()?;
+        let pool_deposit = d.u64()?;
+        let maximum_epoch = d.u64()?;
+        let desired_number_of_stake_pools = d.u32()?;
+        let pool_pledge_influence = d.decode()?;
+        let expansion_rate = d.decode()?;
+        let treasury_growth_rate = d.decode()?;
+
+        let (decentralization_constant, extra_entropy) = match layout {
+            ParamsLayout::Shelley | ParamsLayout::Alonzo => (Some(d.decode()?), Some(d.decode()?)),
+            _ => (None, None),
+        };
+
+        let protocol_version = decode_protocol_version(d)?;
+
+        let mut params = ProtocolParams {
+            minfee_a,
+            minfee_b,
+            max_block_body_size,
+            max_transaction_size,
+            max_block_header_size,
+            key_deposit,
+            pool_deposit,
+            maximum_epoch,
+            desired_number_of_stake_pools,
+            pool_pledge_influence,
+            expansion_rate,
+            treasury_growth_rate,
+            decentralization_constant,
+            extra_entropy,
+            protocol_version,
+            min_utxo_value: None,
+            min_pool_cost: 0,
+            coins_per_utxo_word: None,
+            coins_per_utxo_byte: None,
+            cost_models: None,
+            execution_costs: None,
+            max_tx_ex_units: None,
+            max_block_ex_units: None,
+            max_value_size: None,
+            collateral_percentage: None,
+            max_collateral_inputs: None,
+            pool_voting_thresholds: None,
+            drep_voting_thresholds: None,
+            min_committee_size: None,
+            committee_term_limit: None,
+            governance_action_validity_period: None,
+            governance_action_deposit: None,
+            drep_deposit: None,
+            drep_inactivity_period: None,
+            minfee_refscript_cost_per_byte: None,
+        };
+
+        if layout == ParamsLayout::Shelley {
+            params.min_utxo_value = Some(d.u64()?);
+            params.min_pool_cost = d.u64()?;
+            return Ok(params);
+        }
+
+        params.min_pool_cost = d.u64()?;
+
+        match layout {
+            ParamsLayout::Alonzo => params.coins_per_utxo_word = Some(d.u64()?),
+            _ => params.coins_per_utxo_byte = Some(d.u64()?),
+        };
+
+        params.cost_models = Some(d.decode()?);
+        params.execution_costs = Some(d.decode()?);
+        params.max_tx_ex_units = Some(d.decode()?);
+        params.max_block_ex_units = Some(d.decode()?);
+        params.max_value_size = Some(d.u32()?);
+        params.collateral_percentage = Some(d.u32()?);
+        params.max_collateral_inputs = Some(d.u32()?);
+
+        if layout == ParamsLayout::Conway {
+            params.pool_voting_thresholds = Some(d.decode()?);
+            params.drep_voting_thresholds = Some(d.decode()?);
+            params.min_committee_size = Some(d.u64()?);
+            params.committee_term_limit = Some(d.u64()?);
+            params.governance_action_validity_period = Some(d.u64()?);
+            params.governance_action_deposit = Some(d.u64()?);
+            params.drep_deposit = Some(d.u64()?);
+            params.drep_inactivity_period = Some(d.u64()?);
+            params.minfee_refscript_cost_per_byte = Some(d.decode()?);
+        }
+
+        Ok(params)
+    }
+}
+
+impl Encode<()> for IndividualPoolStake {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(2)?.encode(&self.stake)?.encode(self.vrf_keyhash)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for IndividualPoolStake {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            stake: d.decode()?,
+            vrf_keyhash: d.decode()?,
+        })
+    }
+}
+
+impl Encode<()> for FilteredDelegationsAndRewards {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(2)?
+            .encode(&self.delegations)?
+            .encode(&self.rewards)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for FilteredDelegationsAndRewards {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            delegations: d.decode()?,
+            rewards: d.decode()?,
+        })
+    }
+}
+
+impl Encode<()> for GenesisDelegate {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(2)?.encode(self.delegate)?.encode(self.vrf)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for GenesisDelegate {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        Ok(Self {
+            delegate: d.decode()?,
+            vrf: d.decode()?,
+        })
+    }
+}
+
+impl Encode<()> for GenesisConfig {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        e.array(15)?
+            .encode(&self.system_start)?
+            .u32(self.network_magic)?
+            .u8(self.network_id)?
+            .encode(&self.active_slots_coefficient)?
+            .u64(self.security_param)?
+            .u64(self.epoch_length)?
+            .u64(self.slots_per_kes_period)?
+            .u64(self.max_kes_evolutions)?
+            .u64(self.slot_length)?
+            .u64(self.update_quorum)?
+            .u64(self.max_lovelace_supply)?
+            .encode(&self.protocol_params)?
+            .encode(&self.gen_delegs)?;
+
+        // empty initial funds and staking
+        e.map(0)?;
+        e.array(2)?.map(0)?.map(0)?;
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for GenesisConfig {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        let config = Self {
+            system_start: d.decode()?,
+            network_magic: d.u32()?,
+            network_id: d.u8()?,
+            active_slots_coefficient: d.decode()?,
+            security_param: d.u64()?,
+            epoch_length: d.u64()?,
+            slots_per_kes_period: d.u64()?,
+            max_kes_evolutions: d.u64()?,
+            slot_length: d.u64()?,
+            update_quorum: d.u64()?,
+            max_lovelace_supply: d.u64()?,
+            protocol_params: d.decode()?,
+            gen_delegs: d.decode()?,
+        };
+
+        // initial funds and staking
+        d.skip()?;
+        d.skip()?;
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::minicbor;
+    use pallas_codec::utils::{Bytes, KeyValuePairs};
+    use pallas_crypto::hash::Hash;
+
+    use crate::miniprotocols::localstate::Message;
+
+    use super::*;
+
+    fn roundtrip<T>(value: T)
+    where
+        T: for<'b> Decode<'b, ()> + Encode<()> + PartialEq + std::fmt::Debug,
+    {
+        let bytes = minicbor::to_vec(&value).unwrap();
+        let decoded: T = minicbor::decode(&bytes).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    fn rational(numerator: u64, denominator: u64) -> RationalNumber {
+        RationalNumber {
+            numerator,
+            denominator,
+        }
+    }
+
+    fn babbage_params() -> ProtocolParams {
+        ProtocolParams {
+            minfee_a: 44,
+            minfee_b: 155381,
+            max_block_body_size: 90112,
+            max_transaction_size: 16384,
+            max_block_header_size: 1100,
+            key_deposit: 2000000,
+            pool_deposit: 500000000,
+            maximum_epoch: 18,
+            desired_number_of_stake_pools: 500,
+            pool_pledge_influence: rational(3, 10),
+            expansion_rate: rational(3, 1000),
+            treasury_growth_rate: rational(1, 5),
+            decentralization_constant: None,
+            extra_entropy: None,
+            protocol_version: (8, 0),
+            min_utxo_value: None,
+            min_pool_cost: 340000000,
+            coins_per_utxo_word: None,
+            coins_per_utxo_byte: Some(4310),
+            cost_models: Some(KeyValuePairs::from(vec![(0, vec![205665, -812, 1])])),
+            execution_costs: Some(ExUnitPrices {
+                mem_price: rational(577, 10000),
+                step_price: rational(721, 10000000),
+            }),
+            max_tx_ex_units: Some(ExUnits {
+                mem: 14000000,
+                steps: 10000000000,
+            }),
+            max_block_ex_units: Some(ExUnits {
+                mem: 62000000,
+                steps: 20000000000,
+            }),
+            max_value_size: Some(5000),
+            collateral_percentage: Some(150),
+            max_collateral_inputs: Some(3),
+            pool_voting_thresholds: None,
+            drep_voting_thresholds: None,
+            min_committee_size: None,
+            committee_term_limit: None,
+            governance_action_validity_period: None,
+            governance_action_deposit: None,
+            drep_deposit: None,
+            drep_inactivity_period: None,
+            minfee_refscript_cost_per_byte: None,
+        }
+    }
+
+    fn conway_params() -> ProtocolParams {
+        ProtocolParams {
+            protocol_version: (9, 0),
+            pool_voting_thresholds: Some(PoolVotingThresholds {
+                motion_no_confidence: rational(51, 100),
+                committee_normal: rational(51, 100),
+                committee_no_confidence: rational(51, 100),
+                hard_fork_initiation: rational(51, 100),
+                security_voting_threshold: rational(51, 100),
+            }),
+            drep_voting_thresholds: Some(DRepVotingThresholds {
+                motion_no_confidence: rational(67, 100),
+                committee_normal: rational(67, 100),
+                committee_no_confidence: rational(3, 5),
+                update_constitution: rational(3, 4),
+                hard_fork_initiation: rational(3, 5),
+                pp_network_group: rational(67, 100),
+                pp_economic_group: rational(67, 100),
+                pp_technical_group: rational(67, 100),
+                pp_governance_group: rational(3, 4),
+                treasury_withdrawal: rational(67, 100),
+            }),
+            min_committee_size: Some(7),
+            committee_term_limit: Some(146),
+            governance_action_validity_period: Some(6),
+            governance_action_deposit: Some(100000000000),
+            drep_deposit: Some(500000000),
+            drep_inactivity_period: Some(20),
+            minfee_refscript_cost_per_byte: Some(rational(15, 1)),
+            ..babbage_params()
+        }
+    }
+
+    // The result of a query, as found in the given response fixture
+    fn response_fixture(name: &str) -> Vec<u8> {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("test_data")
+            .join("localstate")
+            .join(format!("{name}.response"));
+
+        let hex = std::fs::read_to_string(path).unwrap();
+        let msg: Message<QueryV10> = minicbor::decode(&hex::decode(hex.trim()).unwrap()).unwrap();
+
+        match msg {
+            Message::Result(GenericResponse(x)) => x,
+            x => panic!("unexpected message {x:?}"),
+        }
+    }
+
+    fn shelley_params() -> ProtocolParams {
+        ProtocolParams {
+            decentralization_constant: Some(rational(1, 1)),
+            extra_entropy: Some(Nonce::Neutral),
+            protocol_version: (2, 0),
+            min_utxo_value: Some(1000000),
+            min_pool_cost: 340000000,
+            coins_per_utxo_byte: None,
+            cost_models: None,
+            execution_costs: None,
+            max_tx_ex_units: None,
+            max_block_ex_units: None,
+            max_value_size: None,
+            collateral_percentage: None,
+            max_collateral_inputs: None,
+            ..babbage_params()
+        }
+    }
+
+    #[test]
+    fn block_queries_are_wrapped_in_era_envelope() {
+        let query =
+            RequestV10::BlockQuery(BlockQuery::IfCurrent(5, LedgerQuery::GetCurrentPParams));
+        let msg = Message::<QueryV10>::Query(query);
+        let bytes = minicbor::to_vec(msg).unwrap();
+
+        assert_eq!(hex::encode(bytes), "82038200820082058103");
+
+        let query = RequestV10::BlockQuery(BlockQuery::HardFork(HardForkQuery::GetCurrentEra));
+        let msg = Message::<QueryV10>::Query(query);
+        let bytes = minicbor::to_vec(msg).unwrap();
+
+        assert_eq!(hex::encode(bytes), "8203820082028101");
+
+        let msg = Message::<QueryV10>::Query(RequestV10::GetSystemStart);
+        let bytes = minicbor::to_vec(msg).unwrap();
+
+        assert_eq!(hex::encode(bytes), "82038101");
+    }
+
+    #[test]
+    fn requests_roundtrip() {
+        let input = TransactionInput {
+            transaction_id: Hash::new([7; 32]),
+            index: 1,
+        };
+
+        let credential = StakeCredential::Scripthash(Hash::new([3; 28]));
+
+        let requests = vec![
+            LedgerQuery::GetUTxOByAddress(vec![Bytes::from(vec![0x61; 29])]),
+            LedgerQuery::GetUTxOByTxIn(vec![input]),
+            LedgerQuery::GetFilteredDelegationsAndRewardAccounts(vec![credential]),
+        ];
+
+        for request in requests {
+            let request = RequestV10::BlockQuery(BlockQuery::IfCurrent(5, request));
+            let bytes = minicbor::to_vec(&request).unwrap();
+            let decoded: RequestV10 = minicbor::decode(&bytes).unwrap();
+
+            assert_eq!(minicbor::to_vec(decoded).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn protocol_params_roundtrip_for_each_layout() {
+        let alonzo = ProtocolParams {
+            decentralization_constant: Some(rational(0, 1)),
+            extra_entropy: Some(Nonce::Hash(Hash::new([1; 32]))),
+            protocol_version: (6, 0),
+            coins_per_utxo_word: Some(34482),
+            coins_per_utxo_byte: None,
+            ..babbage_params()
+        };
+
+        for params in [shelley_params(), alonzo, babbage_params(), conway_params()] {
+            roundtrip(params);
+        }
+    }
+
+    #[test]
+    fn protocol_version_may_be_nested() {
+        let bytes = minicbor::to_vec(babbage_params()).unwrap();
+
+        // [.., 8, 0, ..] as [.., [8, 0], ..] in an array of 22 items
+        let mut nested = vec![0x96];
+        let start = bytes.windows(2).position(|x| x == [0x08, 0x00]).unwrap();
+        nested.extend(&bytes[1..start]);
+        nested.extend([0x82, 0x08, 0x00]);
+        nested.extend(&bytes[start + 2..]);
+
+        let params: ProtocolParams = minicbor::decode(&nested).unwrap();
+        assert_eq!(params, babbage_params());
+    }
+
+    #[test]
+    fn babbage_pparams_response_is_decoded() {
+        let bytes = response_fixture("babbage-pparams");
+        let result: EraResult<ProtocolParams> = minicbor::decode(&bytes).unwrap();
+
+        let expected = ProtocolParams {
+            min_pool_cost: 170000000,
+            cost_models: Some(KeyValuePairs::from(vec![
+                (0, vec![205665, 812, 1, 1, 1000, 571]),
+                (1, vec![205665, 812, 1]),
+            ])),
+            ..babbage_params()
+        };
+
+        assert_eq!(result, EraResult::Ok(expected));
+        assert_eq!(minicbor::to_vec(result).unwrap(), bytes);
+    }
+
+    #[test]
+    fn conway_pparams_response_is_decoded() {
+        let bytes = response_fixture("conway-pparams");
+        let result: EraResult<ProtocolParams> = minicbor::decode(&bytes).unwrap();
+
+        let expected = ProtocolParams {
+            min_pool_cost: 170000000,
+            cost_models: Some(KeyValuePairs::from(vec![
+                (0, vec![205665, 812, 1, 1, 1000, 571]),
+                (1, vec![205665, 812, 1]),
+                (2, vec![100788, 420, 1, 1]),
+            ])),
+            ..conway_params()
+        };
+
+        assert_eq!(result, EraResult::Ok(expected));
+
+        // the protocol version is nested in an array of 31 items
+        assert_eq!(bytes[1..3], [0x98, 31]);
+        assert_eq!(minicbor::to_vec(result).unwrap(), bytes);
+    }
+
+    #[test]
+    fn era_mismatch_is_decoded() {
+        // [[5, "Babbage"], [6, "Conway"]]
+        let bytes = hex::decode("8282056742616262616765820666436f6e776179").unwrap();
+
+        let result: EraResult<Epoch> = minicbor::decode(&bytes).unwrap();
+        assert_eq!(
+            result,
+            EraResult::Mismatch("Babbage".into(), "Conway".into())
+        );
+
+        let bytes = hex::decode("8118d0").unwrap();
+        let result: EraResult<Epoch> = minicbor::decode(&bytes).unwrap();
+        assert_eq!(result, EraResult::Ok(208));
+    }
+
+    #[test]
+    fn utxo_outputs_are_kept_raw() {
+        // {[h'07..', 1]: [h'61..', 1000000]}
+        let input = TransactionInput {
+            transaction_id: Hash::new([7; 32]),
+            index: 1,
+        };
+
+        let mut output = hex::decode("82581d").unwrap();
+        output.extend([0x61; 29]);
+        output.extend(hex::decode("1a000f4240").unwrap());
+
+        let mut bytes = hex::decode("a1").unwrap();
+        bytes.extend(minicbor::to_vec(&input).unwrap());
+        bytes.extend(&output);
+
+        let utxo: UTxO = minicbor::decode(&bytes).unwrap();
+        let utxo = utxo.to_vec();
+
+        assert_eq!(utxo, vec![(input, TransactionOutput(output))]);
+    }
+
+    #[test]
+    fn genesis_config_roundtrip() {
+        let config = GenesisConfig {
+            system_start: SystemStart {
+                year: 2022,
+                day_of_year: 304,
+                picoseconds_of_day: 0,
+            },
+            network_magic: 2,
+            network_id: 0,
+            active_slots_coefficient: rational(1, 20),
+            security_param: 432,
+            epoch_length: 86400,
+            slots_per_kes_period: 129600,
+            max_kes_evolutions: 62,
+            slot_length: 1000000,
+            update_quorum: 5,
+            max_lovelace_supply: 45000000000000000,
+            protocol_params: shelley_params(),
+            gen_delegs: KeyValuePairs::from(vec![(
+                Hash::new([1; 28]),
+                GenesisDelegate {
+                    delegate: Hash::new([2; 28]),
+                    vrf: Hash::new([3; 32]),
+                },
+            )]),
+        };
+
+        roundtrip(config);
+    }
+
+    #[test]
+    fn genesis_config_response_is_decoded() {
+        let bytes = response_fixture("genesis-config");
+        let result: EraResult<GenesisConfig> = minicbor::decode(&bytes).unwrap();
+
+        let config = match &result {
+            EraResult::Ok(x) => x,
+            x => panic!("unexpected result {x:?}"),
+        };
+
+        assert_eq!(config.network_magic, 2);
+        assert_eq!(config.active_slots_coefficient, rational(1, 20));
+        assert_eq!(config.max_lovelace_supply, 45000000000000000);
+        assert_eq!(config.protocol_params.max_block_body_size, 65536);
+        assert_eq!(config.protocol_params.min_utxo_value, Some(1000000));
+        assert_eq!(config.protocol_params.extra_entropy, Some(Nonce::Neutral));
+        assert_eq!(config.gen_delegs.len(), 2);
+        assert_eq!(minicbor::to_vec(result).unwrap(), bytes);
+    }
+
+    #[test]
+    fn utxo_response_is_decoded() {
+        let bytes = response_fixture("utxo");
+        let result: EraResult<UTxO> = minicbor::decode(&bytes).unwrap();
+
+        let utxo = match &result {
+            EraResult::Ok(x) => x.clone().to_vec(),
+            x => panic!("unexpected result {x:?}"),
+        };
+
+        assert_eq!(utxo.len(), 2);
+        assert_eq!(utxo[0].0.transaction_id, Hash::new([7; 32]));
+        assert_eq!(utxo[0].0.index, 1);
+        assert_eq!(utxo[1].0.index, 0);
+
+        // a legacy output and a post-Alonzo one, with an inline datum
+        assert_eq!(utxo[0].1 .0[0], 0x82);
+        assert_eq!(utxo[1].1 .0[0], 0xa3);
+        assert_eq!(minicbor::to_vec(result).unwrap(), bytes);
+    }
+}
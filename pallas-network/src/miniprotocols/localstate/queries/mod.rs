@@ -0,0 +1,26 @@
+//! Queries of the LocalStateQuery mini-protocol
+//!
+//! Besides the raw requests, this module provides functions for the queries
+//! of the Shelley-based eras which decode the response of the node into typed
+//! values.
+
+mod client;
+mod codec;
+mod protocol;
+
+pub use client::*;
+pub use protocol::*;
+
+use super::Query;
+
+/// The CBOR of a query response, as sent by the node
+#[derive(Debug, Clone)]
+pub struct GenericResponse(Vec<u8>);
+
+#[derive(Debug, Clone)]
+pub struct QueryV10 {}
+
+impl Query for QueryV10 {
+    type Request = RequestV10;
+    type Response = GenericResponse;
+}
@@ -0,0 +1,220 @@
+use pallas_codec::utils::{Bytes, KeyValuePairs};
+use pallas_crypto::hash::Hash;
+
+/// Index of an era within the hard-fork combinator, starting from Byron as 0
+pub type Era = u16;
+
+pub type Epoch = u64;
+
+pub type Coin = u64;
+
+pub type PoolKeyhash = Hash<28>;
+
+#[derive(Debug, Clone)]
+pub enum RequestV10 {
+    BlockQuery(BlockQuery),
+    GetSystemStart,
+    GetChainBlockNo,
+    GetChainPoint,
+}
+
+/// Queries answered by the ledger of the hard-fork combinator
+#[derive(Debug, Clone)]
+pub enum BlockQuery {
+    /// Query the ledger of a particular era, only answered by the node while
+    /// its ledger is in that era
+    IfCurrent(Era, LedgerQuery),
+    HardFork(HardForkQuery),
+}
+
+/// Queries for the ledger of the Shelley-based eras
+#[derive(Debug, Clone)]
+pub enum LedgerQuery {
+    GetEpochNo,
+    GetCurrentPParams,
+    GetStakeDistribution,
+    GetUTxOByAddress(Vec<Bytes>),
+    GetFilteredDelegationsAndRewardAccounts(Vec<StakeCredential>),
+    GetGenesisConfig,
+    GetUTxOByTxIn(Vec<TransactionInput>),
+    GetStakePools,
+}
+
+/// Queries about the eras known to the hard-fork combinator
+#[derive(Debug, Clone)]
+pub enum HardForkQuery {
+    GetInterpreter,
+    GetCurrentEra,
+}
+
+/// Result of a query answered only while the ledger is in a particular era
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EraResult<T> {
+    Ok(T),
+    /// Names of the eras of the query and of the ledger, respectively
+    Mismatch(String, String),
+}
+
+/// A rational number, encoded with CBOR tag 30
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RationalNumber {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakeCredential {
+    AddrKeyhash(Hash<28>),
+    Scripthash(Hash<28>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionInput {
+    pub transaction_id: Hash<32>,
+    pub index: u64,
+}
+
+/// The CBOR of a transaction output, as sent by the node
+///
+/// Its structure depends on the era, decoding it is left to a ledger
+/// implementation such as `pallas-primitives`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionOutput(pub Vec<u8>);
+
+pub type UTxO = KeyValuePairs<TransactionInput, TransactionOutput>;
+
+/// Start of the chain, as a UTC date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemStart {
+    pub year: u64,
+    pub day_of_year: u64,
+    pub picoseconds_of_day: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nonce {
+    Neutral,
+    Hash(Hash<32>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExUnits {
+    pub mem: u64,
+    pub steps: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExUnitPrices {
+    pub mem_price: RationalNumber,
+    pub step_price: RationalNumber,
+}
+
+/// Cost model parameters, keyed by Plutus language
+pub type CostModels = KeyValuePairs<u8, Vec<i64>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolVotingThresholds {
+    pub motion_no_confidence: RationalNumber,
+    pub committee_normal: RationalNumber,
+    pub committee_no_confidence: RationalNumber,
+    pub hard_fork_initiation: RationalNumber,
+    pub security_voting_threshold: RationalNumber,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DRepVotingThresholds {
+    pub motion_no_confidence: RationalNumber,
+    pub committee_normal: RationalNumber,
+    pub committee_no_confidence: RationalNumber,
+    pub update_constitution: RationalNumber,
+    pub hard_fork_initiation: RationalNumber,
+    pub pp_network_group: RationalNumber,
+    pub pp_economic_group: RationalNumber,
+    pub pp_technical_group: RationalNumber,
+    pub pp_governance_group: RationalNumber,
+    pub treasury_withdrawal: RationalNumber,
+}
+
+/// Protocol parameters of the Shelley-based eras
+///
+/// The fields introduced or removed along the eras are optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolParams {
+    pub minfee_a: u32,
+    pub minfee_b: u32,
+    pub max_block_body_size: u32,
+    pub max_transaction_size: u32,
+    pub max_block_header_size: u32,
+    pub key_deposit: Coin,
+    pub pool_deposit: Coin,
+    pub maximum_epoch: Epoch,
+    pub desired_number_of_stake_pools: u32,
+    pub pool_pledge_influence: RationalNumber,
+    pub expansion_rate: RationalNumber,
+    pub treasury_growth_rate: RationalNumber,
+    pub decentralization_constant: Option<RationalNumber>,
+    pub extra_entropy: Option<Nonce>,
+    pub protocol_version: (u64, u64),
+    pub min_utxo_value: Option<Coin>,
+    pub min_pool_cost: Coin,
+    pub coins_per_utxo_word: Option<Coin>,
+    pub coins_per_utxo_byte: Option<Coin>,
+    pub cost_models: Option<CostModels>,
+    pub execution_costs: Option<ExUnitPrices>,
+    pub max_tx_ex_units: Option<ExUnits>,
+    pub max_block_ex_units: Option<ExUnits>,
+    pub max_value_size: Option<u32>,
+    pub collateral_percentage: Option<u32>,
+    pub max_collateral_inputs: Option<u32>,
+    pub pool_voting_thresholds: Option<PoolVotingThresholds>,
+    pub drep_voting_thresholds: Option<DRepVotingThresholds>,
+    pub min_committee_size: Option<u64>,
+    pub committee_term_limit: Option<Epoch>,
+    pub governance_action_validity_period: Option<Epoch>,
+    pub governance_action_deposit: Option<Coin>,
+    pub drep_deposit: Option<Coin>,
+    pub drep_inactivity_period: Option<Epoch>,
+    pub minfee_refscript_cost_per_byte: Option<RationalNumber>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndividualPoolStake {
+    pub stake: RationalNumber,
+    pub vrf_keyhash: Hash<32>,
+}
+
+pub type StakeDistribution = KeyValuePairs<PoolKeyhash, IndividualPoolStake>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilteredDelegationsAndRewards {
+    pub delegations: KeyValuePairs<StakeCredential, PoolKeyhash>,
+    pub rewards: KeyValuePairs<StakeCredential, Coin>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisDelegate {
+    pub delegate: Hash<28>,
+    pub vrf: Hash<32>,
+}
+
+/// The Shelley genesis config as kept by the node
+///
+/// The node drops the initial funds and staking of the genesis file once
+/// applied, so these are not part of the response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisConfig {
+    pub system_start: SystemStart,
+    pub network_magic: u32,
+    pub network_id: u8,
+    pub active_slots_coefficient: RationalNumber,
+    pub security_param: u64,
+    pub epoch_length: u64,
+    pub slots_per_kes_period: u64,
+    pub max_kes_evolutions: u64,
+    /// Slot length in microseconds
+    pub slot_length: u64,
+    pub update_quorum: u64,
+    pub max_lovelace_supply: Coin,
+    pub protocol_params: ProtocolParams,
+    pub gen_delegs: KeyValuePairs<Hash<28>, GenesisDelegate>,
+}
@@ -5,8 +5,10 @@ mod common;
 pub mod blockfetch;
 pub mod chainsync;
 pub mod handshake;
+pub mod keepalive;
 pub mod localstate;
 pub mod localtxsubmission;
+pub mod peersharing;
 pub mod txmonitor;
 pub mod txsubmission;
 
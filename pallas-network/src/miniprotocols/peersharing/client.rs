@@ -0,0 +1,163 @@
+use thiserror::Error;
+use tracing::debug;
+
+use crate::multiplexer;
+
+use super::{Amount, Message, PeerAddress, State};
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("attempted to receive message while agency is ours")]
+    AgencyIsOurs,
+
+    #[error("attempted to send message while agency is theirs")]
+    AgencyIsTheirs,
+
+    #[error("inbound message is not valid for current state")]
+    InvalidInbound,
+
+    #[error("outbound message is not valid for current state")]
+    InvalidOutbound,
+
+    #[error("server shared {1} peers while only {0} were requested")]
+    TooManyPeers(Amount, usize),
+
+    #[error("error while sending or receiving data through the multiplexer")]
+    Plexer(multiplexer::Error),
+}
+
+/// Represents the client for the PeerSharing mini-protocol.
+///
+/// The client asks the server for the addresses of other peers it knows
+/// about, which is how nodes discover relays besides their configured ones.
+/// The server only takes part if peer sharing was enabled during the
+/// handshake.
+pub struct Client(State, multiplexer::ChannelBuffer);
+
+impl Client {
+    /// Create a new PeerSharing client from a multiplexer agent channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - A multiplexer agent channel used for communication with
+    ///   the remote node.
+    pub fn new(channel: multiplexer::AgentChannel) -> Self {
+        Self(State::Idle, multiplexer::ChannelBuffer::new(channel))
+    }
+
+    /// Get the current state of the client.
+    ///
+    /// Returns the current state of the client.
+    pub fn state(&self) -> &State {
+        &self.0
+    }
+
+    /// Check if the client is done.
+    ///
+    /// Returns true if the client is in the `Done` state, false otherwise.
+    pub fn is_done(&self) -> bool {
+        self.0 == State::Done
+    }
+
+    fn has_agency(&self) -> bool {
+        match self.state() {
+            State::Idle => true,
+            State::Busy(_) => false,
+            State::Done => false,
+        }
+    }
+
+    fn assert_agency_is_ours(&self) -> Result<(), ClientError> {
+        if !self.has_agency() {
+            Err(ClientError::AgencyIsTheirs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_agency_is_theirs(&self) -> Result<(), ClientError> {
+        if self.has_agency() {
+            Err(ClientError::AgencyIsOurs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_outbound_state(&self, msg: &Message) -> Result<(), ClientError> {
+        match (&self.0, msg) {
+            (State::Idle, Message::ShareRequest(..)) => Ok(()),
+            (State::Idle, Message::Done) => Ok(()),
+            _ => Err(ClientError::InvalidOutbound),
+        }
+    }
+
+    fn assert_inbound_state(&self, msg: &Message) -> Result<(), ClientError> {
+        match (&self.0, msg) {
+            (State::Busy(..), Message::SharePeers(..)) => Ok(()),
+            _ => Err(ClientError::InvalidInbound),
+        }
+    }
+
+    pub async fn send_message(&mut self, msg: &Message) -> Result<(), ClientError> {
+        self.assert_agency_is_ours()?;
+        self.assert_outbound_state(msg)?;
+        self.1
+            .send_msg_chunks(msg)
+            .await
+            .map_err(ClientError::Plexer)?;
+
+        Ok(())
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Message, ClientError> {
+        self.assert_agency_is_theirs()?;
+        let msg = self.1.recv_full_msg().await.map_err(ClientError::Plexer)?;
+        self.assert_inbound_state(&msg)?;
+
+        Ok(msg)
+    }
+
+    /// Ask the server to share up to `amount` peer addresses.
+    pub async fn send_share_request(&mut self, amount: Amount) -> Result<(), ClientError> {
+        let msg = Message::ShareRequest(amount);
+        self.send_message(&msg).await?;
+        self.0 = State::Busy(amount);
+
+        Ok(())
+    }
+
+    /// Receive the peer addresses shared in response to the last request.
+    ///
+    /// Returns an `Error` if the server shares more peers than requested.
+    pub async fn recv_peer_addresses(&mut self) -> Result<Vec<PeerAddress>, ClientError> {
+        match (self.recv_message().await?, &self.0) {
+            (Message::SharePeers(peers), State::Busy(amount)) => {
+                if peers.len() > *amount as usize {
+                    return Err(ClientError::TooManyPeers(*amount, peers.len()));
+                }
+
+                debug!(count = peers.len(), "received shared peers");
+                self.0 = State::Idle;
+
+                Ok(peers)
+            }
+            _ => Err(ClientError::InvalidInbound),
+        }
+    }
+
+    /// Request up to `amount` peer addresses and wait for the response.
+    pub async fn request_peers(&mut self, amount: Amount) -> Result<Vec<PeerAddress>, ClientError> {
+        self.send_share_request(amount).await?;
+        self.recv_peer_addresses().await
+    }
+
+    /// Send a `Done` message to the remote node and set the client's state to
+    /// `Done`.
+    pub async fn send_done(&mut self) -> Result<(), ClientError> {
+        let msg = Message::Done;
+        self.send_message(&msg).await?;
+        self.0 = State::Done;
+
+        Ok(())
+    }
+}
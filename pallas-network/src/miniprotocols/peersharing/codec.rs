@@ -0,0 +1,141 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use pallas_codec::minicbor::{decode, encode, Decode, Decoder, Encode, Encoder};
+
+use super::{Message, PeerAddress};
+
+// The Haskell node encodes the IPv4 address as the word32 holding its octets
+// in network order, as laid out in memory by a little-endian host, while each
+// of the four IPv6 word32 holds its octets in big-endian order.
+
+impl Encode<()> for PeerAddress {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            PeerAddress::V4(address, port) => {
+                e.array(3)?.u16(0)?;
+                e.u32(u32::from_le_bytes(address.octets()))?;
+                e.u16(*port)?;
+            }
+            PeerAddress::V6(address, port) => {
+                e.array(6)?.u16(1)?;
+
+                for word in address.octets().chunks_exact(4) {
+                    e.u32(u32::from_be_bytes(word.try_into().unwrap()))?;
+                }
+
+                e.u16(*port)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for PeerAddress {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => {
+                let address = Ipv4Addr::from(d.u32()?.to_le_bytes());
+                let port = d.u16()?;
+
+                Ok(PeerAddress::V4(address, port))
+            }
+            1 => {
+                let mut octets = [0u8; 16];
+
+                for word in octets.chunks_exact_mut(4) {
+                    word.copy_from_slice(&d.u32()?.to_be_bytes());
+                }
+
+                let port = d.u16()?;
+
+                Ok(PeerAddress::V6(Ipv6Addr::from(octets), port))
+            }
+            _ => Err(decode::Error::message("unknown variant for peer address")),
+        }
+    }
+}
+
+impl Encode<()> for Message {
+    fn encode<W: encode::Write>(
+        &self,
+        e: &mut Encoder<W>,
+        _ctx: &mut (),
+    ) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Message::ShareRequest(amount) => {
+                e.array(2)?.u16(0)?;
+                e.u8(*amount)?;
+                Ok(())
+            }
+            Message::SharePeers(peers) => {
+                e.array(2)?.u16(1)?;
+                e.array(peers.len() as u64)?;
+
+                for peer in peers {
+                    e.encode(peer)?;
+                }
+
+                Ok(())
+            }
+            Message::Done => {
+                e.array(1)?.u16(2)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'b> Decode<'b, ()> for Message {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+        let label = d.u16()?;
+
+        match label {
+            0 => Ok(Message::ShareRequest(d.u8()?)),
+            1 => Ok(Message::SharePeers(d.decode()?)),
+            2 => Ok(Message::Done),
+            _ => Err(decode::Error::message(
+                "unknown variant for peersharing message",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::minicbor;
+
+    use super::*;
+
+    #[test]
+    fn peer_addresses_roundtrip() {
+        let peers = vec![
+            PeerAddress::V4(Ipv4Addr::new(127, 0, 0, 1), 3001),
+            PeerAddress::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1), 6000),
+        ];
+
+        let bytes = minicbor::to_vec(Message::SharePeers(peers.clone())).unwrap();
+
+        match minicbor::decode(&bytes).unwrap() {
+            Message::SharePeers(decoded) => assert_eq!(decoded, peers),
+            x => panic!("unexpected message {x:?}"),
+        }
+    }
+
+    #[test]
+    fn ipv4_address_matches_node_encoding() {
+        // [0, 0x0100007f, 3001] as sent by a node for 127.0.0.1:3001
+        let bytes = hex::decode("83001a0100007f190bb9").unwrap();
+        let peer: PeerAddress = minicbor::decode(&bytes).unwrap();
+
+        assert_eq!(peer, PeerAddress::V4(Ipv4Addr::LOCALHOST, 3001));
+    }
+}
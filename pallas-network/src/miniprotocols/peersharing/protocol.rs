@@ -0,0 +1,25 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Max number of peer addresses requested from the server
+pub type Amount = u8;
+
+/// Address of a peer shared by the server
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PeerAddress {
+    V4(Ipv4Addr, u16),
+    V6(Ipv6Addr, u16),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum State {
+    Idle,
+    Busy(Amount),
+    Done,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    ShareRequest(Amount),
+    SharePeers(Vec<PeerAddress>),
+    Done,
+}
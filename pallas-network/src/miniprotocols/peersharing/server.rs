@@ -0,0 +1,151 @@
+use thiserror::Error;
+
+use crate::multiplexer;
+
+use super::{Amount, Message, PeerAddress, State};
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("attempted to receive message while agency is ours")]
+    AgencyIsOurs,
+
+    #[error("attempted to send message while agency is theirs")]
+    AgencyIsTheirs,
+
+    #[error("inbound message is not valid for current state")]
+    InvalidInbound,
+
+    #[error("outbound message is not valid for current state")]
+    InvalidOutbound,
+
+    #[error("error while sending or receiving data through the multiplexer")]
+    Plexer(multiplexer::Error),
+}
+
+/// Represents the server for the PeerSharing mini-protocol.
+pub struct Server(State, multiplexer::ChannelBuffer);
+
+impl Server {
+    /// Create a new PeerSharing server from a multiplexer agent channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - A multiplexer agent channel used for communication with
+    ///   the client.
+    pub fn new(channel: multiplexer::AgentChannel) -> Self {
+        Self(State::Idle, multiplexer::ChannelBuffer::new(channel))
+    }
+
+    /// Get the current state of the server.
+    ///
+    /// Returns the current state of the server.
+    pub fn state(&self) -> &State {
+        &self.0
+    }
+
+    /// Check if the server is done.
+    ///
+    /// Returns true if server is in the `Done` state, false otherwise.
+    pub fn is_done(&self) -> bool {
+        self.0 == State::Done
+    }
+
+    fn has_agency(&self) -> bool {
+        match self.state() {
+            State::Idle => false,
+            State::Busy(_) => true,
+            State::Done => false,
+        }
+    }
+
+    fn assert_agency_is_ours(&self) -> Result<(), ServerError> {
+        if !self.has_agency() {
+            Err(ServerError::AgencyIsTheirs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_agency_is_theirs(&self) -> Result<(), ServerError> {
+        if self.has_agency() {
+            Err(ServerError::AgencyIsOurs)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn assert_outbound_state(&self, msg: &Message) -> Result<(), ServerError> {
+        match (&self.0, msg) {
+            (State::Busy(amount), Message::SharePeers(peers))
+                if peers.len() <= *amount as usize =>
+            {
+                Ok(())
+            }
+            _ => Err(ServerError::InvalidOutbound),
+        }
+    }
+
+    fn assert_inbound_state(&self, msg: &Message) -> Result<(), ServerError> {
+        match (&self.0, msg) {
+            (State::Idle, Message::ShareRequest(..)) => Ok(()),
+            (State::Idle, Message::Done) => Ok(()),
+            _ => Err(ServerError::InvalidInbound),
+        }
+    }
+
+    pub async fn send_message(&mut self, msg: &Message) -> Result<(), ServerError> {
+        self.assert_agency_is_ours()?;
+        self.assert_outbound_state(msg)?;
+        self.1
+            .send_msg_chunks(msg)
+            .await
+            .map_err(ServerError::Plexer)?;
+
+        Ok(())
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Message, ServerError> {
+        self.assert_agency_is_theirs()?;
+        let msg = self.1.recv_full_msg().await.map_err(ServerError::Plexer)?;
+        self.assert_inbound_state(&msg)?;
+
+        Ok(msg)
+    }
+
+    /// Receive a message from the client while the miniprotocol is in the
+    /// `Idle` state.
+    ///
+    /// If the message is a `ShareRequest`, return the amount of peers
+    /// requested and progress the server state to `Busy`. If the message is a
+    /// `Done`, return None and progress the server state to `Done`.
+    pub async fn recv_while_idle(&mut self) -> Result<Option<Amount>, ServerError> {
+        match self.recv_message().await? {
+            Message::ShareRequest(amount) => {
+                self.0 = State::Busy(amount);
+
+                Ok(Some(amount))
+            }
+            Message::Done => {
+                self.0 = State::Done;
+
+                Ok(None)
+            }
+            _ => Err(ServerError::InvalidInbound),
+        }
+    }
+
+    /// Share peer addresses in response to the last request.
+    ///
+    /// Returns an `Error` if there are more addresses than the client asked
+    /// for.
+    pub async fn send_peer_addresses(
+        &mut self,
+        peers: Vec<PeerAddress>,
+    ) -> Result<(), ServerError> {
+        let msg = Message::SharePeers(peers);
+        self.send_message(&msg).await?;
+        self.0 = State::Idle;
+
+        Ok(())
+    }
+}
@@ -168,6 +168,11 @@ impl SegmentBuffer {
     /// Cancel-safe loop that reads from bearer until certain len
     async fn cancellable_read(&mut self, required: usize) -> Result<(), Error> {
         loop {
+            // a previous, cancelled read might have already buffered enough data
+            if self.1.len() >= required {
+                break Ok(());
+            }
+
             self.0.readable().await.map_err(Error::BearerIo)?;
             trace!("bearer is readable");
 
@@ -1,13 +1,15 @@
-use std::net::{Ipv4Addr, SocketAddrV4};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
 use std::time::Duration;
 
 use pallas_network::facades::{PeerClient, PeerServer};
 use pallas_network::miniprotocols::blockfetch::BlockRequest;
 use pallas_network::miniprotocols::chainsync::{ClientRequest, HeaderContent, Tip};
+use pallas_network::miniprotocols::handshake::n2n::VersionTable;
+use pallas_network::miniprotocols::peersharing::PeerAddress;
 use pallas_network::miniprotocols::{
     blockfetch,
     chainsync::{self, NextResponse},
-    Point,
+    keepalive, peersharing, Point,
 };
 use tokio::net::TcpListener;
 
@@ -434,4 +436,125 @@ pub async fn chainsync_server_and_client_happy_path_n2n() {
     _ = tokio::join!(client, server);
 }
 
+#[tokio::test]
+#[ignore]
+pub async fn keepalive_server_and_client_happy_path() {
+    let server = tokio::spawn(async move {
+        // server setup
+
+        let server_listener = TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 30003))
+            .await
+            .unwrap();
+
+        let mut peer_server = PeerServer::accept(&server_listener, 0).await.unwrap();
+
+        let server_ka = peer_server.keepalive();
+
+        // server answers the first two keep-alive messages
+
+        assert_eq!(server_ka.respond_keepalive().await.unwrap(), Some(0));
+        assert_eq!(*server_ka.state(), keepalive::State::Client);
+
+        assert_eq!(server_ka.respond_keepalive().await.unwrap(), Some(1));
+        assert_eq!(*server_ka.state(), keepalive::State::Client);
+    });
+
+    let client = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // client setup
+
+        let mut client_to_server_conn = PeerClient::connect("localhost:30003", 0).await.unwrap();
+
+        assert!(client_to_server_conn.keepalive_rtt().is_none());
+
+        // background task reports the round-trip time of each keep-alive
+
+        for _ in 0..2 {
+            client_to_server_conn.keepalive_rtt.changed().await.unwrap();
+            assert!(client_to_server_conn.keepalive_rtt().is_some());
+        }
+
+        client_to_server_conn.abort();
+    });
+
+    let (client, server) = tokio::join!(client, server);
+    client.unwrap();
+    server.unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+pub async fn peersharing_server_and_client_happy_path() {
+    let peers = vec![
+        PeerAddress::V4(Ipv4Addr::new(192, 168, 0, 1), 3001),
+        PeerAddress::V6(Ipv6Addr::LOCALHOST, 6000),
+    ];
+
+    let server = tokio::spawn({
+        let peers = peers.clone();
+        async move {
+            // server setup
+
+            let server_listener = TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 30004))
+                .await
+                .unwrap();
+
+            let mut peer_server = PeerServer::accept_with_versions(
+                &server_listener,
+                VersionTable::v11_and_above(0, true),
+            )
+            .await
+            .unwrap();
+
+            assert!(peer_server.version.1.peer_sharing_enabled());
+
+            let server_ps = peer_server.peersharing();
+
+            // server receives share request from client, sends peers
+
+            assert_eq!(server_ps.recv_while_idle().await.unwrap(), Some(5));
+            assert_eq!(*server_ps.state(), peersharing::State::Busy(5));
+
+            server_ps.send_peer_addresses(peers).await.unwrap();
+
+            assert_eq!(*server_ps.state(), peersharing::State::Idle);
+
+            assert!(server_ps.recv_while_idle().await.unwrap().is_none());
+
+            assert_eq!(*server_ps.state(), peersharing::State::Done);
+        }
+    });
+
+    let client = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // client setup
+
+        let mut client_to_server_conn = PeerClient::connect_with_versions(
+            "localhost:30004",
+            VersionTable::v11_and_above(0, true),
+        )
+        .await
+        .unwrap();
+
+        let client_ps = client_to_server_conn.peersharing();
+
+        // client requests peers, receives them
+
+        let received = client_ps.request_peers(5).await.unwrap();
+
+        assert_eq!(received, peers);
+        assert_eq!(*client_ps.state(), peersharing::State::Idle);
+
+        // client sends done
+
+        client_ps.send_done().await.unwrap();
+    });
+
+    let (client, server) = tokio::join!(client, server);
+    client.unwrap();
+    server.unwrap();
+}
+
 // TODO: redo txsubmission client test
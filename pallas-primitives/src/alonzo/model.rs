@@ -682,6 +682,8 @@ pub struct ProtocolParamUpdate {
     pub extra_entropy: Option<Nonce>,
     #[n(14)]
     pub protocol_version: Option<ProtocolVersion>,
+    #[n(15)]
+    pub min_utxo_value: Option<Coin>,
     #[n(16)]
     pub min_pool_cost: Option<Coin>,
     #[n(17)]
@@ -1208,10 +1210,10 @@ pub struct ExUnits {
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub struct ExUnitPrices {
     #[n(0)]
-    mem_price: PositiveInterval,
+    pub mem_price: PositiveInterval,
 
     #[n(1)]
-    step_price: PositiveInterval,
+    pub step_price: PositiveInterval,
 }
 
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
@@ -1490,22 +1492,26 @@ pub struct Block {
 /// This structure is analogous to [Block], but it allows to retrieve the
 /// original CBOR bytes for each structure that might require hashing. In this
 /// way, we make sure that the resulting hash matches what exists on-chain.
+///
+/// Each of the segments of the body is kept raw as a whole too, since the
+/// block body hash in the header is computed from them.
 #[derive(Encode, Decode, Debug, PartialEq, Clone)]
 pub struct MintedBlock<'b> {
     #[n(0)]
     pub header: KeepRaw<'b, Header>,
 
     #[b(1)]
-    pub transaction_bodies: MaybeIndefArray<KeepRaw<'b, TransactionBody>>,
+    pub transaction_bodies: KeepRaw<'b, MaybeIndefArray<KeepRaw<'b, TransactionBody>>>,
 
     #[n(2)]
-    pub transaction_witness_sets: MaybeIndefArray<KeepRaw<'b, MintedWitnessSet<'b>>>,
+    pub transaction_witness_sets: KeepRaw<'b, MaybeIndefArray<KeepRaw<'b, MintedWitnessSet<'b>>>>,
 
     #[n(3)]
-    pub auxiliary_data_set: KeyValuePairs<TransactionIndex, KeepRaw<'b, AuxiliaryData>>,
+    pub auxiliary_data_set:
+        KeepRaw<'b, KeyValuePairs<TransactionIndex, KeepRaw<'b, AuxiliaryData>>>,
 
     #[n(4)]
-    pub invalid_transactions: Option<MaybeIndefArray<TransactionIndex>>,
+    pub invalid_transactions: Option<KeepRaw<'b, MaybeIndefArray<TransactionIndex>>>,
 }
 
 impl<'b> From<MintedBlock<'b>> for Block {
@@ -1514,12 +1520,14 @@ impl<'b> From<MintedBlock<'b>> for Block {
             header: x.header.unwrap(),
             transaction_bodies: x
                 .transaction_bodies
+                .unwrap()
                 .to_vec()
                 .into_iter()
                 .map(|x| x.unwrap())
                 .collect(),
             transaction_witness_sets: x
                 .transaction_witness_sets
+                .unwrap()
                 .to_vec()
                 .into_iter()
                 .map(|x| x.unwrap())
@@ -1527,12 +1535,13 @@ impl<'b> From<MintedBlock<'b>> for Block {
                 .collect(),
             auxiliary_data_set: x
                 .auxiliary_data_set
+                .unwrap()
                 .to_vec()
                 .into_iter()
                 .map(|(k, v)| (k, v.unwrap()))
                 .collect::<Vec<_>>()
                 .into(),
-            invalid_transactions: x.invalid_transactions.map(|x| x.into()),
+            invalid_transactions: x.invalid_transactions.map(|x| x.unwrap().into()),
         }
     }
 }
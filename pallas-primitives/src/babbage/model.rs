@@ -656,12 +656,27 @@ pub type Block = PseudoBlock<Header, TransactionBody, WitnessSet, AuxiliaryData>
 /// This structure is analogous to [Block], but it allows to retrieve the
 /// original CBOR bytes for each structure that might require hashing. In this
 /// way, we make sure that the resulting hash matches what exists on-chain.
-pub type MintedBlock<'b> = PseudoBlock<
-    KeepRaw<'b, Header>,
-    KeepRaw<'b, MintedTransactionBody<'b>>,
-    KeepRaw<'b, MintedWitnessSet<'b>>,
-    KeepRaw<'b, AuxiliaryData>,
->;
+///
+/// Each of the segments of the body is kept raw as a whole too, since the
+/// block body hash in the header is computed from them.
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+pub struct MintedBlock<'b> {
+    #[n(0)]
+    pub header: KeepRaw<'b, Header>,
+
+    #[b(1)]
+    pub transaction_bodies: KeepRaw<'b, MaybeIndefArray<KeepRaw<'b, MintedTransactionBody<'b>>>>,
+
+    #[n(2)]
+    pub transaction_witness_sets: KeepRaw<'b, MaybeIndefArray<KeepRaw<'b, MintedWitnessSet<'b>>>>,
+
+    #[n(3)]
+    pub auxiliary_data_set:
+        KeepRaw<'b, KeyValuePairs<TransactionIndex, KeepRaw<'b, AuxiliaryData>>>,
+
+    #[n(4)]
+    pub invalid_transactions: Option<KeepRaw<'b, MaybeIndefArray<TransactionIndex>>>,
+}
 
 impl<'b> From<MintedBlock<'b>> for Block {
     fn from(x: MintedBlock<'b>) -> Self {
@@ -685,12 +700,13 @@ impl<'b> From<MintedBlock<'b>> for Block {
             ),
             auxiliary_data_set: x
                 .auxiliary_data_set
+                .unwrap()
                 .to_vec()
                 .into_iter()
                 .map(|(k, v)| (k, v.unwrap()))
                 .collect::<Vec<_>>()
                 .into(),
-            invalid_transactions: x.invalid_transactions,
+            invalid_transactions: x.invalid_transactions.map(|x| x.unwrap()),
         }
     }
 }
@@ -579,6 +579,15 @@ pub struct Up {
     pub votes: MaybeIndefArray<UpVote>,
 }
 
+#[derive(Debug, Encode, Decode, Clone)]
+pub struct MintedUp<'b> {
+    #[b(0)]
+    pub proposal: ZeroOrOneArray<KeepRaw<'b, UpProp>>,
+
+    #[n(1)]
+    pub votes: MaybeIndefArray<UpVote>,
+}
+
 // Blocks
 
 pub type Difficulty = MaybeIndefArray<u64>;
@@ -742,7 +751,7 @@ pub struct MintedBlockBody<'b> {
     pub dlg_payload: MaybeIndefArray<Dlg>,
 
     #[b(3)]
-    pub upd_payload: Up,
+    pub upd_payload: MintedUp<'b>,
 }
 
 // Epoch Boundary Blocks
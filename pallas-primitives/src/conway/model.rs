@@ -0,0 +1,1536 @@
+//! Ledger primitives and cbor codec for the Conway era
+//!
+//! Handcrafted, idiomatic rust artifacts based on based on the [Conway CDDL](https://github.com/IntersectMBO/cardano-ledger/blob/master/eras/conway/impl/cddl-files/conway.cddl) file in IOHK repo.
+
+use serde::{Deserialize, Serialize};
+
+use pallas_codec::minicbor::{Decode, Encode};
+use pallas_crypto::hash::Hash;
+
+use pallas_codec::utils::{
+    Bytes, CborWrap, KeepRaw, KeyValuePairs, MaybeIndefArray, NonEmptySet, Nullable, Set,
+};
+
+// required for derive attrs to work
+use pallas_codec::minicbor;
+
+pub use crate::babbage::VrfCert;
+
+pub use crate::babbage::HeaderBody;
+
+pub use crate::babbage::OperationalCert;
+
+pub use crate::babbage::ProtocolVersion;
+
+pub use crate::babbage::KesSignature;
+
+pub use crate::babbage::Header;
+
+pub use crate::babbage::TransactionInput;
+
+pub use crate::babbage::NonceVariant;
+
+pub use crate::babbage::Nonce;
+
+pub use crate::babbage::ScriptHash;
+
+pub use crate::babbage::PolicyId;
+
+pub use crate::babbage::AssetName;
+
+pub use crate::babbage::Multiasset;
+
+pub use crate::babbage::Mint;
+
+pub use crate::babbage::Coin;
+
+pub use crate::babbage::Value;
+
+pub use crate::babbage::LegacyTransactionOutput;
+
+pub use crate::babbage::PoolKeyhash;
+
+pub use crate::babbage::Epoch;
+
+pub use crate::babbage::Genesishash;
+
+pub use crate::babbage::VrfKeyhash;
+
+pub use crate::babbage::RewardAccount;
+
+pub use crate::babbage::Withdrawals;
+
+pub type RequiredSigners = NonEmptySet<AddrKeyhash>;
+
+pub use crate::babbage::Port;
+
+pub use crate::babbage::IPv4;
+
+pub use crate::babbage::IPv6;
+
+pub use crate::babbage::DnsName;
+
+pub use crate::babbage::Relay;
+
+pub use crate::babbage::PoolMetadataHash;
+
+pub use crate::babbage::PoolMetadata;
+
+pub use crate::babbage::AddrKeyhash;
+
+pub use crate::babbage::Scripthash;
+
+pub use crate::babbage::RationalNumber;
+
+pub use crate::babbage::UnitInterval;
+
+pub use crate::babbage::PositiveInterval;
+
+pub type NonNegativeInterval = RationalNumber;
+
+pub use crate::babbage::StakeCredential;
+
+pub type DRepCredential = StakeCredential;
+
+pub type CommitteeColdCredential = StakeCredential;
+
+pub type CommitteeHotCredential = StakeCredential;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct Anchor {
+    #[n(0)]
+    pub url: String,
+
+    #[n(1)]
+    pub content_hash: Hash<32>,
+}
+
+// drep = [ 0, addr_keyhash // 1, scripthash // 2 // 3 ]
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+pub enum DRep {
+    Key(AddrKeyhash),
+    Script(Scripthash),
+    Abstain,
+    NoConfidence,
+}
+
+impl<'b, C> minicbor::decode::Decode<'b, C> for DRep {
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+        let variant = d.u16()?;
+
+        match variant {
+            0 => Ok(DRep::Key(d.decode_with(ctx)?)),
+            1 => Ok(DRep::Script(d.decode_with(ctx)?)),
+            2 => Ok(DRep::Abstain),
+            3 => Ok(DRep::NoConfidence),
+            _ => Err(minicbor::decode::Error::message(
+                "invalid variant id for DRep",
+            )),
+        }
+    }
+}
+
+impl<C> minicbor::encode::Encode<C> for DRep {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            DRep::Key(h) => {
+                e.array(2)?;
+                e.encode_with(0, ctx)?;
+                e.encode_with(h, ctx)?;
+
+                Ok(())
+            }
+            DRep::Script(h) => {
+                e.array(2)?;
+                e.encode_with(1, ctx)?;
+                e.encode_with(h, ctx)?;
+
+                Ok(())
+            }
+            DRep::Abstain => {
+                e.array(1)?;
+                e.encode_with(2, ctx)?;
+
+                Ok(())
+            }
+            DRep::NoConfidence => {
+                e.array(1)?;
+                e.encode_with(3, ctx)?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum Certificate {
+    StakeRegistration(StakeCredential),
+    StakeDeregistration(StakeCredential),
+    StakeDelegation(StakeCredential, PoolKeyhash),
+    PoolRegistration {
+        operator: PoolKeyhash,
+        vrf_keyhash: VrfKeyhash,
+        pledge: Coin,
+        cost: Coin,
+        margin: UnitInterval,
+        reward_account: RewardAccount,
+        pool_owners: Set<AddrKeyhash>,
+        relays: Vec<Relay>,
+        pool_metadata: Option<PoolMetadata>,
+    },
+    PoolRetirement(PoolKeyhash, Epoch),
+
+    Reg(StakeCredential, Coin),
+    UnReg(StakeCredential, Coin),
+    VoteDeleg(StakeCredential, DRep),
+    StakeVoteDeleg(StakeCredential, PoolKeyhash, DRep),
+    StakeRegDeleg(StakeCredential, PoolKeyhash, Coin),
+    VoteRegDeleg(StakeCredential, DRep, Coin),
+    StakeVoteRegDeleg(StakeCredential, PoolKeyhash, DRep, Coin),
+
+    AuthCommitteeHot(CommitteeColdCredential, CommitteeHotCredential),
+    ResignCommitteeCold(CommitteeColdCredential, Option<Anchor>),
+    RegDRepCert(DRepCredential, Coin, Option<Anchor>),
+    UnRegDRepCert(DRepCredential, Coin),
+    UpdateDRepCert(DRepCredential, Option<Anchor>),
+}
+
+impl<'b, C> minicbor::decode::Decode<'b, C> for Certificate {
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+        let variant = d.u16()?;
+
+        match variant {
+            0 => {
+                let a = d.decode_with(ctx)?;
+                Ok(Certificate::StakeRegistration(a))
+            }
+            1 => {
+                let a = d.decode_with(ctx)?;
+                Ok(Certificate::StakeDeregistration(a))
+            }
+            2 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::StakeDelegation(a, b))
+            }
+            3 => {
+                let operator = d.decode_with(ctx)?;
+                let vrf_keyhash = d.decode_with(ctx)?;
+                let pledge = d.decode_with(ctx)?;
+                let cost = d.decode_with(ctx)?;
+                let margin = d.decode_with(ctx)?;
+                let reward_account = d.decode_with(ctx)?;
+                let pool_owners = d.decode_with(ctx)?;
+                let relays = d.decode_with(ctx)?;
+                let pool_metadata = d.decode_with(ctx)?;
+
+                Ok(Certificate::PoolRegistration {
+                    operator,
+                    vrf_keyhash,
+                    pledge,
+                    cost,
+                    margin,
+                    reward_account,
+                    pool_owners,
+                    relays,
+                    pool_metadata,
+                })
+            }
+            4 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::PoolRetirement(a, b))
+            }
+            7 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::Reg(a, b))
+            }
+            8 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::UnReg(a, b))
+            }
+            9 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::VoteDeleg(a, b))
+            }
+            10 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                Ok(Certificate::StakeVoteDeleg(a, b, c))
+            }
+            11 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                Ok(Certificate::StakeRegDeleg(a, b, c))
+            }
+            12 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                Ok(Certificate::VoteRegDeleg(a, b, c))
+            }
+            13 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                let d = d.decode_with(ctx)?;
+                Ok(Certificate::StakeVoteRegDeleg(a, b, c, d))
+            }
+            14 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::AuthCommitteeHot(a, b))
+            }
+            15 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::ResignCommitteeCold(a, b))
+            }
+            16 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                Ok(Certificate::RegDRepCert(a, b, c))
+            }
+            17 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::UnRegDRepCert(a, b))
+            }
+            18 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(Certificate::UpdateDRepCert(a, b))
+            }
+            _ => Err(minicbor::decode::Error::message(
+                "unknown variant id for certificate",
+            )),
+        }
+    }
+}
+
+impl<C> minicbor::encode::Encode<C> for Certificate {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            Certificate::StakeRegistration(a) => {
+                e.array(2)?;
+                e.u16(0)?;
+                e.encode_with(a, ctx)?;
+
+                Ok(())
+            }
+            Certificate::StakeDeregistration(a) => {
+                e.array(2)?;
+                e.u16(1)?;
+                e.encode_with(a, ctx)?;
+
+                Ok(())
+            }
+            Certificate::StakeDelegation(a, b) => {
+                e.array(3)?;
+                e.u16(2)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::PoolRegistration {
+                operator,
+                vrf_keyhash,
+                pledge,
+                cost,
+                margin,
+                reward_account,
+                pool_owners,
+                relays,
+                pool_metadata,
+            } => {
+                e.array(10)?;
+                e.u16(3)?;
+
+                e.encode_with(operator, ctx)?;
+                e.encode_with(vrf_keyhash, ctx)?;
+                e.encode_with(pledge, ctx)?;
+                e.encode_with(cost, ctx)?;
+                e.encode_with(margin, ctx)?;
+                e.encode_with(reward_account, ctx)?;
+                e.encode_with(pool_owners, ctx)?;
+                e.encode_with(relays, ctx)?;
+                e.encode_with(pool_metadata, ctx)?;
+
+                Ok(())
+            }
+            Certificate::PoolRetirement(a, b) => {
+                e.array(3)?;
+                e.u16(4)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::Reg(a, b) => {
+                e.array(3)?;
+                e.u16(7)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::UnReg(a, b) => {
+                e.array(3)?;
+                e.u16(8)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::VoteDeleg(a, b) => {
+                e.array(3)?;
+                e.u16(9)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::StakeVoteDeleg(a, b, c) => {
+                e.array(4)?;
+                e.u16(10)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+
+                Ok(())
+            }
+            Certificate::StakeRegDeleg(a, b, c) => {
+                e.array(4)?;
+                e.u16(11)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+
+                Ok(())
+            }
+            Certificate::VoteRegDeleg(a, b, c) => {
+                e.array(4)?;
+                e.u16(12)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+
+                Ok(())
+            }
+            Certificate::StakeVoteRegDeleg(a, b, c, d) => {
+                e.array(5)?;
+                e.u16(13)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+                e.encode_with(d, ctx)?;
+
+                Ok(())
+            }
+            Certificate::AuthCommitteeHot(a, b) => {
+                e.array(3)?;
+                e.u16(14)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::ResignCommitteeCold(a, b) => {
+                e.array(3)?;
+                e.u16(15)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::RegDRepCert(a, b, c) => {
+                e.array(4)?;
+                e.u16(16)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+
+                Ok(())
+            }
+            Certificate::UnRegDRepCert(a, b) => {
+                e.array(3)?;
+                e.u16(17)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            Certificate::UpdateDRepCert(a, b) => {
+                e.array(3)?;
+                e.u16(18)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+pub use crate::babbage::NetworkId;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[cbor(index_only)]
+pub enum Language {
+    #[n(0)]
+    PlutusV1,
+
+    #[n(1)]
+    PlutusV2,
+
+    #[n(2)]
+    PlutusV3,
+}
+
+pub use crate::babbage::CostModel;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[cbor(map)]
+pub struct CostMdls {
+    #[n(0)]
+    pub plutus_v1: Option<CostModel>,
+
+    #[n(1)]
+    pub plutus_v2: Option<CostModel>,
+
+    #[n(2)]
+    pub plutus_v3: Option<CostModel>,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct PoolVotingThresholds {
+    #[n(0)]
+    pub motion_no_confidence: UnitInterval,
+    #[n(1)]
+    pub committee_normal: UnitInterval,
+    #[n(2)]
+    pub committee_no_confidence: UnitInterval,
+    #[n(3)]
+    pub hard_fork_initiation: UnitInterval,
+    #[n(4)]
+    pub security_voting_threshold: UnitInterval,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct DRepVotingThresholds {
+    #[n(0)]
+    pub motion_no_confidence: UnitInterval,
+    #[n(1)]
+    pub committee_normal: UnitInterval,
+    #[n(2)]
+    pub committee_no_confidence: UnitInterval,
+    #[n(3)]
+    pub update_constitution: UnitInterval,
+    #[n(4)]
+    pub hard_fork_initiation: UnitInterval,
+    #[n(5)]
+    pub pp_network_group: UnitInterval,
+    #[n(6)]
+    pub pp_economic_group: UnitInterval,
+    #[n(7)]
+    pub pp_technical_group: UnitInterval,
+    #[n(8)]
+    pub pp_governance_group: UnitInterval,
+    #[n(9)]
+    pub treasury_withdrawal: UnitInterval,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[cbor(map)]
+pub struct ProtocolParamUpdate {
+    #[n(0)]
+    pub minfee_a: Option<u64>,
+    #[n(1)]
+    pub minfee_b: Option<u64>,
+    #[n(2)]
+    pub max_block_body_size: Option<u64>,
+    #[n(3)]
+    pub max_transaction_size: Option<u64>,
+    #[n(4)]
+    pub max_block_header_size: Option<u64>,
+    #[n(5)]
+    pub key_deposit: Option<Coin>,
+    #[n(6)]
+    pub pool_deposit: Option<Coin>,
+    #[n(7)]
+    pub maximum_epoch: Option<Epoch>,
+    #[n(8)]
+    pub desired_number_of_stake_pools: Option<u64>,
+    #[n(9)]
+    pub pool_pledge_influence: Option<NonNegativeInterval>,
+    #[n(10)]
+    pub expansion_rate: Option<UnitInterval>,
+    #[n(11)]
+    pub treasury_growth_rate: Option<UnitInterval>,
+
+    #[n(16)]
+    pub min_pool_cost: Option<Coin>,
+    #[n(17)]
+    pub ada_per_utxo_byte: Option<Coin>,
+    #[n(18)]
+    pub cost_models_for_script_languages: Option<CostMdls>,
+    #[n(19)]
+    pub execution_costs: Option<ExUnitPrices>,
+    #[n(20)]
+    pub max_tx_ex_units: Option<ExUnits>,
+    #[n(21)]
+    pub max_block_ex_units: Option<ExUnits>,
+    #[n(22)]
+    pub max_value_size: Option<u64>,
+    #[n(23)]
+    pub collateral_percentage: Option<u64>,
+    #[n(24)]
+    pub max_collateral_inputs: Option<u64>,
+
+    #[n(25)]
+    pub pool_voting_thresholds: Option<PoolVotingThresholds>,
+    #[n(26)]
+    pub drep_voting_thresholds: Option<DRepVotingThresholds>,
+    #[n(27)]
+    pub min_committee_size: Option<u64>,
+    #[n(28)]
+    pub committee_term_limit: Option<Epoch>,
+    #[n(29)]
+    pub governance_action_validity_period: Option<Epoch>,
+    #[n(30)]
+    pub governance_action_deposit: Option<Coin>,
+    #[n(31)]
+    pub drep_deposit: Option<Coin>,
+    #[n(32)]
+    pub drep_inactivity_period: Option<Epoch>,
+    #[n(33)]
+    pub minfee_refscript_cost_per_byte: Option<NonNegativeInterval>,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Hash)]
+pub struct GovActionId {
+    #[n(0)]
+    pub transaction_id: Hash<32>,
+
+    #[n(1)]
+    pub action_index: u32,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct Constitution {
+    #[n(0)]
+    pub anchor: Anchor,
+
+    #[n(1)]
+    pub guardrail_script: Nullable<Scripthash>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum GovAction {
+    ParameterChange(
+        Option<GovActionId>,
+        Box<ProtocolParamUpdate>,
+        Option<Scripthash>,
+    ),
+    HardForkInitiation(Option<GovActionId>, ProtocolVersion),
+    TreasuryWithdrawals(KeyValuePairs<RewardAccount, Coin>, Option<Scripthash>),
+    NoConfidence(Option<GovActionId>),
+    UpdateCommittee(
+        Option<GovActionId>,
+        Set<CommitteeColdCredential>,
+        KeyValuePairs<CommitteeColdCredential, Epoch>,
+        UnitInterval,
+    ),
+    NewConstitution(Option<GovActionId>, Constitution),
+    Information,
+}
+
+impl<'b, C> minicbor::decode::Decode<'b, C> for GovAction {
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+        let variant = d.u16()?;
+
+        match variant {
+            0 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                Ok(GovAction::ParameterChange(a, b, c))
+            }
+            1 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(GovAction::HardForkInitiation(a, b))
+            }
+            2 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(GovAction::TreasuryWithdrawals(a, b))
+            }
+            3 => {
+                let a = d.decode_with(ctx)?;
+                Ok(GovAction::NoConfidence(a))
+            }
+            4 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                let c = d.decode_with(ctx)?;
+                let d = d.decode_with(ctx)?;
+                Ok(GovAction::UpdateCommittee(a, b, c, d))
+            }
+            5 => {
+                let a = d.decode_with(ctx)?;
+                let b = d.decode_with(ctx)?;
+                Ok(GovAction::NewConstitution(a, b))
+            }
+            6 => Ok(GovAction::Information),
+            _ => Err(minicbor::decode::Error::message(
+                "unknown variant id for gov action",
+            )),
+        }
+    }
+}
+
+impl<C> minicbor::encode::Encode<C> for GovAction {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            GovAction::ParameterChange(a, b, c) => {
+                e.array(4)?;
+                e.u16(0)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+
+                Ok(())
+            }
+            GovAction::HardForkInitiation(a, b) => {
+                e.array(3)?;
+                e.u16(1)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            GovAction::TreasuryWithdrawals(a, b) => {
+                e.array(3)?;
+                e.u16(2)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            GovAction::NoConfidence(a) => {
+                e.array(2)?;
+                e.u16(3)?;
+                e.encode_with(a, ctx)?;
+
+                Ok(())
+            }
+            GovAction::UpdateCommittee(a, b, c, d) => {
+                e.array(5)?;
+                e.u16(4)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+                e.encode_with(c, ctx)?;
+                e.encode_with(d, ctx)?;
+
+                Ok(())
+            }
+            GovAction::NewConstitution(a, b) => {
+                e.array(3)?;
+                e.u16(5)?;
+                e.encode_with(a, ctx)?;
+                e.encode_with(b, ctx)?;
+
+                Ok(())
+            }
+            GovAction::Information => {
+                e.array(1)?;
+                e.u16(6)?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct ProposalProcedure {
+    #[n(0)]
+    pub deposit: Coin,
+
+    #[n(1)]
+    pub reward_account: RewardAccount,
+
+    #[n(2)]
+    pub gov_action: GovAction,
+
+    #[n(3)]
+    pub anchor: Anchor,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy)]
+#[cbor(index_only)]
+pub enum Vote {
+    #[n(0)]
+    No,
+    #[n(1)]
+    Yes,
+    #[n(2)]
+    Abstain,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct VotingProcedure {
+    #[n(0)]
+    pub vote: Vote,
+
+    #[n(1)]
+    pub anchor: Nullable<Anchor>,
+}
+
+// voter =
+//   [ 0, addr_keyhash   ; constitutional committee hot key
+//   // 1, scripthash    ; constitutional committee hot script
+//   // 2, addr_keyhash  ; drep key
+//   // 3, scripthash    ; drep script
+//   // 4, addr_keyhash  ; stake pool key
+//   ]
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Hash)]
+pub enum Voter {
+    ConstitutionalCommitteeKey(AddrKeyhash),
+    ConstitutionalCommitteeScript(Scripthash),
+    DRepKey(AddrKeyhash),
+    DRepScript(Scripthash),
+    StakePoolKey(PoolKeyhash),
+}
+
+impl<'b, C> minicbor::decode::Decode<'b, C> for Voter {
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+        let variant = d.u16()?;
+
+        match variant {
+            0 => Ok(Voter::ConstitutionalCommitteeKey(d.decode_with(ctx)?)),
+            1 => Ok(Voter::ConstitutionalCommitteeScript(d.decode_with(ctx)?)),
+            2 => Ok(Voter::DRepKey(d.decode_with(ctx)?)),
+            3 => Ok(Voter::DRepScript(d.decode_with(ctx)?)),
+            4 => Ok(Voter::StakePoolKey(d.decode_with(ctx)?)),
+            _ => Err(minicbor::decode::Error::message(
+                "invalid variant id for Voter",
+            )),
+        }
+    }
+}
+
+impl<C> minicbor::encode::Encode<C> for Voter {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        let (variant, hash) = match self {
+            Voter::ConstitutionalCommitteeKey(h) => (0, h),
+            Voter::ConstitutionalCommitteeScript(h) => (1, h),
+            Voter::DRepKey(h) => (2, h),
+            Voter::DRepScript(h) => (3, h),
+            Voter::StakePoolKey(h) => (4, h),
+        };
+
+        e.array(2)?;
+        e.encode_with(variant, ctx)?;
+        e.encode_with(hash, ctx)?;
+
+        Ok(())
+    }
+}
+
+pub type VotingProcedures = KeyValuePairs<Voter, KeyValuePairs<GovActionId, VotingProcedure>>;
+
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[cbor(map)]
+pub struct PseudoTransactionBody<T1> {
+    #[n(0)]
+    pub inputs: Set<TransactionInput>,
+
+    #[n(1)]
+    pub outputs: Vec<T1>,
+
+    #[n(2)]
+    pub fee: u64,
+
+    #[n(3)]
+    pub ttl: Option<u64>,
+
+    #[n(4)]
+    pub certificates: Option<NonEmptySet<Certificate>>,
+
+    #[n(5)]
+    pub withdrawals: Option<KeyValuePairs<RewardAccount, Coin>>,
+
+    #[n(7)]
+    pub auxiliary_data_hash: Option<Bytes>,
+
+    #[n(8)]
+    pub validity_interval_start: Option<u64>,
+
+    #[n(9)]
+    pub mint: Option<Multiasset<i64>>,
+
+    #[n(11)]
+    pub script_data_hash: Option<Hash<32>>,
+
+    #[n(13)]
+    pub collateral: Option<NonEmptySet<TransactionInput>>,
+
+    #[n(14)]
+    pub required_signers: Option<RequiredSigners>,
+
+    #[n(15)]
+    pub network_id: Option<NetworkId>,
+
+    #[n(16)]
+    pub collateral_return: Option<T1>,
+
+    #[n(17)]
+    pub total_collateral: Option<Coin>,
+
+    #[n(18)]
+    pub reference_inputs: Option<NonEmptySet<TransactionInput>>,
+
+    // -- NEW IN CONWAY
+    #[n(19)]
+    pub voting_procedures: Option<VotingProcedures>,
+
+    #[n(20)]
+    pub proposal_procedures: Option<NonEmptySet<ProposalProcedure>>,
+
+    #[n(21)]
+    pub treasury_value: Option<Coin>,
+
+    #[n(22)]
+    pub donation: Option<Coin>,
+}
+
+pub type TransactionBody = PseudoTransactionBody<TransactionOutput>;
+
+pub type MintedTransactionBody<'a> = PseudoTransactionBody<MintedTransactionOutput<'a>>;
+
+impl<'a> From<MintedTransactionBody<'a>> for TransactionBody {
+    fn from(value: MintedTransactionBody<'a>) -> Self {
+        Self {
+            inputs: value.inputs,
+            outputs: value.outputs.into_iter().map(|x| x.into()).collect(),
+            fee: value.fee,
+            ttl: value.ttl,
+            certificates: value.certificates,
+            withdrawals: value.withdrawals,
+            auxiliary_data_hash: value.auxiliary_data_hash,
+            validity_interval_start: value.validity_interval_start,
+            mint: value.mint,
+            script_data_hash: value.script_data_hash,
+            collateral: value.collateral,
+            required_signers: value.required_signers,
+            network_id: value.network_id,
+            collateral_return: value.collateral_return.map(|x| x.into()),
+            total_collateral: value.total_collateral,
+            reference_inputs: value.reference_inputs,
+            voting_procedures: value.voting_procedures,
+            proposal_procedures: value.proposal_procedures,
+            treasury_value: value.treasury_value,
+            donation: value.donation,
+        }
+    }
+}
+
+pub use crate::babbage::PseudoTransactionOutput;
+
+pub type TransactionOutput = PseudoTransactionOutput<PostAlonzoTransactionOutput>;
+
+pub type MintedTransactionOutput<'b> =
+    PseudoTransactionOutput<MintedPostAlonzoTransactionOutput<'b>>;
+
+impl<'b> From<MintedTransactionOutput<'b>> for TransactionOutput {
+    fn from(value: MintedTransactionOutput<'b>) -> Self {
+        match value {
+            PseudoTransactionOutput::Legacy(x) => Self::Legacy(x),
+            PseudoTransactionOutput::PostAlonzo(x) => Self::PostAlonzo(x.into()),
+        }
+    }
+}
+
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[cbor(map)]
+pub struct PseudoPostAlonzoTransactionOutput<T1> {
+    #[n(0)]
+    pub address: Bytes,
+
+    #[n(1)]
+    pub value: Value,
+
+    #[n(2)]
+    pub datum_option: Option<T1>,
+
+    #[n(3)]
+    pub script_ref: Option<ScriptRef>,
+}
+
+pub type PostAlonzoTransactionOutput = PseudoPostAlonzoTransactionOutput<DatumOption>;
+
+pub type MintedPostAlonzoTransactionOutput<'b> =
+    PseudoPostAlonzoTransactionOutput<MintedDatumOption<'b>>;
+
+impl<'b> From<MintedPostAlonzoTransactionOutput<'b>> for PostAlonzoTransactionOutput {
+    fn from(value: MintedPostAlonzoTransactionOutput<'b>) -> Self {
+        Self {
+            address: value.address,
+            value: value.value,
+            datum_option: value.datum_option.map(|x| x.into()),
+            script_ref: value.script_ref,
+        }
+    }
+}
+
+pub use crate::babbage::VKeyWitness;
+
+pub use crate::babbage::NativeScript;
+
+pub use crate::babbage::PlutusV1Script;
+
+pub use crate::babbage::PlutusV2Script;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+#[cbor(transparent)]
+pub struct PlutusV3Script(#[n(0)] pub Bytes);
+
+impl AsRef<[u8]> for PlutusV3Script {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+pub use crate::babbage::BigInt;
+
+pub use crate::babbage::PlutusData;
+
+pub use crate::babbage::Constr;
+
+pub use crate::babbage::ExUnits;
+
+pub use crate::babbage::ExUnitPrices;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy)]
+#[cbor(index_only)]
+pub enum RedeemerTag {
+    #[n(0)]
+    Spend,
+    #[n(1)]
+    Mint,
+    #[n(2)]
+    Cert,
+    #[n(3)]
+    Reward,
+    #[n(4)]
+    Vote,
+    #[n(5)]
+    Propose,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct Redeemer {
+    #[n(0)]
+    pub tag: RedeemerTag,
+
+    #[n(1)]
+    pub index: u32,
+
+    #[n(2)]
+    pub data: PlutusData,
+
+    #[n(3)]
+    pub ex_units: ExUnits,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct RedeemersKey {
+    #[n(0)]
+    pub tag: RedeemerTag,
+
+    #[n(1)]
+    pub index: u32,
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct RedeemersValue {
+    #[n(0)]
+    pub data: PlutusData,
+
+    #[n(1)]
+    pub ex_units: ExUnits,
+}
+
+// redeemers =
+//   [ + [ tag: redeemer_tag, index: uint, data: plutus_data, ex_units: ex_units ] ]
+//   / { + [ tag: redeemer_tag, index: uint ] => [ data: plutus_data, ex_units: ex_units ] }
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum Redeemers {
+    List(MaybeIndefArray<Redeemer>),
+    Map(KeyValuePairs<RedeemersKey, RedeemersValue>),
+}
+
+impl Redeemers {
+    pub fn len(&self) -> usize {
+        match self {
+            Redeemers::List(x) => x.len(),
+            Redeemers::Map(x) => x.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'b, C> minicbor::Decode<'b, C> for Redeemers {
+    fn decode(d: &mut minicbor::Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        match d.datatype()? {
+            minicbor::data::Type::Array | minicbor::data::Type::ArrayIndef => {
+                Ok(Self::List(d.decode_with(ctx)?))
+            }
+            minicbor::data::Type::Map | minicbor::data::Type::MapIndef => {
+                Ok(Self::Map(d.decode_with(ctx)?))
+            }
+            _ => Err(minicbor::decode::Error::message(
+                "invalid type for redeemers struct",
+            )),
+        }
+    }
+}
+
+impl<C> minicbor::Encode<C> for Redeemers {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            Self::List(x) => e.encode_with(x, ctx)?,
+            Self::Map(x) => e.encode_with(x, ctx)?,
+        };
+
+        Ok(())
+    }
+}
+
+pub use crate::babbage::BootstrapWitness;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
+#[cbor(map)]
+pub struct WitnessSet {
+    #[n(0)]
+    pub vkeywitness: Option<NonEmptySet<VKeyWitness>>,
+
+    #[n(1)]
+    pub native_script: Option<NonEmptySet<NativeScript>>,
+
+    #[n(2)]
+    pub bootstrap_witness: Option<NonEmptySet<BootstrapWitness>>,
+
+    #[n(3)]
+    pub plutus_v1_script: Option<NonEmptySet<PlutusV1Script>>,
+
+    #[n(4)]
+    pub plutus_data: Option<NonEmptySet<PlutusData>>,
+
+    #[n(5)]
+    pub redeemer: Option<Redeemers>,
+
+    #[n(6)]
+    pub plutus_v2_script: Option<NonEmptySet<PlutusV2Script>>,
+
+    #[n(7)]
+    pub plutus_v3_script: Option<NonEmptySet<PlutusV3Script>>,
+}
+
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+#[cbor(map)]
+pub struct MintedWitnessSet<'b> {
+    #[n(0)]
+    pub vkeywitness: Option<NonEmptySet<VKeyWitness>>,
+
+    #[n(1)]
+    pub native_script: Option<NonEmptySet<NativeScript>>,
+
+    #[n(2)]
+    pub bootstrap_witness: Option<NonEmptySet<BootstrapWitness>>,
+
+    #[n(3)]
+    pub plutus_v1_script: Option<NonEmptySet<PlutusV1Script>>,
+
+    #[b(4)]
+    pub plutus_data: Option<NonEmptySet<KeepRaw<'b, PlutusData>>>,
+
+    #[n(5)]
+    pub redeemer: Option<Redeemers>,
+
+    #[n(6)]
+    pub plutus_v2_script: Option<NonEmptySet<PlutusV2Script>>,
+
+    #[n(7)]
+    pub plutus_v3_script: Option<NonEmptySet<PlutusV3Script>>,
+}
+
+impl<'b> From<MintedWitnessSet<'b>> for WitnessSet {
+    fn from(x: MintedWitnessSet<'b>) -> Self {
+        WitnessSet {
+            vkeywitness: x.vkeywitness,
+            native_script: x.native_script,
+            bootstrap_witness: x.bootstrap_witness,
+            plutus_v1_script: x.plutus_v1_script,
+            plutus_data: x.plutus_data.and_then(|x| {
+                NonEmptySet::from_vec(x.to_vec().into_iter().map(|x| x.unwrap()).collect())
+            }),
+            redeemer: x.redeemer,
+            plutus_v2_script: x.plutus_v2_script,
+            plutus_v3_script: x.plutus_v3_script,
+        }
+    }
+}
+
+pub use crate::babbage::DatumHash;
+
+pub use crate::babbage::PseudoDatumOption;
+
+pub use crate::babbage::DatumOption;
+
+pub use crate::babbage::MintedDatumOption;
+
+// script_ref = #6.24(bytes .cbor script)
+pub type ScriptRef = CborWrap<Script>;
+
+// script = [ 0, native_script // 1, plutus_v1_script // 2, plutus_v2_script // 3, plutus_v3_script ]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum Script {
+    NativeScript(NativeScript),
+    PlutusV1Script(PlutusV1Script),
+    PlutusV2Script(PlutusV2Script),
+    PlutusV3Script(PlutusV3Script),
+}
+
+impl<'b, C> minicbor::Decode<'b, C> for Script {
+    fn decode(
+        d: &mut minicbor::Decoder<'b>,
+        _ctx: &mut C,
+    ) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+
+        match d.u8()? {
+            0 => Ok(Self::NativeScript(d.decode()?)),
+            1 => Ok(Self::PlutusV1Script(d.decode()?)),
+            2 => Ok(Self::PlutusV2Script(d.decode()?)),
+            3 => Ok(Self::PlutusV3Script(d.decode()?)),
+            _ => Err(minicbor::decode::Error::message(
+                "invalid variant for script enum",
+            )),
+        }
+    }
+}
+
+impl<C> minicbor::Encode<C> for Script {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        match self {
+            Self::NativeScript(x) => e.encode_with((0, x), ctx)?,
+            Self::PlutusV1Script(x) => e.encode_with((1, x), ctx)?,
+            Self::PlutusV2Script(x) => e.encode_with((2, x), ctx)?,
+            Self::PlutusV3Script(x) => e.encode_with((3, x), ctx)?,
+        };
+
+        Ok(())
+    }
+}
+
+impl From<crate::babbage::Script> for Script {
+    fn from(value: crate::babbage::Script) -> Self {
+        match value {
+            crate::babbage::Script::NativeScript(x) => Self::NativeScript(x),
+            crate::babbage::Script::PlutusV1Script(x) => Self::PlutusV1Script(x),
+            crate::babbage::Script::PlutusV2Script(x) => Self::PlutusV2Script(x),
+        }
+    }
+}
+
+pub use crate::babbage::Metadatum;
+
+pub use crate::babbage::MetadatumLabel;
+
+pub use crate::babbage::Metadata;
+
+pub use crate::babbage::AuxiliaryData;
+
+pub use crate::babbage::TransactionIndex;
+
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Clone)]
+pub struct PseudoBlock<T1, T2, T3, T4>
+where
+    T4: std::clone::Clone,
+{
+    #[n(0)]
+    pub header: T1,
+
+    #[b(1)]
+    pub transaction_bodies: MaybeIndefArray<T2>,
+
+    #[n(2)]
+    pub transaction_witness_sets: MaybeIndefArray<T3>,
+
+    #[n(3)]
+    pub auxiliary_data_set: KeyValuePairs<TransactionIndex, T4>,
+
+    #[n(4)]
+    pub invalid_transactions: Option<MaybeIndefArray<TransactionIndex>>,
+}
+
+pub type Block = PseudoBlock<Header, TransactionBody, WitnessSet, AuxiliaryData>;
+
+/// A memory representation of an already minted block
+///
+/// This structure is analogous to [Block], but it allows to retrieve the
+/// original CBOR bytes for each structure that might require hashing. In this
+/// way, we make sure that the resulting hash matches what exists on-chain.
+///
+/// Each of the segments of the body is kept raw as a whole too, since the
+/// block body hash in the header is computed from them.
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+pub struct MintedBlock<'b> {
+    #[n(0)]
+    pub header: KeepRaw<'b, Header>,
+
+    #[b(1)]
+    pub transaction_bodies: KeepRaw<'b, MaybeIndefArray<KeepRaw<'b, MintedTransactionBody<'b>>>>,
+
+    #[n(2)]
+    pub transaction_witness_sets: KeepRaw<'b, MaybeIndefArray<KeepRaw<'b, MintedWitnessSet<'b>>>>,
+
+    #[n(3)]
+    pub auxiliary_data_set:
+        KeepRaw<'b, KeyValuePairs<TransactionIndex, KeepRaw<'b, AuxiliaryData>>>,
+
+    #[n(4)]
+    pub invalid_transactions: Option<KeepRaw<'b, MaybeIndefArray<TransactionIndex>>>,
+}
+
+impl<'b> From<MintedBlock<'b>> for Block {
+    fn from(x: MintedBlock<'b>) -> Self {
+        Block {
+            header: x.header.unwrap(),
+            transaction_bodies: MaybeIndefArray::Def(
+                x.transaction_bodies
+                    .iter()
+                    .cloned()
+                    .map(|x| x.unwrap())
+                    .map(TransactionBody::from)
+                    .collect(),
+            ),
+            transaction_witness_sets: MaybeIndefArray::Def(
+                x.transaction_witness_sets
+                    .iter()
+                    .cloned()
+                    .map(|x| x.unwrap())
+                    .map(WitnessSet::from)
+                    .collect(),
+            ),
+            auxiliary_data_set: x
+                .auxiliary_data_set
+                .unwrap()
+                .to_vec()
+                .into_iter()
+                .map(|(k, v)| (k, v.unwrap()))
+                .collect::<Vec<_>>()
+                .into(),
+            invalid_transactions: x.invalid_transactions.map(|x| x.unwrap()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Encode, Decode, Debug)]
+pub struct PseudoTx<T1, T2, T3>
+where
+    T1: std::clone::Clone,
+    T2: std::clone::Clone,
+    T3: std::clone::Clone,
+{
+    #[n(0)]
+    pub transaction_body: T1,
+
+    #[n(1)]
+    pub transaction_witness_set: T2,
+
+    #[n(2)]
+    pub success: bool,
+
+    #[n(3)]
+    pub auxiliary_data: Nullable<T3>,
+}
+
+pub type Tx = PseudoTx<TransactionBody, WitnessSet, AuxiliaryData>;
+
+pub type MintedTx<'b> = PseudoTx<
+    KeepRaw<'b, MintedTransactionBody<'b>>,
+    KeepRaw<'b, MintedWitnessSet<'b>>,
+    KeepRaw<'b, AuxiliaryData>,
+>;
+
+impl<'b> From<MintedTx<'b>> for Tx {
+    fn from(x: MintedTx<'b>) -> Self {
+        Tx {
+            transaction_body: x.transaction_body.unwrap().into(),
+            transaction_witness_set: x.transaction_witness_set.unwrap().into(),
+            success: x.success,
+            auxiliary_data: x.auxiliary_data.map(|x| x.unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::minicbor;
+
+    use pallas_codec::utils::{KeyValuePairs, NonEmptySet, Nullable, Set};
+    use pallas_crypto::hash::Hash;
+
+    use super::*;
+
+    type BlockWrapper<'b> = (u16, MintedBlock<'b>);
+
+    #[test]
+    fn block_isomorphic_decoding_encoding() {
+        // Conway blocks keep the shape of Babbage ones, so Babbage blocks are
+        // valid Conway blocks too.
+        let test_blocks = [
+            include_str!("../../../test_data/conway1.block"),
+            include_str!("../../../test_data/babbage1.block"),
+            include_str!("../../../test_data/babbage2.block"),
+            include_str!("../../../test_data/babbage3.block"),
+            include_str!("../../../test_data/babbage9.block"),
+        ];
+
+        for (idx, block_str) in test_blocks.iter().enumerate() {
+            println!("decoding test block {}", idx + 1);
+            let bytes = hex::decode(block_str).unwrap_or_else(|_| panic!("bad block file {idx}"));
+
+            let block: BlockWrapper = minicbor::decode(&bytes[..])
+                .unwrap_or_else(|e| panic!("error decoding cbor for file {idx}: {e:?}"));
+
+            let bytes2 = minicbor::to_vec(block)
+                .unwrap_or_else(|e| panic!("error encoding block cbor for file {idx}: {e:?}"));
+
+            assert!(bytes.eq(&bytes2), "re-encoded bytes didn't match original");
+        }
+    }
+
+    #[test]
+    fn governance_isomorphic_encoding_decoding() {
+        let credential = StakeCredential::AddrKeyhash(Hash::from([1; 28]));
+        let anchor = Anchor {
+            url: "https://example.com/anchor.json".to_string(),
+            content_hash: Hash::from([2; 32]),
+        };
+        let action_id = GovActionId {
+            transaction_id: Hash::from([3; 32]),
+            action_index: 0,
+        };
+
+        let certificates = vec![
+            Certificate::Reg(credential.clone(), 2000000),
+            Certificate::VoteDeleg(credential.clone(), DRep::Abstain),
+            Certificate::StakeVoteRegDeleg(
+                credential.clone(),
+                Hash::from([4; 28]),
+                DRep::Script(Hash::from([5; 28])),
+                2000000,
+            ),
+            Certificate::RegDRepCert(credential.clone(), 500000000, Some(anchor.clone())),
+            Certificate::ResignCommitteeCold(credential.clone(), None),
+        ];
+
+        let proposal = ProposalProcedure {
+            deposit: 100000000000,
+            reward_account: vec![0xe1; 29].into(),
+            gov_action: GovAction::UpdateCommittee(
+                Some(action_id.clone()),
+                Set::from(vec![credential.clone()]),
+                KeyValuePairs::from(vec![(credential, 500)]),
+                UnitInterval {
+                    numerator: 2,
+                    denominator: 3,
+                },
+            ),
+            anchor: anchor.clone(),
+        };
+
+        let votes = KeyValuePairs::from(vec![(
+            Voter::DRepKey(Hash::from([6; 28])),
+            KeyValuePairs::from(vec![(
+                action_id,
+                VotingProcedure {
+                    vote: Vote::Yes,
+                    anchor: Nullable::Null,
+                },
+            )]),
+        )]);
+
+        let body = TransactionBody {
+            inputs: Set::from(vec![]),
+            outputs: vec![],
+            fee: 200000,
+            ttl: None,
+            certificates: NonEmptySet::from_vec(certificates),
+            withdrawals: None,
+            auxiliary_data_hash: None,
+            validity_interval_start: None,
+            mint: None,
+            script_data_hash: None,
+            collateral: None,
+            required_signers: None,
+            network_id: None,
+            collateral_return: None,
+            total_collateral: None,
+            reference_inputs: None,
+            voting_procedures: Some(votes),
+            proposal_procedures: NonEmptySet::from_vec(vec![proposal]),
+            treasury_value: None,
+            donation: Some(1000000),
+        };
+
+        let bytes = minicbor::to_vec(&body).unwrap();
+        let body2: TransactionBody = minicbor::decode(&bytes).unwrap();
+        assert_eq!(body, body2);
+        assert_eq!(minicbor::to_vec(&body2).unwrap(), bytes);
+    }
+}
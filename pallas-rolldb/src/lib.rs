@@ -1,3 +1,4 @@
 pub mod chain;
 mod kvtable;
+pub mod utxo;
 pub mod wal;
@@ -0,0 +1,12 @@
+use pallas_applying::EraCbor;
+use pallas_traverse::OutputRef;
+
+mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub type UtxoRef = OutputRef;
+pub type UtxoBody = EraCbor;
+
+pub use store::*;
@@ -0,0 +1,139 @@
+use pallas_applying::{UtxoDelta, UtxoProvider};
+use pallas_crypto::hash::Hash;
+use pallas_traverse::Era;
+use std::{path::Path, sync::Arc};
+
+use rocksdb::{Options, WriteBatch, DB};
+
+use super::{UtxoBody, UtxoRef};
+
+use crate::kvtable::*;
+
+pub struct DBUtxoRef(pub UtxoRef);
+
+impl From<DBUtxoRef> for Box<[u8]> {
+    fn from(value: DBUtxoRef) -> Self {
+        let hash = value.0.hash().as_slice();
+        let index = value.0.index().to_be_bytes();
+        [hash, index.as_slice()].concat().into()
+    }
+}
+
+impl From<Box<[u8]>> for DBUtxoRef {
+    fn from(value: Box<[u8]>) -> Self {
+        let hash: [u8; 32] = value[0..32].try_into().unwrap();
+        let index: [u8; 8] = value[32..40].try_into().unwrap();
+        Self(UtxoRef::new(Hash::from(hash), u64::from_be_bytes(index)))
+    }
+}
+
+// the era tag, as used by the node, followed by the output cbor
+pub struct DBUtxoBody(pub UtxoBody);
+
+impl From<DBUtxoBody> for Box<[u8]> {
+    fn from(value: DBUtxoBody) -> Self {
+        let era = u16::from(value.0.era()).to_be_bytes();
+        [era.as_slice(), value.0.cbor()].concat().into()
+    }
+}
+
+impl From<Box<[u8]>> for DBUtxoBody {
+    fn from(value: Box<[u8]>) -> Self {
+        let era: [u8; 2] = value[0..2].try_into().unwrap();
+        let era = Era::try_from(u16::from_be_bytes(era)).unwrap();
+        Self(UtxoBody(era, value[2..].to_vec()))
+    }
+}
+
+// output ref -> era-tagged output cbor
+pub struct UtxoKV;
+
+impl KVTable<DBUtxoRef, DBUtxoBody> for UtxoKV {
+    const CF_NAME: &'static str = "UtxoKV";
+}
+
+pub struct UtxoIterator<'a>(pub EntryIterator<'a, DBUtxoRef, DBUtxoBody>);
+
+impl Iterator for UtxoIterator<'_> {
+    type Item = Result<(UtxoRef, UtxoBody), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|v| v.map(|(k, v)| (k.0, v.0)))
+    }
+}
+
+/// A UTxO set persisted in RocksDB, usable as a source of outputs for
+/// validation
+#[derive(Clone)]
+pub struct Store {
+    db: Arc<DB>,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let db = DB::open_cf(&opts, path, [UtxoKV::CF_NAME]).map_err(|_| Error::IO)?;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    pub fn get_utxo(&self, utxo: &UtxoRef) -> Result<Option<UtxoBody>, Error> {
+        let dbval = UtxoKV::get_by_key(&self.db, DBUtxoRef(utxo.clone()))?;
+        Ok(dbval.map(|x| x.0))
+    }
+
+    /// Adds the produced outputs and removes the consumed ones, atomically
+    pub fn apply(
+        &mut self,
+        produced: impl IntoIterator<Item = (UtxoRef, UtxoBody)>,
+        consumed: impl IntoIterator<Item = UtxoRef>,
+    ) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+
+        for (utxo, body) in produced {
+            UtxoKV::stage_upsert(&self.db, DBUtxoRef(utxo), DBUtxoBody(body), &mut batch);
+        }
+
+        for utxo in consumed {
+            UtxoKV::stage_delete(&self.db, DBUtxoRef(utxo), &mut batch);
+        }
+
+        self.db.write(batch).map_err(|_| Error::IO)
+    }
+
+    /// Applies the changes made by a block, or undoes them if given the
+    /// inverse delta
+    pub fn apply_delta(&mut self, delta: &UtxoDelta) -> Result<(), Error> {
+        let produced = delta.produced.iter().map(|(k, v)| (k.clone(), v.clone()));
+        let consumed = delta.consumed.keys().cloned();
+
+        self.apply(produced, consumed)
+    }
+
+    pub fn reset(&mut self) -> Result<(), Error> {
+        UtxoKV::reset(&self.db)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        UtxoKV::is_empty(&self.db)
+    }
+
+    pub fn iter_utxos(&self) -> UtxoIterator {
+        UtxoIterator(UtxoKV::iter_entries_start(&self.db))
+    }
+
+    pub fn destroy(path: impl AsRef<Path>) -> Result<(), Error> {
+        DB::destroy(&Options::default(), path).map_err(|_| Error::IO)
+    }
+}
+
+impl UtxoProvider for Store {
+    type Error = Error;
+
+    fn resolve(&self, output_ref: &UtxoRef) -> Result<Option<UtxoBody>, Self::Error> {
+        self.get_utxo(output_ref)
+    }
+}
@@ -0,0 +1,80 @@
+use pallas_applying::{UtxoDelta, UtxoProvider};
+use pallas_traverse::Era;
+
+use super::{Store, UtxoBody, UtxoRef};
+
+fn with_tmp_db<T>(op: fn(db: Store) -> T) {
+    let path = tempfile::tempdir().unwrap().into_path();
+    let db = Store::open(path.clone()).unwrap();
+
+    op(db);
+
+    Store::destroy(path).unwrap();
+}
+
+fn dummy_utxo(index: u64) -> (UtxoRef, UtxoBody) {
+    let hash = pallas_crypto::hash::Hasher::<256>::hash(index.to_be_bytes().as_slice());
+    let body = UtxoBody(Era::Babbage, index.to_be_bytes().to_vec());
+    (UtxoRef::new(hash, index), body)
+}
+
+#[test]
+fn test_apply_blackbox() {
+    with_tmp_db(|mut db| {
+        let utxos: Vec<_> = (0..5).map(dummy_utxo).collect();
+        db.apply(utxos.clone(), []).unwrap();
+
+        // ensure produced outputs are resolved with their era
+        for (utxo, body) in utxos.iter() {
+            assert_eq!(db.resolve(utxo).unwrap().as_ref(), Some(body));
+        }
+
+        // consume some of them
+        let consumed: Vec<_> = utxos[..2].iter().map(|(utxo, _)| utxo.clone()).collect();
+        db.apply([dummy_utxo(5)], consumed.clone()).unwrap();
+
+        for utxo in consumed.iter() {
+            assert!(db.resolve(utxo).unwrap().is_none());
+        }
+
+        let remaining: Vec<_> = db.iter_utxos().map(|x| x.unwrap().0).collect();
+        assert_eq!(remaining.len(), 4);
+        assert!(remaining.contains(&dummy_utxo(5).0));
+    });
+}
+
+#[test]
+fn test_reset_blackbox() {
+    with_tmp_db(|mut db| {
+        db.apply((0..5).map(dummy_utxo), []).unwrap();
+        assert!(!db.is_empty());
+
+        db.reset().unwrap();
+        assert!(db.is_empty());
+    });
+}
+
+#[test]
+fn test_undo_delta_blackbox() {
+    with_tmp_db(|mut db| {
+        db.apply((0..5).map(dummy_utxo), []).unwrap();
+
+        let delta = UtxoDelta {
+            consumed: (0..2).map(dummy_utxo).collect(),
+            produced: (5..7).map(dummy_utxo).collect(),
+        };
+
+        db.apply_delta(&delta).unwrap();
+        assert!(db.get_utxo(&dummy_utxo(0).0).unwrap().is_none());
+        assert!(db.get_utxo(&dummy_utxo(5).0).unwrap().is_some());
+
+        // undoing the delta restores the consumed outputs
+        db.apply_delta(&delta.inverse()).unwrap();
+        assert_eq!(
+            db.get_utxo(&dummy_utxo(0).0).unwrap(),
+            Some(dummy_utxo(0).1)
+        );
+        assert!(db.get_utxo(&dummy_utxo(5).0).unwrap().is_none());
+        assert_eq!(db.iter_utxos().count(), 5);
+    });
+}
@@ -1,13 +1,41 @@
 use std::borrow::Cow;
 
 use pallas_codec::minicbor;
-use pallas_crypto::hash::Hash;
-use pallas_primitives::{alonzo, babbage, byron};
+use pallas_crypto::hash::{Hash, Hasher};
+use pallas_primitives::{alonzo, babbage, byron, conway};
 
 use crate::{probe, support, Era, Error, MultiEraBlock, MultiEraHeader, MultiEraTx};
 
 type BlockWrapper<T> = (u16, T);
 
+// The body hash is the hash of the concatenated hashes of each segment of the
+// body, which are three up to Mary, and four from Alonzo onwards.
+fn body_hash(segments: &[&[u8]]) -> Hash<32> {
+    let mut hasher = Hasher::<256>::new();
+
+    for segment in segments {
+        hasher.input(Hasher::<256>::hash(segment).as_ref());
+    }
+
+    hasher.finalize()
+}
+
+macro_rules! body_segments {
+    ($block:expr) => {{
+        let mut segments = vec![
+            $block.transaction_bodies.raw_cbor(),
+            $block.transaction_witness_sets.raw_cbor(),
+            $block.auxiliary_data_set.raw_cbor(),
+        ];
+
+        if let Some(x) = &$block.invalid_transactions {
+            segments.push(x.raw_cbor());
+        }
+
+        segments
+    }};
+}
+
 impl<'b> MultiEraBlock<'b> {
     pub fn decode_epoch_boundary(cbor: &'b [u8]) -> Result<Self, Error> {
         let (_, block): BlockWrapper<byron::MintedEbBlock> =
@@ -58,6 +86,13 @@ impl<'b> MultiEraBlock<'b> {
         Ok(Self::Babbage(Box::new(block)))
     }
 
+    pub fn decode_conway(cbor: &'b [u8]) -> Result<Self, Error> {
+        let (_, block): BlockWrapper<conway::MintedBlock> =
+            minicbor::decode(cbor).map_err(Error::invalid_cbor)?;
+
+        Ok(Self::Conway(Box::new(block)))
+    }
+
     pub fn decode(cbor: &'b [u8]) -> Result<MultiEraBlock<'b>, Error> {
         match probe::block_era(cbor) {
             probe::Outcome::EpochBoundary => Self::decode_epoch_boundary(cbor),
@@ -68,6 +103,7 @@ impl<'b> MultiEraBlock<'b> {
                 Era::Mary => Self::decode_mary(cbor),
                 Era::Alonzo => Self::decode_alonzo(cbor),
                 Era::Babbage => Self::decode_babbage(cbor),
+                Era::Conway => Self::decode_conway(cbor),
             },
             probe::Outcome::Inconclusive => Err(Error::unknown_cbor(cbor)),
         }
@@ -83,6 +119,8 @@ impl<'b> MultiEraBlock<'b> {
                 MultiEraHeader::AlonzoCompatible(Cow::Borrowed(&x.header))
             }
             MultiEraBlock::Babbage(x) => MultiEraHeader::Babbage(Cow::Borrowed(&x.header)),
+            // the header didn't change in Conway
+            MultiEraBlock::Conway(x) => MultiEraHeader::Babbage(Cow::Borrowed(&x.header)),
         }
     }
 
@@ -96,6 +134,7 @@ impl<'b> MultiEraBlock<'b> {
             MultiEraBlock::EpochBoundary(_) => Era::Byron,
             MultiEraBlock::AlonzoCompatible(_, x) => *x,
             MultiEraBlock::Babbage(_) => Era::Babbage,
+            MultiEraBlock::Conway(_) => Era::Conway,
             MultiEraBlock::Byron(_) => Era::Byron,
         }
     }
@@ -119,6 +158,10 @@ impl<'b> MultiEraBlock<'b> {
                 .into_iter()
                 .map(|x| MultiEraTx::Babbage(Box::new(Cow::Owned(x))))
                 .collect(),
+            MultiEraBlock::Conway(x) => support::clone_conway_txs(x)
+                .into_iter()
+                .map(|x| MultiEraTx::Conway(Box::new(Cow::Owned(x))))
+                .collect(),
             MultiEraBlock::Byron(x) => support::clone_byron_txs(x)
                 .into_iter()
                 .map(|x| MultiEraTx::Byron(Box::new(Cow::Owned(x))))
@@ -133,6 +176,7 @@ impl<'b> MultiEraBlock<'b> {
             MultiEraBlock::EpochBoundary(_) => true,
             MultiEraBlock::AlonzoCompatible(x, _) => x.transaction_bodies.is_empty(),
             MultiEraBlock::Babbage(x) => x.transaction_bodies.is_empty(),
+            MultiEraBlock::Conway(x) => x.transaction_bodies.is_empty(),
             MultiEraBlock::Byron(x) => x.body.tx_payload.is_empty(),
         }
     }
@@ -143,6 +187,7 @@ impl<'b> MultiEraBlock<'b> {
             MultiEraBlock::EpochBoundary(_) => 0,
             MultiEraBlock::AlonzoCompatible(x, _) => x.transaction_bodies.len(),
             MultiEraBlock::Babbage(x) => x.transaction_bodies.len(),
+            MultiEraBlock::Conway(x) => x.transaction_bodies.len(),
             MultiEraBlock::Byron(x) => x.body.tx_payload.len(),
         }
     }
@@ -153,16 +198,54 @@ impl<'b> MultiEraBlock<'b> {
             MultiEraBlock::EpochBoundary(_) => false,
             MultiEraBlock::AlonzoCompatible(x, _) => !x.auxiliary_data_set.is_empty(),
             MultiEraBlock::Babbage(x) => !x.auxiliary_data_set.is_empty(),
+            MultiEraBlock::Conway(x) => !x.auxiliary_data_set.is_empty(),
             MultiEraBlock::Byron(_) => false,
         }
     }
 
+    /// Computes the hash of the block body from the original bytes of its
+    /// segments, or returns `None` for Byron blocks, whose body is hashed
+    /// differently
+    pub fn compute_body_hash(&self) -> Option<Hash<32>> {
+        let segments = match self {
+            MultiEraBlock::EpochBoundary(_) => return None,
+            MultiEraBlock::Byron(_) => return None,
+            MultiEraBlock::AlonzoCompatible(x, _) => body_segments!(x),
+            MultiEraBlock::Babbage(x) => body_segments!(x),
+            MultiEraBlock::Conway(x) => body_segments!(x),
+        };
+
+        Some(body_hash(&segments))
+    }
+
+    /// Checks that the body of the block is the one its header commits to
+    ///
+    /// Byron blocks are rejected with [Error::InvalidEra].
+    pub fn validate_body_hash(&self) -> Result<(), Error> {
+        let declared = match self {
+            MultiEraBlock::EpochBoundary(_) => return Err(Error::InvalidEra(Era::Byron)),
+            MultiEraBlock::Byron(_) => return Err(Error::InvalidEra(Era::Byron)),
+            MultiEraBlock::AlonzoCompatible(x, _) => x.header.header_body.block_body_hash,
+            MultiEraBlock::Babbage(x) => x.header.header_body.block_body_hash,
+            MultiEraBlock::Conway(x) => x.header.header_body.block_body_hash,
+        };
+
+        // only Byron blocks lack a body hash
+        let computed = self.compute_body_hash().unwrap();
+
+        match declared == computed {
+            true => Ok(()),
+            false => Err(Error::BodyHashMismatch(declared, computed)),
+        }
+    }
+
     pub fn as_alonzo(&self) -> Option<&alonzo::MintedBlock> {
         match self {
             MultiEraBlock::EpochBoundary(_) => None,
             MultiEraBlock::AlonzoCompatible(x, _) => Some(x),
             MultiEraBlock::Babbage(_) => None,
             MultiEraBlock::Byron(_) => None,
+            MultiEraBlock::Conway(_) => None,
         }
     }
 
@@ -172,6 +255,7 @@ impl<'b> MultiEraBlock<'b> {
             MultiEraBlock::AlonzoCompatible(_, _) => None,
             MultiEraBlock::Babbage(x) => Some(x),
             MultiEraBlock::Byron(_) => None,
+            MultiEraBlock::Conway(_) => None,
         }
     }
 
@@ -181,6 +265,17 @@ impl<'b> MultiEraBlock<'b> {
             MultiEraBlock::AlonzoCompatible(_, _) => None,
             MultiEraBlock::Babbage(_) => None,
             MultiEraBlock::Byron(x) => Some(x),
+            MultiEraBlock::Conway(_) => None,
+        }
+    }
+
+    pub fn as_conway(&self) -> Option<&conway::MintedBlock> {
+        match self {
+            MultiEraBlock::EpochBoundary(_) => None,
+            MultiEraBlock::AlonzoCompatible(_, _) => None,
+            MultiEraBlock::Babbage(_) => None,
+            MultiEraBlock::Byron(_) => None,
+            MultiEraBlock::Conway(x) => Some(x),
         }
     }
 }
@@ -205,4 +300,118 @@ mod tests {
             assert_eq!(block.txs().len(), tx_count);
         }
     }
+
+    #[test]
+    fn conway_block_decodes() {
+        let block_str = include_str!("../../test_data/babbage1.block");
+        let babbage_cbor = hex::decode(block_str).expect("invalid hex");
+
+        // the body of this block is also valid in Conway, so re-tagging the era
+        // of the wrapper is enough to get a Conway block
+        let mut conway_cbor = babbage_cbor.clone();
+        conway_cbor[1] = 7;
+
+        let babbage = MultiEraBlock::decode(&babbage_cbor).expect("invalid cbor");
+        let conway = MultiEraBlock::decode(&conway_cbor).expect("invalid cbor");
+
+        assert_eq!(conway.era(), Era::Conway);
+        assert!(conway.as_conway().is_some());
+        assert_eq!(conway.hash(), babbage.hash());
+        assert_eq!(conway.tx_count(), babbage.tx_count());
+
+        for (a, b) in conway.txs().iter().zip(babbage.txs().iter()) {
+            assert_eq!(a.era(), Era::Conway);
+            assert_eq!(a.hash(), b.hash());
+            assert_eq!(a.outputs().len(), b.outputs().len());
+            assert_eq!(a.redeemers().len(), b.redeemers().len());
+            assert_eq!(a.fee(), b.fee());
+        }
+    }
+
+    #[test]
+    fn conway_governance_block_traverses() {
+        let cbor = hex::decode(include_str!("../../test_data/conway1.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).expect("invalid cbor");
+
+        assert_eq!(block.era(), Era::Conway);
+        assert_eq!(block.slot(), 75_000_000);
+        assert_eq!(block.number(), 2_500_000);
+        block.validate_body_hash().unwrap();
+
+        let txs = block.txs();
+        assert_eq!(txs.len(), 2);
+
+        let governance = &txs[0];
+        assert_eq!(governance.era(), Era::Conway);
+        assert_eq!(governance.inputs().len(), 2);
+        assert_eq!(governance.outputs().len(), 2);
+        assert_eq!(governance.certs().len(), 15);
+        assert_eq!(governance.fee(), Some(250_000));
+        assert_eq!(governance.ttl(), Some(80_000_000));
+        assert_eq!(governance.validity_start(), Some(79_000_000));
+
+        let body = &governance.as_conway().unwrap().transaction_body;
+        assert_eq!(body.voting_procedures.as_ref().unwrap().len(), 3);
+        assert_eq!(body.proposal_procedures.as_ref().unwrap().len(), 7);
+        assert_eq!(body.treasury_value, Some(1_000_000_000_000));
+        assert_eq!(body.donation, Some(1_000_000));
+
+        let payment = &txs[1];
+        assert_eq!(payment.inputs().len(), 1);
+        assert_eq!(payment.reference_inputs().len(), 1);
+        assert_eq!(payment.outputs()[0].lovelace_amount(), 5_000_000);
+    }
+
+    #[test]
+    fn body_hashes_match_headers() {
+        let blocks = [
+            include_str!("../../test_data/shelley1.block"),
+            include_str!("../../test_data/allegra1.block"),
+            include_str!("../../test_data/mary1.block"),
+            include_str!("../../test_data/alonzo1.block"),
+            include_str!("../../test_data/alonzo9.block"),
+            include_str!("../../test_data/babbage1.block"),
+            include_str!("../../test_data/babbage9.block"),
+            include_str!("../../test_data/conway1.block"),
+        ];
+
+        for block_str in blocks {
+            let cbor = hex::decode(block_str).expect("invalid hex");
+            let block = MultiEraBlock::decode(&cbor).expect("invalid cbor");
+            block.validate_body_hash().unwrap();
+        }
+
+        let cbor = hex::decode(include_str!("../../test_data/byron1.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+        assert_eq!(block.compute_body_hash(), None);
+        assert!(matches!(
+            block.validate_body_hash(),
+            Err(Error::InvalidEra(Era::Byron))
+        ));
+    }
+
+    #[test]
+    fn tampered_body_is_detected() {
+        let cbor = hex::decode(include_str!("../../test_data/alonzo1.block")).unwrap();
+
+        let (tag, mut block): BlockWrapper<alonzo::Block> = minicbor::decode(&cbor).unwrap();
+        block.transaction_bodies[0].fee += 1;
+        let tampered = minicbor::to_vec((tag, block)).unwrap();
+
+        let block = MultiEraBlock::decode(&tampered).unwrap();
+        let declared = block
+            .header()
+            .as_alonzo()
+            .unwrap()
+            .header_body
+            .block_body_hash;
+
+        match block.validate_body_hash() {
+            Err(Error::BodyHashMismatch(x, y)) => {
+                assert_eq!(x, declared);
+                assert_eq!(Some(y), block.compute_body_hash());
+            }
+            x => panic!("unexpected result {x:?}"),
+        }
+    }
 }
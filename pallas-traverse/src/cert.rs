@@ -1,4 +1,4 @@
-use pallas_primitives::alonzo;
+use pallas_primitives::{alonzo, conway};
 
 use crate::MultiEraCert;
 
@@ -9,4 +9,11 @@ impl<'b> MultiEraCert<'b> {
             _ => None,
         }
     }
+
+    pub fn as_conway(&self) -> Option<&conway::Certificate> {
+        match self {
+            MultiEraCert::Conway(x) => Some(x),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,241 @@
+//! Hard-fork combinator envelopes
+//!
+//! The node-to-client protocols, as well as the storage of the node, tag the
+//! bytes of blocks, txs and other era-specific values with the era they
+//! belong to, as in `[era_index, tag24(bytes)]`. The era index is the one of
+//! the hard-fork combinator, see [Era::hfc_index].
+//!
+//! Byron values keep their own encoding within the envelope, which for
+//! blocks tells epoch boundary blocks apart, and for headers is itself an
+//! envelope of the form `[[subtag, size], tag24(bytes)]`.
+
+use pallas_codec::minicbor::{self, data::Tag, Decoder, Encoder};
+
+use crate::{probe, Era, Error, MultiEraBlock, MultiEraHeader, MultiEraOutput, MultiEraTx};
+
+/// Splits an envelope into its era and the bytes it wraps
+pub fn decode_envelope(cbor: &[u8]) -> Result<(Era, &[u8]), Error> {
+    let era = match probe::envelope_era(cbor) {
+        probe::Outcome::Matched(era) => era,
+        _ => return Err(Error::unknown_cbor(cbor)),
+    };
+
+    let mut decoder = Decoder::new(cbor);
+    decoder.array().map_err(Error::invalid_cbor)?;
+    decoder.u16().map_err(Error::invalid_cbor)?;
+
+    Ok((era, decode_tag24(&mut decoder)?))
+}
+
+/// Wraps the given bytes in an envelope for the given era
+pub fn encode_envelope(era: Era, bytes: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(bytes.len() + 16);
+
+    // writing to a vec is infallible
+    Encoder::new(&mut buffer)
+        .array(2)
+        .unwrap()
+        .u16(era.hfc_index())
+        .unwrap()
+        .tag(Tag::Cbor)
+        .unwrap()
+        .bytes(bytes)
+        .unwrap();
+
+    buffer
+}
+
+fn decode_tag24<'b>(decoder: &mut Decoder<'b>) -> Result<&'b [u8], Error> {
+    match decoder.tag().map_err(Error::invalid_cbor)? {
+        Tag::Cbor => decoder.bytes().map_err(Error::invalid_cbor),
+        x => Err(Error::InvalidCbor(format!("expected tag 24, found {x:?}"))),
+    }
+}
+
+impl<'b> MultiEraBlock<'b> {
+    pub fn decode_enveloped(cbor: &'b [u8]) -> Result<Self, Error> {
+        let (era, bytes) = decode_envelope(cbor)?;
+
+        match era {
+            // Byron blocks are already wrapped as expected by the decoder
+            Era::Byron => Self::decode(bytes),
+            Era::Shelley | Era::Allegra | Era::Mary | Era::Alonzo => {
+                let block = minicbor::decode(bytes).map_err(Error::invalid_cbor)?;
+                Ok(Self::AlonzoCompatible(Box::new(block), era))
+            }
+            Era::Babbage => {
+                let block = minicbor::decode(bytes).map_err(Error::invalid_cbor)?;
+                Ok(Self::Babbage(Box::new(block)))
+            }
+            Era::Conway => {
+                let block = minicbor::decode(bytes).map_err(Error::invalid_cbor)?;
+                Ok(Self::Conway(Box::new(block)))
+            }
+        }
+    }
+
+    /// Encodes the block within an envelope, keeping the original bytes of
+    /// the parts that are hashed
+    pub fn encode_enveloped(&self) -> Vec<u8> {
+        // to_vec is infallible
+        let bytes = match self {
+            MultiEraBlock::EpochBoundary(x) => minicbor::to_vec((0, x)).unwrap(),
+            MultiEraBlock::Byron(x) => minicbor::to_vec((1, x)).unwrap(),
+            MultiEraBlock::AlonzoCompatible(x, _) => minicbor::to_vec(x).unwrap(),
+            MultiEraBlock::Babbage(x) => minicbor::to_vec(x).unwrap(),
+            MultiEraBlock::Conway(x) => minicbor::to_vec(x).unwrap(),
+        };
+
+        encode_envelope(self.era(), &bytes)
+    }
+}
+
+impl<'b> MultiEraHeader<'b> {
+    pub fn decode_enveloped(cbor: &'b [u8]) -> Result<Self, Error> {
+        let era = match probe::envelope_era(cbor) {
+            probe::Outcome::Matched(era) => era,
+            _ => return Err(Error::unknown_cbor(cbor)),
+        };
+
+        let mut decoder = Decoder::new(cbor);
+        decoder.array().map_err(Error::invalid_cbor)?;
+        let tag = decoder.u8().map_err(Error::invalid_cbor)?;
+
+        let subtag = match era {
+            Era::Byron => {
+                decoder.array().map_err(Error::invalid_cbor)?;
+                decoder.array().map_err(Error::invalid_cbor)?;
+                let subtag = decoder.u8().map_err(Error::invalid_cbor)?;
+                decoder.skip().map_err(Error::invalid_cbor)?;
+                Some(subtag)
+            }
+            _ => None,
+        };
+
+        let bytes = decode_tag24(&mut decoder)?;
+
+        Self::decode(tag, subtag, bytes)
+    }
+}
+
+impl<'b> MultiEraTx<'b> {
+    pub fn decode_enveloped(cbor: &'b [u8]) -> Result<Self, Error> {
+        let (era, bytes) = decode_envelope(cbor)?;
+        Self::decode(era, bytes).map_err(Error::invalid_cbor)
+    }
+
+    pub fn encode_enveloped(&self) -> Vec<u8> {
+        encode_envelope(self.era(), &self.encode())
+    }
+}
+
+impl<'b> MultiEraOutput<'b> {
+    pub fn decode_enveloped(cbor: &'b [u8]) -> Result<Self, Error> {
+        let (era, bytes) = decode_envelope(cbor)?;
+        Self::decode(era, bytes).map_err(Error::invalid_cbor)
+    }
+
+    /// Encodes the output within an envelope for the given era, which can't
+    /// be told from the output alone
+    pub fn encode_enveloped(&self, era: Era) -> Vec<u8> {
+        encode_envelope(era, &self.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_round_trip() {
+        let blocks = [
+            include_str!("../../test_data/genesis.block"),
+            include_str!("../../test_data/byron1.block"),
+            include_str!("../../test_data/shelley1.block"),
+            include_str!("../../test_data/mary1.block"),
+            include_str!("../../test_data/alonzo1.block"),
+            include_str!("../../test_data/babbage9.block"),
+        ];
+
+        for block in blocks {
+            let cbor = hex::decode(block).unwrap();
+            let expected = MultiEraBlock::decode(&cbor).unwrap();
+
+            let enveloped = expected.encode_enveloped();
+
+            // Byron blocks keep their wrapper, later ones go without it
+            let (_, bytes) = decode_envelope(&enveloped).unwrap();
+            match expected.era() {
+                Era::Byron => assert_eq!(bytes, cbor),
+                _ => assert_eq!(bytes, &cbor[2..]),
+            }
+
+            let block = MultiEraBlock::decode_enveloped(&enveloped).unwrap();
+
+            assert_eq!(block.era(), expected.era());
+            assert_eq!(block.hash(), expected.hash());
+
+            for (tx, expected) in block.txs().iter().zip(expected.txs()) {
+                assert_eq!(tx.hash(), expected.hash());
+
+                let enveloped = tx.encode_enveloped();
+                let tx = MultiEraTx::decode_enveloped(&enveloped).unwrap();
+                assert_eq!(tx.era(), expected.era());
+                assert_eq!(tx.hash(), expected.hash());
+            }
+        }
+    }
+
+    #[test]
+    fn outputs_round_trip() {
+        let cbor = hex::decode(include_str!("../../test_data/babbage9.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+
+        for tx in block.txs() {
+            for output in tx.outputs() {
+                let enveloped = output.encode_enveloped(Era::Babbage);
+                assert_eq!(enveloped[1], 5);
+
+                let decoded = MultiEraOutput::decode_enveloped(&enveloped).unwrap();
+                assert_eq!(decoded.encode(), output.encode());
+            }
+        }
+    }
+
+    #[test]
+    fn headers_decode() {
+        let cbor = hex::decode(include_str!("../../test_data/babbage9.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+
+        let header = block.header();
+        let enveloped = encode_envelope(Era::Babbage, header.cbor());
+        let decoded = MultiEraHeader::decode_enveloped(&enveloped).unwrap();
+        assert_eq!(decoded.hash(), header.hash());
+
+        let cbor = hex::decode(include_str!("../../test_data/byron1.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+        let header = block.header();
+
+        // [0, [[1, size], 24(h'...')]]
+        let mut enveloped = vec![];
+        let mut encoder = Encoder::new(&mut enveloped);
+        encoder.array(2).unwrap().u8(0).unwrap();
+        encoder.array(2).unwrap().array(2).unwrap().u8(1).unwrap();
+        encoder.u64(header.cbor().len() as u64).unwrap();
+        encoder
+            .tag(Tag::Cbor)
+            .unwrap()
+            .bytes(header.cbor())
+            .unwrap();
+
+        let decoded = MultiEraHeader::decode_enveloped(&enveloped).unwrap();
+        assert_eq!(decoded.hash(), header.hash());
+    }
+
+    #[test]
+    fn unknown_eras_fail() {
+        // [9, 24(h'80')]
+        let cbor = hex::decode("8209d8184180").unwrap();
+        assert!(decode_envelope(&cbor).is_err());
+    }
+}
@@ -13,6 +13,7 @@ impl Era {
             Feature::CIP31 => self.ge(&Era::Babbage),
             Feature::CIP32 => self.ge(&Era::Babbage),
             Feature::CIP33 => self.ge(&Era::Babbage),
+            Feature::CIP1694 => self.ge(&Era::Conway),
         }
     }
 }
@@ -31,6 +32,7 @@ impl TryFrom<u16> for Era {
             4 => Ok(Era::Mary),
             5 => Ok(Era::Alonzo),
             6 => Ok(Era::Babbage),
+            7 => Ok(Era::Conway),
             x => Err(crate::Error::UnknownEra(x)),
         }
     }
@@ -45,10 +47,26 @@ impl From<Era> for u16 {
             Era::Mary => 4,
             Era::Alonzo => 5,
             Era::Babbage => 6,
+            Era::Conway => 7,
         }
     }
 }
 
+impl Era {
+    /// The index of the era in the hard-fork combinator, which numbers eras
+    /// from Byron onwards without distinguishing epoch boundary blocks
+    pub fn hfc_index(&self) -> u16 {
+        u16::from(*self) - 1
+    }
+
+    pub fn from_hfc_index(index: u16) -> Result<Self, crate::Error> {
+        index
+            .checked_add(1)
+            .ok_or(crate::Error::UnknownEra(index))
+            .and_then(Era::try_from)
+    }
+}
+
 impl Display for Era {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -58,6 +76,7 @@ impl Display for Era {
             Era::Mary => write!(f, "Mary"),
             Era::Alonzo => write!(f, "Alonzo"),
             Era::Babbage => write!(f, "Babbage"),
+            Era::Conway => write!(f, "Conway"),
         }
     }
 }
@@ -1,9 +1,16 @@
-use pallas_codec::minicbor::to_vec;
-use pallas_primitives::byron;
+use pallas_codec::{minicbor::to_vec, utils::Nullable};
+use pallas_primitives::{
+    alonzo::{ExUnitPrices, ExUnits, RationalNumber},
+    byron,
+};
 
+use crate::MultiEraTx;
+
+/// Parameters of the Byron linear fee policy, expressed in nano-lovelace
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PolicyParams {
-    constant: u64,
-    size_coeficient: u64,
+    pub constant: u64,
+    pub size_coeficient: u64,
 }
 
 impl Default for PolicyParams {
@@ -37,9 +44,162 @@ pub fn compute_byron_fee(tx: &byron::MintedTxPayload, params: Option<&PolicyPara
     }
 }
 
+/// Parameters of the fee policy in effect since Shelley, expressed in lovelace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeParams {
+    /// Fee per byte of the tx
+    pub min_fee_a: u64,
+    /// Constant fee paid by every tx
+    pub min_fee_b: u64,
+    /// Prices of the execution units of Plutus scripts, from Alonzo onwards
+    pub ex_unit_prices: Option<ExUnitPrices>,
+    /// Base price per byte of the reference scripts used by a tx, from Conway
+    /// onwards
+    pub min_fee_ref_script_cost_per_byte: Option<RationalNumber>,
+}
+
+/// The components of the minimum fee of a tx
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    /// Fee for the size of the tx (`min_fee_a * size`)
+    pub size_fee: u64,
+    /// Constant fee (`min_fee_b`)
+    pub base_fee: u64,
+    /// Fee for the execution units claimed by the redeemers
+    pub script_fee: u64,
+    /// Fee for the size of the reference scripts used
+    pub ref_script_fee: u64,
+}
+
+impl FeeBreakdown {
+    pub fn total(&self) -> u64 {
+        self.size_fee + self.base_fee + self.script_fee + self.ref_script_fee
+    }
+}
+
+/// Computes the cost of execution units, rounded up to the next lovelace
+pub fn compute_script_fee(ex_units: &ExUnits, prices: &ExUnitPrices) -> u64 {
+    let mem_num = prices.mem_price.numerator as u128;
+    let mem_den = prices.mem_price.denominator as u128;
+    let step_num = prices.step_price.numerator as u128;
+    let step_den = prices.step_price.denominator as u128;
+
+    let num =
+        ex_units.mem as u128 * mem_num * step_den + ex_units.steps as u128 * step_num * mem_den;
+    let den = mem_den * step_den;
+
+    match den {
+        0 => 0,
+        _ => num.div_ceil(den) as u64,
+    }
+}
+
+/// Computes the Conway fee for the total size of the reference scripts used by
+/// a tx
+///
+/// The price per byte grows by a factor of 1.2 every 25 KiB, and the overall
+/// cost is rounded down to the lovelace.
+pub fn compute_ref_script_fee(ref_scripts_size: u64, cost_per_byte: &RationalNumber) -> u64 {
+    const SIZE_INCREMENT: u128 = 25_600;
+    const MULTIPLIER_NUM: u128 = 6;
+    const MULTIPLIER_DEN: u128 = 5;
+
+    if cost_per_byte.denominator == 0 {
+        return 0;
+    }
+
+    // the fee accumulated so far and the price of the current tier share the
+    // same denominator, which grows with each tier
+    let mut acc: u128 = 0;
+    let mut price: u128 = cost_per_byte.numerator as u128;
+    let mut den: u128 = cost_per_byte.denominator as u128;
+    let mut remaining = ref_scripts_size as u128;
+
+    while remaining >= SIZE_INCREMENT {
+        acc = acc.saturating_add(SIZE_INCREMENT.saturating_mul(price));
+        acc = acc.saturating_mul(MULTIPLIER_DEN);
+        price = price.saturating_mul(MULTIPLIER_NUM);
+        den = den.saturating_mul(MULTIPLIER_DEN);
+        remaining -= SIZE_INCREMENT;
+    }
+
+    acc = acc.saturating_add(remaining.saturating_mul(price));
+
+    u64::try_from(acc / den).unwrap_or(u64::MAX)
+}
+
+/// Computes the minimum fee of a Shelley-onwards tx, or `None` for Byron ones
+///
+/// Since the size of the reference scripts used by the tx depends on the
+/// outputs it spends or references, it must be provided by the caller. It is
+/// only charged for when the parameters include its price.
+pub fn compute_fee(
+    tx: &MultiEraTx,
+    params: &FeeParams,
+    ref_scripts_size: u64,
+) -> Option<FeeBreakdown> {
+    let size = fee_size(tx)?;
+
+    let ex_units = tx
+        .redeemers()
+        .iter()
+        .fold(ExUnits { mem: 0, steps: 0 }, |acc, x| ExUnits {
+            mem: acc.mem.saturating_add(x.ex_units().mem),
+            steps: acc.steps.saturating_add(x.ex_units().steps),
+        });
+
+    let script_fee = match &params.ex_unit_prices {
+        Some(prices) => compute_script_fee(&ex_units, prices),
+        None => 0,
+    };
+
+    let ref_script_fee = match &params.min_fee_ref_script_cost_per_byte {
+        Some(cost) => compute_ref_script_fee(ref_scripts_size, cost),
+        None => 0,
+    };
+
+    Some(FeeBreakdown {
+        size_fee: params.min_fee_a * size,
+        base_fee: params.min_fee_b,
+        script_fee,
+        ref_script_fee,
+    })
+}
+
+// The size of a tx, as charged for by the ledger, is the one of an array
+// holding its body, witness set and auxiliary data (or null), leaving out the
+// validity flag of Alonzo onwards.
+fn fee_size(tx: &MultiEraTx) -> Option<u64> {
+    let (body, wits, aux) = match tx {
+        MultiEraTx::AlonzoCompatible(x, _) => (
+            x.transaction_body.raw_cbor(),
+            x.transaction_witness_set.raw_cbor(),
+            &x.auxiliary_data,
+        ),
+        MultiEraTx::Babbage(x) => (
+            x.transaction_body.raw_cbor(),
+            x.transaction_witness_set.raw_cbor(),
+            &x.auxiliary_data,
+        ),
+        MultiEraTx::Conway(x) => (
+            x.transaction_body.raw_cbor(),
+            x.transaction_witness_set.raw_cbor(),
+            &x.auxiliary_data,
+        ),
+        _ => return None,
+    };
+
+    let aux_size = match aux {
+        Nullable::Some(x) => x.raw_cbor().len(),
+        _ => 1,
+    };
+
+    Some((1 + body.len() + wits.len() + aux_size) as u64)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::compute_byron_fee;
+    use super::*;
 
     #[test]
     fn known_fee_matches() {
@@ -59,4 +219,81 @@ mod tests {
             assert_eq!(fee, 171070);
         }
     }
+
+    fn mainnet_params() -> FeeParams {
+        FeeParams {
+            min_fee_a: 44,
+            min_fee_b: 155381,
+            ex_unit_prices: Some(ExUnitPrices {
+                mem_price: RationalNumber {
+                    numerator: 577,
+                    denominator: 10000,
+                },
+                step_price: RationalNumber {
+                    numerator: 721,
+                    denominator: 10000000,
+                },
+            }),
+            min_fee_ref_script_cost_per_byte: None,
+        }
+    }
+
+    #[test]
+    fn script_fee_rounds_up() {
+        let prices = mainnet_params().ex_unit_prices.unwrap();
+        let ex_units = ExUnits {
+            mem: 1_000_000,
+            steps: 500_000_000,
+        };
+
+        // 57_700 + 36_050 lovelace
+        assert_eq!(compute_script_fee(&ex_units, &prices), 93_750);
+
+        let ex_units = ExUnits { mem: 1, steps: 1 };
+        assert_eq!(compute_script_fee(&ex_units, &prices), 1);
+    }
+
+    #[test]
+    fn ref_script_fee_tiers() {
+        let cost = RationalNumber {
+            numerator: 15,
+            denominator: 1,
+        };
+
+        assert_eq!(compute_ref_script_fee(0, &cost), 0);
+        assert_eq!(compute_ref_script_fee(25_599, &cost), 383_985);
+        assert_eq!(compute_ref_script_fee(25_600, &cost), 384_000);
+        assert_eq!(compute_ref_script_fee(25_601, &cost), 384_018);
+        assert_eq!(compute_ref_script_fee(51_200, &cost), 844_800);
+        // 8 full tiers, the last one being rounded down
+        assert_eq!(compute_ref_script_fee(204_800, &cost), 6_335_648);
+    }
+
+    #[test]
+    fn declared_fees_cover_min_fee() {
+        let block_str = include_str!("../../test_data/babbage9.block");
+        let block_bytes = hex::decode(block_str.trim()).expect("bad block file");
+        let block = crate::MultiEraBlock::decode(&block_bytes).unwrap();
+        let params = mainnet_params();
+
+        for tx in block.txs() {
+            let fee = compute_fee(&tx, &params, 0).unwrap();
+
+            assert_eq!(fee.base_fee, 155381);
+            assert_eq!(fee.ref_script_fee, 0);
+            assert_eq!(fee.script_fee > 0, !tx.redeemers().is_empty());
+            assert!(fee.total() <= tx.fee().unwrap());
+        }
+    }
+
+    #[test]
+    fn byron_fee_not_computed() {
+        let block_str = include_str!("../../test_data/byron4.block");
+        let block_bytes = hex::decode(block_str).expect("bad block file");
+        let block = crate::MultiEraBlock::decode_byron(&block_bytes).unwrap();
+
+        for tx in block.txs() {
+            assert!(compute_fee(&tx, &mainnet_params(), 0).is_none());
+        }
+    }
 }
@@ -20,7 +20,8 @@ impl<'b> MultiEraHeader<'b> {
                     Ok(MultiEraHeader::Byron(Cow::Owned(header)))
                 }
             },
-            5 => {
+            // the header didn't change in Conway
+            5 | 6 => {
                 let header = minicbor::decode(cbor).map_err(Error::invalid_cbor)?;
                 Ok(MultiEraHeader::Babbage(Cow::Owned(header)))
             }
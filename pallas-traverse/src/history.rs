@@ -0,0 +1,576 @@
+//! Era history (a.k.a. hard-fork summaries) for slot and time conversions
+//!
+//! The slot length and the epoch size of the chain may change on every hard
+//! fork, so converting slots to time or epochs requires knowing where each era
+//! starts and which parameters it uses. This is what the node reports through
+//! the `GetInterpreter` local state query, and what can be derived from the
+//! genesis files of a network once the epoch of each hard fork is known.
+
+use pallas_codec::minicbor::{
+    self,
+    data::{Tag, Type},
+    Decode, Decoder,
+};
+use pallas_configs::{byron, shelley};
+
+use crate::{
+    time::{Epoch, Slot, SubSlot},
+    wellknown::GenesisValues,
+    Error,
+};
+
+/// The start or end of an era
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraBound {
+    /// Milliseconds elapsed since the system start
+    pub time: u64,
+    pub slot: Slot,
+    pub epoch: Epoch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraParams {
+    /// Number of slots in an epoch
+    pub epoch_size: u64,
+    /// Length of a slot in milliseconds
+    pub slot_length: u64,
+    /// Number of slots past the tip of the chain within which the era is
+    /// guaranteed not to end, if known
+    pub safe_zone: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraSummary {
+    pub start: EraBound,
+    /// The end of the era, or `None` if it isn't known yet
+    pub end: Option<EraBound>,
+    pub params: EraParams,
+}
+
+impl EraSummary {
+    /// The bound reached after the given number of epochs since the start of
+    /// the era
+    ///
+    /// Bounds too far away to be represented are past the horizon.
+    pub fn bound_after_epochs(&self, epochs: u64) -> Result<EraBound, Error> {
+        let slots = epochs.checked_mul(self.params.epoch_size);
+        let time = slots
+            .and_then(|x| x.checked_mul(self.params.slot_length))
+            .and_then(|x| x.checked_add(self.start.time));
+
+        match (
+            time,
+            slots.and_then(|x| x.checked_add(self.start.slot)),
+            epochs.checked_add(self.start.epoch),
+        ) {
+            (Some(time), Some(slot), Some(epoch)) => Ok(EraBound { time, slot, epoch }),
+            _ => Err(Error::PastHorizon),
+        }
+    }
+
+    fn contains_slot(&self, slot: Slot) -> bool {
+        self.start.slot <= slot && self.end.is_none_or(|end| slot < end.slot)
+    }
+
+    fn contains_epoch(&self, epoch: Epoch) -> bool {
+        self.start.epoch <= epoch && self.end.is_none_or(|end| epoch < end.epoch)
+    }
+
+    fn contains_time(&self, time: u64) -> bool {
+        self.start.time <= time && self.end.is_none_or(|end| time < end.time)
+    }
+}
+
+/// The summaries of the eras of a network, in chronological order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraHistory {
+    system_start: u64,
+    eras: Vec<EraSummary>,
+}
+
+impl EraHistory {
+    /// Builds a history from the unix time of the system start, in seconds,
+    /// and the summaries of its eras
+    ///
+    /// Each era must start where the previous one ends, and only the last one
+    /// may have an unknown end.
+    pub fn new(system_start: u64, eras: Vec<EraSummary>) -> Result<Self, Error> {
+        if eras.is_empty() {
+            return Err(Error::InvalidEraHistory("no eras".into()));
+        }
+
+        if eras
+            .iter()
+            .any(|x| x.params.epoch_size == 0 || x.params.slot_length == 0)
+        {
+            return Err(Error::InvalidEraHistory(
+                "epoch size and slot length must be positive".into(),
+            ));
+        }
+
+        if eras.windows(2).any(|x| x[0].end != Some(x[1].start)) {
+            return Err(Error::InvalidEraHistory("eras aren't contiguous".into()));
+        }
+
+        Ok(Self { system_start, eras })
+    }
+
+    /// Builds a history from the result of the `GetInterpreter` local state
+    /// query, given the system start of the network
+    ///
+    /// The end of the last era is the horizon up to which the node can
+    /// convert slots, and so is this history.
+    pub fn from_interpreter(system_start: u64, cbor: &[u8]) -> Result<Self, Error> {
+        let eras: Vec<EraSummary> = minicbor::decode(cbor).map_err(Error::invalid_cbor)?;
+
+        Self::new(system_start, eras)
+    }
+
+    /// Builds a history from the Byron and Shelley genesis files of a network,
+    /// given the epoch of the hard fork into Shelley
+    ///
+    /// Eras after Shelley keep its parameters, so they are all covered by the
+    /// Shelley summary, which has no end.
+    pub fn from_genesis(
+        byron: &byron::GenesisFile,
+        shelley: &shelley::GenesisFile,
+        shelley_start_epoch: Epoch,
+    ) -> Result<Self, Error> {
+        let byron_k = byron.protocol_consts.k as u64;
+
+        let byron_slot_length = byron
+            .block_version_data
+            .slot_duration
+            .parse()
+            .map_err(|_| Error::InvalidEraHistory("invalid Byron slot duration".into()))?;
+
+        let byron_era = EraSummary {
+            start: EraBound {
+                time: 0,
+                slot: 0,
+                epoch: 0,
+            },
+            end: None,
+            params: EraParams {
+                epoch_size: 10 * byron_k,
+                slot_length: byron_slot_length,
+                safe_zone: Some(2 * byron_k),
+            },
+        };
+
+        let shelley_start = byron_era.bound_after_epochs(shelley_start_epoch)?;
+
+        let byron_era = EraSummary {
+            end: Some(shelley_start),
+            ..byron_era
+        };
+
+        let shelley_safe_zone = 3.0 * shelley.security_param as f64 / shelley.active_slots_coeff;
+
+        let shelley_era = EraSummary {
+            start: shelley_start,
+            end: None,
+            params: EraParams {
+                epoch_size: shelley.epoch_length,
+                slot_length: (shelley.slot_length * 1000.0).round() as u64,
+                safe_zone: Some(shelley_safe_zone.ceil() as u64),
+            },
+        };
+
+        Self::new(byron.start_time, vec![byron_era, shelley_era])
+    }
+
+    /// Unix time of the system start, in seconds
+    pub fn system_start(&self) -> u64 {
+        self.system_start
+    }
+
+    pub fn eras(&self) -> &[EraSummary] {
+        &self.eras
+    }
+
+    /// Milliseconds elapsed between the system start and the start of the
+    /// given slot
+    pub fn slot_to_relative_time(&self, slot: Slot) -> Result<u64, Error> {
+        let era = self.find_era(|x| x.contains_slot(slot))?;
+
+        (slot - era.start.slot)
+            .checked_mul(era.params.slot_length)
+            .and_then(|x| x.checked_add(era.start.time))
+            .ok_or(Error::PastHorizon)
+    }
+
+    /// Unix time of the start of the given slot, in seconds
+    pub fn slot_to_wallclock(&self, slot: Slot) -> Result<u64, Error> {
+        let time = self.slot_to_relative_time(slot)?;
+
+        Ok(self.system_start + time / 1000)
+    }
+
+    /// The slot ongoing at the given unix time, in seconds
+    pub fn wallclock_to_slot(&self, wallclock: u64) -> Result<Slot, Error> {
+        let time = wallclock
+            .checked_sub(self.system_start)
+            .and_then(|x| x.checked_mul(1000))
+            .ok_or(Error::PastHorizon)?;
+
+        let era = self.find_era(|x| x.contains_time(time))?;
+
+        Ok(era.start.slot + (time - era.start.time) / era.params.slot_length)
+    }
+
+    /// The epoch of the given slot, together with the position of the slot
+    /// within the epoch
+    pub fn slot_to_epoch(&self, slot: Slot) -> Result<(Epoch, SubSlot), Error> {
+        let era = self.find_era(|x| x.contains_slot(slot))?;
+        let era_slot = slot - era.start.slot;
+
+        Ok((
+            era.start.epoch + era_slot / era.params.epoch_size,
+            era_slot % era.params.epoch_size,
+        ))
+    }
+
+    /// The absolute slot at the given position within an epoch
+    pub fn epoch_to_slot(&self, epoch: Epoch, sub_slot: SubSlot) -> Result<Slot, Error> {
+        let era = self.find_era(|x| x.contains_epoch(epoch))?;
+
+        era.bound_after_epochs(epoch - era.start.epoch)?
+            .slot
+            .checked_add(sub_slot)
+            .ok_or(Error::PastHorizon)
+    }
+
+    // Points outside of the history are either past its horizon or, for times,
+    // before the system start.
+    fn find_era(&self, predicate: impl Fn(&EraSummary) -> bool) -> Result<&EraSummary, Error> {
+        self.eras
+            .iter()
+            .find(|x| predicate(x))
+            .ok_or(Error::PastHorizon)
+    }
+}
+
+impl From<&GenesisValues> for EraHistory {
+    /// Builds the two-era history assumed by the well-known values, where the
+    /// safe zones are unknown
+    fn from(genesis: &GenesisValues) -> Self {
+        let byron_start = EraBound {
+            time: 0,
+            slot: genesis.byron_known_slot,
+            epoch: 0,
+        };
+
+        let shelley_start = EraBound {
+            time: (genesis.shelley_known_time - genesis.byron_known_time) * 1000,
+            slot: genesis.shelley_known_slot,
+            epoch: genesis.shelley_start_epoch(),
+        };
+
+        let eras = vec![
+            EraSummary {
+                start: byron_start,
+                end: Some(shelley_start),
+                params: EraParams {
+                    epoch_size: (genesis.byron_epoch_length / genesis.byron_slot_length) as u64,
+                    slot_length: genesis.byron_slot_length as u64 * 1000,
+                    safe_zone: None,
+                },
+            },
+            EraSummary {
+                start: shelley_start,
+                end: None,
+                params: EraParams {
+                    epoch_size: (genesis.shelley_epoch_length / genesis.shelley_slot_length) as u64,
+                    slot_length: genesis.shelley_slot_length as u64 * 1000,
+                    safe_zone: None,
+                },
+            },
+        ];
+
+        Self {
+            system_start: genesis.byron_known_time,
+            eras,
+        }
+    }
+}
+
+// The node reports relative times in picoseconds, which overflow 64 bits a few
+// months after the system start, hence may be encoded as big numbers.
+fn decode_relative_time(d: &mut Decoder<'_>) -> Result<u64, minicbor::decode::Error> {
+    const PICOS_PER_MILLI: u128 = 1_000_000_000;
+
+    let picos = match d.datatype()? {
+        Type::Tag => {
+            if d.tag()? != Tag::PosBignum {
+                return Err(minicbor::decode::Error::message("invalid relative time"));
+            }
+
+            let bytes = d.bytes()?;
+
+            if bytes.len() > 16 {
+                return Err(minicbor::decode::Error::message("relative time too large"));
+            }
+
+            bytes.iter().fold(0u128, |acc, x| (acc << 8) | *x as u128)
+        }
+        _ => d.u64()? as u128,
+    };
+
+    u64::try_from(picos / PICOS_PER_MILLI)
+        .map_err(|_| minicbor::decode::Error::message("relative time too large"))
+}
+
+impl<'b, C> Decode<'b, C> for EraBound {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+
+        let time = decode_relative_time(d)?;
+        let slot = d.u64()?;
+        let epoch = d.u64()?;
+
+        Ok(Self { time, slot, epoch })
+    }
+}
+
+impl<'b, C> Decode<'b, C> for EraParams {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        let len = d.array()?;
+
+        let epoch_size = d.u64()?;
+        let slot_length = d.u64()?;
+
+        // [0, safe_from_tip, safe_before_epoch] / [1] for an indefinite zone
+        d.array()?;
+        let safe_zone = match d.u8()? {
+            0 => {
+                let zone = d.u64()?;
+                d.skip()?;
+                Some(zone)
+            }
+            1 => None,
+            _ => return Err(minicbor::decode::Error::message("invalid safe zone")),
+        };
+
+        // newer nodes append the genesis window
+        if len == Some(4) {
+            d.skip()?;
+        }
+
+        Ok(Self {
+            epoch_size,
+            slot_length,
+            safe_zone,
+        })
+    }
+}
+
+impl<'b, C> Decode<'b, C> for EraSummary {
+    fn decode(d: &mut Decoder<'b>, ctx: &mut C) -> Result<Self, minicbor::decode::Error> {
+        d.array()?;
+
+        let start = d.decode_with(ctx)?;
+
+        let end = match d.datatype()? {
+            Type::Null => {
+                d.null()?;
+                None
+            }
+            _ => Some(d.decode_with(ctx)?),
+        };
+
+        let params = d.decode_with(ctx)?;
+
+        Ok(Self { start, end, params })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::minicbor::Encoder;
+
+    use super::*;
+
+    fn test_data_path(name: &str) -> std::path::PathBuf {
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("test_data")
+            .join(name)
+    }
+
+    #[test]
+    fn matches_well_known_values() {
+        for genesis in [GenesisValues::mainnet(), GenesisValues::preprod()] {
+            let history = EraHistory::from(&genesis);
+
+            for slot in [0, 1_000, 4_492_799, 4_492_800, 86_400, 72_316_896] {
+                assert_eq!(
+                    history.slot_to_wallclock(slot).unwrap(),
+                    genesis.slot_to_wallclock(slot)
+                );
+
+                let (epoch, sub_slot) = history.slot_to_epoch(slot).unwrap();
+                assert_eq!(epoch, genesis.absolute_slot_to_relative(slot).0);
+                assert_eq!(history.epoch_to_slot(epoch, sub_slot).unwrap(), slot);
+
+                let wallclock = genesis.slot_to_wallclock(slot);
+                assert_eq!(history.wallclock_to_slot(wallclock).unwrap(), slot);
+            }
+        }
+    }
+
+    #[test]
+    fn builds_from_genesis_files() {
+        let byron = byron::from_file(&test_data_path("preview-byron-genesis.json")).unwrap();
+        let shelley = shelley::from_file(&test_data_path("preview-shelley-genesis.json")).unwrap();
+
+        // preview starts straight into Shelley
+        let history = EraHistory::from_genesis(&byron, &shelley, 0).unwrap();
+
+        assert_eq!(history.eras()[0].start, history.eras()[0].end.unwrap());
+        assert_eq!(history.eras()[1].params.safe_zone, Some(25920));
+        assert_eq!(history.slot_to_wallclock(0).unwrap(), 1666656000);
+        assert_eq!(history.slot_to_epoch(86_401).unwrap(), (1, 1));
+        assert_eq!(history.epoch_to_slot(2, 5).unwrap(), 172_805);
+    }
+
+    #[test]
+    fn custom_layout_from_interpreter() {
+        // a devnet with 100ms Byron slots for 2 epochs, followed by an era of
+        // 500ms slots whose horizon is known up to epoch 5
+        let mut cbor = Vec::new();
+        let mut e = Encoder::new(&mut cbor);
+        e.begin_array().unwrap();
+
+        e.array(3).unwrap();
+        e.array(3)
+            .unwrap()
+            .u64(0)
+            .unwrap()
+            .u64(0)
+            .unwrap()
+            .u64(0)
+            .unwrap();
+        e.array(3)
+            .unwrap()
+            .u64(20_000_000_000_000)
+            .unwrap()
+            .u64(200)
+            .unwrap()
+            .u64(2)
+            .unwrap();
+        e.array(3).unwrap().u64(100).unwrap().u64(100).unwrap();
+        e.array(3).unwrap().u8(0).unwrap().u64(20).unwrap();
+        e.array(1).unwrap().u8(0).unwrap();
+
+        e.array(3).unwrap();
+        e.array(3)
+            .unwrap()
+            .u64(20_000_000_000_000)
+            .unwrap()
+            .u64(200)
+            .unwrap()
+            .u64(2)
+            .unwrap();
+        // 170s expressed as a big number of picoseconds
+        e.array(3).unwrap();
+        e.tag(Tag::PosBignum)
+            .unwrap()
+            .bytes(&170_000_000_000_000u128.to_be_bytes()[8..])
+            .unwrap();
+        e.u64(500).unwrap().u64(5).unwrap();
+        e.array(4).unwrap().u64(100).unwrap().u64(500).unwrap();
+        e.array(1).unwrap().u8(1).unwrap();
+        e.u64(30).unwrap();
+
+        e.end().unwrap();
+
+        let history = EraHistory::from_interpreter(1_700_000_000, &cbor).unwrap();
+
+        assert_eq!(history.eras()[0].params.safe_zone, Some(20));
+        assert_eq!(history.eras()[1].params.safe_zone, None);
+
+        assert_eq!(history.slot_to_relative_time(150).unwrap(), 15_000);
+        assert_eq!(history.slot_to_relative_time(250).unwrap(), 45_000);
+        assert_eq!(history.slot_to_wallclock(250).unwrap(), 1_700_000_045);
+        assert_eq!(history.wallclock_to_slot(1_700_000_045).unwrap(), 250);
+        assert_eq!(history.slot_to_epoch(250).unwrap(), (2, 50));
+        assert_eq!(history.epoch_to_slot(4, 10).unwrap(), 410);
+
+        assert!(matches!(
+            history.slot_to_wallclock(500),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            history.epoch_to_slot(5, 0),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            history.wallclock_to_slot(1_699_999_999),
+            Err(Error::PastHorizon)
+        ));
+    }
+
+    #[test]
+    fn overflows_are_past_the_horizon() {
+        let history = EraHistory::from(&GenesisValues::mainnet());
+        let shelley = &history.eras()[1];
+
+        assert!(matches!(
+            shelley.bound_after_epochs(u64::MAX / shelley.params.epoch_size + 1),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            shelley.bound_after_epochs(u64::MAX / shelley.params.epoch_size),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            history.slot_to_relative_time(u64::MAX),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            history.wallclock_to_slot(u64::MAX),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            history.epoch_to_slot(u64::MAX, 0),
+            Err(Error::PastHorizon)
+        ));
+        assert!(matches!(
+            history.epoch_to_slot(1, u64::MAX),
+            Err(Error::PastHorizon)
+        ));
+    }
+
+    #[test]
+    fn reads_interpreter_response() {
+        let hex = std::fs::read_to_string(test_data_path("localstate/interpreter.response"));
+        let msg = hex::decode(hex.unwrap().trim()).unwrap();
+
+        // the result follows the header of the [4, result] message
+        let history = EraHistory::from_interpreter(1_666_656_000, &msg[2..]).unwrap();
+
+        assert_eq!(history.eras().len(), 3);
+        assert_eq!(history.eras()[1].start.time, 1_728_000_000);
+        assert_eq!(history.eras()[1].end.unwrap().time, 19_008_000_000);
+        assert_eq!(history.eras()[2].end, None);
+
+        assert_eq!(
+            history.slot_to_relative_time(86_410).unwrap(),
+            1_728_010_000
+        );
+        assert_eq!(history.slot_to_epoch(17_366_400).unwrap(), (204, 0));
+        assert_eq!(history.slot_to_wallclock(0).unwrap(), 1_666_656_000);
+    }
+
+    #[test]
+    fn rejects_gaps_between_eras() {
+        let mut eras = EraHistory::from(&GenesisValues::mainnet()).eras().to_vec();
+        eras[1].start.slot += 1;
+
+        assert!(matches!(
+            EraHistory::new(0, eras),
+            Err(Error::InvalidEraHistory(_))
+        ));
+    }
+}
@@ -1,12 +1,12 @@
 //! Utilities to traverse over multi-era block data
 
-use std::{borrow::Cow, fmt::Display, hash::Hash as StdHash};
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display, hash::Hash as StdHash};
 
 use thiserror::Error;
 
 use pallas_codec::utils::{KeepRaw, KeyValuePairs};
 use pallas_crypto::hash::Hash;
-use pallas_primitives::{alonzo, babbage, byron};
+use pallas_primitives::{alonzo, babbage, byron, conway};
 
 mod support;
 
@@ -14,18 +14,26 @@ pub mod assets;
 pub mod auxiliary;
 pub mod block;
 pub mod cert;
+pub mod envelope;
 pub mod era;
 pub mod fees;
 pub mod hashes;
 pub mod header;
+pub mod history;
 pub mod input;
 pub mod meta;
+pub mod native_script;
+pub mod nft;
 pub mod output;
+pub mod owned;
 pub mod probe;
+pub mod redeemers;
+pub mod script_data;
 pub mod signers;
 pub mod size;
 pub mod time;
 pub mod tx;
+pub mod value;
 pub mod withdrawals;
 pub mod witnesses;
 
@@ -41,6 +49,7 @@ pub enum Era {
     Mary,    // multi-assets
     Alonzo,  // smart-contracts
     Babbage, // CIP-31/32/33
+    Conway,  // governance (CIP-1694)
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -53,6 +62,7 @@ pub enum Feature {
     CIP31,
     CIP32,
     CIP33,
+    CIP1694,
 }
 
 #[derive(Debug)]
@@ -70,6 +80,7 @@ pub enum MultiEraBlock<'b> {
     AlonzoCompatible(Box<alonzo::MintedBlock<'b>>, Era),
     Babbage(Box<babbage::MintedBlock<'b>>),
     Byron(Box<byron::MintedBlock<'b>>),
+    Conway(Box<conway::MintedBlock<'b>>),
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +89,7 @@ pub enum MultiEraTx<'b> {
     AlonzoCompatible(Box<Cow<'b, alonzo::MintedTx<'b>>>, Era),
     Babbage(Box<Cow<'b, babbage::MintedTx<'b>>>),
     Byron(Box<Cow<'b, byron::MintedTxPayload<'b>>>),
+    Conway(Box<Cow<'b, conway::MintedTx<'b>>>),
 }
 
 #[derive(Debug, Clone)]
@@ -86,6 +98,7 @@ pub enum MultiEraOutput<'b> {
     AlonzoCompatible(Box<Cow<'b, alonzo::TransactionOutput>>),
     Babbage(Box<Cow<'b, babbage::MintedTransactionOutput<'b>>>),
     Byron(Box<Cow<'b, byron::TxOut>>),
+    Conway(Box<Cow<'b, conway::MintedTransactionOutput<'b>>>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, StdHash)]
@@ -100,6 +113,7 @@ pub enum MultiEraInput<'b> {
 pub enum MultiEraCert<'b> {
     NotApplicable,
     AlonzoCompatible(Box<Cow<'b, alonzo::Certificate>>),
+    Conway(Box<Cow<'b, conway::Certificate>>),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -131,6 +145,16 @@ pub enum MultiEraAsset<'b> {
     AlonzoCompatibleMint(&'b alonzo::PolicyId, &'b alonzo::AssetName, i64),
 }
 
+/// Lovelace and native assets with signed amounts, so that mints, burns and
+/// differences between values are represented alike
+///
+/// Zero amounts are never kept, hence equal values compare as such.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiEraValue {
+    coin: i128,
+    assets: BTreeMap<alonzo::PolicyId, BTreeMap<alonzo::AssetName, i128>>,
+}
+
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum MultiEraWithdrawals<'b> {
@@ -145,6 +169,17 @@ pub enum MultiEraSigners<'b> {
     NotApplicable,
     Empty,
     AlonzoCompatible(&'b alonzo::RequiredSigners),
+    Conway(&'b conway::RequiredSigners),
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum MultiEraRedeemer<'b> {
+    AlonzoCompatible(Box<Cow<'b, alonzo::Redeemer>>),
+    Conway(
+        Box<Cow<'b, conway::RedeemersKey>>,
+        Box<Cow<'b, conway::RedeemersValue>>,
+    ),
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -166,6 +201,24 @@ pub enum Error {
 
     #[error("Invalid UTxO ref: {0}")]
     InvalidUtxoRef(String),
+
+    #[error("Unresolved UTxO ref: {0}")]
+    UnresolvedUtxoRef(OutputRef),
+
+    #[error("Invalid redeemer pointer: {0:?} {1}")]
+    InvalidRedeemerPointer(conway::RedeemerTag, u32),
+
+    #[error("Invalid era history: {0}")]
+    InvalidEraHistory(String),
+
+    #[error("Past the horizon of the era history")]
+    PastHorizon,
+
+    #[error("Block body hash mismatch: declared {0}, computed {1}")]
+    BodyHashMismatch(Hash<32>, Hash<32>),
+
+    #[error("Unknown genesis key: {0}")]
+    UnknownGenesisKey(Hash<28>),
 }
 
 impl Error {
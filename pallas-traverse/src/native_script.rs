@@ -0,0 +1,193 @@
+//! Evaluation of native scripts, which combine multi-signature and time-lock
+//! constraints
+
+use std::collections::HashSet;
+
+use pallas_primitives::alonzo::{AddrKeyhash, NativeScript};
+
+use crate::MultiEraTx;
+
+/// The slot bounds of the validity interval of a tx
+///
+/// Both bounds are optional: a tx lacking one of them is valid from the
+/// beginning or until the end of time, respectively.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValidityInterval {
+    /// First slot in which the tx is valid
+    pub invalid_before: Option<u64>,
+    /// First slot in which the tx is no longer valid (i.e., its ttl)
+    pub invalid_hereafter: Option<u64>,
+}
+
+impl ValidityInterval {
+    pub fn new(invalid_before: Option<u64>, invalid_hereafter: Option<u64>) -> Self {
+        Self {
+            invalid_before,
+            invalid_hereafter,
+        }
+    }
+}
+
+impl MultiEraTx<'_> {
+    pub fn validity_interval(&self) -> ValidityInterval {
+        ValidityInterval::new(self.validity_start(), self.ttl())
+    }
+}
+
+/// Evaluates a native script against the key hashes that signed a tx and its
+/// validity interval
+///
+/// Time-lock constraints are checked against the validity interval rather
+/// than against any particular slot: they are satisfied only when the
+/// corresponding bound is present and at least as strict as the constraint.
+pub fn eval_native_script(
+    script: &NativeScript,
+    vkey_hashes: &HashSet<AddrKeyhash>,
+    validity: &ValidityInterval,
+) -> bool {
+    let eval = |s: &NativeScript| eval_native_script(s, vkey_hashes, validity);
+
+    match script {
+        NativeScript::ScriptPubkey(hash) => vkey_hashes.contains(hash),
+        NativeScript::ScriptAll(scripts) => scripts.iter().all(eval),
+        NativeScript::ScriptAny(scripts) => scripts.iter().any(eval),
+        NativeScript::ScriptNOfK(n, scripts) => {
+            scripts.iter().filter(|s| eval(s)).count() >= *n as usize
+        }
+        NativeScript::InvalidBefore(slot) => {
+            matches!(validity.invalid_before, Some(start) if *slot <= start)
+        }
+        NativeScript::InvalidHereafter(slot) => {
+            matches!(validity.invalid_hereafter, Some(end) if end <= *slot)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pallas_crypto::hash::Hash;
+
+    fn key(n: u8) -> AddrKeyhash {
+        Hash::from([n; 28])
+    }
+
+    fn signed_by(keys: &[u8]) -> HashSet<AddrKeyhash> {
+        keys.iter().copied().map(key).collect()
+    }
+
+    fn unbounded() -> ValidityInterval {
+        ValidityInterval::default()
+    }
+
+    #[test]
+    fn script_pubkey() {
+        let script = NativeScript::ScriptPubkey(key(1));
+
+        assert!(eval_native_script(
+            &script,
+            &signed_by(&[0, 1]),
+            &unbounded()
+        ));
+        assert!(!eval_native_script(&script, &signed_by(&[0]), &unbounded()));
+    }
+
+    #[test]
+    fn empty_combinators() {
+        let signers = signed_by(&[]);
+
+        let all = NativeScript::ScriptAll(vec![]);
+        assert!(eval_native_script(&all, &signers, &unbounded()));
+
+        let any = NativeScript::ScriptAny(vec![]);
+        assert!(!eval_native_script(&any, &signers, &unbounded()));
+
+        let none_of_none = NativeScript::ScriptNOfK(0, vec![]);
+        assert!(eval_native_script(&none_of_none, &signers, &unbounded()));
+
+        let one_of_none = NativeScript::ScriptNOfK(1, vec![]);
+        assert!(!eval_native_script(&one_of_none, &signers, &unbounded()));
+    }
+
+    #[test]
+    fn multisig() {
+        let keys = || {
+            (1..=3)
+                .map(|n| NativeScript::ScriptPubkey(key(n)))
+                .collect()
+        };
+
+        let all = NativeScript::ScriptAll(keys());
+        assert!(eval_native_script(
+            &all,
+            &signed_by(&[1, 2, 3]),
+            &unbounded()
+        ));
+        assert!(!eval_native_script(&all, &signed_by(&[1, 3]), &unbounded()));
+
+        let any = NativeScript::ScriptAny(keys());
+        assert!(eval_native_script(&any, &signed_by(&[2]), &unbounded()));
+        assert!(!eval_native_script(&any, &signed_by(&[4]), &unbounded()));
+
+        let two_of_three = NativeScript::ScriptNOfK(2, keys());
+        assert!(eval_native_script(
+            &two_of_three,
+            &signed_by(&[1, 3]),
+            &unbounded()
+        ));
+        assert!(!eval_native_script(
+            &two_of_three,
+            &signed_by(&[2, 4]),
+            &unbounded()
+        ));
+    }
+
+    #[test]
+    fn invalid_before() {
+        let script = NativeScript::InvalidBefore(100);
+        let signers = signed_by(&[]);
+
+        let eval =
+            |start| eval_native_script(&script, &signers, &ValidityInterval::new(start, None));
+
+        assert!(eval(Some(100)));
+        assert!(eval(Some(101)));
+        assert!(!eval(Some(99)));
+        assert!(!eval(None));
+    }
+
+    #[test]
+    fn invalid_hereafter() {
+        let script = NativeScript::InvalidHereafter(100);
+        let signers = signed_by(&[]);
+
+        let eval = |ttl| eval_native_script(&script, &signers, &ValidityInterval::new(None, ttl));
+
+        assert!(eval(Some(100)));
+        assert!(eval(Some(99)));
+        assert!(!eval(Some(101)));
+        assert!(!eval(None));
+    }
+
+    #[test]
+    fn timelocked_multisig() {
+        // key 1 may spend anytime, key 2 only within slots [100, 200)
+        let script = NativeScript::ScriptAny(vec![
+            NativeScript::ScriptPubkey(key(1)),
+            NativeScript::ScriptAll(vec![
+                NativeScript::ScriptPubkey(key(2)),
+                NativeScript::InvalidBefore(100),
+                NativeScript::InvalidHereafter(200),
+            ]),
+        ]);
+
+        let within = ValidityInterval::new(Some(120), Some(180));
+        let too_wide = ValidityInterval::new(Some(120), Some(250));
+
+        assert!(eval_native_script(&script, &signed_by(&[1]), &unbounded()));
+        assert!(eval_native_script(&script, &signed_by(&[2]), &within));
+        assert!(!eval_native_script(&script, &signed_by(&[2]), &too_wide));
+        assert!(!eval_native_script(&script, &signed_by(&[2]), &unbounded()));
+    }
+}
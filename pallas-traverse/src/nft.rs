@@ -0,0 +1,564 @@
+//! Token metadata as defined by CIP-25 and CIP-68
+//!
+//! CIP-25 metadata is attached to the tx minting the tokens, under the 721
+//! label, while CIP-68 metadata lives in the inline datum of an output holding
+//! the reference NFT of the token, whose name carries the (100) label of
+//! CIP-67 in front of the name shared with the user token. Both are parsed
+//! into the same [TokenMetadata] struct.
+
+use std::ops::Deref;
+
+use pallas_primitives::{
+    alonzo::{self, Metadatum, PlutusData, PolicyId},
+    babbage,
+};
+
+use crate::{MultiEraTx, OriginalHash};
+
+/// The metadata label of CIP-25
+pub const CIP25_LABEL: alonzo::MetadatumLabel = 721;
+
+/// The CIP-67 label of CIP-68 reference NFTs, which hold the metadata
+pub const REFERENCE_NFT_LABEL: u16 = 100;
+
+/// The CIP-67 label of CIP-68 user NFTs
+pub const NFT_LABEL: u16 = 222;
+
+/// The CIP-67 label of CIP-68 user fungible tokens
+pub const FT_LABEL: u16 = 333;
+
+/// The CIP-67 label of CIP-68 user rich fungible tokens
+pub const RFT_LABEL: u16 = 444;
+
+/// A metadata value, as found either in tx metadata or in Plutus data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataValue {
+    Int(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    List(Vec<MetadataValue>),
+    Map(Vec<(MetadataValue, MetadataValue)>),
+}
+
+impl MetadataValue {
+    /// Reads the value as text, joining the chunks of strings split to fit
+    /// the 64 bytes limit of metadata, and decoding byte strings as utf8
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Self::Text(x) => Some(x.clone()),
+            Self::Bytes(x) => String::from_utf8(x.clone()).ok(),
+            Self::List(x) => x.iter().map(|x| x.as_text()).collect(),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i128> {
+        match self {
+            Self::Int(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Metadatum> for MetadataValue {
+    fn from(value: &Metadatum) -> Self {
+        match value {
+            Metadatum::Int(x) => Self::Int(i128::from(*x)),
+            Metadatum::Bytes(x) => Self::Bytes(x.to_vec()),
+            Metadatum::Text(x) => Self::Text(x.clone()),
+            Metadatum::Array(x) => Self::List(x.iter().map(Self::from).collect()),
+            Metadatum::Map(x) => Self::Map(x.iter().map(|(k, v)| (k.into(), v.into())).collect()),
+        }
+    }
+}
+
+impl From<&PlutusData> for MetadataValue {
+    fn from(value: &PlutusData) -> Self {
+        match value {
+            PlutusData::Constr(x) => Self::List(x.fields.iter().map(Self::from).collect()),
+            PlutusData::Map(x) => Self::Map(x.iter().map(|(k, v)| (k.into(), v.into())).collect()),
+            PlutusData::BigInt(alonzo::BigInt::Int(x)) => Self::Int(i128::from(*x)),
+            PlutusData::BigInt(alonzo::BigInt::BigUInt(x)) => Self::Bytes(x.to_vec()),
+            PlutusData::BigInt(alonzo::BigInt::BigNInt(x)) => Self::Bytes(x.to_vec()),
+            PlutusData::BoundedBytes(x) => Self::Bytes(x.to_vec()),
+            PlutusData::Array(x) => Self::List(x.iter().map(Self::from).collect()),
+        }
+    }
+}
+
+/// The standard, and version thereof, that token metadata follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataStandard {
+    Cip25(u64),
+    Cip68(u64),
+}
+
+/// A file attached to a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenFile {
+    pub name: Option<String>,
+    pub media_type: Option<String>,
+    pub src: String,
+}
+
+/// The metadata of a token, either from CIP-25 or CIP-68
+///
+/// For CIP-68, the asset name is the one shared by the reference and user
+/// tokens, without their CIP-67 label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub policy: PolicyId,
+    pub asset_name: Vec<u8>,
+    pub standard: MetadataStandard,
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub media_type: Option<String>,
+    pub description: Option<String>,
+    pub files: Vec<TokenFile>,
+
+    /// The fields other than the ones above, with text keys
+    pub extra: Vec<(String, MetadataValue)>,
+}
+
+impl TokenMetadata {
+    fn from_fields(
+        policy: PolicyId,
+        asset_name: Vec<u8>,
+        standard: MetadataStandard,
+        fields: &[(MetadataValue, MetadataValue)],
+    ) -> Self {
+        let mut out = Self {
+            policy,
+            asset_name,
+            standard,
+            name: None,
+            image: None,
+            media_type: None,
+            description: None,
+            files: vec![],
+            extra: vec![],
+        };
+
+        for (key, value) in fields {
+            let key = match key.as_text() {
+                Some(x) => x,
+                None => continue,
+            };
+
+            match key.as_str() {
+                "name" => out.name = value.as_text(),
+                "image" => out.image = value.as_text(),
+                "mediaType" => out.media_type = value.as_text(),
+                "description" => out.description = value.as_text(),
+                "files" => out.files = parse_files(value),
+                _ => out.extra.push((key, value.clone())),
+            }
+        }
+
+        out
+    }
+}
+
+fn find_field<'a>(
+    fields: &'a [(MetadataValue, MetadataValue)],
+    key: &str,
+) -> Option<&'a MetadataValue> {
+    fields
+        .iter()
+        .find(|(k, _)| k.as_text().as_deref() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn parse_files(value: &MetadataValue) -> Vec<TokenFile> {
+    let files = match value {
+        MetadataValue::List(x) => x,
+        _ => return vec![],
+    };
+
+    files
+        .iter()
+        .filter_map(|file| match file {
+            MetadataValue::Map(fields) => Some(TokenFile {
+                name: find_field(fields, "name").and_then(|x| x.as_text()),
+                media_type: find_field(fields, "mediaType").and_then(|x| x.as_text()),
+                src: find_field(fields, "src")?.as_text()?,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+// The checksum of CIP-67 labels is a CRC-8 with polynomial 0x07.
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+
+    for byte in bytes {
+        crc ^= byte;
+
+        for _ in 0..8 {
+            crc = match crc & 0x80 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x07,
+            };
+        }
+    }
+
+    crc
+}
+
+/// Splits an asset name into its CIP-67 label and the rest of the name, or
+/// returns `None` if the name doesn't start with a valid label
+pub fn split_asset_label(asset_name: &[u8]) -> Option<(u16, &[u8])> {
+    let prefix: [u8; 4] = asset_name.get(..4)?.try_into().ok()?;
+    let prefix = u32::from_be_bytes(prefix);
+
+    // the label is enclosed in zero nibbles
+    if prefix >> 28 != 0 || prefix & 0x0f != 0 {
+        return None;
+    }
+
+    let label = (prefix >> 12) as u16;
+    let checksum = (prefix >> 4) as u8;
+
+    match crc8(&label.to_be_bytes()) == checksum {
+        true => Some((label, &asset_name[4..])),
+        false => None,
+    }
+}
+
+/// Prepends the given CIP-67 label to an asset name
+pub fn with_asset_label(label: u16, asset_name: &[u8]) -> Vec<u8> {
+    let checksum = crc8(&label.to_be_bytes());
+    let prefix = ((label as u32) << 12) | ((checksum as u32) << 4);
+
+    [&prefix.to_be_bytes(), asset_name].concat()
+}
+
+/// Parses the CIP-25 metadata under the 721 label of a tx
+///
+/// Asset names given as text are taken as utf8, as mandated by the first
+/// version of the standard. Use [MultiEraTx::token_metadata] to match them
+/// against the assets minted by the tx, which also accepts hex names.
+pub fn parse_cip25(metadatum: &Metadatum) -> Vec<TokenMetadata> {
+    parse_cip25_with(metadatum, |_, x| x.as_bytes().to_vec())
+}
+
+fn parse_cip25_with(
+    metadatum: &Metadatum,
+    text_asset_name: impl Fn(&PolicyId, &str) -> Vec<u8>,
+) -> Vec<TokenMetadata> {
+    let policies = match metadatum {
+        Metadatum::Map(x) => x,
+        _ => return vec![],
+    };
+
+    let version = policies
+        .iter()
+        .find(|(k, _)| matches!(k, Metadatum::Text(x) if x == "version"))
+        .and_then(|(_, v)| MetadataValue::from(v).as_int())
+        .and_then(|x| u64::try_from(x).ok())
+        .unwrap_or(1);
+
+    let mut out = vec![];
+
+    for (policy, assets) in policies.iter() {
+        let policy = match policy {
+            Metadatum::Text(x) => hex::decode(x).ok(),
+            Metadatum::Bytes(x) => Some(x.to_vec()),
+            _ => None,
+        };
+
+        let policy = match policy {
+            Some(x) if x.len() == 28 => PolicyId::from(x.as_slice()),
+            _ => continue,
+        };
+
+        let assets = match assets {
+            Metadatum::Map(x) => x,
+            _ => continue,
+        };
+
+        for (asset_name, fields) in assets.iter() {
+            let asset_name = match asset_name {
+                Metadatum::Text(x) => text_asset_name(&policy, x),
+                Metadatum::Bytes(x) => x.to_vec(),
+                _ => continue,
+            };
+
+            if let MetadataValue::Map(fields) = MetadataValue::from(fields) {
+                out.push(TokenMetadata::from_fields(
+                    policy,
+                    asset_name,
+                    MetadataStandard::Cip25(version),
+                    &fields,
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+/// Parses the CIP-68 datum `[metadata, version, extra]` held next to the
+/// reference NFT of the given name
+///
+/// Returns `None` if the name doesn't carry the reference NFT label or the
+/// datum doesn't have the expected shape.
+pub fn parse_cip68(
+    policy: PolicyId,
+    reference_name: &[u8],
+    datum: &PlutusData,
+) -> Option<TokenMetadata> {
+    let (label, asset_name) = split_asset_label(reference_name)?;
+
+    if label != REFERENCE_NFT_LABEL {
+        return None;
+    }
+
+    // the datum is the first constructor, with tag 121
+    let fields = match datum {
+        PlutusData::Constr(x) if x.tag == 121 => &x.fields,
+        _ => return None,
+    };
+
+    let metadata = match MetadataValue::from(fields.first()?) {
+        MetadataValue::Map(x) => x,
+        _ => return None,
+    };
+
+    let version = MetadataValue::from(fields.get(1)?).as_int()?;
+    let version = u64::try_from(version).ok()?;
+
+    Some(TokenMetadata::from_fields(
+        policy,
+        asset_name.to_vec(),
+        MetadataStandard::Cip68(version),
+        &metadata,
+    ))
+}
+
+impl<'b> MultiEraTx<'b> {
+    /// Returns the metadata of the tokens described by the tx, either by its
+    /// CIP-25 metadata or by the CIP-68 reference NFTs held by its outputs
+    ///
+    /// CIP-25 asset names given as text are matched against the assets
+    /// minted by the tx, both as utf8 and as hex.
+    pub fn token_metadata(&self) -> Vec<TokenMetadata> {
+        let mint = self.mint_value();
+
+        let text_asset_name = |policy: &PolicyId, name: &str| {
+            let utf8 = alonzo::AssetName::from(name.as_bytes().to_vec());
+
+            match hex::decode(name) {
+                Ok(x)
+                    if mint.asset(policy, &utf8) == 0
+                        && mint.asset(policy, &x.clone().into()) != 0 =>
+                {
+                    x
+                }
+                _ => utf8.to_vec(),
+            }
+        };
+
+        let metadata = self.metadata();
+
+        let mut out = match metadata.find(CIP25_LABEL) {
+            Some(x) => parse_cip25_with(x, text_asset_name),
+            None => vec![],
+        };
+
+        for output in self.outputs() {
+            let value = output.value();
+
+            let references: Vec<_> = value
+                .assets()
+                .filter(|(_, name, _)| {
+                    matches!(split_asset_label(name), Some((REFERENCE_NFT_LABEL, _)))
+                })
+                .collect();
+
+            if references.is_empty() {
+                continue;
+            }
+
+            let datum = match output.datum() {
+                Some(babbage::PseudoDatumOption::Data(x)) => Some(x.0.deref().clone()),
+                Some(babbage::PseudoDatumOption::Hash(hash)) => self
+                    .plutus_data()
+                    .iter()
+                    .find(|x| x.original_hash() == hash)
+                    .map(|x| x.deref().clone()),
+                None => None,
+            };
+
+            if let Some(datum) = datum {
+                out.extend(
+                    references
+                        .into_iter()
+                        .filter_map(|(policy, name, _)| parse_cip68(*policy, name, &datum)),
+                );
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::utils::{Int, KeyValuePairs};
+
+    use super::*;
+
+    fn text(x: &str) -> Metadatum {
+        Metadatum::Text(x.to_owned())
+    }
+
+    fn bytes(x: &str) -> PlutusData {
+        PlutusData::BoundedBytes(x.as_bytes().to_vec().into())
+    }
+
+    #[test]
+    fn asset_labels_match_cip67() {
+        assert_eq!(hex::encode(with_asset_label(100, &[])), "000643b0");
+        assert_eq!(hex::encode(with_asset_label(222, &[])), "000de140");
+        assert_eq!(hex::encode(with_asset_label(333, &[])), "0014df10");
+        assert_eq!(hex::encode(with_asset_label(444, &[])), "001bc280");
+
+        let name = with_asset_label(NFT_LABEL, b"abc");
+        assert_eq!(split_asset_label(&name), Some((NFT_LABEL, &b"abc"[..])));
+
+        // wrong checksum
+        assert_eq!(split_asset_label(&hex::decode("000de150").unwrap()), None);
+        assert_eq!(split_asset_label(b"abc"), None);
+    }
+
+    #[test]
+    fn cip25_v1_with_split_strings() {
+        let policy = "a0028f350aaabe0545fdcb56b039bfb08e4bb4d8c4d7c3c7d481c235";
+
+        let file = Metadatum::Map(KeyValuePairs::from(vec![
+            (text("name"), text("hd")),
+            (text("mediaType"), text("image/png")),
+            (
+                text("src"),
+                Metadatum::Array(vec![text("ipfs://"), text("Qm123")]),
+            ),
+        ]));
+
+        let fields = Metadatum::Map(KeyValuePairs::from(vec![
+            (text("name"), text("Token #1")),
+            (
+                text("image"),
+                Metadatum::Array(vec![text("ipfs://"), text("QmAbc")]),
+            ),
+            (text("files"), Metadatum::Array(vec![file])),
+            (text("rarity"), text("rare")),
+        ]));
+
+        let metadatum = Metadatum::Map(KeyValuePairs::from(vec![(
+            text(policy),
+            Metadatum::Map(KeyValuePairs::from(vec![(text("Token1"), fields)])),
+        )]));
+
+        let parsed = parse_cip25(&metadatum);
+        assert_eq!(parsed.len(), 1);
+
+        let token = &parsed[0];
+        assert_eq!(token.policy.to_string(), policy);
+        assert_eq!(token.asset_name, b"Token1");
+        assert_eq!(token.standard, MetadataStandard::Cip25(1));
+        assert_eq!(token.name.as_deref(), Some("Token #1"));
+        assert_eq!(token.image.as_deref(), Some("ipfs://QmAbc"));
+        assert_eq!(token.files[0].src, "ipfs://Qm123");
+        assert_eq!(token.files[0].media_type.as_deref(), Some("image/png"));
+        assert_eq!(
+            token.extra,
+            vec![("rarity".to_owned(), MetadataValue::Text("rare".to_owned()))]
+        );
+    }
+
+    #[test]
+    fn cip25_v2_with_byte_keys() {
+        let policy = [1u8; 28];
+
+        let metadatum = Metadatum::Map(KeyValuePairs::from(vec![
+            (
+                Metadatum::Bytes(policy.to_vec().into()),
+                Metadatum::Map(KeyValuePairs::from(vec![(
+                    Metadatum::Bytes(vec![0xff, 0x00].into()),
+                    Metadatum::Map(KeyValuePairs::from(vec![(text("name"), text("x"))])),
+                )])),
+            ),
+            (text("version"), Metadatum::Int(Int::from(2))),
+        ]));
+
+        let parsed = parse_cip25(&metadatum);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].policy, PolicyId::from(policy));
+        assert_eq!(parsed[0].asset_name, vec![0xff, 0x00]);
+        assert_eq!(parsed[0].standard, MetadataStandard::Cip25(2));
+    }
+
+    #[test]
+    fn cip68_reference_datum() {
+        let metadata = PlutusData::Map(KeyValuePairs::from(vec![
+            (bytes("name"), bytes("Token #2")),
+            (bytes("image"), bytes("ipfs://QmDef")),
+            (
+                bytes("level"),
+                PlutusData::BigInt(alonzo::BigInt::Int(Int::from(3))),
+            ),
+        ]));
+
+        let datum = PlutusData::Constr(alonzo::Constr {
+            tag: 121,
+            any_constructor: None,
+            fields: vec![
+                metadata,
+                PlutusData::BigInt(alonzo::BigInt::Int(Int::from(1))),
+                PlutusData::Array(vec![]),
+            ],
+        });
+
+        let policy = PolicyId::from([2u8; 28]);
+        let reference = with_asset_label(REFERENCE_NFT_LABEL, b"Token2");
+
+        let token = parse_cip68(policy, &reference, &datum).unwrap();
+        assert_eq!(token.asset_name, b"Token2");
+        assert_eq!(token.standard, MetadataStandard::Cip68(1));
+        assert_eq!(token.name.as_deref(), Some("Token #2"));
+        assert_eq!(token.image.as_deref(), Some("ipfs://QmDef"));
+        assert_eq!(
+            token.extra,
+            vec![("level".to_owned(), MetadataValue::Int(3))]
+        );
+
+        // user tokens don't hold metadata
+        let user = with_asset_label(NFT_LABEL, b"Token2");
+        assert_eq!(parse_cip68(policy, &user, &datum), None);
+    }
+
+    #[test]
+    fn minted_tokens_have_metadata() {
+        let cbor = hex::decode(include_str!("../../test_data/mary1.block")).unwrap();
+        let block = crate::MultiEraBlock::decode(&cbor).unwrap();
+
+        let mut found = 0;
+
+        for tx in block.txs() {
+            let mint = tx.mint_value();
+
+            for token in tx.token_metadata() {
+                assert!(matches!(token.standard, MetadataStandard::Cip25(_)));
+                assert_ne!(
+                    mint.asset(&token.policy, &token.asset_name.clone().into()),
+                    0
+                );
+                found += 1;
+            }
+        }
+
+        assert!(found > 0);
+    }
+}
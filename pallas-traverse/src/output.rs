@@ -1,8 +1,8 @@
 use std::{borrow::Cow, ops::Deref};
 
 use pallas_addresses::{Address, ByronAddress, Error as AddressError};
-use pallas_codec::minicbor;
-use pallas_primitives::{alonzo, babbage, byron};
+use pallas_codec::{minicbor, utils::CborWrap};
+use pallas_primitives::{alonzo, babbage, byron, conway};
 
 use crate::{Era, MultiEraOutput, MultiEraPolicyAssets};
 
@@ -19,6 +19,10 @@ impl<'b> MultiEraOutput<'b> {
         Self::Babbage(Box::new(Cow::Borrowed(output)))
     }
 
+    pub fn from_conway(output: &'b conway::MintedTransactionOutput<'b>) -> Self {
+        Self::Conway(Box::new(Cow::Borrowed(output)))
+    }
+
     pub fn datum(&self) -> Option<babbage::MintedDatumOption> {
         match self {
             MultiEraOutput::AlonzoCompatible(x) => {
@@ -30,15 +34,31 @@ impl<'b> MultiEraOutput<'b> {
                 }
                 babbage::MintedTransactionOutput::PostAlonzo(x) => x.datum_option.clone(),
             },
+            MultiEraOutput::Conway(x) => match x.deref().deref() {
+                conway::MintedTransactionOutput::Legacy(x) => {
+                    x.datum_hash.map(babbage::MintedDatumOption::Hash)
+                }
+                conway::MintedTransactionOutput::PostAlonzo(x) => x.datum_option.clone(),
+            },
             _ => None,
         }
     }
 
-    pub fn script_ref(&self) -> Option<&babbage::ScriptRef> {
+    /// The reference script held by the output, if any
+    ///
+    /// Babbage scripts are upgraded to their Conway counterpart, which is a
+    /// superset of them, so that scripts from every era share a single type.
+    pub fn script_ref(&self) -> Option<conway::ScriptRef> {
         match &self {
             MultiEraOutput::Babbage(x) => match x.deref().deref() {
                 babbage::MintedTransactionOutput::Legacy(_) => None,
-                babbage::MintedTransactionOutput::PostAlonzo(x) => x.script_ref.as_ref(),
+                babbage::MintedTransactionOutput::PostAlonzo(x) => {
+                    x.script_ref.as_ref().map(|x| CborWrap(x.0.clone().into()))
+                }
+            },
+            MultiEraOutput::Conway(x) => match x.deref().deref() {
+                conway::MintedTransactionOutput::Legacy(_) => None,
+                conway::MintedTransactionOutput::PostAlonzo(x) => x.script_ref.clone(),
             },
             _ => None,
         }
@@ -54,6 +74,10 @@ impl<'b> MultiEraOutput<'b> {
             MultiEraOutput::Byron(x) => {
                 Ok(ByronAddress::new(&x.address.payload.0, x.address.crc).into())
             }
+            MultiEraOutput::Conway(x) => match x.deref().deref() {
+                conway::MintedTransactionOutput::Legacy(x) => Address::from_bytes(&x.address),
+                conway::MintedTransactionOutput::PostAlonzo(x) => Address::from_bytes(&x.address),
+            },
         }
     }
 
@@ -62,6 +86,7 @@ impl<'b> MultiEraOutput<'b> {
             MultiEraOutput::AlonzoCompatible(_) => None,
             MultiEraOutput::Babbage(x) => Some(x),
             MultiEraOutput::Byron(_) => None,
+            MultiEraOutput::Conway(_) => None,
         }
     }
 
@@ -70,6 +95,7 @@ impl<'b> MultiEraOutput<'b> {
             MultiEraOutput::AlonzoCompatible(x) => Some(x),
             MultiEraOutput::Babbage(_) => None,
             MultiEraOutput::Byron(_) => None,
+            MultiEraOutput::Conway(_) => None,
         }
     }
 
@@ -78,6 +104,16 @@ impl<'b> MultiEraOutput<'b> {
             MultiEraOutput::AlonzoCompatible(_) => None,
             MultiEraOutput::Babbage(_) => None,
             MultiEraOutput::Byron(x) => Some(x),
+            MultiEraOutput::Conway(_) => None,
+        }
+    }
+
+    pub fn as_conway(&self) -> Option<&conway::MintedTransactionOutput> {
+        match self {
+            MultiEraOutput::AlonzoCompatible(_) => None,
+            MultiEraOutput::Babbage(_) => None,
+            MultiEraOutput::Byron(_) => None,
+            MultiEraOutput::Conway(x) => Some(x),
         }
     }
 
@@ -87,6 +123,7 @@ impl<'b> MultiEraOutput<'b> {
             Self::AlonzoCompatible(x) => minicbor::to_vec(x).unwrap(),
             Self::Babbage(x) => minicbor::to_vec(x).unwrap(),
             Self::Byron(x) => minicbor::to_vec(x).unwrap(),
+            Self::Conway(x) => minicbor::to_vec(x).unwrap(),
         }
     }
 
@@ -107,6 +144,11 @@ impl<'b> MultiEraOutput<'b> {
                 let tx = Box::new(Cow::Owned(tx));
                 Ok(Self::Babbage(tx))
             }
+            Era::Conway => {
+                let tx = minicbor::decode(cbor)?;
+                let tx = Box::new(Cow::Owned(tx));
+                Ok(Self::Conway(tx))
+            }
         }
     }
 
@@ -132,6 +174,16 @@ impl<'b> MultiEraOutput<'b> {
                 alonzo::Value::Coin(c) => c,
                 alonzo::Value::Multiasset(c, _) => c,
             },
+            MultiEraOutput::Conway(x) => match x.deref().deref() {
+                conway::MintedTransactionOutput::Legacy(x) => match x.amount {
+                    conway::Value::Coin(c) => c,
+                    conway::Value::Multiasset(c, _) => c,
+                },
+                conway::MintedTransactionOutput::PostAlonzo(x) => match x.value {
+                    conway::Value::Coin(c) => c,
+                    conway::Value::Multiasset(c, _) => c,
+                },
+            },
         }
     }
 
@@ -166,6 +218,22 @@ impl<'b> MultiEraOutput<'b> {
                     .map(|(k, v)| MultiEraPolicyAssets::AlonzoCompatibleOutput(k, v))
                     .collect(),
             },
+            MultiEraOutput::Conway(x) => match x.deref().deref() {
+                conway::MintedTransactionOutput::Legacy(x) => match &x.amount {
+                    conway::Value::Coin(_) => vec![],
+                    conway::Value::Multiasset(_, x) => x
+                        .iter()
+                        .map(|(k, v)| MultiEraPolicyAssets::AlonzoCompatibleOutput(k, v))
+                        .collect(),
+                },
+                conway::MintedTransactionOutput::PostAlonzo(x) => match &x.value {
+                    conway::Value::Coin(_) => vec![],
+                    conway::Value::Multiasset(_, x) => x
+                        .iter()
+                        .map(|(k, v)| MultiEraPolicyAssets::AlonzoCompatibleOutput(k, v))
+                        .collect(),
+                },
+            },
         }
     }
 }
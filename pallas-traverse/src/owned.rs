@@ -0,0 +1,306 @@
+//! Owned counterparts of the MultiEra types
+//!
+//! The MultiEra types borrow the CBOR buffer they were decoded from, which is
+//! what allows them to keep the original bytes around for hashing. The types
+//! in this module hold the buffer themselves, next to the decoded view, so
+//! they can be moved across threads, kept in caches or returned from the
+//! function that fetched the bytes.
+//!
+//! The common accessors are forwarded, the others are reached through the
+//! decoded view, which blocks lend directly. Txs and outputs are not
+//! covariant over the lifetime of their buffer, hence they lend it to a
+//! closure instead, and their accessors return owned values only.
+
+use pallas_addresses::{Address, Error as AddressError};
+use pallas_crypto::hash::Hash;
+use self_cell::self_cell;
+
+use crate::{Era, Error, MultiEraBlock, MultiEraHeader, MultiEraOutput, MultiEraTx, OutputRef};
+
+self_cell!(
+    struct BlockCell {
+        owner: Vec<u8>,
+
+        #[covariant]
+        dependent: MultiEraBlock,
+    }
+);
+
+self_cell!(
+    struct TxCell {
+        owner: Vec<u8>,
+
+        #[not_covariant]
+        dependent: MultiEraTx,
+    }
+);
+
+self_cell!(
+    struct OutputCell {
+        owner: Vec<u8>,
+
+        #[not_covariant]
+        dependent: MultiEraOutput,
+    }
+);
+
+/// A block decoded from a CBOR buffer that it owns
+pub struct OwnedMultiEraBlock(BlockCell);
+
+impl OwnedMultiEraBlock {
+    pub fn decode(cbor: Vec<u8>) -> Result<Self, Error> {
+        BlockCell::try_new(cbor, |x| MultiEraBlock::decode(x)).map(Self)
+    }
+
+    pub fn view(&self) -> &MultiEraBlock {
+        self.0.borrow_dependent()
+    }
+
+    /// The original CBOR of the block
+    pub fn cbor(&self) -> &[u8] {
+        self.0.borrow_owner()
+    }
+
+    pub fn into_cbor(self) -> Vec<u8> {
+        self.0.into_owner()
+    }
+
+    pub fn era(&self) -> Era {
+        self.view().era()
+    }
+
+    pub fn hash(&self) -> Hash<32> {
+        self.view().hash()
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.view().slot()
+    }
+
+    pub fn number(&self) -> u64 {
+        self.view().number()
+    }
+
+    pub fn header(&self) -> MultiEraHeader<'_> {
+        self.view().header()
+    }
+
+    pub fn txs(&self) -> Vec<MultiEraTx<'_>> {
+        self.view().txs()
+    }
+
+    pub fn tx_count(&self) -> usize {
+        self.view().tx_count()
+    }
+}
+
+impl Clone for OwnedMultiEraBlock {
+    fn clone(&self) -> Self {
+        // the bytes were decoded successfully already
+        Self::decode(self.cbor().to_vec()).unwrap()
+    }
+}
+
+impl std::fmt::Debug for OwnedMultiEraBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.view().fmt(f)
+    }
+}
+
+/// A tx decoded from a CBOR buffer that it owns
+pub struct OwnedMultiEraTx(TxCell);
+
+impl OwnedMultiEraTx {
+    pub fn decode(era: Era, cbor: Vec<u8>) -> Result<Self, Error> {
+        TxCell::try_new(cbor, |x| {
+            MultiEraTx::decode(era, x).map_err(Error::invalid_cbor)
+        })
+        .map(Self)
+    }
+
+    /// Takes a copy of a borrowed tx, which keeps its original bytes
+    pub fn from_view(tx: &MultiEraTx) -> Self {
+        // the bytes come from a valid tx
+        Self::decode(tx.era(), tx.encode()).unwrap()
+    }
+
+    pub fn with_view<R>(&self, f: impl for<'a> FnOnce(&MultiEraTx<'a>) -> R) -> R {
+        self.0.with_dependent(|_, x| f(x))
+    }
+
+    /// The original CBOR of the tx
+    pub fn cbor(&self) -> &[u8] {
+        self.0.borrow_owner()
+    }
+
+    pub fn into_cbor(self) -> Vec<u8> {
+        self.0.into_owner()
+    }
+
+    pub fn era(&self) -> Era {
+        self.with_view(|x| x.era())
+    }
+
+    pub fn hash(&self) -> Hash<32> {
+        self.with_view(|x| x.hash())
+    }
+
+    /// The outputs spent by the tx
+    pub fn inputs(&self) -> Vec<OutputRef> {
+        self.with_view(|x| x.inputs().iter().map(|x| x.output_ref()).collect())
+    }
+
+    pub fn outputs(&self) -> Vec<OwnedMultiEraOutput> {
+        self.with_view(|x| {
+            x.outputs()
+                .iter()
+                .map(OwnedMultiEraOutput::from_view)
+                .collect()
+        })
+    }
+
+    pub fn fee(&self) -> Option<u64> {
+        self.with_view(|x| x.fee())
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.with_view(|x| x.is_valid())
+    }
+}
+
+impl Clone for OwnedMultiEraTx {
+    fn clone(&self) -> Self {
+        self.with_view(Self::from_view)
+    }
+}
+
+impl std::fmt::Debug for OwnedMultiEraTx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.with_view(|x| x.fmt(f))
+    }
+}
+
+/// An output decoded from a CBOR buffer that it owns
+pub struct OwnedMultiEraOutput(Era, OutputCell);
+
+impl OwnedMultiEraOutput {
+    pub fn decode(era: Era, cbor: Vec<u8>) -> Result<Self, Error> {
+        let cell = OutputCell::try_new(cbor, |x| {
+            MultiEraOutput::decode(era, x).map_err(Error::invalid_cbor)
+        })?;
+
+        Ok(Self(era, cell))
+    }
+
+    /// Takes a copy of a borrowed output, which keeps its original bytes
+    pub fn from_view(output: &MultiEraOutput) -> Self {
+        // any era sharing the encoding of the output will do
+        let era = match output {
+            MultiEraOutput::Byron(_) => Era::Byron,
+            MultiEraOutput::AlonzoCompatible(_) => Era::Alonzo,
+            MultiEraOutput::Babbage(_) => Era::Babbage,
+            MultiEraOutput::Conway(_) => Era::Conway,
+        };
+
+        // the bytes come from a valid output
+        Self::decode(era, output.encode()).unwrap()
+    }
+
+    pub fn with_view<R>(&self, f: impl for<'a> FnOnce(&MultiEraOutput<'a>) -> R) -> R {
+        self.1.with_dependent(|_, x| f(x))
+    }
+
+    /// The original CBOR of the output
+    pub fn cbor(&self) -> &[u8] {
+        self.1.borrow_owner()
+    }
+
+    pub fn into_cbor(self) -> Vec<u8> {
+        self.1.into_owner()
+    }
+
+    pub fn address(&self) -> Result<Address, AddressError> {
+        self.with_view(|x| x.address())
+    }
+
+    pub fn lovelace_amount(&self) -> u64 {
+        self.with_view(|x| x.lovelace_amount())
+    }
+}
+
+impl Clone for OwnedMultiEraOutput {
+    fn clone(&self) -> Self {
+        // the bytes were decoded successfully already
+        Self::decode(self.0, self.cbor().to_vec()).unwrap()
+    }
+}
+
+impl std::fmt::Debug for OwnedMultiEraOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.with_view(|x| x.fmt(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owned_block_crosses_threads() {
+        let cbor = hex::decode(include_str!("../../test_data/babbage9.block")).unwrap();
+
+        let expected = MultiEraBlock::decode(&cbor).unwrap().hash();
+        let owned = OwnedMultiEraBlock::decode(cbor).unwrap();
+
+        let hash = std::thread::spawn(move || owned.hash()).join().unwrap();
+
+        assert_eq!(hash, expected);
+    }
+
+    #[test]
+    fn owned_types_are_send_and_sync() {
+        fn check<T: Send + Sync>() {}
+
+        check::<OwnedMultiEraBlock>();
+        check::<OwnedMultiEraTx>();
+        check::<OwnedMultiEraOutput>();
+    }
+
+    #[test]
+    fn owned_txs_keep_original_hashes() {
+        let cbor = hex::decode(include_str!("../../test_data/alonzo9.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+
+        let owned: Vec<_> = block.txs().iter().map(OwnedMultiEraTx::from_view).collect();
+        drop(block);
+
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+
+        for (tx, owned) in block.txs().iter().zip(owned.iter()) {
+            assert_eq!(owned.hash(), tx.hash());
+            assert_eq!(owned.clone().hash(), tx.hash());
+            assert_eq!(owned.fee(), tx.fee());
+
+            let inputs: Vec<_> = tx.inputs().iter().map(|x| x.output_ref()).collect();
+            assert_eq!(owned.inputs(), inputs);
+
+            for (output, expected) in owned.outputs().iter().zip(tx.outputs()) {
+                assert_eq!(output.cbor(), expected.encode());
+                assert_eq!(output.lovelace_amount(), expected.lovelace_amount());
+                assert_eq!(output.address().unwrap(), expected.address().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn owned_types_report_traverse_errors() {
+        assert!(matches!(
+            OwnedMultiEraTx::decode(Era::Babbage, vec![0x80]),
+            Err(Error::InvalidCbor(_))
+        ));
+        assert!(matches!(
+            OwnedMultiEraOutput::decode(Era::Babbage, vec![0x80]),
+            Err(Error::InvalidCbor(_))
+        ));
+    }
+}
@@ -30,12 +30,34 @@ pub fn block_era(cbor: &[u8]) -> Outcome {
             4 => Outcome::Matched(Era::Mary),
             5 => Outcome::Matched(Era::Alonzo),
             6 => Outcome::Matched(Era::Babbage),
+            7 => Outcome::Matched(Era::Conway),
             _ => Outcome::Inconclusive,
         },
         _ => Outcome::Inconclusive,
     }
 }
 
+// Inspects the initial tokens of a hard-fork combinator envelope, as in
+// `[era_index, tag24(bytes)]`, which is how txs, among others, carry their era.
+pub fn envelope_era(cbor: &[u8]) -> Outcome {
+    let mut tokenizer = Tokenizer::new(cbor);
+
+    if !matches!(tokenizer.next(), Some(Ok(Token::Array(2)))) {
+        return Outcome::Inconclusive;
+    }
+
+    let index = match tokenizer.next() {
+        Some(Ok(Token::U8(x))) => x as u16,
+        Some(Ok(Token::U16(x))) => x,
+        _ => return Outcome::Inconclusive,
+    };
+
+    match Era::from_hfc_index(index) {
+        Ok(era) => Outcome::Matched(era),
+        Err(_) => Outcome::Inconclusive,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,4 +121,14 @@ mod tests {
 
         assert!(matches!(inference, Outcome::Matched(Era::Alonzo)));
     }
+
+    #[test]
+    fn enveloped_tx_detected() {
+        // [6, 24(h'80')]
+        let bytes = hex::decode("8206d8184180").unwrap();
+
+        let inference = envelope_era(bytes.as_slice());
+
+        assert!(matches!(inference, Outcome::Matched(Era::Conway)));
+    }
 }
@@ -0,0 +1,383 @@
+use std::{borrow::Cow, collections::HashMap, ops::Deref};
+
+use pallas_addresses::{Address, ShelleyPaymentPart};
+use pallas_crypto::hash::Hash;
+use pallas_primitives::{alonzo, babbage, conway};
+
+use crate::{
+    Error, MultiEraCert, MultiEraInput, MultiEraOutput, MultiEraRedeemer, MultiEraTx, OriginalHash,
+    OutputRef,
+};
+
+/// The item of a tx that a redeemer points to
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ScriptPurpose<'b> {
+    Spend(MultiEraInput<'b>),
+    Mint(Hash<28>),
+    Cert(MultiEraCert<'b>),
+    Reward(alonzo::RewardAccount),
+    Vote(conway::Voter),
+    Propose(conway::ProposalProcedure),
+}
+
+/// A redeemer together with the purpose it points to, the hash of the script
+/// it is passed to, and the datum of the output being spent, if any
+#[derive(Debug, Clone)]
+pub struct ResolvedRedeemer<'b> {
+    pub redeemer: MultiEraRedeemer<'b>,
+    pub purpose: ScriptPurpose<'b>,
+    pub script_hash: Option<Hash<28>>,
+    pub datum: Option<alonzo::PlutusData>,
+}
+
+impl<'b> MultiEraRedeemer<'b> {
+    pub fn from_alonzo_compatible(redeemer: &'b alonzo::Redeemer) -> Self {
+        Self::AlonzoCompatible(Box::new(Cow::Borrowed(redeemer)))
+    }
+
+    pub fn from_conway(key: &'b conway::RedeemersKey, value: &'b conway::RedeemersValue) -> Self {
+        Self::Conway(Box::new(Cow::Borrowed(key)), Box::new(Cow::Borrowed(value)))
+    }
+
+    /// Builds a redeemer from the list form still accepted in Conway, which
+    /// is split into the key and value of the map form
+    pub fn from_conway_list(redeemer: &conway::Redeemer) -> Self {
+        let key = conway::RedeemersKey {
+            tag: redeemer.tag,
+            index: redeemer.index,
+        };
+
+        let value = conway::RedeemersValue {
+            data: redeemer.data.clone(),
+            ex_units: redeemer.ex_units.clone(),
+        };
+
+        Self::Conway(Box::new(Cow::Owned(key)), Box::new(Cow::Owned(value)))
+    }
+
+    /// The purpose of the redeemer, using the Conway tags, which are a
+    /// superset of the ones of previous eras
+    pub fn tag(&self) -> conway::RedeemerTag {
+        match self {
+            Self::AlonzoCompatible(x) => match x.tag {
+                alonzo::RedeemerTag::Spend => conway::RedeemerTag::Spend,
+                alonzo::RedeemerTag::Mint => conway::RedeemerTag::Mint,
+                alonzo::RedeemerTag::Cert => conway::RedeemerTag::Cert,
+                alonzo::RedeemerTag::Reward => conway::RedeemerTag::Reward,
+            },
+            Self::Conway(x, _) => x.tag,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        match self {
+            Self::AlonzoCompatible(x) => x.index,
+            Self::Conway(x, _) => x.index,
+        }
+    }
+
+    pub fn data(&self) -> &alonzo::PlutusData {
+        match self {
+            Self::AlonzoCompatible(x) => &x.data,
+            Self::Conway(_, x) => &x.data,
+        }
+    }
+
+    pub fn ex_units(&self) -> alonzo::ExUnits {
+        match self {
+            Self::AlonzoCompatible(x) => x.ex_units.clone(),
+            Self::Conway(_, x) => x.ex_units.clone(),
+        }
+    }
+
+    pub fn as_alonzo(&self) -> Option<&alonzo::Redeemer> {
+        match self {
+            Self::AlonzoCompatible(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+fn credential_script(credential: &alonzo::StakeCredential) -> Option<Hash<28>> {
+    match credential {
+        alonzo::StakeCredential::Scripthash(x) => Some(*x),
+        alonzo::StakeCredential::AddrKeyhash(_) => None,
+    }
+}
+
+fn cert_script(cert: &MultiEraCert) -> Option<Hash<28>> {
+    match cert {
+        MultiEraCert::AlonzoCompatible(x) => match x.deref().deref() {
+            alonzo::Certificate::StakeDeregistration(x) => credential_script(x),
+            alonzo::Certificate::StakeDelegation(x, _) => credential_script(x),
+            _ => None,
+        },
+        MultiEraCert::Conway(x) => match x.deref().deref() {
+            conway::Certificate::StakeDeregistration(x)
+            | conway::Certificate::StakeDelegation(x, _)
+            | conway::Certificate::Reg(x, _)
+            | conway::Certificate::UnReg(x, _)
+            | conway::Certificate::VoteDeleg(x, _)
+            | conway::Certificate::StakeVoteDeleg(x, _, _)
+            | conway::Certificate::StakeRegDeleg(x, _, _)
+            | conway::Certificate::VoteRegDeleg(x, _, _)
+            | conway::Certificate::StakeVoteRegDeleg(x, _, _, _)
+            | conway::Certificate::AuthCommitteeHot(x, _)
+            | conway::Certificate::ResignCommitteeCold(x, _)
+            | conway::Certificate::RegDRepCert(x, _, _)
+            | conway::Certificate::UnRegDRepCert(x, _)
+            | conway::Certificate::UpdateDRepCert(x, _) => credential_script(x),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// The ledger sorts reward accounts by network, then by credential, placing
+// scripts before keys regardless of their hashes.
+fn reward_account_order(account: &[u8]) -> (u8, bool, &[u8]) {
+    match account.split_first() {
+        Some((header, hash)) => (header & 0x0f, header & 0x10 == 0, hash),
+        None => (0, false, account),
+    }
+}
+
+fn reward_account_script(account: &[u8]) -> Option<Hash<28>> {
+    match reward_account_order(account) {
+        (_, false, hash) if hash.len() == 28 => Some(Hash::from(hash)),
+        _ => None,
+    }
+}
+
+// Voters are sorted by role, committee members first and pools last, then by
+// credential as reward accounts are.
+fn voter_order(voter: &conway::Voter) -> (u8, bool, &[u8]) {
+    match voter {
+        conway::Voter::ConstitutionalCommitteeScript(x) => (0, false, x.as_ref()),
+        conway::Voter::ConstitutionalCommitteeKey(x) => (0, true, x.as_ref()),
+        conway::Voter::DRepScript(x) => (1, false, x.as_ref()),
+        conway::Voter::DRepKey(x) => (1, true, x.as_ref()),
+        conway::Voter::StakePoolKey(x) => (2, true, x.as_ref()),
+    }
+}
+
+fn voter_script(voter: &conway::Voter) -> Option<Hash<28>> {
+    match voter {
+        conway::Voter::ConstitutionalCommitteeScript(x) => Some(*x),
+        conway::Voter::DRepScript(x) => Some(*x),
+        _ => None,
+    }
+}
+
+fn proposal_script(proposal: &conway::ProposalProcedure) -> Option<Hash<28>> {
+    match &proposal.gov_action {
+        conway::GovAction::ParameterChange(_, _, x) => *x,
+        conway::GovAction::TreasuryWithdrawals(_, x) => *x,
+        _ => None,
+    }
+}
+
+impl<'b> MultiEraTx<'b> {
+    /// Resolves the redeemers of the tx into the items they point to
+    ///
+    /// Redeemer indexes point into the canonical order of each kind of item:
+    /// inputs are sorted by tx hash and index, mint policies by hash and
+    /// withdrawals and votes by credential, while certificates and proposals
+    /// keep the order of the tx. The outputs spent by the inputs are resolved
+    /// through the given UTxOs to find their script hash and datum.
+    pub fn resolve_redeemers(
+        &self,
+        utxos: &HashMap<OutputRef, MultiEraOutput>,
+    ) -> Result<Vec<ResolvedRedeemer>, Error> {
+        let mut inputs = self.inputs();
+        inputs.sort_by(|a, b| (a.hash(), a.index()).cmp(&(b.hash(), b.index())));
+
+        let mut policies: Vec<_> = self.mints().iter().map(|x| *x.policy()).collect();
+        policies.sort();
+        policies.dedup();
+
+        let withdrawals = self.withdrawals();
+        let mut accounts: Vec<_> = withdrawals.collect::<Vec<_>>();
+        accounts.sort_by(|(a, _), (b, _)| reward_account_order(a).cmp(&reward_account_order(b)));
+
+        let certs = self.certs();
+
+        let mut voters: Vec<_> = self
+            .as_conway()
+            .and_then(|x| x.transaction_body.voting_procedures.as_ref())
+            .iter()
+            .flat_map(|x| x.iter().map(|(k, _)| k))
+            .collect();
+        voters.sort_by(|a, b| voter_order(a).cmp(&voter_order(b)));
+
+        let proposals: &[conway::ProposalProcedure] = self
+            .as_conway()
+            .and_then(|x| x.transaction_body.proposal_procedures.as_ref())
+            .map(|x| x.as_slice())
+            .unwrap_or(&[]);
+
+        let mut out = vec![];
+
+        for redeemer in self.redeemers() {
+            let tag = redeemer.tag();
+            let index = redeemer.index();
+            let invalid = || Error::InvalidRedeemerPointer(tag, index);
+            let at = index as usize;
+
+            let (purpose, script_hash, datum) = match tag {
+                conway::RedeemerTag::Spend => {
+                    let input = inputs.get(at).ok_or_else(invalid)?;
+                    let output_ref = input.output_ref();
+
+                    let output = utxos
+                        .get(&output_ref)
+                        .ok_or(Error::UnresolvedUtxoRef(output_ref))?;
+
+                    let script_hash = match output.address().map_err(Error::invalid_cbor)? {
+                        Address::Shelley(x) => match x.payment() {
+                            ShelleyPaymentPart::Script(x) => Some(*x),
+                            ShelleyPaymentPart::Key(_) => None,
+                        },
+                        _ => None,
+                    };
+
+                    let datum = match output.datum() {
+                        Some(babbage::PseudoDatumOption::Hash(hash)) => self
+                            .plutus_data()
+                            .iter()
+                            .find(|x| x.original_hash() == hash)
+                            .map(|x| x.deref().clone()),
+                        Some(babbage::PseudoDatumOption::Data(x)) => Some(x.0.deref().clone()),
+                        None => None,
+                    };
+
+                    (ScriptPurpose::Spend(input.clone()), script_hash, datum)
+                }
+                conway::RedeemerTag::Mint => {
+                    let policy = policies.get(at).ok_or_else(invalid)?;
+                    (ScriptPurpose::Mint(*policy), Some(*policy), None)
+                }
+                conway::RedeemerTag::Cert => {
+                    let cert = certs.get(at).ok_or_else(invalid)?;
+                    (ScriptPurpose::Cert(cert.clone()), cert_script(cert), None)
+                }
+                conway::RedeemerTag::Reward => {
+                    let (account, _) = accounts.get(at).ok_or_else(invalid)?;
+                    let script_hash = reward_account_script(account);
+                    (
+                        ScriptPurpose::Reward(account.to_vec().into()),
+                        script_hash,
+                        None,
+                    )
+                }
+                conway::RedeemerTag::Vote => {
+                    let voter = voters.get(at).ok_or_else(invalid)?;
+                    (
+                        ScriptPurpose::Vote((*voter).clone()),
+                        voter_script(voter),
+                        None,
+                    )
+                }
+                conway::RedeemerTag::Propose => {
+                    let proposal = proposals.get(at).ok_or_else(invalid)?;
+                    let script_hash = proposal_script(proposal);
+                    (ScriptPurpose::Propose(proposal.clone()), script_hash, None)
+                }
+            };
+
+            out.push(ResolvedRedeemer {
+                redeemer,
+                purpose,
+                script_hash,
+                datum,
+            });
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MultiEraBlock;
+
+    #[test]
+    fn redeemers_resolve_to_purposes() {
+        let blocks = [
+            include_str!("../../test_data/alonzo9.block"),
+            include_str!("../../test_data/babbage9.block"),
+        ];
+
+        let script = Hash::<28>::from([9; 28]);
+
+        // a script address holding no datum
+        let output = alonzo::TransactionOutput {
+            address: [&[0x71], script.as_ref()].concat().into(),
+            amount: alonzo::Value::Coin(1_000_000),
+            datum_hash: None,
+        };
+
+        let mut resolved = 0;
+
+        for block in blocks {
+            let cbor = hex::decode(block).unwrap();
+            let block = MultiEraBlock::decode(&cbor).unwrap();
+
+            for tx in block.txs() {
+                let utxos: HashMap<_, _> = tx
+                    .inputs()
+                    .iter()
+                    .map(|x| {
+                        (
+                            x.output_ref(),
+                            MultiEraOutput::from_alonzo_compatible(&output),
+                        )
+                    })
+                    .collect();
+
+                let mut sorted = tx.inputs();
+                sorted.sort_by_key(|x| (*x.hash(), x.index()));
+
+                for item in tx.resolve_redeemers(&utxos).unwrap() {
+                    let index = item.redeemer.index() as usize;
+
+                    match item.purpose {
+                        ScriptPurpose::Spend(x) => {
+                            assert_eq!(x, sorted[index]);
+                            assert_eq!(item.script_hash, Some(script));
+                        }
+                        ScriptPurpose::Mint(x) => assert_eq!(item.script_hash, Some(x)),
+                        _ => (),
+                    }
+
+                    resolved += 1;
+                }
+            }
+        }
+
+        assert!(resolved > 0);
+    }
+
+    #[test]
+    fn unresolved_inputs_fail() {
+        let cbor = hex::decode(include_str!("../../test_data/babbage9.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+
+        let tx = block
+            .txs()
+            .into_iter()
+            .find(|tx| {
+                tx.redeemers()
+                    .iter()
+                    .any(|x| x.tag() == conway::RedeemerTag::Spend)
+            })
+            .unwrap();
+
+        assert!(matches!(
+            tx.resolve_redeemers(&HashMap::new()),
+            Err(Error::UnresolvedUtxoRef(_))
+        ));
+    }
+}
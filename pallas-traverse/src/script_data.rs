@@ -0,0 +1,224 @@
+//! Script data hash (a.k.a. script integrity hash) of Alonzo-onwards txs
+//!
+//! The hash covers the redeemers and datums in the witness set of a tx,
+//! followed by the "language views" of the cost models of the Plutus
+//! languages used by the tx. Which languages are in use depends on the
+//! scripts run by the tx, some of which may be reference scripts held in the
+//! UTxO set, so it is up to the caller to provide them.
+
+use pallas_codec::minicbor::{self, data::Type, Decoder, Encode, Encoder};
+use pallas_crypto::hash::{Hash, Hasher};
+use pallas_primitives::{alonzo::Redeemer, conway::Language};
+
+use crate::MultiEraTx;
+
+/// Encodes the language views of the given cost models, as included in the
+/// script data hash
+///
+/// The PlutusV1 view keeps the quirks of the original Alonzo implementation:
+/// the language id is serialized and then wrapped in a byte string, and so is
+/// the cost model, serialized as an indefinite-length list. Later languages
+/// are plainly encoded: the language id as an unsigned integer, and the cost
+/// model as a definite-length list. Entries are sorted canonically, so the
+/// PlutusV1 key, being the longest, goes last.
+pub fn encode_language_views(views: &[(Language, &[i64])]) -> Vec<u8> {
+    // to_vec is infallible
+    let mut entries: Vec<(Vec<u8>, Vec<u8>)> = views
+        .iter()
+        .map(|(language, cost_model)| match language {
+            Language::PlutusV1 => {
+                let mut model = Vec::new();
+                let mut encoder = Encoder::new(&mut model);
+                encoder.begin_array().unwrap();
+                for cost in cost_model.iter() {
+                    encoder.i64(*cost).unwrap();
+                }
+                encoder.end().unwrap();
+
+                let mut key = Vec::new();
+                Encoder::new(&mut key).bytes(&[0]).unwrap();
+                let mut value = Vec::new();
+                Encoder::new(&mut value).bytes(&model).unwrap();
+                (key, value)
+            }
+            Language::PlutusV2 => {
+                let key = minicbor::to_vec(1u8).unwrap();
+                let value = minicbor::to_vec(cost_model).unwrap();
+                (key, value)
+            }
+            Language::PlutusV3 => {
+                let key = minicbor::to_vec(2u8).unwrap();
+                let value = minicbor::to_vec(cost_model).unwrap();
+                (key, value)
+            }
+        })
+        .collect();
+
+    entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    entries.dedup_by(|(a, _), (b, _)| a == b);
+
+    let mut buff = Vec::new();
+    Encoder::new(&mut buff).map(entries.len() as u64).unwrap();
+
+    for (key, value) in entries {
+        buff.extend(key);
+        buff.extend(value);
+    }
+
+    buff
+}
+
+/// Hashes the CBOR of the redeemers and datums of a tx, as they appear in its
+/// witness set, together with the encoded language views
+///
+/// Returns `None` if the tx has neither redeemers nor datums, in which case it
+/// must not declare a script data hash. Txs with datums but no redeemers hash
+/// an empty list in their place, which Conway txs must provide explicitly as
+/// an empty map instead.
+pub fn hash_script_data(
+    redeemers: Option<&[u8]>,
+    datums: Option<&[u8]>,
+    language_views: &[u8],
+) -> Option<Hash<32>> {
+    let redeemers: &[u8] = match (redeemers, datums) {
+        (None, None) => return None,
+        // txs with datums but no redeemers hash an empty list of redeemers
+        (None, Some(_)) => &[0x80],
+        (Some(x), _) => x,
+    };
+
+    let datums: &[u8] = datums.unwrap_or(&[]);
+
+    Some(Hasher::<256>::hash(
+        &[redeemers, datums, language_views].concat(),
+    ))
+}
+
+/// Computes the script data hash of a tx being built from its redeemers and
+/// datums, which are encoded as definite-length lists
+///
+/// Datums may either be built or keep their original encoding, as in
+/// `KeepRaw<PlutusData>`.
+pub fn compute_script_data_hash<D: Encode<()>>(
+    redeemers: &[Redeemer],
+    datums: &[D],
+    views: &[(Language, &[i64])],
+) -> Option<Hash<32>> {
+    // to_vec is infallible
+    let redeemers = match redeemers {
+        [] => None,
+        x => Some(minicbor::to_vec(x).unwrap()),
+    };
+
+    let datums = match datums {
+        [] => None,
+        x => Some(minicbor::to_vec(x).unwrap()),
+    };
+
+    hash_script_data(
+        redeemers.as_deref(),
+        datums.as_deref(),
+        &encode_language_views(views),
+    )
+}
+
+impl MultiEraTx<'_> {
+    /// Computes the script data hash of the tx, given the cost models of the
+    /// languages of the Plutus scripts it runs
+    ///
+    /// The original bytes of the redeemers and datums in the witness set are
+    /// hashed, so the result can be checked against the declared hash.
+    pub fn compute_script_data_hash(&self, views: &[(Language, &[i64])]) -> Option<Hash<32>> {
+        let (raw_wits, no_redeemers): (_, Option<&[u8]>) = match self {
+            MultiEraTx::AlonzoCompatible(x, _) => (x.transaction_witness_set.raw_cbor(), None),
+            MultiEraTx::Babbage(x) => (x.transaction_witness_set.raw_cbor(), None),
+            MultiEraTx::Conway(x) => (x.transaction_witness_set.raw_cbor(), Some(&[0xa0])),
+            _ => return None,
+        };
+
+        let redeemers = match self.redeemers().is_empty() {
+            true => None,
+            false => Some(find_map_entry(raw_wits, 5)?),
+        };
+
+        let datums = match self.plutus_data() {
+            [] => None,
+            _ => Some(find_map_entry(raw_wits, 4)?),
+        };
+
+        let redeemers = match (redeemers, datums) {
+            (None, Some(_)) => no_redeemers,
+            (x, _) => x,
+        };
+
+        hash_script_data(redeemers, datums, &encode_language_views(views))
+    }
+}
+
+// Returns the original bytes of the value under the given key of a CBOR map.
+fn find_map_entry(raw: &[u8], key: u64) -> Option<&[u8]> {
+    let mut decoder = Decoder::new(raw);
+    let len = decoder.map().ok()?;
+    let mut index = 0;
+
+    while len.is_none_or(|x| index < x) {
+        if decoder.datatype().ok()? == Type::Break {
+            break;
+        }
+
+        let current = decoder.u64().ok()?;
+        let start = decoder.position();
+        decoder.skip().ok()?;
+
+        if current == key {
+            return Some(&raw[start..decoder.position()]);
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plutus_v1_view_is_double_encoded() {
+        let views = encode_language_views(&[(Language::PlutusV1, &[1, -1])]);
+
+        // {h'00': h'9f0120ff'}
+        assert_eq!(hex::encode(views), "a14100449f0120ff");
+    }
+
+    #[test]
+    fn plutus_v2_view_goes_first() {
+        let views =
+            encode_language_views(&[(Language::PlutusV1, &[1]), (Language::PlutusV2, &[2, 3])]);
+
+        // {1: [2, 3], h'00': h'9f01ff'}
+        assert_eq!(hex::encode(views), "a2018202034100439f01ff");
+    }
+
+    #[test]
+    fn plutus_v3_view_is_plainly_encoded() {
+        let views =
+            encode_language_views(&[(Language::PlutusV3, &[4]), (Language::PlutusV2, &[5])]);
+
+        // {1: [5], 2: [4]}
+        assert_eq!(hex::encode(views), "a2018105028104");
+    }
+
+    #[test]
+    fn datums_without_redeemers() {
+        let datums = [0x81, 0x00];
+        let views = encode_language_views(&[]);
+
+        assert_eq!(hash_script_data(None, None, &views), None);
+        assert_eq!(
+            hash_script_data(None, Some(&datums), &views),
+            Some(Hasher::<256>::hash(&[0x80, 0x81, 0x00, 0xa0]))
+        );
+    }
+}
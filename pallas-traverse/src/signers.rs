@@ -1,7 +1,10 @@
+use std::collections::{BTreeSet, HashMap};
+
+use pallas_addresses::{Address, ShelleyPaymentPart, StakePayload};
 use pallas_crypto::hash::Hash;
-use pallas_primitives::alonzo;
+use pallas_primitives::{alonzo, conway};
 
-use crate::MultiEraSigners;
+use crate::{Error, MultiEraCert, MultiEraOutput, MultiEraSigners, MultiEraTx, OutputRef};
 
 impl Default for MultiEraSigners<'_> {
     fn default() -> Self {
@@ -17,9 +20,17 @@ impl<'b> MultiEraSigners<'b> {
         }
     }
 
+    pub fn as_conway(&self) -> Option<&conway::RequiredSigners> {
+        match self {
+            Self::Conway(x) => Some(x),
+            _ => None,
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         match self {
             Self::AlonzoCompatible(x) => x.is_empty(),
+            Self::Conway(x) => x.is_empty(),
             _ => true,
         }
     }
@@ -32,6 +43,293 @@ impl<'b> MultiEraSigners<'b> {
             Self::NotApplicable => std::iter::empty().collect(),
             Self::Empty => std::iter::empty().collect(),
             Self::AlonzoCompatible(x) => x.iter().collect(),
+            Self::Conway(x) => x.iter().collect(),
+        }
+    }
+}
+
+fn stake_credential_key(credential: &alonzo::StakeCredential) -> Option<Hash<28>> {
+    match credential {
+        alonzo::StakeCredential::AddrKeyhash(x) => Some(*x),
+        alonzo::StakeCredential::Scripthash(_) => None,
+    }
+}
+
+fn alonzo_cert_keys(cert: &alonzo::Certificate) -> Vec<Hash<28>> {
+    match cert {
+        // registering a stake key requires no witness
+        alonzo::Certificate::StakeRegistration(_) => vec![],
+        alonzo::Certificate::StakeDeregistration(x) => {
+            stake_credential_key(x).into_iter().collect()
+        }
+        alonzo::Certificate::StakeDelegation(x, _) => stake_credential_key(x).into_iter().collect(),
+        alonzo::Certificate::PoolRegistration {
+            operator,
+            pool_owners,
+            ..
+        } => std::iter::once(*operator)
+            .chain(pool_owners.iter().copied())
+            .collect(),
+        alonzo::Certificate::PoolRetirement(x, _) => vec![*x],
+        alonzo::Certificate::GenesisKeyDelegation(x, _, _) => vec![Hash::from(x.as_slice())],
+        // the quorum of genesis delegates depends on the ledger state
+        alonzo::Certificate::MoveInstantaneousRewardsCert(_) => vec![],
+    }
+}
+
+fn conway_cert_keys(cert: &conway::Certificate) -> Vec<Hash<28>> {
+    match cert {
+        conway::Certificate::StakeRegistration(_) => vec![],
+        conway::Certificate::StakeDeregistration(x)
+        | conway::Certificate::StakeDelegation(x, _)
+        | conway::Certificate::Reg(x, _)
+        | conway::Certificate::UnReg(x, _)
+        | conway::Certificate::VoteDeleg(x, _)
+        | conway::Certificate::StakeVoteDeleg(x, _, _)
+        | conway::Certificate::StakeRegDeleg(x, _, _)
+        | conway::Certificate::VoteRegDeleg(x, _, _)
+        | conway::Certificate::StakeVoteRegDeleg(x, _, _, _)
+        | conway::Certificate::AuthCommitteeHot(x, _)
+        | conway::Certificate::ResignCommitteeCold(x, _)
+        | conway::Certificate::RegDRepCert(x, _, _)
+        | conway::Certificate::UnRegDRepCert(x, _)
+        | conway::Certificate::UpdateDRepCert(x, _) => {
+            stake_credential_key(x).into_iter().collect()
+        }
+        conway::Certificate::PoolRegistration {
+            operator,
+            pool_owners,
+            ..
+        } => std::iter::once(*operator)
+            .chain(pool_owners.iter().copied())
+            .collect(),
+        conway::Certificate::PoolRetirement(x, _) => vec![*x],
+    }
+}
+
+fn conway_voter_key(voter: &conway::Voter) -> Option<Hash<28>> {
+    match voter {
+        conway::Voter::ConstitutionalCommitteeKey(x) => Some(*x),
+        conway::Voter::DRepKey(x) => Some(*x),
+        conway::Voter::StakePoolKey(x) => Some(*x),
+        _ => None,
+    }
+}
+
+impl<'b> MultiEraTx<'b> {
+    /// Returns the hashes of all the verification keys that must sign the tx
+    ///
+    /// These are the key hashes in the payment credentials of the addresses
+    /// spent by inputs and collateral, which are resolved through the given
+    /// UTxOs, in the stake credentials of withdrawals and certificates, the
+    /// owners and operators of pool certificates, the Conway voters and the
+    /// explicit required signers. Script credentials are skipped, and so are
+    /// Byron addresses, which are witnessed by bootstrap witnesses instead.
+    ///
+    /// Protocol parameter updates contribute the current delegates of the
+    /// genesis keys proposing them, looked up in `gen_delegs`, which maps
+    /// genesis key hashes to the key hashes of their delegates. Instantaneous
+    /// rewards are authorized by a quorum of any genesis delegates rather than
+    /// by particular ones, so they contribute no key hash.
+    pub fn required_vkey_hashes(
+        &self,
+        utxos: &HashMap<OutputRef, MultiEraOutput>,
+        gen_delegs: &HashMap<Hash<28>, Hash<28>>,
+    ) -> Result<BTreeSet<Hash<28>>, Error> {
+        let mut out = BTreeSet::new();
+
+        for input in self.inputs().iter().chain(self.collateral().iter()) {
+            let output_ref = input.output_ref();
+
+            let address = utxos
+                .get(&output_ref)
+                .ok_or(Error::UnresolvedUtxoRef(output_ref))?
+                .address()
+                .map_err(Error::invalid_cbor)?;
+
+            if let Address::Shelley(x) = address {
+                if let ShelleyPaymentPart::Key(x) = x.payment() {
+                    out.insert(*x);
+                }
+            }
+        }
+
+        let withdrawals = self.withdrawals();
+
+        for (account, _) in withdrawals.collect::<Vec<_>>() {
+            if let Ok(Address::Stake(x)) = Address::from_bytes(account) {
+                if let StakePayload::Stake(x) = x.payload() {
+                    out.insert(*x);
+                }
+            }
+        }
+
+        for cert in self.certs() {
+            match cert {
+                MultiEraCert::AlonzoCompatible(x) => out.extend(alonzo_cert_keys(&x)),
+                MultiEraCert::Conway(x) => out.extend(conway_cert_keys(&x)),
+                _ => (),
+            }
         }
+
+        let proposers: Vec<&alonzo::Genesishash> = match self {
+            MultiEraTx::AlonzoCompatible(x, _) => x
+                .transaction_body
+                .update
+                .iter()
+                .flat_map(|x| x.proposed_protocol_parameter_updates.iter().map(|(k, _)| k))
+                .collect(),
+            MultiEraTx::Babbage(x) => x
+                .transaction_body
+                .update
+                .iter()
+                .flat_map(|x| x.proposed_protocol_parameter_updates.iter().map(|(k, _)| k))
+                .collect(),
+            _ => vec![],
+        };
+
+        for proposer in proposers {
+            let genesis = Hash::from(proposer.as_slice());
+
+            let delegate = gen_delegs
+                .get(&genesis)
+                .ok_or(Error::UnknownGenesisKey(genesis))?;
+
+            out.insert(*delegate);
+        }
+
+        if let Some(votes) = self
+            .as_conway()
+            .and_then(|x| x.transaction_body.voting_procedures.as_ref())
+        {
+            out.extend(votes.iter().filter_map(|(x, _)| conway_voter_key(x)));
+        }
+
+        let signers = self.required_signers();
+        out.extend(signers.collect::<Vec<_>>());
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::minicbor;
+    use pallas_primitives::alonzo::Value;
+
+    use super::*;
+    use crate::MultiEraBlock;
+
+    #[test]
+    fn inputs_and_certs_require_keys() {
+        let blocks = [
+            include_str!("../../test_data/shelley1.block"),
+            include_str!("../../test_data/mary1.block"),
+            include_str!("../../test_data/alonzo1.block"),
+            include_str!("../../test_data/babbage1.block"),
+        ];
+
+        let spender = Hash::<28>::from([7; 28]);
+
+        // an enterprise address paying to the spender key
+        let output = alonzo::TransactionOutput {
+            address: [&[0x61], spender.as_ref()].concat().into(),
+            amount: Value::Coin(1_000_000),
+            datum_hash: None,
+        };
+
+        for block in blocks {
+            let cbor = hex::decode(block).unwrap();
+            let block = MultiEraBlock::decode(&cbor).unwrap();
+
+            for tx in block.txs() {
+                assert!(matches!(
+                    tx.required_vkey_hashes(&HashMap::new(), &HashMap::new()),
+                    Err(Error::UnresolvedUtxoRef(_))
+                ));
+
+                let utxos: HashMap<_, _> = tx
+                    .inputs()
+                    .iter()
+                    .chain(tx.collateral().iter())
+                    .map(|x| {
+                        (
+                            x.output_ref(),
+                            MultiEraOutput::from_alonzo_compatible(&output),
+                        )
+                    })
+                    .collect();
+
+                let required = tx.required_vkey_hashes(&utxos, &HashMap::new()).unwrap();
+                assert!(required.contains(&spender));
+
+                let signers = tx.required_signers();
+                for signer in signers.collect::<Vec<_>>() {
+                    assert!(required.contains(signer));
+                }
+
+                for cert in tx.certs() {
+                    if let Some(alonzo::Certificate::PoolRegistration {
+                        operator,
+                        pool_owners,
+                        ..
+                    }) = cert.as_alonzo()
+                    {
+                        assert!(required.contains(operator));
+                        assert!(pool_owners.iter().all(|x| required.contains(x)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn updates_require_genesis_delegates() {
+        let cbor = hex::decode(include_str!("../../test_data/shelley1.block")).unwrap();
+        let (tag, mut block): (u16, alonzo::Block) = minicbor::decode(&cbor).unwrap();
+
+        let genesis = Hash::<28>::from([1; 28]);
+        let delegate = Hash::<28>::from([2; 28]);
+
+        block.transaction_bodies[0].update = Some(alonzo::Update {
+            proposed_protocol_parameter_updates: vec![(
+                genesis.to_vec().into(),
+                // an update of the minfee_a parameter alone
+                minicbor::decode(&hex::decode("a100182c").unwrap()).unwrap(),
+            )]
+            .into(),
+            epoch: 10,
+        });
+
+        let cbor = minicbor::to_vec((tag, block)).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+        let tx = &block.txs()[0];
+
+        let output = alonzo::TransactionOutput {
+            address: [&[0x61], [7; 28].as_slice()].concat().into(),
+            amount: Value::Coin(1_000_000),
+            datum_hash: None,
+        };
+
+        let utxos: HashMap<_, _> = tx
+            .inputs()
+            .iter()
+            .map(|x| {
+                (
+                    x.output_ref(),
+                    MultiEraOutput::from_alonzo_compatible(&output),
+                )
+            })
+            .collect();
+
+        assert!(matches!(
+            tx.required_vkey_hashes(&utxos, &HashMap::new()),
+            Err(Error::UnknownGenesisKey(x)) if x == genesis
+        ));
+
+        let gen_delegs = HashMap::from([(genesis, delegate)]);
+        let required = tx.required_vkey_hashes(&utxos, &gen_delegs).unwrap();
+        assert!(required.contains(&delegate));
+        assert!(!required.contains(&genesis));
     }
 }
@@ -14,6 +14,10 @@ impl<'b> MultiEraTx<'b> {
                 _ => 2,
             },
             MultiEraTx::Byron(_) => 0,
+            MultiEraTx::Conway(x) => match &x.auxiliary_data {
+                Nullable::Some(x) => x.raw_cbor().len(),
+                _ => 2,
+            },
         }
     }
 
@@ -22,6 +26,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_body.raw_cbor().len(),
             MultiEraTx::Babbage(x) => x.transaction_body.raw_cbor().len(),
             MultiEraTx::Byron(x) => x.transaction.raw_cbor().len(),
+            MultiEraTx::Conway(x) => x.transaction_body.raw_cbor().len(),
         }
     }
 
@@ -30,6 +35,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_witness_set.raw_cbor().len(),
             MultiEraTx::Babbage(x) => x.transaction_witness_set.raw_cbor().len(),
             MultiEraTx::Byron(x) => x.witness.raw_cbor().len(),
+            MultiEraTx::Conway(x) => x.transaction_witness_set.raw_cbor().len(),
         }
     }
 
@@ -45,6 +51,7 @@ impl<'b> MultiEraBlock<'b> {
                 Some(x.header.header_body.block_body_size as usize)
             }
             MultiEraBlock::Babbage(x) => Some(x.header.header_body.block_body_size as usize),
+            MultiEraBlock::Conway(x) => Some(x.header.header_body.block_body_size as usize),
             MultiEraBlock::EpochBoundary(_) => None,
             MultiEraBlock::Byron(_) => None,
         }
@@ -1,6 +1,6 @@
 //! Internal supporting utilities
 
-use pallas_primitives::{alonzo, babbage, byron};
+use pallas_primitives::{alonzo, babbage, byron, conway};
 
 macro_rules! clone_tx_fn {
     ($fn_name:ident, $era:tt) => {
@@ -42,6 +42,7 @@ macro_rules! clone_tx_fn {
 
 clone_tx_fn!(babbage_clone_tx_at, babbage);
 clone_tx_fn!(alonzo_clone_tx_at, alonzo);
+clone_tx_fn!(conway_clone_tx_at, conway);
 
 pub fn clone_alonzo_txs<'b>(block: &'b alonzo::MintedBlock) -> Vec<alonzo::MintedTx<'b>> {
     (0..block.transaction_bodies.len())
@@ -57,6 +58,13 @@ pub fn clone_babbage_txs<'b>(block: &'b babbage::MintedBlock) -> Vec<babbage::Mi
         .collect()
 }
 
+pub fn clone_conway_txs<'b>(block: &'b conway::MintedBlock) -> Vec<conway::MintedTx<'b>> {
+    (0..block.transaction_bodies.len())
+        .step_by(1)
+        .filter_map(|idx| conway_clone_tx_at(block, idx))
+        .collect()
+}
+
 pub fn clone_byron_txs<'b>(block: &'b byron::MintedBlock) -> Vec<byron::MintedTxPayload<'b>> {
     block.body.tx_payload.iter().cloned().collect()
 }
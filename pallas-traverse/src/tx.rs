@@ -5,7 +5,7 @@ use pallas_crypto::hash::Hash;
 use pallas_primitives::{
     alonzo,
     babbage::{self, NetworkId},
-    byron,
+    byron, conway,
 };
 
 use crate::{
@@ -26,12 +26,17 @@ impl<'b> MultiEraTx<'b> {
         Self::Babbage(Box::new(Cow::Borrowed(tx)))
     }
 
+    pub fn from_conway(tx: &'b conway::MintedTx<'b>) -> Self {
+        Self::Conway(Box::new(Cow::Borrowed(tx)))
+    }
+
     pub fn encode(&self) -> Vec<u8> {
         // to_vec is infallible
         match self {
             MultiEraTx::AlonzoCompatible(x, _) => minicbor::to_vec(x).unwrap(),
             MultiEraTx::Babbage(x) => minicbor::to_vec(x).unwrap(),
             MultiEraTx::Byron(x) => minicbor::to_vec(x).unwrap(),
+            MultiEraTx::Conway(x) => minicbor::to_vec(x).unwrap(),
         }
     }
 
@@ -52,6 +57,11 @@ impl<'b> MultiEraTx<'b> {
                 let tx = Box::new(Cow::Owned(tx));
                 Ok(MultiEraTx::Babbage(tx))
             }
+            Era::Conway => {
+                let tx = minicbor::decode(cbor)?;
+                let tx = Box::new(Cow::Owned(tx));
+                Ok(MultiEraTx::Conway(tx))
+            }
         }
     }
 
@@ -60,6 +70,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(_, era) => *era,
             MultiEraTx::Babbage(_) => Era::Babbage,
             MultiEraTx::Byron(_) => Era::Byron,
+            MultiEraTx::Conway(_) => Era::Conway,
         }
     }
 
@@ -68,6 +79,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_body.original_hash(),
             MultiEraTx::Babbage(x) => x.transaction_body.original_hash(),
             MultiEraTx::Byron(x) => x.transaction.original_hash(),
+            MultiEraTx::Conway(x) => x.transaction_body.original_hash(),
         }
     }
 
@@ -91,6 +103,12 @@ impl<'b> MultiEraTx<'b> {
                 .iter()
                 .map(MultiEraOutput::from_byron)
                 .collect(),
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .outputs
+                .iter()
+                .map(MultiEraOutput::from_conway)
+                .collect(),
         }
     }
 
@@ -111,6 +129,11 @@ impl<'b> MultiEraTx<'b> {
                 .outputs
                 .get(index)
                 .map(MultiEraOutput::from_byron),
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .outputs
+                .get(index)
+                .map(MultiEraOutput::from_conway),
         }
     }
 
@@ -138,6 +161,12 @@ impl<'b> MultiEraTx<'b> {
                 .iter()
                 .map(MultiEraInput::from_byron)
                 .collect(),
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .inputs
+                .iter()
+                .map(MultiEraInput::from_alonzo_compatible)
+                .collect(),
         }
     }
 
@@ -158,6 +187,17 @@ impl<'b> MultiEraTx<'b> {
                         .collect()
                 })
                 .unwrap_or_default(),
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .reference_inputs
+                .as_ref()
+                .map(|inputs| {
+                    inputs
+                        .iter()
+                        .map(MultiEraInput::from_alonzo_compatible)
+                        .collect()
+                })
+                .unwrap_or_default(),
             _ => vec![],
         }
     }
@@ -179,6 +219,13 @@ impl<'b> MultiEraTx<'b> {
                 .map(|c| MultiEraCert::AlonzoCompatible(Box::new(Cow::Borrowed(c))))
                 .collect(),
             MultiEraTx::Byron(_) => vec![],
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .certificates
+                .iter()
+                .flat_map(|c| c.iter())
+                .map(|c| MultiEraCert::Conway(Box::new(Cow::Borrowed(c))))
+                .collect(),
         }
     }
 
@@ -199,6 +246,13 @@ impl<'b> MultiEraTx<'b> {
                 .map(|(k, v)| MultiEraPolicyAssets::AlonzoCompatibleMint(k, v))
                 .collect(),
             MultiEraTx::Byron(_) => vec![],
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .mint
+                .iter()
+                .flat_map(|x| x.iter())
+                .map(|(k, v)| MultiEraPolicyAssets::AlonzoCompatibleMint(k, v))
+                .collect(),
         }
     }
 
@@ -223,6 +277,13 @@ impl<'b> MultiEraTx<'b> {
                 .map(MultiEraInput::from_alonzo_compatible)
                 .collect(),
             MultiEraTx::Byron(_) => vec![],
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .collateral
+                .iter()
+                .flat_map(|x| x.iter())
+                .map(MultiEraInput::from_alonzo_compatible)
+                .collect(),
         }
     }
 
@@ -233,6 +294,11 @@ impl<'b> MultiEraTx<'b> {
                 .collateral_return
                 .as_ref()
                 .map(MultiEraOutput::from_babbage),
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .collateral_return
+                .as_ref()
+                .map(MultiEraOutput::from_conway),
             _ => None,
         }
     }
@@ -240,6 +306,7 @@ impl<'b> MultiEraTx<'b> {
     pub fn total_collateral(&self) -> Option<u64> {
         match self {
             MultiEraTx::Babbage(x) => x.transaction_body.total_collateral,
+            MultiEraTx::Conway(x) => x.transaction_body.total_collateral,
             _ => None,
         }
     }
@@ -326,6 +393,10 @@ impl<'b> MultiEraTx<'b> {
                 None => MultiEraWithdrawals::Empty,
             },
             MultiEraTx::Byron(_) => MultiEraWithdrawals::NotApplicable,
+            MultiEraTx::Conway(x) => match &x.transaction_body.withdrawals {
+                Some(x) => MultiEraWithdrawals::AlonzoCompatible(x),
+                None => MultiEraWithdrawals::Empty,
+            },
         }
     }
 
@@ -334,6 +405,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => Some(x.transaction_body.fee),
             MultiEraTx::Babbage(x) => Some(x.transaction_body.fee),
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(x) => Some(x.transaction_body.fee),
         }
     }
 
@@ -342,6 +414,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_body.ttl,
             MultiEraTx::Babbage(x) => x.transaction_body.ttl,
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(x) => x.transaction_body.ttl,
         }
     }
 
@@ -356,6 +429,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_body.fee,
             MultiEraTx::Babbage(x) => x.transaction_body.fee,
             MultiEraTx::Byron(x) => crate::fees::compute_byron_fee(x, None),
+            MultiEraTx::Conway(x) => x.transaction_body.fee,
         }
     }
 
@@ -372,6 +446,11 @@ impl<'b> MultiEraTx<'b> {
                 pallas_codec::utils::Nullable::Undefined => None,
             },
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(x) => match &x.auxiliary_data {
+                pallas_codec::utils::Nullable::Some(x) => Some(x),
+                pallas_codec::utils::Nullable::Null => None,
+                pallas_codec::utils::Nullable::Undefined => None,
+            },
         }
     }
 
@@ -407,6 +486,12 @@ impl<'b> MultiEraTx<'b> {
                 .map(MultiEraSigners::AlonzoCompatible)
                 .unwrap_or_default(),
             MultiEraTx::Byron(_) => MultiEraSigners::NotApplicable,
+            MultiEraTx::Conway(x) => x
+                .transaction_body
+                .required_signers
+                .as_ref()
+                .map(MultiEraSigners::Conway)
+                .unwrap_or_default(),
         }
     }
 
@@ -415,6 +500,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_body.validity_interval_start,
             MultiEraTx::Babbage(x) => x.transaction_body.validity_interval_start,
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(x) => x.transaction_body.validity_interval_start,
         }
     }
 
@@ -423,6 +509,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.transaction_body.network_id,
             MultiEraTx::Babbage(x) => x.transaction_body.network_id,
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(x) => x.transaction_body.network_id,
         }
     }
 
@@ -431,6 +518,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => x.success,
             MultiEraTx::Babbage(x) => x.success,
             MultiEraTx::Byron(_) => true,
+            MultiEraTx::Conway(x) => x.success,
         }
     }
 
@@ -439,6 +527,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(_, _) => None,
             MultiEraTx::Babbage(x) => Some(x),
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(_) => None,
         }
     }
 
@@ -447,6 +536,7 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(x, _) => Some(x),
             MultiEraTx::Babbage(_) => None,
             MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(_) => None,
         }
     }
 
@@ -455,6 +545,16 @@ impl<'b> MultiEraTx<'b> {
             MultiEraTx::AlonzoCompatible(_, _) => None,
             MultiEraTx::Babbage(_) => None,
             MultiEraTx::Byron(x) => Some(x),
+            MultiEraTx::Conway(_) => None,
+        }
+    }
+
+    pub fn as_conway(&self) -> Option<&conway::MintedTx> {
+        match self {
+            MultiEraTx::AlonzoCompatible(_, _) => None,
+            MultiEraTx::Babbage(_) => None,
+            MultiEraTx::Byron(_) => None,
+            MultiEraTx::Conway(x) => Some(x),
         }
     }
 }
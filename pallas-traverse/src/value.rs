@@ -0,0 +1,366 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use pallas_codec::utils::KeyValuePairs;
+use pallas_primitives::alonzo::{AssetName, Mint, PolicyId, Value};
+
+use crate::{MultiEraOutput, MultiEraPolicyAssets, MultiEraTx, MultiEraValue};
+
+impl MultiEraValue {
+    pub fn from_coin(coin: u64) -> Self {
+        Self {
+            coin: coin as i128,
+            assets: BTreeMap::new(),
+        }
+    }
+
+    pub fn from_asset(policy: PolicyId, name: AssetName, amount: i128) -> Self {
+        let mut value = Self::default();
+        value.set_asset(policy, name, amount);
+        value
+    }
+
+    /// Builds a value from its encoding in any of the eras from Shelley
+    /// onwards
+    pub fn from_alonzo_compatible(value: &Value) -> Self {
+        match value {
+            Value::Coin(coin) => Self::from_coin(*coin),
+            Value::Multiasset(coin, multiasset) => {
+                let mut out = Self::from_coin(*coin);
+
+                for (policy, assets) in multiasset.iter() {
+                    for (name, amount) in assets.iter() {
+                        let amount = out.asset(policy, name) + *amount as i128;
+                        out.set_asset(*policy, name.clone(), amount);
+                    }
+                }
+
+                out
+            }
+        }
+    }
+
+    /// Builds a value from the assets minted (positive) and burnt (negative)
+    /// by a tx
+    pub fn from_mint(mint: &Mint) -> Self {
+        let mut out = Self::default();
+
+        for (policy, assets) in mint.iter() {
+            for (name, amount) in assets.iter() {
+                let amount = out.asset(policy, name) + *amount as i128;
+                out.set_asset(*policy, name.clone(), amount);
+            }
+        }
+
+        out
+    }
+
+    /// The amount of lovelace
+    pub fn coin(&self) -> i128 {
+        self.coin
+    }
+
+    /// The amount of the given asset, which is zero if absent
+    pub fn asset(&self, policy: &PolicyId, name: &AssetName) -> i128 {
+        self.assets
+            .get(policy)
+            .and_then(|x| x.get(name))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The native assets with a non-zero amount, sorted by policy and name
+    pub fn assets(&self) -> impl Iterator<Item = (&PolicyId, &AssetName, i128)> {
+        self.assets
+            .iter()
+            .flat_map(|(policy, assets)| assets.iter().map(move |(name, x)| (policy, name, *x)))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coin == 0 && self.assets.is_empty()
+    }
+
+    /// Returns true if no amount is negative, as required by output values
+    pub fn is_non_negative(&self) -> bool {
+        self.coin >= 0 && self.assets().all(|(_, _, x)| x >= 0)
+    }
+
+    /// Adds up both values, or returns `None` on overflow
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.combine(other, i128::checked_add)
+    }
+
+    /// Subtracts the other value from this one, or returns `None` on overflow
+    ///
+    /// The result may hold negative amounts. Use the `>=` comparison to check
+    /// that this value covers the other one beforehand.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.combine(other, i128::checked_sub)
+    }
+
+    /// Negates every amount of the value, or returns `None` on overflow
+    pub fn checked_neg(&self) -> Option<Self> {
+        let mut out = Self {
+            coin: self.coin.checked_neg()?,
+            assets: BTreeMap::new(),
+        };
+
+        for (policy, name, amount) in self.assets() {
+            out.set_asset(*policy, name.clone(), amount.checked_neg()?);
+        }
+
+        Some(out)
+    }
+
+    /// Encodes the value as in the eras from Shelley onwards, or returns
+    /// `None` if any of its amounts is negative or doesn't fit
+    pub fn to_alonzo_compatible(&self) -> Option<Value> {
+        let coin = u64::try_from(self.coin).ok()?;
+
+        if self.assets.is_empty() {
+            return Some(Value::Coin(coin));
+        }
+
+        let multiasset = self.to_multiasset(|x| u64::try_from(x).ok())?;
+
+        Some(Value::Multiasset(coin, multiasset))
+    }
+
+    /// Encodes the assets of the value as a mint, or returns `None` if it holds
+    /// lovelace or any of its amounts doesn't fit
+    pub fn to_mint(&self) -> Option<Mint> {
+        if self.coin != 0 {
+            return None;
+        }
+
+        self.to_multiasset(|x| i64::try_from(x).ok())
+    }
+
+    fn to_multiasset<A>(
+        &self,
+        convert: impl Fn(i128) -> Option<A>,
+    ) -> Option<KeyValuePairs<PolicyId, KeyValuePairs<AssetName, A>>>
+    where
+        A: Clone,
+    {
+        let mut multiasset = Vec::with_capacity(self.assets.len());
+
+        for (policy, assets) in self.assets.iter() {
+            let assets = assets
+                .iter()
+                .map(|(name, x)| Some((name.clone(), convert(*x)?)))
+                .collect::<Option<Vec<_>>>()?;
+
+            multiasset.push((*policy, assets.into()));
+        }
+
+        Some(multiasset.into())
+    }
+
+    fn combine(&self, other: &Self, op: impl Fn(i128, i128) -> Option<i128>) -> Option<Self> {
+        let mut out = self.clone();
+        out.coin = op(self.coin, other.coin)?;
+
+        for (policy, name, amount) in other.assets() {
+            let amount = op(out.asset(policy, name), amount)?;
+            out.set_asset(*policy, name.clone(), amount);
+        }
+
+        Some(out)
+    }
+
+    // Keeps the representation normalised by dropping zero amounts, as well as
+    // policies left without assets.
+    fn set_asset(&mut self, policy: PolicyId, name: AssetName, amount: i128) {
+        if amount != 0 {
+            self.assets.entry(policy).or_default().insert(name, amount);
+            return;
+        }
+
+        if let Some(assets) = self.assets.get_mut(&policy) {
+            assets.remove(&name);
+
+            if assets.is_empty() {
+                self.assets.remove(&policy);
+            }
+        }
+    }
+}
+
+/// Values are compared component-wise, so `a >= b` holds when `a` covers every
+/// amount in `b`, and values that are neither are not comparable
+impl PartialOrd for MultiEraValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let names = self
+            .assets()
+            .chain(other.assets())
+            .map(|(policy, name, _)| (policy, name));
+
+        let mut ordering = self.coin.cmp(&other.coin);
+
+        for (policy, name) in names {
+            let current = self.asset(policy, name).cmp(&other.asset(policy, name));
+
+            ordering = match (ordering, current) {
+                (Ordering::Equal, x) => x,
+                (x, Ordering::Equal) => x,
+                (x, y) if x == y => x,
+                _ => return None,
+            };
+        }
+
+        Some(ordering)
+    }
+}
+
+impl<'b> MultiEraOutput<'b> {
+    /// The lovelace and native assets held by the output
+    pub fn value(&self) -> MultiEraValue {
+        let mut out = MultiEraValue::from_coin(self.lovelace_amount());
+
+        for policy_assets in self.non_ada_assets() {
+            if let MultiEraPolicyAssets::AlonzoCompatibleOutput(policy, assets) = policy_assets {
+                for (name, amount) in assets.iter() {
+                    let amount = out.asset(policy, name) + *amount as i128;
+                    out.set_asset(*policy, name.clone(), amount);
+                }
+            }
+        }
+
+        out
+    }
+}
+
+impl<'b> MultiEraTx<'b> {
+    /// The assets minted by the tx, with negative amounts for the ones burnt
+    pub fn mint_value(&self) -> MultiEraValue {
+        let mut out = MultiEraValue::default();
+
+        for policy_assets in self.mints() {
+            if let MultiEraPolicyAssets::AlonzoCompatibleMint(policy, assets) = policy_assets {
+                for (name, amount) in assets.iter() {
+                    let amount = out.asset(policy, name) + *amount as i128;
+                    out.set_asset(*policy, name.clone(), amount);
+                }
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pallas_codec::utils::Bytes;
+    use pallas_crypto::hash::Hash;
+
+    use super::*;
+    use crate::MultiEraBlock;
+
+    fn token(policy: u8, name: &str, amount: i128) -> MultiEraValue {
+        MultiEraValue::from_asset(
+            Hash::from([policy; 28]),
+            Bytes::from(name.as_bytes().to_vec()),
+            amount,
+        )
+    }
+
+    #[test]
+    fn arithmetic_drops_zero_amounts() {
+        let a = MultiEraValue::from_coin(10)
+            .checked_add(&token(1, "abc", 5))
+            .unwrap();
+        let b = token(1, "abc", 5);
+
+        let diff = a.checked_sub(&b).unwrap();
+        assert_eq!(diff, MultiEraValue::from_coin(10));
+        assert_eq!(diff.assets().count(), 0);
+
+        let zero = diff.checked_sub(&MultiEraValue::from_coin(10)).unwrap();
+        assert!(zero.is_zero());
+        assert_eq!(zero, MultiEraValue::default());
+
+        let overflow =
+            MultiEraValue::from_asset(Hash::from([1; 28]), Bytes::from(vec![]), i128::MAX);
+        assert_eq!(overflow.checked_add(&token(1, "", 1)), None);
+    }
+
+    #[test]
+    fn comparison_is_component_wise() {
+        let a = MultiEraValue::from_coin(10)
+            .checked_add(&token(1, "abc", 5))
+            .unwrap();
+        let b = MultiEraValue::from_coin(10);
+        let c = MultiEraValue::from_coin(20);
+
+        assert!(a >= b);
+        assert!(b <= a);
+        assert!(c >= b);
+        assert_eq!(a.partial_cmp(&c), None);
+        assert_eq!(c.partial_cmp(&a), None);
+        assert!(a >= a.clone());
+    }
+
+    #[test]
+    fn negation_overflows_at_the_minimum() {
+        let value = MultiEraValue::from_coin(7)
+            .checked_add(&token(1, "abc", -5))
+            .unwrap();
+        let expected = MultiEraValue::default()
+            .checked_sub(&MultiEraValue::from_coin(7))
+            .unwrap()
+            .checked_add(&token(1, "abc", 5))
+            .unwrap();
+
+        assert_eq!(value.checked_neg(), Some(expected));
+        assert_eq!(token(1, "abc", i128::MIN).checked_neg(), None);
+        assert_eq!(
+            token(1, "abc", i128::MAX).checked_neg(),
+            Some(token(1, "abc", -i128::MAX))
+        );
+    }
+
+    #[test]
+    fn mint_round_trip() {
+        let mint = token(1, "abc", 5)
+            .checked_add(&token(2, "xyz", 3).checked_neg().unwrap())
+            .unwrap();
+
+        assert!(!mint.is_non_negative());
+        assert_eq!(mint.to_alonzo_compatible(), None);
+
+        let encoded = mint.to_mint().unwrap();
+        assert_eq!(MultiEraValue::from_mint(&encoded), mint);
+
+        let value = MultiEraValue::from_coin(7)
+            .checked_add(&token(1, "abc", 5))
+            .unwrap();
+        assert_eq!(value.to_mint(), None);
+
+        let encoded = value.to_alonzo_compatible().unwrap();
+        assert_eq!(MultiEraValue::from_alonzo_compatible(&encoded), value);
+    }
+
+    #[test]
+    fn output_values_add_up() {
+        let cbor = hex::decode(include_str!("../../test_data/mary1.block")).unwrap();
+        let block = MultiEraBlock::decode(&cbor).unwrap();
+
+        for tx in block.txs() {
+            let total = tx
+                .outputs()
+                .iter()
+                .try_fold(MultiEraValue::default(), |acc, x| {
+                    acc.checked_add(&x.value())
+                })
+                .unwrap();
+
+            let lovelace: u64 = tx.outputs().iter().map(|x| x.lovelace_amount()).sum();
+            assert_eq!(total.coin(), lovelace as i128);
+            assert!(total.is_non_negative());
+
+            for output in tx.outputs() {
+                assert!(total >= output.value());
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use pallas_codec::utils::KeepRaw;
 use pallas_primitives::{
-    alonzo::{self, BootstrapWitness, NativeScript, PlutusData, Redeemer, VKeyWitness},
+    alonzo::{self, BootstrapWitness, NativeScript, PlutusData, VKeyWitness},
     babbage::PlutusV2Script,
+    conway::{self, PlutusV3Script},
 };
 
-use crate::MultiEraTx;
+use crate::{MultiEraRedeemer, MultiEraTx};
 
 impl<'b> MultiEraTx<'b> {
     pub fn vkey_witnesses(&self) -> &[VKeyWitness] {
@@ -21,6 +22,12 @@ impl<'b> MultiEraTx<'b> {
                 .as_ref()
                 .map(|x| x.as_ref())
                 .unwrap_or(&[]),
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .vkeywitness
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
             _ => &[],
         }
     }
@@ -39,6 +46,12 @@ impl<'b> MultiEraTx<'b> {
                 .as_ref()
                 .map(|x| x.as_ref())
                 .unwrap_or(&[]),
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .native_script
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
             _ => &[],
         }
     }
@@ -57,6 +70,12 @@ impl<'b> MultiEraTx<'b> {
                 .as_ref()
                 .map(|x| x.as_ref())
                 .unwrap_or(&[]),
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .bootstrap_witness
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
             _ => &[],
         }
     }
@@ -75,6 +94,12 @@ impl<'b> MultiEraTx<'b> {
                 .as_ref()
                 .map(|x| x.as_ref())
                 .unwrap_or(&[]),
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .plutus_v1_script
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
             _ => &[],
         }
     }
@@ -93,25 +118,43 @@ impl<'b> MultiEraTx<'b> {
                 .as_ref()
                 .map(|x| x.as_ref())
                 .unwrap_or(&[]),
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .plutus_data
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
             _ => &[],
         }
     }
 
-    pub fn redeemers(&self) -> &[Redeemer] {
+    pub fn redeemers(&self) -> Vec<MultiEraRedeemer> {
         match self {
             Self::AlonzoCompatible(x, _) => x
                 .transaction_witness_set
                 .redeemer
-                .as_ref()
-                .map(|x| x.as_ref())
-                .unwrap_or(&[]),
+                .iter()
+                .flatten()
+                .map(MultiEraRedeemer::from_alonzo_compatible)
+                .collect(),
             Self::Babbage(x) => x
                 .transaction_witness_set
                 .redeemer
-                .as_ref()
-                .map(|x| x.as_ref())
-                .unwrap_or(&[]),
-            _ => &[],
+                .iter()
+                .flatten()
+                .map(MultiEraRedeemer::from_alonzo_compatible)
+                .collect(),
+            Self::Conway(x) => match &x.transaction_witness_set.redeemer {
+                Some(conway::Redeemers::List(x)) => {
+                    x.iter().map(MultiEraRedeemer::from_conway_list).collect()
+                }
+                Some(conway::Redeemers::Map(x)) => x
+                    .iter()
+                    .map(|(k, v)| MultiEraRedeemer::from_conway(k, v))
+                    .collect(),
+                None => vec![],
+            },
+            _ => vec![],
         }
     }
 
@@ -123,6 +166,24 @@ impl<'b> MultiEraTx<'b> {
                 .as_ref()
                 .map(|x| x.as_ref())
                 .unwrap_or(&[]),
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .plutus_v2_script
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
+            _ => &[],
+        }
+    }
+
+    pub fn plutus_v3_scripts(&self) -> &[PlutusV3Script] {
+        match self {
+            Self::Conway(x) => x
+                .transaction_witness_set
+                .plutus_v3_script
+                .as_ref()
+                .map(|x| x.as_slice())
+                .unwrap_or(&[]),
             _ => &[],
         }
     }
@@ -1,39 +1,42 @@
 use std::ops::Deref;
 
 use pallas_codec::utils::KeyValuePairs;
-use pallas_primitives::{alonzo, babbage};
+use pallas_primitives::{alonzo, babbage, conway};
 use pallas_traverse as trv;
 
 use trv::OriginalHash;
 
 use utxorpc::proto::cardano::v1 as u5c;
 
-pub fn map_purpose(x: &alonzo::RedeemerTag) -> u5c::RedeemerPurpose {
+pub fn map_purpose(x: &conway::RedeemerTag) -> u5c::RedeemerPurpose {
     match x {
-        babbage::RedeemerTag::Spend => u5c::RedeemerPurpose::Spend,
-        babbage::RedeemerTag::Mint => u5c::RedeemerPurpose::Mint,
-        babbage::RedeemerTag::Cert => u5c::RedeemerPurpose::Cert,
-        babbage::RedeemerTag::Reward => u5c::RedeemerPurpose::Reward,
+        conway::RedeemerTag::Spend => u5c::RedeemerPurpose::Spend,
+        conway::RedeemerTag::Mint => u5c::RedeemerPurpose::Mint,
+        conway::RedeemerTag::Cert => u5c::RedeemerPurpose::Cert,
+        conway::RedeemerTag::Reward => u5c::RedeemerPurpose::Reward,
+        // TODO: map governance purposes once the spec supports them
+        conway::RedeemerTag::Vote => u5c::RedeemerPurpose::Unspecified,
+        conway::RedeemerTag::Propose => u5c::RedeemerPurpose::Unspecified,
     }
 }
 
-pub fn map_redeemer(x: &alonzo::Redeemer) -> u5c::Redeemer {
+pub fn map_redeemer(x: &trv::MultiEraRedeemer) -> u5c::Redeemer {
     u5c::Redeemer {
-        purpose: map_purpose(&x.tag).into(),
-        datum: map_plutus_datum(&x.data).into(),
+        purpose: map_purpose(&x.tag()).into(),
+        datum: map_plutus_datum(x.data()).into(),
     }
 }
 
 pub fn map_tx_input(i: &trv::MultiEraInput, tx: &trv::MultiEraTx) -> u5c::TxInput {
     let redeemer = tx
         .redeemers()
-        .iter()
-        .find(|r| (r.index as u64) == i.index());
+        .into_iter()
+        .find(|r| (r.index() as u64) == i.index());
 
     u5c::TxInput {
         tx_hash: i.hash().to_vec().into(),
         output_index: i.index() as u32,
-        redeemer: redeemer.map(map_redeemer),
+        redeemer: redeemer.as_ref().map(map_redeemer),
         // TODO: map output data from some context
         as_output: None,
     }
@@ -56,19 +59,20 @@ pub fn map_tx_output(x: &trv::MultiEraOutput) -> u5c::TxOutput {
             Some(babbage::PseudoDatumOption::Hash(x)) => x.to_vec().into(),
             _ => vec![].into(),
         },
-        script: match x.script_ref().map(|x| x.deref()) {
-            Some(babbage::Script::NativeScript(x)) => u5c::Script {
+        script: match x.script_ref().as_deref() {
+            Some(conway::Script::NativeScript(x)) => u5c::Script {
                 script: u5c::script::Script::Native(map_native_script(x)).into(),
             }
             .into(),
-            Some(babbage::Script::PlutusV1Script(x)) => u5c::Script {
+            Some(conway::Script::PlutusV1Script(x)) => u5c::Script {
                 script: u5c::script::Script::PlutusV1(x.0.to_vec().into()).into(),
             }
             .into(),
-            Some(babbage::Script::PlutusV2Script(x)) => u5c::Script {
+            Some(conway::Script::PlutusV2Script(x)) => u5c::Script {
                 script: u5c::script::Script::PlutusV2(x.0.to_vec().into()).into(),
             }
             .into(),
+            // TODO: map PlutusV3 scripts once the spec supports them
             _ => None,
         },
     }
@@ -112,8 +116,59 @@ pub fn map_relay(x: &alonzo::Relay) -> u5c::Relay {
     }
 }
 
-pub fn map_cert(x: &trv::MultiEraCert) -> u5c::Certificate {
-    let inner = match x.as_alonzo().unwrap() {
+/// Maps the certificates known to the spec, which leaves out the governance
+/// ones introduced in Conway
+pub fn map_cert(x: &trv::MultiEraCert) -> Option<u5c::Certificate> {
+    let inner = match x {
+        trv::MultiEraCert::AlonzoCompatible(x) => map_alonzo_cert(x),
+        trv::MultiEraCert::Conway(x) => map_alonzo_cert(&conway_to_alonzo_cert(x)?),
+        _ => return None,
+    };
+
+    Some(u5c::Certificate {
+        certificate: inner.into(),
+    })
+}
+
+// Conway certs that have a pre-Conway counterpart. The deposits held by the
+// new registration certs are dropped.
+fn conway_to_alonzo_cert(x: &conway::Certificate) -> Option<alonzo::Certificate> {
+    let cert = match x.clone() {
+        conway::Certificate::StakeRegistration(a) => alonzo::Certificate::StakeRegistration(a),
+        conway::Certificate::Reg(a, _) => alonzo::Certificate::StakeRegistration(a),
+        conway::Certificate::StakeDeregistration(a) => alonzo::Certificate::StakeDeregistration(a),
+        conway::Certificate::UnReg(a, _) => alonzo::Certificate::StakeDeregistration(a),
+        conway::Certificate::StakeDelegation(a, b) => alonzo::Certificate::StakeDelegation(a, b),
+        conway::Certificate::PoolRegistration {
+            operator,
+            vrf_keyhash,
+            pledge,
+            cost,
+            margin,
+            reward_account,
+            pool_owners,
+            relays,
+            pool_metadata,
+        } => alonzo::Certificate::PoolRegistration {
+            operator,
+            vrf_keyhash,
+            pledge,
+            cost,
+            margin,
+            reward_account,
+            pool_owners: pool_owners.to_vec(),
+            relays,
+            pool_metadata,
+        },
+        conway::Certificate::PoolRetirement(a, b) => alonzo::Certificate::PoolRetirement(a, b),
+        _ => return None,
+    };
+
+    Some(cert)
+}
+
+fn map_alonzo_cert(x: &alonzo::Certificate) -> u5c::certificate::Certificate {
+    match x {
         babbage::Certificate::StakeRegistration(a) => {
             u5c::certificate::Certificate::StakeRegistration(map_stake_credential(a))
         }
@@ -189,10 +244,6 @@ pub fn map_cert(x: &trv::MultiEraCert) -> u5c::Certificate {
                 },
             })
         }
-    };
-
-    u5c::Certificate {
-        certificate: inner.into(),
     }
 }
 
@@ -410,7 +461,7 @@ pub fn map_tx(tx: &trv::MultiEraTx) -> u5c::Tx {
     u5c::Tx {
         inputs: tx.inputs().iter().map(|i| map_tx_input(i, tx)).collect(),
         outputs: tx.outputs().iter().map(map_tx_output).collect(),
-        certificates: tx.certs().iter().map(map_cert).collect(),
+        certificates: tx.certs().iter().filter_map(map_cert).collect(),
         withdrawals: tx
             .withdrawals()
             .collect::<Vec<_>>()